@@ -0,0 +1,239 @@
+//! Hard dependencies between the addon folders in `Interface/AddOns`.
+//!
+//! The game client skips an addon whose `## Dependencies` (or
+//! `## RequiredDeps`) names a folder that is missing or disabled, usually
+//! without telling the player why. Optional dependencies never block loading
+//! and are ignored here.
+
+use crate::toc::{self, AddonToc};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// `repo_dependencies` relationship recorded when an addon of the parent repo
+/// requires an addon folder installed by the child repo.
+pub const ADDON_DEPENDENCY: &str = "addon-dependency";
+
+/// Why a hard dependency keeps an addon from loading.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyProblem {
+    /// No folder of that name is installed.
+    Missing,
+    /// The folder belongs to a tracked repo that is disabled.
+    Disabled,
+}
+
+/// Where an unmet dependency can be installed or re-enabled from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum DependencySource {
+    /// A tracked repo that installs the folder; enable or repair it.
+    TrackedRepo { repo_id: i64 },
+    /// A repository known to provide the folder, from the caller's mapping.
+    Known { url: String },
+}
+
+/// A hard dependency of an installed addon that is not satisfied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnmetDependency {
+    /// Addon folder that declares the dependency.
+    pub addon: String,
+    /// Tracked repo that installed `addon`, if any.
+    pub repo_id: Option<i64>,
+    pub dependency: String,
+    pub problem: DependencyProblem,
+    pub source: Option<DependencySource>,
+}
+
+impl UnmetDependency {
+    /// A one-line explanation for logs and warnings.
+    pub fn message(&self) -> String {
+        match self.problem {
+            DependencyProblem::Missing => format!(
+                "{} requires {}, which is not installed.",
+                self.addon, self.dependency
+            ),
+            DependencyProblem::Disabled => format!(
+                "{} requires {}, which is disabled.",
+                self.addon, self.dependency
+            ),
+        }
+    }
+}
+
+/// The tracked repo that installed an addon folder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct FolderOwner {
+    pub repo_id: i64,
+    pub enabled: bool,
+}
+
+/// Addon folders directly under `addons_dir` with their `.toc` metadata.
+/// Hidden folders, such as Wuddle's own `.wuddle`, and folders without a
+/// `.toc` are skipped.
+pub fn scan_addon_folders(addons_dir: &Path) -> Vec<(String, AddonToc)> {
+    let Ok(entries) = fs::read_dir(addons_dir) else {
+        return Vec::new();
+    };
+    let mut folders = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            if name.starts_with('.') {
+                return None;
+            }
+            Some((name, toc::read_addon_toc(&entry.path())?))
+        })
+        .collect::<Vec<_>>();
+    folders.sort_by_key(|(name, _)| name.to_ascii_lowercase());
+    folders
+}
+
+/// Client-provided addons are always present, even without a folder on disk.
+pub(crate) fn is_builtin(dependency: &str) -> bool {
+    dependency
+        .get(..9)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("blizzard_"))
+}
+
+/// Each `(addon, dependency)` pair of the graph whose dependency folder is
+/// installed, with both names as found on disk.
+pub(crate) fn dependency_edges(folders: &[(String, AddonToc)]) -> Vec<(String, String)> {
+    let by_key = folders
+        .iter()
+        .map(|(name, _)| (name.to_ascii_lowercase(), name.as_str()))
+        .collect::<HashMap<_, _>>();
+    folders
+        .iter()
+        .flat_map(|(name, toc)| {
+            toc.dependencies.iter().filter_map(|dependency| {
+                by_key
+                    .get(&dependency.to_ascii_lowercase())
+                    .map(|found| (name.clone(), found.to_string()))
+            })
+        })
+        .collect()
+}
+
+/// Hard dependencies that are missing or owned by a disabled repo. Addons
+/// that are disabled themselves are not checked. `owners` is keyed by the
+/// lower-case folder name.
+pub(crate) fn unmet_dependencies(
+    folders: &[(String, AddonToc)],
+    owners: &HashMap<String, FolderOwner>,
+) -> Vec<UnmetDependency> {
+    let installed = folders
+        .iter()
+        .map(|(name, _)| name.to_ascii_lowercase())
+        .collect::<std::collections::HashSet<_>>();
+    let mut unmet = Vec::new();
+    for (name, toc) in folders {
+        let owner = owners.get(&name.to_ascii_lowercase());
+        if owner.is_some_and(|owner| !owner.enabled) {
+            continue;
+        }
+        for dependency in &toc.dependencies {
+            if is_builtin(dependency) {
+                continue;
+            }
+            let key = dependency.to_ascii_lowercase();
+            let problem = if !installed.contains(&key) {
+                DependencyProblem::Missing
+            } else if owners.get(&key).is_some_and(|owner| !owner.enabled) {
+                DependencyProblem::Disabled
+            } else {
+                continue;
+            };
+            unmet.push(UnmetDependency {
+                addon: name.clone(),
+                repo_id: owner.map(|owner| owner.repo_id),
+                dependency: dependency.clone(),
+                problem,
+                source: None,
+            });
+        }
+    }
+    unmet
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        dependency_edges, unmet_dependencies, DependencyProblem, FolderOwner, UnmetDependency,
+    };
+    use crate::toc::AddonToc;
+    use std::collections::HashMap;
+
+    fn addon(name: &str, dependencies: &[&str]) -> (String, AddonToc) {
+        (
+            name.to_string(),
+            AddonToc {
+                dependencies: dependencies.iter().map(|dep| dep.to_string()).collect(),
+                ..AddonToc::default()
+            },
+        )
+    }
+
+    #[test]
+    fn reports_missing_and_disabled_hard_dependencies() {
+        let folders = vec![
+            addon("Raids", &["libcore", "LibMissing", "Blizzard_RaidUI"]),
+            addon("LibCore", &[]),
+            addon("Quiet", &["LibGone"]),
+            addon("Meters", &["LibCore"]),
+        ];
+        let mut owners = HashMap::new();
+        owners.insert(
+            "libcore".to_string(),
+            FolderOwner {
+                repo_id: 2,
+                enabled: false,
+            },
+        );
+        owners.insert(
+            "quiet".to_string(),
+            FolderOwner {
+                repo_id: 3,
+                enabled: false,
+            },
+        );
+        owners.insert(
+            "raids".to_string(),
+            FolderOwner {
+                repo_id: 1,
+                enabled: true,
+            },
+        );
+
+        let unmet = unmet_dependencies(&folders, &owners);
+        let summary = unmet
+            .iter()
+            .map(|entry: &UnmetDependency| {
+                (
+                    entry.addon.as_str(),
+                    entry.dependency.as_str(),
+                    entry.problem,
+                    entry.repo_id,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("Raids", "libcore", DependencyProblem::Disabled, Some(1)),
+                ("Raids", "LibMissing", DependencyProblem::Missing, Some(1)),
+                ("Meters", "LibCore", DependencyProblem::Disabled, None),
+            ]
+        );
+        assert_eq!(
+            dependency_edges(&folders),
+            vec![
+                ("Raids".to_string(), "LibCore".to_string()),
+                ("Meters".to_string(), "LibCore".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,278 @@
+//! The local addon catalog: addons and mods described by the people who
+//! curate them, searchable without network access.
+//!
+//! A catalog is a JSON file (`{"name": ..., "entries": [...]}`) imported from
+//! disk or fetched from a URL. Each import replaces every entry the catalog of
+//! that name had, so a guild can publish one file and members refresh it.
+//! The folders an entry lists also name the source of untracked addons and
+//! of missing dependencies.
+
+use crate::toc::ClientFamily;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Catalog files larger than this are refused.
+const MAX_INDEX_BYTES: usize = 4 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexKind {
+    #[default]
+    Addon,
+    /// A client mod such as a DLL or a patch, installed into the game folder.
+    Mod,
+}
+
+impl IndexKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Addon => "addon",
+            Self::Mod => "mod",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "addon" => Some(Self::Addon),
+            "mod" => Some(Self::Mod),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    /// Repository or release URL the entry installs from.
+    pub url: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub kind: IndexKind,
+    /// Clients the entry supports; empty when the curator did not say.
+    #[serde(default)]
+    pub clients: Vec<ClientFamily>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Folders the addon installs under `Interface/AddOns`.
+    #[serde(default)]
+    pub folders: Vec<String>,
+}
+
+impl IndexEntry {
+    pub fn supports(&self, client: ClientFamily) -> bool {
+        self.clients.is_empty() || self.clients.contains(&client)
+    }
+}
+
+/// The shareable catalog file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexFile {
+    #[serde(default)]
+    pub name: String,
+    pub entries: Vec<IndexEntry>,
+}
+
+/// One imported catalog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IndexSource {
+    pub id: i64,
+    pub name: String,
+    /// Where the catalog is refreshed from, when it was fetched.
+    pub url: Option<String>,
+    pub updated_unix: i64,
+    pub entries: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IndexQuery {
+    /// Words that must all appear in the name, folders, categories or
+    /// description. Empty lists every entry.
+    pub text: String,
+    pub client: Option<ClientFamily>,
+    pub category: Option<String>,
+    pub kind: Option<IndexKind>,
+}
+
+/// Parse and check a catalog file. Entries without a name or URL are
+/// refused; a URL listed twice keeps its first entry.
+pub fn parse_index(raw: &str) -> Result<IndexFile> {
+    anyhow::ensure!(
+        raw.len() <= MAX_INDEX_BYTES,
+        "Addon catalogs are limited to {} MiB.",
+        MAX_INDEX_BYTES / (1024 * 1024)
+    );
+    let mut file: IndexFile =
+        serde_json::from_str(raw.trim_start_matches('\u{feff}')).context("parse addon catalog")?;
+    let mut seen = HashSet::new();
+    let mut entries = Vec::with_capacity(file.entries.len());
+    for mut entry in file.entries {
+        entry.name = entry.name.trim().to_string();
+        entry.url = entry.url.trim().to_string();
+        if entry.name.is_empty() || entry.url.is_empty() {
+            anyhow::bail!("Every catalog entry needs a name and a url.");
+        }
+        crate::url_safety::reject_embedded_credentials(&entry.url)?;
+        if seen.insert(entry.url.trim_end_matches('/').to_ascii_lowercase()) {
+            entries.push(entry);
+        }
+    }
+    file.name = file.name.trim().to_string();
+    file.entries = entries;
+    Ok(file)
+}
+
+/// Download a catalog file. Only HTTPS URLs are fetched.
+pub async fn fetch_index(url: &str) -> Result<String> {
+    let parsed = url::Url::parse(url.trim()).context("Catalog URL is invalid")?;
+    anyhow::ensure!(
+        parsed.scheme() == "https",
+        "Catalogs are only fetched over HTTPS."
+    );
+    crate::url_safety::reject_embedded_credentials(parsed.as_str())?;
+    let client = reqwest::Client::builder()
+        .user_agent("wuddle-engine")
+        .timeout(std::time::Duration::from_secs(30))
+        .build()?;
+    let response = client.get(parsed).send().await?.error_for_status()?;
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_INDEX_BYTES as u64)
+    {
+        anyhow::bail!(
+            "Addon catalogs are limited to {} MiB.",
+            MAX_INDEX_BYTES / (1024 * 1024)
+        );
+    }
+    Ok(response.text().await?)
+}
+
+fn rank(entry: &IndexEntry, text: &str, words: &[String]) -> Option<u8> {
+    let name = entry.name.to_lowercase();
+    let folders = entry
+        .folders
+        .iter()
+        .map(|folder| folder.to_lowercase())
+        .collect::<Vec<_>>();
+    let rest = format!(
+        "{} {}",
+        entry.categories.join(" ").to_lowercase(),
+        entry.description.to_lowercase()
+    );
+    let found = |word: &str| {
+        name.contains(word)
+            || folders.iter().any(|folder| folder.contains(word))
+            || rest.contains(word)
+    };
+    if !words.iter().all(|word| found(word)) {
+        return None;
+    }
+    Some(if text.is_empty() || name == text {
+        0
+    } else if name.starts_with(text) {
+        1
+    } else if words.iter().all(|word| name.contains(word.as_str())) {
+        2
+    } else if folders.iter().any(|folder| folder == text) {
+        3
+    } else {
+        4
+    })
+}
+
+/// Entries matching `query`, best first: an exact name, then a name prefix,
+/// names containing every word, folder names and finally descriptions.
+pub fn search(entries: &[IndexEntry], query: &IndexQuery) -> Vec<IndexEntry> {
+    let text = query.text.trim().to_lowercase();
+    let words = text
+        .split_whitespace()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let mut ranked = entries
+        .iter()
+        .filter(|entry| query.client.is_none_or(|client| entry.supports(client)))
+        .filter(|entry| query.kind.is_none_or(|kind| entry.kind == kind))
+        .filter(|entry| {
+            query.category.as_deref().is_none_or(|category| {
+                entry
+                    .categories
+                    .iter()
+                    .any(|candidate| candidate.eq_ignore_ascii_case(category))
+            })
+        })
+        .filter_map(|entry| rank(entry, &text, &words).map(|rank| (rank, entry)))
+        .collect::<Vec<_>>();
+    ranked.sort_by_cached_key(|(rank, entry)| (*rank, entry.name.to_lowercase()));
+    ranked.into_iter().map(|(_, entry)| entry.clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_index, search, IndexKind, IndexQuery};
+    use crate::toc::ClientFamily;
+
+    #[test]
+    fn catalog_search_filters_and_ranks_offline() {
+        let file = parse_index(
+            r#"{
+              "name": "Guild list",
+              "entries": [
+                {"name": "pfQuest", "url": "https://github.com/shagu/pfQuest",
+                 "description": "Quest helper with a database of every quest.",
+                 "clients": ["vanilla", "tbc"], "categories": ["Quests"],
+                 "folders": ["pfQuest"]},
+                {"name": "Questie", "url": "https://github.com/AeroScripts/QuestieDev",
+                 "clients": ["vanilla"], "categories": ["Quests"]},
+                {"name": "Atlas", "url": "https://github.com/a/Atlas",
+                 "description": "Instance maps; shows quest givers."},
+                {"name": "Dupe", "url": "https://github.com/shagu/pfQuest/"},
+                {"name": "SuperWoW", "url": "https://github.com/balakethelock/SuperWoW",
+                 "kind": "mod", "clients": ["vanilla"]}
+              ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(file.name, "Guild list");
+        assert_eq!(
+            file.entries.len(),
+            4,
+            "a repeated URL keeps its first entry"
+        );
+
+        let names = |query: IndexQuery| {
+            search(&file.entries, &query)
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect::<Vec<_>>()
+        };
+        let quest = IndexQuery {
+            text: "quest".to_string(),
+            ..IndexQuery::default()
+        };
+        assert_eq!(names(quest.clone()), vec!["Questie", "pfQuest", "Atlas"]);
+        assert_eq!(
+            names(IndexQuery {
+                client: Some(ClientFamily::Tbc),
+                ..quest.clone()
+            }),
+            vec!["pfQuest", "Atlas"]
+        );
+        assert_eq!(
+            names(IndexQuery {
+                category: Some("quests".to_string()),
+                ..quest
+            }),
+            vec!["Questie", "pfQuest"]
+        );
+        assert_eq!(
+            names(IndexQuery {
+                kind: Some(IndexKind::Mod),
+                ..IndexQuery::default()
+            }),
+            vec!["SuperWoW"]
+        );
+
+        assert!(parse_index(r#"{"entries": [{"name": "", "url": "https://x"}]}"#).is_err());
+    }
+}
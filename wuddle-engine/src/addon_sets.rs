@@ -0,0 +1,136 @@
+//! Named addon loadouts that switch several addons on or off at once.
+//!
+//! A set names tracked repos and untracked addon folders with the state each
+//! should have. Applying it switches the repos through
+//! `Engine::set_repo_enabled` and writes the characters' `AddOns.txt` through
+//! [`crate::character_addons`]: either everything changes or, when one step
+//! fails, the steps already taken are undone.
+
+use crate::character_addons::{replace_file, AddonsTxt, WtfCharacter};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddonSetRepo {
+    pub repo_id: i64,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddonSetFolder {
+    /// Folder name under `Interface/AddOns` that no tracked repo installed.
+    pub folder: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddonSet {
+    pub name: String,
+    pub repos: Vec<AddonSetRepo>,
+    pub folders: Vec<AddonSetFolder>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportedSetRepo {
+    pub url: String,
+    pub enabled: bool,
+}
+
+/// The portable form of an [`AddonSet`]: repos are named by URL because repo
+/// ids only mean something in the database that assigned them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddonSetExport {
+    pub name: String,
+    pub repos: Vec<ExportedSetRepo>,
+    pub folders: Vec<AddonSetFolder>,
+}
+
+/// An entry of a set that could not be toggled, with the reason.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SkippedSetEntry {
+    pub entry: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AddonSetReport {
+    /// Addon folders whose state the set decided.
+    pub folders: usize,
+    /// Tracked repos whose enabled state changed.
+    pub repos_changed: usize,
+    /// `AddOns.txt` files that were rewritten.
+    pub characters_updated: usize,
+    pub skipped: Vec<SkippedSetEntry>,
+}
+
+/// Write `states` into the `AddOns.txt` of every character in `characters`.
+/// If any write fails, the files written so far get their previous content
+/// back. Returns how many files changed.
+pub(crate) fn apply_states(
+    characters: &[WtfCharacter],
+    states: &[(String, bool)],
+) -> Result<usize> {
+    let mut written = Vec::<(std::path::PathBuf, Option<Vec<u8>>)>::new();
+    let outcome = characters.iter().try_for_each(|character| {
+        let path = character.addons_txt();
+        let before = fs::read(&path).ok();
+        let mut file = AddonsTxt::read(&path)?;
+        let mut changed = false;
+        for (folder, enabled) in states {
+            changed |= file.set_enabled(folder, *enabled);
+        }
+        if changed {
+            file.write_atomic(&path)?;
+            written.push((path, before));
+        }
+        Ok::<_, anyhow::Error>(())
+    });
+    if let Err(error) = outcome {
+        for (path, before) in written.iter().rev() {
+            let restored = match before {
+                Some(contents) => replace_file(path, contents),
+                None => fs::remove_file(path).map_err(Into::into),
+            };
+            restored.with_context(|| {
+                format!(
+                    "{error:#}; restoring {} also failed, check it in game",
+                    path.display()
+                )
+            })?;
+        }
+        return Err(error);
+    }
+    Ok(written.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::apply_states;
+    use crate::character_addons::discover_characters;
+    use std::fs;
+
+    #[test]
+    fn failed_write_restores_files_already_updated() {
+        let temp = tempfile::tempdir().unwrap();
+        let realm = temp.path().join("WTF/Account/ACC/Realm");
+        fs::create_dir_all(realm.join("Alpha")).unwrap();
+        fs::write(realm.join("Alpha/AddOns.txt"), "Atlas: enabled\n").unwrap();
+        // A folder in place of AddOns.txt makes the second write fail.
+        fs::create_dir_all(realm.join("Beta/AddOns.txt")).unwrap();
+
+        let characters = discover_characters(temp.path());
+        let states = vec![("Atlas".to_string(), false)];
+        assert!(apply_states(&characters, &states).is_err());
+        assert_eq!(
+            fs::read_to_string(realm.join("Alpha/AddOns.txt")).unwrap(),
+            "Atlas: enabled\n"
+        );
+
+        assert_eq!(apply_states(&characters[..1], &states).unwrap(), 1);
+        assert_eq!(
+            fs::read_to_string(realm.join("Alpha/AddOns.txt")).unwrap(),
+            "Atlas: disabled\n"
+        );
+    }
+}
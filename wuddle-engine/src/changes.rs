@@ -0,0 +1,534 @@
+//! Dry runs: what an install, update, reinstall or removal would change.
+//!
+//! A dry run is the real operation given a [`ChangeSet`] to record into:
+//! each step that would move a file or write a record adds it to the set
+//! instead. Plans are built without storing release metadata and releases
+//! are staged in the system temp directory, so the game folder, its
+//! `.wuddle` cache and the profile database stay as they were. A release
+//! asset missing from the cache, or a Git addon's commits, are still fetched
+//! into that temp directory.
+
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::deployment::ReplacementTransaction;
+use crate::{db, AddonConflictReplacementPlan, CheckMode, Engine, InstallOptions, UpdatePlan};
+
+/// The changes one operation would make. Paths are relative to the WoW
+/// directory, as install records store them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ChangeSet {
+    pub operation: String,
+    pub repo_id: Option<i64>,
+    /// Repo label such as `owner/name`.
+    pub label: String,
+    pub created: Vec<String>,
+    pub replaced: Vec<String>,
+    /// Live files kept as displaced-file backups before they are replaced,
+    /// so removing the new owner restores them.
+    pub backed_up: Vec<String>,
+    pub deleted: Vec<String>,
+    /// Files put back from a displaced-file backup.
+    pub restored: Vec<String>,
+    pub dlls_txt_added: Vec<String>,
+    pub dlls_txt_removed: Vec<String>,
+    /// Profile database rows the operation would write or remove.
+    pub database: Vec<String>,
+    pub conflicts: Vec<String>,
+    /// Why the operation would stop before changing anything.
+    pub blocked: Option<String>,
+}
+
+impl ChangeSet {
+    pub fn new(operation: &str, repo_id: Option<i64>, label: impl Into<String>) -> Self {
+        Self {
+            operation: operation.to_string(),
+            repo_id,
+            label: label.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether the operation would leave files and records as they are.
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty()
+            && self.replaced.is_empty()
+            && self.backed_up.is_empty()
+            && self.deleted.is_empty()
+            && self.restored.is_empty()
+            && self.dlls_txt_added.is_empty()
+            && self.dlls_txt_removed.is_empty()
+            && self.database.is_empty()
+    }
+
+    /// One line per change, for text output.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let files = [
+            ("create", &self.created),
+            ("replace", &self.replaced),
+            ("back up", &self.backed_up),
+            ("delete", &self.deleted),
+            ("restore", &self.restored),
+        ];
+        for (verb, paths) in files {
+            lines.extend(paths.iter().map(|path| format!("{verb} {path}")));
+        }
+        lines.extend(
+            self.dlls_txt_added
+                .iter()
+                .map(|entry| format!("dlls.txt: add {entry}")),
+        );
+        lines.extend(
+            self.dlls_txt_removed
+                .iter()
+                .map(|entry| format!("dlls.txt: remove {entry}")),
+        );
+        lines.extend(self.database.iter().map(|row| format!("database: {row}")));
+        lines.extend(
+            self.conflicts
+                .iter()
+                .map(|conflict| format!("conflict: {conflict}")),
+        );
+        if let Some(blocked) = &self.blocked {
+            lines.push(format!("would stop: {blocked}"));
+        }
+        lines
+    }
+
+    /// Record the `dlls.txt` lines that differ between `before` and `after`.
+    pub(crate) fn diff_dlls_txt(&mut self, before: &str, after: &str) {
+        let entries = |text: &str| {
+            text.lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>()
+        };
+        let (before, after) = (entries(before), entries(after));
+        let missing_from = |lines: &[String], line: &String| {
+            !lines.iter().any(|other| other.eq_ignore_ascii_case(line))
+        };
+        self.dlls_txt_added = after
+            .iter()
+            .filter(|line| missing_from(&before, line))
+            .cloned()
+            .collect();
+        self.dlls_txt_removed = before
+            .iter()
+            .filter(|line| missing_from(&after, line))
+            .cloned()
+            .collect();
+    }
+
+    /// Record `manifest` as deleted when `live` exists and is not listed
+    /// yet. Returns whether it was recorded.
+    fn record_removal(&mut self, live: &Path, manifest: &str) -> bool {
+        let listed = self
+            .deleted
+            .iter()
+            .any(|path| path.eq_ignore_ascii_case(manifest));
+        if listed || Engine::find_actual_case(live).is_none() {
+            return false;
+        }
+        self.deleted.push(manifest.to_string());
+        true
+    }
+
+    /// Record the rows a release install would write.
+    pub(crate) fn record_release_replacement(
+        &mut self,
+        plan: &UpdatePlan,
+        replacement: &db::ReleaseReplacement,
+        added_backups: usize,
+    ) {
+        self.database.push(format!(
+            "record {} installed file(s) at {}",
+            replacement.installs.len(),
+            plan.install_version
+        ));
+        if plan.current.as_deref() != Some(plan.install_version.as_str()) {
+            self.database.push(format!(
+                "version {} -> {}",
+                plan.current.as_deref().unwrap_or("none"),
+                plan.install_version
+            ));
+        }
+        if added_backups > 0 {
+            self.database
+                .push(format!("add {added_backups} displaced-file backup row(s)"));
+        }
+        if !self.restored.is_empty() {
+            self.database.push(format!(
+                "remove {} displaced-file backup row(s)",
+                self.restored.len()
+            ));
+        }
+        if replacement.mark_manual {
+            self.database
+                .push("mark the repo as manually installed".to_string());
+        }
+    }
+}
+
+/// Where a deployment's file moves go: into the game folder under a
+/// replacement transaction, or into a dry run's change set.
+pub(crate) enum Deploy<'a> {
+    Live(&'a mut ReplacementTransaction),
+    DryRun(&'a mut ChangeSet),
+}
+
+impl Deploy<'_> {
+    /// Move `live`, tracked as `manifest`, out of the game folder. Returns
+    /// whether anything was there.
+    pub(crate) fn remove(&mut self, live: &Path, manifest: &str) -> Result<bool> {
+        match self {
+            Deploy::Live(transaction) => transaction.backup_target(live),
+            Deploy::DryRun(changes) => Ok(changes.record_removal(live, manifest)),
+        }
+    }
+
+    /// Put the displaced-file `backup` of `manifest` back at `live`.
+    pub(crate) fn restore(&mut self, backup: &Path, live: &Path, manifest: &str) -> Result<()> {
+        match self {
+            Deploy::Live(transaction) => transaction.deploy_returnable(backup, live),
+            Deploy::DryRun(changes) => {
+                changes.restored.push(manifest.to_string());
+                Ok(())
+            }
+        }
+    }
+
+    /// Replace the live `dlls.txt` with `staged`.
+    pub(crate) fn replace_dlls_txt(&mut self, staged: &Path, live: &Path) -> Result<()> {
+        match self {
+            Deploy::Live(transaction) => transaction.deploy(staged, live),
+            Deploy::DryRun(changes) => {
+                changes.diff_dlls_txt(
+                    &fs::read_to_string(live).unwrap_or_default(),
+                    &fs::read_to_string(staged)
+                        .context("read staged DLL launcher configuration")?,
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// A directory to build replacement files in: beside the game files so
+    /// they can be renamed into place, or in the system temp directory for a
+    /// dry run.
+    pub(crate) fn staging_dir(
+        &self,
+        wow_dir: &Path,
+        prefix: &str,
+    ) -> std::io::Result<tempfile::TempDir> {
+        let mut builder = tempfile::Builder::new();
+        builder.prefix(prefix);
+        match self {
+            Deploy::Live(_) => builder.tempdir_in(wow_dir),
+            Deploy::DryRun(_) => builder.tempdir(),
+        }
+    }
+}
+
+impl Engine {
+    /// What [`Engine::update_repo`] would change. `None` when the repo is up
+    /// to date.
+    pub async fn dry_run_update(
+        &self,
+        repo_id: i64,
+        wow_dir: &Path,
+        raw_dest: Option<&Path>,
+        opts: InstallOptions,
+    ) -> Result<Option<ChangeSet>> {
+        let repo = self.db().get_repo(repo_id)?;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let plan = self
+            .build_update_plan_for_repo(
+                &repo,
+                true,
+                false,
+                Some(wow_dir),
+                CheckMode::Force,
+                None,
+                None,
+                &cancelled,
+            )
+            .await?;
+        if let Some(err) = plan.error.clone() {
+            anyhow::bail!(err);
+        }
+        if plan.asset_url.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(
+            self.dry_run_plan(&plan, wow_dir, raw_dest, opts, "update", false)
+                .await,
+        ))
+    }
+
+    /// What [`Engine::apply_updates`] would change, one set per repo it
+    /// would update. A repo whose update would fail reports why in `blocked`.
+    pub async fn dry_run_updates(
+        &self,
+        wow_dir: &Path,
+        raw_dest: Option<&Path>,
+        opts: InstallOptions,
+    ) -> Result<Vec<ChangeSet>> {
+        let repos = self.db().list_repos()?;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut out = Vec::new();
+        for r in repos.into_iter().filter(|r| r.enabled) {
+            let plan = match self
+                .build_update_plan_for_repo(
+                    &r,
+                    true,
+                    false,
+                    Some(wow_dir),
+                    CheckMode::Force,
+                    None,
+                    None,
+                    &cancelled,
+                )
+                .await
+            {
+                Ok(plan) => plan,
+                Err(error) => {
+                    let mut changes =
+                        ChangeSet::new("update", Some(r.id), format!("{}/{}", r.owner, r.name));
+                    changes.blocked = Some(format!("{error:#}"));
+                    out.push(changes);
+                    continue;
+                }
+            };
+            if plan.asset_url.is_empty() || plan.externally_modified {
+                continue;
+            }
+            out.push(
+                self.dry_run_plan(&plan, wow_dir, raw_dest, opts, "update", false)
+                    .await,
+            );
+        }
+        Ok(out)
+    }
+
+    /// What [`Engine::reinstall_repo`] would change.
+    pub async fn dry_run_reinstall(
+        &self,
+        repo_id: i64,
+        wow_dir: &Path,
+        raw_dest: Option<&Path>,
+        opts: InstallOptions,
+    ) -> Result<ChangeSet> {
+        let repo = self.db().get_repo(repo_id)?;
+        let (plan, force_clean_git_reinstall) = self.reinstall_plan(&repo, wow_dir, false).await?;
+        Ok(self
+            .dry_run_plan(
+                &plan,
+                wow_dir,
+                raw_dest,
+                opts,
+                "reinstall",
+                force_clean_git_reinstall,
+            )
+            .await)
+    }
+
+    /// Run `plan`'s install in record mode. An error the install would stop
+    /// on is reported in `blocked`.
+    pub(crate) async fn dry_run_plan(
+        &self,
+        plan: &UpdatePlan,
+        wow_dir: &Path,
+        raw_dest: Option<&Path>,
+        opts: InstallOptions,
+        operation: &str,
+        force_clean_git_reinstall: bool,
+    ) -> ChangeSet {
+        let mut changes = ChangeSet::new(operation, Some(plan.repo_id), plan_label(plan));
+        if let Err(error) = self
+            .apply_one_files(
+                plan,
+                wow_dir,
+                raw_dest,
+                opts,
+                force_clean_git_reinstall,
+                Some(&mut changes),
+            )
+            .await
+        {
+            changes.blocked = Some(format!("{error:#}"));
+        }
+        changes
+    }
+
+    /// What [`Engine::remove_repo`] would change.
+    pub fn dry_run_remove(
+        &self,
+        repo_id: i64,
+        wow_dir: Option<&Path>,
+        remove_local_files: bool,
+    ) -> Result<ChangeSet> {
+        let repo = self.db().get_repo(repo_id)?;
+        let mut changes = ChangeSet::new(
+            "remove",
+            Some(repo_id),
+            format!("{}/{}", repo.owner, repo.name),
+        );
+        if let Err(error) = self.remove_repo_records_and_files(
+            repo_id,
+            wow_dir,
+            remove_local_files,
+            Some(&mut changes),
+        ) {
+            changes.blocked = Some(format!("{error:#}"));
+        }
+        Ok(changes)
+    }
+
+    /// Remove `path`, tracked as `manifest`, or record its removal. Returns
+    /// whether anything was there.
+    pub(crate) fn remove_or_record(
+        path: &Path,
+        manifest: &str,
+        record: Option<&mut ChangeSet>,
+    ) -> Result<bool> {
+        match record {
+            Some(changes) => Ok(changes.record_removal(path, manifest)),
+            None => Self::remove_any_target(path),
+        }
+    }
+
+    /// Record the `dlls.txt` lines that removing `dll_names` would drop.
+    pub(crate) fn record_dlls_txt_removal(
+        wow_dir: &Path,
+        dll_names: &[String],
+        changes: &mut ChangeSet,
+    ) -> Result<()> {
+        let live_dlls_txt = wow_dir.join("dlls.txt");
+        if dll_names.is_empty() || !live_dlls_txt.is_file() {
+            return Ok(());
+        }
+        let scratch = tempfile::tempdir().context("create DLL configuration scratch directory")?;
+        fs::copy(&live_dlls_txt, scratch.path().join("dlls.txt"))
+            .context("copy DLL launcher configuration")?;
+        Self::remove_dlls_txt_entries(scratch.path(), dll_names)?;
+        changes.diff_dlls_txt(
+            &fs::read_to_string(&live_dlls_txt)?,
+            &fs::read_to_string(scratch.path().join("dlls.txt"))?,
+        );
+        Ok(())
+    }
+
+    /// Record what replacing a Git addon's live folders with its staged
+    /// worktree would change. `exposed` names the addon folders it installs
+    /// and `final_name` the worktree folder.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn record_addon_git_replacement(
+        &self,
+        repo_id: i64,
+        wow_dir: &Path,
+        exposed: &[String],
+        final_name: &str,
+        installed_worktree: Option<&Path>,
+        conflict_plan: &AddonConflictReplacementPlan,
+        version: &str,
+        changes: &mut ChangeSet,
+    ) -> Result<()> {
+        let addons_dir = wow_dir.join("Interface").join("AddOns");
+        let mut folders = exposed.to_vec();
+        if !folders
+            .iter()
+            .any(|name| name.eq_ignore_ascii_case(final_name))
+        {
+            folders.push(final_name.to_string());
+        }
+        let mut written = HashSet::new();
+        for name in &folders {
+            let target = addons_dir.join(name);
+            let manifest = Self::to_manifest_path(&target, wow_dir);
+            written.insert(Self::install_path_key(&manifest));
+            if Self::find_actual_case(&target).is_some() {
+                changes.replaced.push(manifest);
+            } else {
+                changes.created.push(manifest);
+            }
+        }
+
+        let mut displaced = self
+            .db()
+            .list_installs(repo_id)?
+            .into_iter()
+            .filter(|install| install.kind == "addon")
+            .map(|install| install.path)
+            .collect::<Vec<_>>();
+        displaced.extend(
+            installed_worktree
+                .into_iter()
+                .chain(
+                    conflict_plan
+                        .additional_backup_paths
+                        .iter()
+                        .map(PathBuf::as_path),
+                )
+                .map(|path| Self::to_manifest_path(path, wow_dir)),
+        );
+        for manifest in displaced {
+            if written.contains(&Self::install_path_key(&manifest)) {
+                continue;
+            }
+            if let Some(live) = Self::resolve_install_path(&manifest, Some(wow_dir)) {
+                changes.record_removal(&live, &manifest);
+            }
+        }
+
+        changes.database.push(format!(
+            "record {} addon folder(s) at {version}",
+            exposed.len()
+        ));
+        for mutation in &conflict_plan.database_mutations {
+            if mutation.remove_repo {
+                changes.database.push(format!(
+                    "remove repo {}, whose addons this one replaces",
+                    mutation.repo_id
+                ));
+            } else if !mutation.removed_paths.is_empty() {
+                changes.database.push(format!(
+                    "untrack {} replaced addon folder(s) of repo {}",
+                    mutation.removed_paths.len(),
+                    mutation.repo_id
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn plan_label(plan: &UpdatePlan) -> String {
+    format!("{}/{}", plan.owner, plan.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChangeSet;
+
+    #[test]
+    fn dlls_txt_diff_ignores_case_and_blank_lines() {
+        let mut changes = ChangeSet::new("update", Some(1), "tests/repo");
+        assert!(changes.is_empty());
+        changes.diff_dlls_txt("Other.dll\n\nold.dll\n", "other.dll\nnew.dll\n");
+        assert_eq!(changes.dlls_txt_added, vec!["new.dll"]);
+        assert_eq!(changes.dlls_txt_removed, vec!["old.dll"]);
+        assert!(!changes.is_empty());
+        assert_eq!(
+            changes.lines(),
+            vec!["dlls.txt: add new.dll", "dlls.txt: remove old.dll"]
+        );
+    }
+}
@@ -0,0 +1,331 @@
+//! Per-character addon state kept by the game in
+//! `WTF/Account/<ACCOUNT>/<Realm>/<Character>/AddOns.txt`.
+//!
+//! Each line reads `AddonName: enabled` or `AddonName: disabled`; an addon
+//! without a line loads by default. Toggling here only rewrites those files
+//! and never moves installed addon folders, unlike `Engine::set_repo_enabled`.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::deployment::sync_dir;
+use crate::saved_variables::game_running;
+
+const ADDONS_TXT: &str = "AddOns.txt";
+
+/// One character folder under `WTF/Account`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WtfCharacter {
+    pub account: String,
+    pub realm: String,
+    pub name: String,
+    /// The character folder that holds `AddOns.txt`.
+    pub path: PathBuf,
+}
+
+impl WtfCharacter {
+    pub fn addons_txt(&self) -> PathBuf {
+        self.path.join(ADDONS_TXT)
+    }
+
+    /// `Name - Realm (ACCOUNT)`, as shown in character pickers.
+    pub fn label(&self) -> String {
+        format!("{} - {} ({})", self.name, self.realm, self.account)
+    }
+}
+
+/// Visible sub-folders of `dir`, sorted case-insensitively. Per-account and
+/// per-character `SavedVariables` folders are not realms or characters.
+fn child_dirs(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            if name.starts_with('.') || name.eq_ignore_ascii_case("SavedVariables") {
+                return None;
+            }
+            Some((name, entry.path()))
+        })
+        .collect::<Vec<_>>();
+    dirs.sort_by_key(|(name, _)| name.to_ascii_lowercase());
+    dirs
+}
+
+/// Every character the client has created settings for, grouped by account
+/// and realm. Empty when the game has not written a `WTF` folder yet.
+pub fn discover_characters(wow_dir: &Path) -> Vec<WtfCharacter> {
+    let Some(accounts) = crate::mpq::find_case_insensitive_child(wow_dir, "WTF")
+        .and_then(|wtf| crate::mpq::find_case_insensitive_child(&wtf, "Account"))
+    else {
+        return Vec::new();
+    };
+    let mut characters = Vec::new();
+    for (account, account_dir) in child_dirs(&accounts) {
+        for (realm, realm_dir) in child_dirs(&account_dir) {
+            for (name, path) in child_dirs(&realm_dir) {
+                characters.push(WtfCharacter {
+                    account: account.clone(),
+                    realm: realm.clone(),
+                    name,
+                    path,
+                });
+            }
+        }
+    }
+    characters
+}
+
+/// The parsed contents of one `AddOns.txt`. Lines that are not addon entries
+/// are kept verbatim so a rewrite changes nothing but the toggled entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AddonsTxt {
+    lines: Vec<String>,
+    crlf: bool,
+}
+
+fn parse_entry(line: &str) -> Option<(&str, bool)> {
+    let (name, state) = line.split_once(':')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+    let state = state.trim();
+    if state.eq_ignore_ascii_case("enabled") {
+        Some((name, true))
+    } else if state.eq_ignore_ascii_case("disabled") {
+        Some((name, false))
+    } else {
+        None
+    }
+}
+
+impl AddonsTxt {
+    pub fn parse(raw: &str) -> Self {
+        Self {
+            lines: raw.lines().map(str::to_string).collect(),
+            crlf: raw.contains("\r\n"),
+        }
+    }
+
+    /// Read `path`, treating a missing file as empty. Bytes that are not
+    /// UTF-8, such as names saved in a legacy code page, are replaced.
+    pub fn read(path: &Path) -> Result<Self> {
+        match fs::read(path) {
+            Ok(raw) => Ok(Self::parse(&String::from_utf8_lossy(&raw))),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| format!("read {}", path.display())),
+        }
+    }
+
+    /// Addon names with their state, in file order.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, bool)> {
+        self.lines.iter().filter_map(|line| parse_entry(line))
+    }
+
+    /// The recorded state of `addon`, or `None` when the file has no entry
+    /// for it and the game's default applies.
+    pub fn is_enabled(&self, addon: &str) -> Option<bool> {
+        self.entries()
+            .find(|(name, _)| name.eq_ignore_ascii_case(addon))
+            .map(|(_, enabled)| enabled)
+    }
+
+    /// Record `addon` as enabled or disabled. Returns whether the file
+    /// content changed.
+    pub fn set_enabled(&mut self, addon: &str, enabled: bool) -> bool {
+        let state = if enabled { "enabled" } else { "disabled" };
+        let position = self.lines.iter().position(|line| {
+            parse_entry(line).is_some_and(|(name, _)| name.eq_ignore_ascii_case(addon))
+        });
+        match position {
+            Some(index) => {
+                let (name, current) = parse_entry(&self.lines[index]).expect("matched above");
+                if current == enabled {
+                    return false;
+                }
+                self.lines[index] = format!("{name}: {state}");
+            }
+            None => self.lines.push(format!("{addon}: {state}")),
+        }
+        true
+    }
+
+    pub fn render(&self) -> String {
+        let newline = if self.crlf { "\r\n" } else { "\n" };
+        let mut out = self.lines.join(newline);
+        if !out.is_empty() {
+            out.push_str(newline);
+        }
+        out
+    }
+
+    /// Replace `path` through a temporary file in the same folder, so the
+    /// game never reads a half-written file.
+    pub fn write_atomic(&self, path: &Path) -> Result<()> {
+        let parent = path
+            .parent()
+            .with_context(|| format!("resolve folder of {}", path.display()))?;
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+        replace_file(path, self.render().as_bytes())
+    }
+}
+
+/// Replace `path` with `contents` through a synced temporary file in the
+/// same folder.
+pub(crate) fn replace_file(path: &Path, contents: &[u8]) -> Result<()> {
+    let parent = path
+        .parent()
+        .with_context(|| format!("resolve folder of {}", path.display()))?;
+    let mut staged = tempfile::Builder::new()
+        .prefix(&format!(".{ADDONS_TXT}.wuddle-"))
+        .tempfile_in(parent)
+        .with_context(|| format!("stage {}", path.display()))?;
+    staged.write_all(contents)?;
+    staged
+        .as_file()
+        .sync_all()
+        .with_context(|| format!("flush {}", path.display()))?;
+    staged
+        .persist(path)
+        .map_err(|error| error.error)
+        .with_context(|| format!("replace {}", path.display()))?;
+    sync_dir(parent)
+}
+
+/// Refuse to edit `AddOns.txt` while the game runs: it writes its own
+/// addon list back when you log out.
+pub fn ensure_game_closed(wow_dir: &Path) -> Result<()> {
+    anyhow::ensure!(
+        !game_running(wow_dir),
+        "Close the game before changing which addons load; it rewrites AddOns.txt when you log out."
+    );
+    Ok(())
+}
+
+/// Enable or disable `addons` for one character. Returns whether its
+/// `AddOns.txt` was rewritten.
+pub fn set_character_addons_enabled(
+    character: &WtfCharacter,
+    addons: &[String],
+    enabled: bool,
+) -> Result<bool> {
+    let path = character.addons_txt();
+    let mut file = AddonsTxt::read(&path)?;
+    let mut changed = false;
+    for addon in addons {
+        changed |= file.set_enabled(addon, enabled);
+    }
+    if changed {
+        file.write_atomic(&path)?;
+    }
+    Ok(changed)
+}
+
+/// Enable or disable `addons` for every character under `wow_dir`. Returns
+/// how many `AddOns.txt` files were rewritten.
+pub fn set_addons_enabled_for_all(
+    wow_dir: &Path,
+    addons: &[String],
+    enabled: bool,
+) -> Result<usize> {
+    let mut changed = 0;
+    for character in discover_characters(wow_dir) {
+        if set_character_addons_enabled(&character, addons, enabled)? {
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{discover_characters, set_addons_enabled_for_all, AddonsTxt};
+    use std::fs;
+
+    #[test]
+    fn reads_addons_txt_that_is_not_utf8() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("AddOns.txt");
+        fs::write(&path, b"Atlas: enabled\nCarte\xe9: disabled\n").unwrap();
+
+        let mut file = AddonsTxt::read(&path).unwrap();
+        assert_eq!(file.is_enabled("Atlas"), Some(true));
+        assert_eq!(file.entries().count(), 2);
+        assert!(file.set_enabled("Atlas", false));
+        file.write_atomic(&path).unwrap();
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("Atlas: disabled\n"));
+        assert_eq!(fs::read_dir(temp.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn toggling_keeps_unrelated_lines_and_line_endings() {
+        let mut file = AddonsTxt::parse("Atlas: enabled\r\n# note\r\npfQuest: disabled\r\n");
+        assert_eq!(file.is_enabled("atlas"), Some(true));
+        assert_eq!(file.is_enabled("Questie"), None);
+
+        assert!(!file.set_enabled("PFQUEST", false));
+        assert!(file.set_enabled("pfQuest", true));
+        assert!(file.set_enabled("Questie", false));
+        assert_eq!(
+            file.render(),
+            "Atlas: enabled\r\n# note\r\npfQuest: enabled\r\nQuestie: disabled\r\n"
+        );
+    }
+
+    #[test]
+    fn discovers_characters_and_toggles_them_all() {
+        let temp = tempfile::tempdir().unwrap();
+        let account = temp.path().join("WTF").join("Account").join("PLAYER");
+        fs::create_dir_all(account.join("SavedVariables")).unwrap();
+        fs::create_dir_all(
+            account
+                .join("Realm One")
+                .join("Alpha")
+                .join("SavedVariables"),
+        )
+        .unwrap();
+        fs::create_dir_all(account.join("Realm One").join("beta")).unwrap();
+        fs::write(
+            account.join("Realm One").join("Alpha").join("AddOns.txt"),
+            "Atlas: disabled\npfQuest: enabled\n",
+        )
+        .unwrap();
+
+        let characters = discover_characters(temp.path());
+        let labels = characters
+            .iter()
+            .map(|character| character.label())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            vec!["Alpha - Realm One (PLAYER)", "beta - Realm One (PLAYER)"]
+        );
+
+        let addons = vec!["Atlas".to_string()];
+        assert_eq!(
+            set_addons_enabled_for_all(temp.path(), &addons, true).unwrap(),
+            2
+        );
+        assert_eq!(
+            set_addons_enabled_for_all(temp.path(), &addons, true).unwrap(),
+            0
+        );
+        assert_eq!(
+            fs::read_to_string(characters[0].addons_txt()).unwrap(),
+            "Atlas: enabled\npfQuest: enabled\n"
+        );
+        assert_eq!(
+            fs::read_to_string(characters[1].addons_txt()).unwrap(),
+            "Atlas: enabled\n"
+        );
+    }
+}
@@ -4,12 +4,34 @@ use std::collections::HashSet;
 use std::sync::Mutex;
 use std::time::Duration;
 
-use crate::model::{InstallMode, Repo};
-
-const SCHEMA_VERSION: i32 = 21;
+use crate::addon_index::{IndexEntry, IndexFile, IndexKind, IndexSource};
+use crate::addon_sets::{AddonSet, AddonSetFolder, AddonSetRepo};
+use crate::fingerprint::{CatalogEntry, CatalogSource};
+use crate::forge::{CustomForgeHost, ForgeKind};
+use crate::model::{
+    GitTagPolicy, HistoryAction, InstallHistoryEntry, InstallMode, LatestRelease, ReleaseAsset,
+    Repo,
+};
+use crate::toc::AddonToc;
+
+const SCHEMA_VERSION: i32 = 32;
 const REPO_CASING_RECOVERY: &str = "repo_casing_recovery_v4";
+/// Install history entries kept per repo; older ones are dropped on insert.
+const INSTALL_HISTORY_KEPT: i64 = 100;
 static DB_OPEN_LOCK: Mutex<()> = Mutex::new(());
 
+/// Last known release of one forge endpoint, as stored in `release_cache`.
+#[derive(Debug, Clone)]
+pub struct CachedReleaseRow {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub release: LatestRelease,
+    /// When the payload was last downloaded.
+    pub fetched_at_unix: i64,
+    /// When the forge last confirmed the payload, by a download or a 304.
+    pub validated_at_unix: i64,
+}
+
 #[derive(Debug, Clone)]
 pub struct InstallEntry {
     /// Path relative to WoW root (preferred), e.g:
@@ -59,6 +81,28 @@ pub(crate) struct InstallBackupRow {
     pub displaced_repo_id: Option<i64>,
 }
 
+/// The records one release replacement writes when it commits.
+#[derive(Debug, Clone)]
+pub(crate) struct ReleaseReplacement {
+    pub repo_id: i64,
+    pub installs: Vec<InstallEntry>,
+    pub backups: Vec<InstallBackupRow>,
+    pub installed_asset: InstalledAssetState,
+    pub merge_installs: bool,
+    pub mark_manual: bool,
+}
+
+/// A replacement transaction's journal file. `committed` is set in the same
+/// SQLite transaction as the install records the replacement writes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct InstallJournalRow {
+    pub id: String,
+    pub path: String,
+    pub operation: String,
+    pub repo_id: i64,
+    pub committed: bool,
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct InstallPathOwner {
     pub repo_id: i64,
@@ -109,6 +153,136 @@ pub struct Db {
     conn: Connection,
 }
 
+fn mark_journal_committed(tx: &rusqlite::Transaction<'_>, journal_id: &str) -> Result<()> {
+    let marked = tx.execute(
+        "UPDATE install_journals SET committed=1 WHERE id=?1",
+        params![journal_id],
+    )?;
+    anyhow::ensure!(marked == 1, "The install journal is not registered");
+    Ok(())
+}
+
+fn write_release_replacement(
+    tx: &rusqlite::Transaction<'_>,
+    replacement: &ReleaseReplacement,
+) -> Result<()> {
+    let ReleaseReplacement {
+        repo_id,
+        installs,
+        backups,
+        installed_asset,
+        merge_installs,
+        mark_manual,
+    } = replacement;
+    if !*merge_installs {
+        tx.execute("DELETE FROM installs WHERE repo_id=?1", params![repo_id])?;
+    }
+    for install in installs {
+        tx.execute(
+            "DELETE FROM installs WHERE repo_id=?1 AND path=?2 COLLATE NOCASE",
+            params![repo_id, install.path],
+        )?;
+        tx.execute(
+            r#"
+            INSERT INTO installs(
+              repo_id, path, kind, sha256, version, display_name, file_fingerprint
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            "#,
+            params![
+                repo_id,
+                install.path,
+                install.kind,
+                install.sha256,
+                install.version,
+                install.display_name,
+                install.file_fingerprint
+            ],
+        )?;
+    }
+
+    tx.execute(
+        "DELETE FROM install_backups WHERE replacement_repo_id=?1",
+        params![repo_id],
+    )?;
+    for backup in backups {
+        tx.execute(
+            r#"
+            INSERT INTO install_backups(
+              replacement_repo_id, path, backup_path, kind, sha256, displaced_repo_id
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+            params![
+                repo_id,
+                backup.path,
+                backup.backup_path,
+                backup.kind,
+                backup.sha256,
+                backup.displaced_repo_id
+            ],
+        )?;
+    }
+
+    let updated = if *mark_manual {
+        tx.execute(
+            r#"
+            UPDATE repos
+            SET
+              url='',
+              forge='manual',
+              host='',
+              owner='',
+              mode='manual',
+              git_branch=NULL,
+              asset_regex=NULL,
+              last_version='Manual',
+              etag=NULL,
+              installed_asset_id=NULL,
+              installed_asset_name=NULL,
+              installed_asset_size=NULL,
+              installed_asset_url=NULL,
+              installed_at_unix=NULL,
+              published_at_unix=NULL,
+              pinned_version=NULL,
+              selected_addons_json=NULL
+            WHERE id=?1
+            "#,
+            params![repo_id],
+        )?
+    } else {
+        let safe_asset_url = installed_asset
+            .asset_url
+            .as_deref()
+            .map(crate::url_safety::sanitize_remote_for_storage);
+        tx.execute(
+            r#"
+            UPDATE repos
+            SET last_version=?1,
+                installed_asset_id=?2,
+                installed_asset_name=?3,
+                installed_asset_size=?4,
+                installed_asset_url=?5,
+                installed_at_unix=?6
+            WHERE id=?7
+            "#,
+            params![
+                installed_asset.version,
+                installed_asset.asset_id,
+                installed_asset.asset_name,
+                installed_asset.asset_size,
+                safe_asset_url,
+                installed_asset.installed_at_unix,
+                repo_id
+            ],
+        )?
+    };
+    if updated != 1 {
+        anyhow::bail!("The repository no longer exists in the profile database");
+    }
+    Ok(())
+}
+
 impl Db {
     pub fn open(path: &std::path::Path) -> Result<Self> {
         // Frontend services use separate short-lived Engine connections. Keep
@@ -503,6 +677,248 @@ impl Db {
             )?;
         }
 
+        // v21 -> v22: persist the last release payload per forge endpoint with
+        // its validators so restarts can revalidate instead of re-downloading.
+        if current < 22 {
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS release_cache (
+                  cache_key          TEXT PRIMARY KEY,
+                  etag               TEXT,
+                  last_modified      TEXT,
+                  release_json       TEXT NOT NULL,
+                  fetched_at_unix    INTEGER NOT NULL,
+                  validated_at_unix  INTEGER NOT NULL
+                );
+
+                PRAGMA user_version = 22;
+                "#,
+            )?;
+        }
+
+        // v22 -> v23: user-registered self-hosted GitLab/Gitea instances.
+        if current < 23 {
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS forge_hosts (
+                  host           TEXT PRIMARY KEY COLLATE NOCASE,
+                  kind           TEXT NOT NULL,
+                  api_base_path  TEXT
+                );
+
+                PRAGMA user_version = 23;
+                "#,
+            )?;
+        }
+
+        // v23 -> v24: addon_git repos may follow tags instead of a branch.
+        if current < 24 {
+            let cols = self.existing_repo_columns()?;
+            if !cols.contains("git_tag_policy") {
+                self.conn
+                    .execute_batch("ALTER TABLE repos ADD COLUMN git_tag_policy TEXT")?;
+            }
+            self.conn.execute_batch("PRAGMA user_version = 24")?;
+        }
+
+        // v24 -> v25: parsed .toc metadata per installed addon folder.
+        if current < 25 {
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS addon_metadata (
+                  repo_id   INTEGER NOT NULL,
+                  path      TEXT NOT NULL,
+                  toc_json  TEXT NOT NULL,
+                  PRIMARY KEY(repo_id, path),
+                  FOREIGN KEY(repo_id) REFERENCES repos(id) ON DELETE CASCADE
+                );
+
+                PRAGMA user_version = 25;
+                "#,
+            )?;
+        }
+
+        // v25 -> v26: named addon sets of repos and untracked addon folders.
+        if current < 26 {
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS addon_sets (
+                  id    INTEGER PRIMARY KEY AUTOINCREMENT,
+                  name  TEXT NOT NULL UNIQUE COLLATE NOCASE
+                );
+
+                CREATE TABLE IF NOT EXISTS addon_set_repos (
+                  set_id   INTEGER NOT NULL,
+                  repo_id  INTEGER NOT NULL,
+                  enabled  INTEGER NOT NULL,
+                  PRIMARY KEY(set_id, repo_id),
+                  FOREIGN KEY(set_id) REFERENCES addon_sets(id) ON DELETE CASCADE,
+                  FOREIGN KEY(repo_id) REFERENCES repos(id) ON DELETE CASCADE
+                );
+
+                CREATE TABLE IF NOT EXISTS addon_set_folders (
+                  set_id   INTEGER NOT NULL,
+                  folder   TEXT NOT NULL COLLATE NOCASE,
+                  enabled  INTEGER NOT NULL,
+                  PRIMARY KEY(set_id, folder),
+                  FOREIGN KEY(set_id) REFERENCES addon_sets(id) ON DELETE CASCADE
+                );
+
+                PRAGMA user_version = 26;
+                "#,
+            )?;
+        }
+
+        // v26 -> v27: installs and updates, linked to the SavedVariables
+        // snapshot taken before the files were replaced.
+        if current < 27 {
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS install_events (
+                  id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+                  repo_id            INTEGER NOT NULL,
+                  created_unix       INTEGER NOT NULL,
+                  from_version       TEXT,
+                  to_version         TEXT NOT NULL,
+                  settings_snapshot  TEXT,
+                  FOREIGN KEY(repo_id) REFERENCES repos(id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_install_events_repo
+                  ON install_events(repo_id);
+
+                PRAGMA user_version = 27;
+                "#,
+            )?;
+        }
+
+        // v27 -> v28: catalog of known addon sources and their fingerprints.
+        if current < 28 {
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS addon_catalog (
+                  source       TEXT NOT NULL,
+                  url          TEXT NOT NULL COLLATE NOCASE,
+                  folder       TEXT NOT NULL COLLATE NOCASE,
+                  version      TEXT NOT NULL DEFAULT '',
+                  mode         TEXT,
+                  toc_sha256   TEXT,
+                  tree_sha256  TEXT,
+                  PRIMARY KEY(source, url, folder, version)
+                );
+
+                PRAGMA user_version = 28;
+                "#,
+            )?;
+        }
+
+        // v28 -> v29: curated addon catalogs, searchable offline.
+        if current < 29 {
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS addon_index_sources (
+                  id            INTEGER PRIMARY KEY,
+                  name          TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                  url           TEXT,
+                  updated_unix  INTEGER NOT NULL
+                );
+
+                CREATE TABLE IF NOT EXISTS addon_index_entries (
+                  source_id        INTEGER NOT NULL,
+                  url              TEXT NOT NULL COLLATE NOCASE,
+                  name             TEXT NOT NULL,
+                  description      TEXT NOT NULL DEFAULT '',
+                  kind             TEXT NOT NULL,
+                  clients_json     TEXT NOT NULL DEFAULT '[]',
+                  categories_json  TEXT NOT NULL DEFAULT '[]',
+                  folders_json     TEXT NOT NULL DEFAULT '[]',
+                  PRIMARY KEY(source_id, url),
+                  FOREIGN KEY(source_id) REFERENCES addon_index_sources(id) ON DELETE CASCADE
+                );
+
+                PRAGMA user_version = 29;
+                "#,
+            )?;
+        }
+
+        if current < 30 {
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS install_journals (
+                  id            TEXT PRIMARY KEY,
+                  path          TEXT NOT NULL,
+                  operation     TEXT NOT NULL,
+                  repo_id       INTEGER NOT NULL,
+                  created_unix  INTEGER NOT NULL,
+                  committed     INTEGER NOT NULL DEFAULT 0
+                );
+
+                PRAGMA user_version = 30;
+                "#,
+            )?;
+        }
+
+        // v30 -> v31: install history that survives removing the repo. Earlier
+        // install events are carried over without their asset details.
+        if current < 31 {
+            self.conn.execute_batch(
+                r#"
+                CREATE TABLE IF NOT EXISTS install_history (
+                  id                 INTEGER PRIMARY KEY AUTOINCREMENT,
+                  repo_id            INTEGER NOT NULL,
+                  action             TEXT NOT NULL,
+                  created_unix       INTEGER NOT NULL,
+                  from_version       TEXT,
+                  to_version         TEXT,
+                  asset_id           TEXT,
+                  asset_name         TEXT,
+                  asset_url          TEXT,
+                  asset_sha256       TEXT,
+                  extra_assets_json  TEXT
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_install_history_repo
+                  ON install_history(repo_id);
+
+                INSERT INTO install_history(repo_id, action, created_unix, from_version, to_version)
+                SELECT repo_id,
+                       CASE WHEN from_version IS NULL THEN 'install' ELSE 'update' END,
+                       created_unix, from_version, to_version
+                FROM install_events ORDER BY id;
+
+                PRAGMA user_version = 31;
+                "#,
+            )?;
+        }
+
+        // v31 -> v32: install history records the settings snapshot, so
+        // install_events is folded into it and dropped.
+        if current < 32 {
+            if !self
+                .existing_install_history_columns()?
+                .contains("settings_snapshot")
+            {
+                self.conn.execute_batch(
+                    "ALTER TABLE install_history ADD COLUMN settings_snapshot TEXT",
+                )?;
+            }
+            self.conn.execute_batch(
+                r#"
+                UPDATE install_history SET settings_snapshot = (
+                  SELECT e.settings_snapshot FROM install_events e
+                  WHERE e.repo_id = install_history.repo_id
+                    AND e.created_unix = install_history.created_unix
+                    AND e.to_version = install_history.to_version
+                  ORDER BY e.id DESC LIMIT 1
+                );
+
+                DROP TABLE IF EXISTS install_events;
+
+                PRAGMA user_version = 32;
+                "#,
+            )?;
+        }
+
         Ok(())
     }
 
@@ -761,6 +1177,14 @@ impl Db {
         Ok(names.into_iter().collect())
     }
 
+    fn existing_install_history_columns(&self) -> Result<HashSet<String>> {
+        let mut stmt = self.conn.prepare("PRAGMA table_info(install_history)")?;
+        let names = stmt
+            .query_map([], |row| row.get::<_, String>(1))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        Ok(names.into_iter().collect())
+    }
+
     fn existing_mpq_protection_columns(&self) -> Result<HashSet<String>> {
         let mut stmt = self.conn.prepare("PRAGMA table_info(mpq_protection)")?;
         let names = stmt
@@ -812,6 +1236,10 @@ impl Db {
             "selected_addons_json",
             "ALTER TABLE repos ADD COLUMN selected_addons_json TEXT",
         )?;
+        ensure(
+            "git_tag_policy",
+            "ALTER TABLE repos ADD COLUMN git_tag_policy TEXT",
+        )?;
         Ok(())
     }
 
@@ -828,12 +1256,12 @@ impl Db {
             INSERT INTO repos(
               url, forge, host, owner, name, mode, enabled, git_branch, asset_regex, last_version, etag,
               installed_asset_id, installed_asset_name, installed_asset_size, installed_asset_url, installed_at_unix,
-                            published_at_unix, merge_installs, pinned_version, selected_addons_json
+                            published_at_unix, merge_installs, pinned_version, selected_addons_json, git_tag_policy
             )
             VALUES (
               ?1,  ?2,   ?3,   ?4,    ?5,   ?6,   ?7,      ?8,         ?9,         ?10,         ?11,
               ?12,               ?13,                 ?14,                  ?15,                 ?16,
-                            ?17, ?18, ?19, ?20
+                            ?17, ?18, ?19, ?20, ?21
             )
             "#,
             params![
@@ -857,6 +1285,7 @@ impl Db {
                 if repo.merge_installs { 1 } else { 0 },
                 repo.pinned_version,
                 repo.selected_addons_json,
+                repo.git_tag_policy.as_ref().map(GitTagPolicy::to_storage),
             ],
         );
 
@@ -919,7 +1348,7 @@ impl Db {
             SELECT
               id, url, forge, host, owner, name, mode, enabled, git_branch, asset_regex, last_version, etag,
               installed_asset_id, installed_asset_name, installed_asset_size, installed_asset_url, installed_at_unix,
-                            published_at_unix, merge_installs, pinned_version, selected_addons_json
+                            published_at_unix, merge_installs, pinned_version, selected_addons_json, git_tag_policy
             FROM repos
             ORDER BY host, owner, name
             "#,
@@ -949,6 +1378,10 @@ impl Db {
                 merge_installs: row.get::<_, i64>(18).unwrap_or(0) != 0,
                 pinned_version: row.get(19)?,
                 selected_addons_json: row.get(20)?,
+                git_tag_policy: row
+                    .get::<_, Option<String>>(21)?
+                    .as_deref()
+                    .and_then(GitTagPolicy::from_storage),
             })
         })?;
 
@@ -970,7 +1403,7 @@ impl Db {
             SELECT
               id, url, forge, host, owner, name, mode, enabled, git_branch, asset_regex, last_version, etag,
               installed_asset_id, installed_asset_name, installed_asset_size, installed_asset_url, installed_at_unix,
-                            published_at_unix, merge_installs, pinned_version, selected_addons_json
+                            published_at_unix, merge_installs, pinned_version, selected_addons_json, git_tag_policy
             FROM repos
             WHERE host=?1 COLLATE NOCASE AND owner=?2 COLLATE NOCASE AND name=?3 COLLATE NOCASE
             LIMIT 1
@@ -1001,6 +1434,10 @@ impl Db {
                 merge_installs: row.get::<_, i64>(18).unwrap_or(0) != 0,
                 pinned_version: row.get(19)?,
                 selected_addons_json: row.get(20)?,
+                git_tag_policy: row
+                    .get::<_, Option<String>>(21)?
+                    .as_deref()
+                    .and_then(GitTagPolicy::from_storage),
             })
         })?;
 
@@ -1017,7 +1454,7 @@ impl Db {
             SELECT
               id, url, forge, host, owner, name, mode, enabled, git_branch, asset_regex, last_version, etag,
               installed_asset_id, installed_asset_name, installed_asset_size, installed_asset_url, installed_at_unix,
-                            published_at_unix, merge_installs, pinned_version, selected_addons_json
+                            published_at_unix, merge_installs, pinned_version, selected_addons_json, git_tag_policy
             FROM repos
             WHERE id=?1
             "#,
@@ -1047,6 +1484,10 @@ impl Db {
                 merge_installs: row.get::<_, i64>(18).unwrap_or(0) != 0,
                 pinned_version: row.get(19)?,
                 selected_addons_json: row.get(20)?,
+                git_tag_policy: row
+                    .get::<_, Option<String>>(21)?
+                    .as_deref()
+                    .and_then(GitTagPolicy::from_storage),
             })
         });
 
@@ -1092,6 +1533,14 @@ impl Db {
         Ok(())
     }
 
+    pub fn set_repo_git_tag_policy(&self, id: i64, policy: Option<&GitTagPolicy>) -> Result<()> {
+        self.conn.execute(
+            r#"UPDATE repos SET git_tag_policy=?1 WHERE id=?2"#,
+            params![policy.map(GitTagPolicy::to_storage), id],
+        )?;
+        Ok(())
+    }
+
     pub fn set_repo_release_source(
         &self,
         id: i64,
@@ -1438,146 +1887,55 @@ impl Db {
             .map_err(Into::into)
     }
 
-    #[allow(clippy::too_many_arguments)]
     pub(crate) fn commit_release_replacement(
         &self,
-        repo_id: i64,
-        installs: &[InstallEntry],
-        backups: &[InstallBackupRow],
-        installed_asset: &InstalledAssetState,
-        merge_installs: bool,
-        mark_manual: bool,
+        replacement: &ReleaseReplacement,
+        journal_id: Option<&str>,
+    ) -> Result<()> {
+        self.commit_release_replacements(std::slice::from_ref(replacement), journal_id)
+    }
+
+    /// Record several release replacements deployed under one transaction.
+    /// Either every repo's records change or none do.
+    pub(crate) fn commit_release_replacements(
+        &self,
+        replacements: &[ReleaseReplacement],
+        journal_id: Option<&str>,
     ) -> Result<()> {
         let tx = self.conn.unchecked_transaction()?;
-        if !merge_installs {
-            tx.execute("DELETE FROM installs WHERE repo_id=?1", params![repo_id])?;
+        for replacement in replacements {
+            write_release_replacement(&tx, replacement)?;
         }
-        for install in installs {
-            tx.execute(
-                "DELETE FROM installs WHERE repo_id=?1 AND path=?2 COLLATE NOCASE",
-                params![repo_id, install.path],
-            )?;
+        if let Some(journal_id) = journal_id {
+            mark_journal_committed(&tx, journal_id)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub(crate) fn remove_repo_with_displaced_backups(
+        &self,
+        repo_id: i64,
+        delete_displaced_backups: bool,
+        journal_id: Option<&str>,
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        if delete_displaced_backups {
             tx.execute(
-                r#"
-                INSERT INTO installs(
-                  repo_id, path, kind, sha256, version, display_name, file_fingerprint
-                )
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
-                "#,
-                params![
-                    repo_id,
-                    install.path,
-                    install.kind,
-                    install.sha256,
-                    install.version,
-                    install.display_name,
-                    install.file_fingerprint
-                ],
+                "DELETE FROM install_backups WHERE displaced_repo_id=?1",
+                params![repo_id],
             )?;
         }
-
-        tx.execute(
-            "DELETE FROM install_backups WHERE replacement_repo_id=?1",
-            params![repo_id],
-        )?;
-        for backup in backups {
-            tx.execute(
-                r#"
-                INSERT INTO install_backups(
-                  replacement_repo_id, path, backup_path, kind, sha256, displaced_repo_id
-                )
-                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
-                "#,
-                params![
-                    repo_id,
-                    backup.path,
-                    backup.backup_path,
-                    backup.kind,
-                    backup.sha256,
-                    backup.displaced_repo_id
-                ],
-            )?;
-        }
-
-        let updated = if mark_manual {
-            tx.execute(
-                r#"
-                UPDATE repos
-                SET
-                  url='',
-                  forge='manual',
-                  host='',
-                  owner='',
-                  mode='manual',
-                  git_branch=NULL,
-                  asset_regex=NULL,
-                  last_version='Manual',
-                  etag=NULL,
-                  installed_asset_id=NULL,
-                  installed_asset_name=NULL,
-                  installed_asset_size=NULL,
-                  installed_asset_url=NULL,
-                  installed_at_unix=NULL,
-                  published_at_unix=NULL,
-                  pinned_version=NULL,
-                  selected_addons_json=NULL
-                WHERE id=?1
-                "#,
-                params![repo_id],
-            )?
-        } else {
-            let safe_asset_url = installed_asset
-                .asset_url
-                .as_deref()
-                .map(crate::url_safety::sanitize_remote_for_storage);
-            tx.execute(
-                r#"
-                UPDATE repos
-                SET last_version=?1,
-                    installed_asset_id=?2,
-                    installed_asset_name=?3,
-                    installed_asset_size=?4,
-                    installed_asset_url=?5,
-                    installed_at_unix=?6
-                WHERE id=?7
-                "#,
-                params![
-                    installed_asset.version,
-                    installed_asset.asset_id,
-                    installed_asset.asset_name,
-                    installed_asset.asset_size,
-                    safe_asset_url,
-                    installed_asset.installed_at_unix,
-                    repo_id
-                ],
-            )?
-        };
-        if updated != 1 {
-            anyhow::bail!("The repository no longer exists in the profile database");
-        }
-        tx.commit()?;
-        Ok(())
-    }
-
-    pub(crate) fn remove_repo_with_displaced_backups(
-        &self,
-        repo_id: i64,
-        delete_displaced_backups: bool,
-    ) -> Result<()> {
-        let tx = self.conn.unchecked_transaction()?;
-        if delete_displaced_backups {
-            tx.execute(
-                "DELETE FROM install_backups WHERE displaced_repo_id=?1",
-                params![repo_id],
-            )?;
-        }
-        let removed = tx.execute("DELETE FROM repos WHERE id=?1", params![repo_id])?;
-        if removed != 1 {
-            anyhow::bail!("The repository no longer exists in the profile database");
-        }
-        tx.commit()?;
-        Ok(())
-    }
+        let removed = tx.execute("DELETE FROM repos WHERE id=?1", params![repo_id])?;
+        if removed != 1 {
+            anyhow::bail!("The repository no longer exists in the profile database");
+        }
+        if let Some(journal_id) = journal_id {
+            mark_journal_committed(&tx, journal_id)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
 
     pub(crate) fn commit_addon_git_replacement(
         &self,
@@ -1816,6 +2174,49 @@ impl Db {
         }
     }
 
+    /// Replace the stored `.toc` metadata of a repo's addon folders. `path`
+    /// is the install path, as in `installs`.
+    /// `None` records a folder without a readable `.toc`, so listings do not
+    /// go back to disk for it.
+    pub fn replace_addon_metadata(
+        &self,
+        repo_id: i64,
+        entries: &[(String, Option<AddonToc>)],
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM addon_metadata WHERE repo_id=?1",
+            params![repo_id],
+        )?;
+        for (path, toc) in entries {
+            tx.execute(
+                "INSERT OR REPLACE INTO addon_metadata(repo_id, path, toc_json) VALUES (?1, ?2, ?3)",
+                params![repo_id, path, serde_json::to_string(toc)?],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn list_addon_metadata(&self, repo_id: i64) -> Result<Vec<(String, Option<AddonToc>)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT path, toc_json FROM addon_metadata WHERE repo_id=?1 ORDER BY path")?;
+        let rows = stmt.query_map(params![repo_id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (path, json) = row?;
+            // A row that no longer parses is skipped; the next refresh
+            // rewrites it.
+            if let Ok(toc) = serde_json::from_str(&json) {
+                out.push((path, toc));
+            }
+        }
+        Ok(out)
+    }
+
     pub fn ensure_mpq_package_display_name(&self, repo_id: i64, display_name: &str) -> Result<()> {
         self.conn.execute(
             r#"
@@ -2233,6 +2634,462 @@ impl Db {
             .map_err(Into::into)
     }
 
+    /// Repos that depend on `child_repo_id`, with the relationship.
+    pub fn list_repo_dependents(&self, child_repo_id: i64) -> Result<Vec<(i64, String)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT parent_repo_id, relationship FROM repo_dependencies
+            WHERE child_repo_id=?1 ORDER BY parent_repo_id
+            "#,
+        )?;
+        let rows = stmt.query_map(params![child_repo_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    /// Replace every `relationship` row with the `(parent, child)` pairs in
+    /// `links`.
+    pub fn replace_repo_dependencies(
+        &self,
+        relationship: &str,
+        links: &[(i64, i64)],
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM repo_dependencies WHERE relationship=?1",
+            params![relationship],
+        )?;
+        for (parent, child) in links {
+            tx.execute(
+                r#"
+                INSERT OR IGNORE INTO repo_dependencies(parent_repo_id, child_repo_id, relationship)
+                VALUES (?1, ?2, ?3)
+                "#,
+                params![parent, child, relationship],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Every addon set with its entries, ordered by name.
+    pub fn list_addon_sets(&self) -> Result<Vec<AddonSet>> {
+        let sets = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT id, name FROM addon_sets ORDER BY name")?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?;
+            rows.collect::<std::result::Result<Vec<_>, _>>()?
+        };
+        let mut out = Vec::with_capacity(sets.len());
+        for (id, name) in sets {
+            let mut stmt = self.conn.prepare(
+                "SELECT repo_id, enabled FROM addon_set_repos WHERE set_id=?1 ORDER BY repo_id",
+            )?;
+            let repos = stmt
+                .query_map(params![id], |row| {
+                    Ok(AddonSetRepo {
+                        repo_id: row.get(0)?,
+                        enabled: row.get::<_, i64>(1)? != 0,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            let mut stmt = self.conn.prepare(
+                "SELECT folder, enabled FROM addon_set_folders WHERE set_id=?1 ORDER BY folder",
+            )?;
+            let folders = stmt
+                .query_map(params![id], |row| {
+                    Ok(AddonSetFolder {
+                        folder: row.get(0)?,
+                        enabled: row.get::<_, i64>(1)? != 0,
+                    })
+                })?
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+            out.push(AddonSet {
+                name,
+                repos,
+                folders,
+            });
+        }
+        Ok(out)
+    }
+
+    /// Create `set`, or replace the entries of the set with the same name.
+    pub fn save_addon_set(&self, set: &AddonSet) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO addon_sets(name) VALUES (?1) ON CONFLICT(name) DO UPDATE SET name=excluded.name",
+            params![set.name],
+        )?;
+        let id: i64 = tx.query_row(
+            "SELECT id FROM addon_sets WHERE name=?1",
+            params![set.name],
+            |row| row.get(0),
+        )?;
+        tx.execute("DELETE FROM addon_set_repos WHERE set_id=?1", params![id])?;
+        tx.execute("DELETE FROM addon_set_folders WHERE set_id=?1", params![id])?;
+        for repo in &set.repos {
+            tx.execute(
+                "INSERT OR REPLACE INTO addon_set_repos(set_id, repo_id, enabled) VALUES (?1, ?2, ?3)",
+                params![id, repo.repo_id, repo.enabled as i64],
+            )?;
+        }
+        for folder in &set.folders {
+            tx.execute(
+                "INSERT OR REPLACE INTO addon_set_folders(set_id, folder, enabled) VALUES (?1, ?2, ?3)",
+                params![id, folder.folder, folder.enabled as i64],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Returns whether a set of that name existed.
+    pub fn delete_addon_set(&self, name: &str) -> Result<bool> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM addon_sets WHERE name=?1", params![name])?;
+        Ok(removed > 0)
+    }
+
+    /// Append to the install history of `entry.repo_id`, keeping only the
+    /// newest [`INSTALL_HISTORY_KEPT`] entries of that repo. Returns the new id.
+    pub fn add_history_entry(&self, entry: &InstallHistoryEntry) -> Result<i64> {
+        let extra_assets = if entry.extra_assets.is_empty() {
+            None
+        } else {
+            Some(serde_json::to_string(&entry.extra_assets)?)
+        };
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            r#"
+            INSERT INTO install_history(
+              repo_id, action, created_unix, from_version, to_version,
+              asset_id, asset_name, asset_url, asset_sha256, extra_assets_json,
+              settings_snapshot
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)
+            "#,
+            params![
+                entry.repo_id,
+                entry.action.as_str(),
+                entry.created_unix,
+                entry.from_version,
+                entry.to_version,
+                entry.asset_id,
+                entry.asset_name,
+                entry
+                    .asset_url
+                    .as_deref()
+                    .map(crate::url_safety::sanitize_remote_for_storage),
+                entry.asset_sha256,
+                extra_assets,
+                entry.settings_snapshot
+            ],
+        )?;
+        let id = tx.last_insert_rowid();
+        tx.execute(
+            r#"
+            DELETE FROM install_history
+            WHERE repo_id=?1 AND id NOT IN (
+              SELECT id FROM install_history WHERE repo_id=?1 ORDER BY id DESC LIMIT ?2
+            )
+            "#,
+            params![entry.repo_id, INSTALL_HISTORY_KEPT],
+        )?;
+        tx.commit()?;
+        Ok(id)
+    }
+
+    /// Install history of a repo, newest first.
+    pub fn list_install_history(&self, repo_id: i64) -> Result<Vec<InstallHistoryEntry>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, repo_id, action, created_unix, from_version, to_version,
+                   asset_id, asset_name, asset_url, asset_sha256, extra_assets_json,
+                   settings_snapshot
+            FROM install_history WHERE repo_id=?1 ORDER BY id DESC
+            "#,
+        )?;
+        let rows = stmt.query_map(params![repo_id], Self::history_entry_row)?;
+        Ok(rows.collect::<std::result::Result<Vec<_>, _>>()?)
+    }
+
+    pub fn get_history_entry(&self, id: i64) -> Result<InstallHistoryEntry> {
+        self.conn
+            .query_row(
+                r#"
+                SELECT id, repo_id, action, created_unix, from_version, to_version,
+                       asset_id, asset_name, asset_url, asset_sha256, extra_assets_json,
+                       settings_snapshot
+                FROM install_history WHERE id=?1
+                "#,
+                params![id],
+                Self::history_entry_row,
+            )
+            .with_context(|| format!("No install history entry with id {id}"))
+    }
+
+    fn history_entry_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<InstallHistoryEntry> {
+        let action: String = row.get(2)?;
+        let extra_assets: Option<String> = row.get(10)?;
+        Ok(InstallHistoryEntry {
+            id: row.get(0)?,
+            repo_id: row.get(1)?,
+            action: HistoryAction::parse(&action).unwrap_or(HistoryAction::Update),
+            created_unix: row.get(3)?,
+            from_version: row.get(4)?,
+            to_version: row.get(5)?,
+            asset_id: row.get(6)?,
+            asset_name: row.get(7)?,
+            asset_url: row.get(8)?,
+            asset_sha256: row.get(9)?,
+            extra_assets: extra_assets
+                .and_then(|raw| serde_json::from_str::<Vec<ReleaseAsset>>(&raw).ok())
+                .unwrap_or_default(),
+            settings_snapshot: row.get(11)?,
+        })
+    }
+
+    /// Add or refresh catalog entries. An entry is identified by its source,
+    /// URL, folder and version.
+    pub fn upsert_catalog_entries(
+        &self,
+        source: CatalogSource,
+        entries: &[CatalogEntry],
+    ) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        for entry in entries {
+            tx.execute(
+                r#"
+                INSERT INTO addon_catalog(source, url, folder, version, mode, toc_sha256, tree_sha256)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                ON CONFLICT(source, url, folder, version) DO UPDATE SET
+                  mode=excluded.mode,
+                  toc_sha256=excluded.toc_sha256,
+                  tree_sha256=excluded.tree_sha256
+                "#,
+                params![
+                    source.as_str(),
+                    entry.url,
+                    entry.folder,
+                    entry.version.as_deref().unwrap_or_default(),
+                    entry.mode,
+                    entry.toc_sha256,
+                    entry.tree_sha256
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Catalog entries, optionally of one source only.
+    pub fn list_catalog_entries(
+        &self,
+        source: Option<CatalogSource>,
+    ) -> Result<Vec<(CatalogSource, CatalogEntry)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT source, url, folder, version, mode, toc_sha256, tree_sha256
+            FROM addon_catalog
+            WHERE ?1 IS NULL OR source=?1
+            ORDER BY url, folder, version
+            "#,
+        )?;
+        let rows = stmt.query_map(params![source.map(CatalogSource::as_str)], |row| {
+            let version: String = row.get(3)?;
+            Ok((
+                row.get::<_, String>(0)?,
+                CatalogEntry {
+                    url: row.get(1)?,
+                    folder: row.get(2)?,
+                    version: (!version.is_empty()).then_some(version),
+                    mode: row.get(4)?,
+                    toc_sha256: row.get(5)?,
+                    tree_sha256: row.get(6)?,
+                },
+            ))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (source, entry) = row?;
+            if let Some(source) = CatalogSource::parse(&source) {
+                out.push((source, entry));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Store `file` as the catalog `name`, replacing the entries a catalog
+    /// of that name had. Returns the catalog's id.
+    pub fn replace_addon_index(
+        &self,
+        name: &str,
+        url: Option<&str>,
+        updated_unix: i64,
+        file: &IndexFile,
+    ) -> Result<i64> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            r#"
+            INSERT INTO addon_index_sources(name, url, updated_unix) VALUES (?1, ?2, ?3)
+            ON CONFLICT(name) DO UPDATE SET url=excluded.url, updated_unix=excluded.updated_unix
+            "#,
+            params![name, url, updated_unix],
+        )?;
+        let id: i64 = tx.query_row(
+            "SELECT id FROM addon_index_sources WHERE name=?1",
+            params![name],
+            |row| row.get(0),
+        )?;
+        tx.execute(
+            "DELETE FROM addon_index_entries WHERE source_id=?1",
+            params![id],
+        )?;
+        for entry in &file.entries {
+            tx.execute(
+                r#"
+                INSERT OR REPLACE INTO addon_index_entries(
+                  source_id, url, name, description, kind, clients_json, categories_json, folders_json
+                )
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                "#,
+                params![
+                    id,
+                    entry.url,
+                    entry.name,
+                    entry.description,
+                    entry.kind.as_str(),
+                    serde_json::to_string(&entry.clients)?,
+                    serde_json::to_string(&entry.categories)?,
+                    serde_json::to_string(&entry.folders)?
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(id)
+    }
+
+    pub fn list_addon_index_sources(&self) -> Result<Vec<IndexSource>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT s.id, s.name, s.url, s.updated_unix,
+                   (SELECT COUNT(*) FROM addon_index_entries e WHERE e.source_id = s.id)
+            FROM addon_index_sources s
+            ORDER BY s.name
+            "#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(IndexSource {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                url: row.get(2)?,
+                updated_unix: row.get(3)?,
+                entries: row.get::<_, i64>(4)? as usize,
+            })
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            out.push(row?);
+        }
+        Ok(out)
+    }
+
+    /// Record a replacement journal before its transaction touches files.
+    pub(crate) fn register_install_journal(
+        &self,
+        id: &str,
+        path: &str,
+        operation: &str,
+        repo_id: i64,
+        created_unix: i64,
+    ) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO install_journals(id, path, operation, repo_id, created_unix)
+            VALUES (?1, ?2, ?3, ?4, ?5)
+            "#,
+            params![id, path, operation, repo_id, created_unix],
+        )?;
+        Ok(())
+    }
+
+    pub(crate) fn list_install_journals(&self) -> Result<Vec<InstallJournalRow>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, path, operation, repo_id, committed
+            FROM install_journals
+            ORDER BY created_unix, id
+            "#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(InstallJournalRow {
+                id: row.get(0)?,
+                path: row.get(1)?,
+                operation: row.get(2)?,
+                repo_id: row.get(3)?,
+                committed: row.get::<_, i64>(4)? != 0,
+            })
+        })?;
+        rows.collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(Into::into)
+    }
+
+    pub(crate) fn remove_install_journal(&self, id: &str) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM install_journals WHERE id=?1", params![id])?;
+        Ok(())
+    }
+
+    /// Returns whether a catalog with that id existed.
+    pub fn remove_addon_index_source(&self, id: i64) -> Result<bool> {
+        let removed = self
+            .conn
+            .execute("DELETE FROM addon_index_sources WHERE id=?1", params![id])?;
+        Ok(removed > 0)
+    }
+
+    /// Every catalog entry, the oldest catalog's first.
+    pub fn list_addon_index_entries(&self) -> Result<Vec<IndexEntry>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT url, name, description, kind, clients_json, categories_json, folders_json
+            FROM addon_index_entries
+            ORDER BY source_id, name COLLATE NOCASE
+            "#,
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                IndexEntry {
+                    url: row.get(0)?,
+                    name: row.get(1)?,
+                    description: row.get(2)?,
+                    kind: IndexKind::Addon,
+                    clients: Vec::new(),
+                    categories: Vec::new(),
+                    folders: Vec::new(),
+                },
+                row.get::<_, String>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, String>(5)?,
+                row.get::<_, String>(6)?,
+            ))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (mut entry, kind, clients, categories, folders) = row?;
+            entry.kind = IndexKind::parse(&kind).unwrap_or_default();
+            entry.clients = serde_json::from_str(&clients).unwrap_or_default();
+            entry.categories = serde_json::from_str(&categories).unwrap_or_default();
+            entry.folders = serde_json::from_str(&folders).unwrap_or_default();
+            out.push(entry);
+        }
+        Ok(out)
+    }
+
     pub fn set_install_sha256(&self, repo_id: i64, path: &str, sha256: Option<&str>) -> Result<()> {
         self.conn.execute(
             r#"UPDATE installs SET sha256=?1 WHERE repo_id=?2 AND path=?3"#,
@@ -2376,14 +3233,149 @@ impl Db {
             .execute("DELETE FROM rate_limits WHERE host=?1", params![host])?;
         Ok(())
     }
+
+    pub fn list_forge_hosts(&self) -> Result<Vec<CustomForgeHost>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT host, kind, api_base_path FROM forge_hosts ORDER BY host")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+            ))
+        })?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (host, kind, api_base_path) = row?;
+            // Rows with a kind this build does not understand are skipped
+            // rather than failing every engine open.
+            if let Some(kind) = ForgeKind::parse_custom(&kind) {
+                out.push(CustomForgeHost {
+                    host,
+                    kind,
+                    api_base_path,
+                });
+            }
+        }
+        Ok(out)
+    }
+
+    pub fn upsert_forge_host(&self, host: &CustomForgeHost) -> Result<()> {
+        let kind = match host.kind {
+            ForgeKind::GitLab => "gitlab",
+            ForgeKind::Gitea => "gitea",
+            ForgeKind::GitHub | ForgeKind::Generic => {
+                anyhow::bail!("only GitLab and Gitea hosts can be registered")
+            }
+        };
+        self.conn.execute(
+            r#"
+            INSERT INTO forge_hosts(host, kind, api_base_path)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT(host) DO UPDATE SET
+              kind=excluded.kind,
+              api_base_path=excluded.api_base_path
+            "#,
+            params![host.host, kind, host.api_base_path],
+        )?;
+        Ok(())
+    }
+
+    pub fn delete_forge_host(&self, host: &str) -> Result<bool> {
+        let n = self
+            .conn
+            .execute("DELETE FROM forge_hosts WHERE host=?1", params![host])?;
+        Ok(n > 0)
+    }
+
+    /// Relabel the stored forge of every repository on `host` that currently
+    /// carries the `from` label.
+    pub fn relabel_repo_forge(&self, host: &str, from: &str, to: &str) -> Result<usize> {
+        let n = self.conn.execute(
+            "UPDATE repos SET forge=?3 WHERE host=?1 COLLATE NOCASE AND forge=?2",
+            params![host, from, to],
+        )?;
+        Ok(n)
+    }
+
+    pub fn get_cached_release(&self, cache_key: &str) -> Result<Option<CachedReleaseRow>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT etag, last_modified, release_json, fetched_at_unix, validated_at_unix
+            FROM release_cache WHERE cache_key=?1
+            "#,
+        )?;
+        let mut rows = stmt.query(params![cache_key])?;
+        let Some(row) = rows.next()? else {
+            return Ok(None);
+        };
+        let release_json: String = row.get(2)?;
+        // A payload written by a different model version is only a cache
+        // miss; the next check simply downloads it again.
+        let Ok(release) = serde_json::from_str::<LatestRelease>(&release_json) else {
+            return Ok(None);
+        };
+        Ok(Some(CachedReleaseRow {
+            etag: row.get(0)?,
+            last_modified: row.get(1)?,
+            release,
+            fetched_at_unix: row.get(3)?,
+            validated_at_unix: row.get(4)?,
+        }))
+    }
+
+    pub fn put_cached_release(
+        &self,
+        cache_key: &str,
+        etag: Option<&str>,
+        last_modified: Option<&str>,
+        release: &LatestRelease,
+        fetched_at_unix: i64,
+    ) -> Result<()> {
+        let release_json = serde_json::to_string(release)?;
+        self.conn.execute(
+            r#"
+            INSERT INTO release_cache(
+              cache_key, etag, last_modified, release_json, fetched_at_unix, validated_at_unix
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?5)
+            ON CONFLICT(cache_key) DO UPDATE SET
+              etag=excluded.etag,
+              last_modified=excluded.last_modified,
+              release_json=excluded.release_json,
+              fetched_at_unix=excluded.fetched_at_unix,
+              validated_at_unix=excluded.validated_at_unix
+            "#,
+            params![
+                cache_key,
+                etag,
+                last_modified,
+                release_json,
+                fetched_at_unix
+            ],
+        )?;
+        Ok(())
+    }
+
+    pub fn mark_cached_release_validated(&self, cache_key: &str, now_unix: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE release_cache SET validated_at_unix=?2 WHERE cache_key=?1",
+            params![cache_key, now_unix],
+        )?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
         AddonConflictMutation, Db, InstallBackupRow, InstallEntry, InstalledAssetState,
-        SCHEMA_VERSION,
+        ReleaseReplacement, INSTALL_HISTORY_KEPT, SCHEMA_VERSION,
     };
+    use crate::addon_index::{IndexEntry, IndexFile, IndexKind};
+    use crate::model::{HistoryAction, InstallHistoryEntry, ReleaseAsset};
+    use crate::toc::ClientFamily;
     use rusqlite::{params, Connection};
     use std::sync::{Arc, Barrier};
 
@@ -2794,29 +3786,32 @@ mod tests {
             .unwrap();
 
         let result = db.commit_release_replacement(
-            replacement_id,
-            &[InstallEntry {
-                path: "Shared.dll".to_string(),
-                kind: "dll".to_string(),
-                sha256: Some("new".to_string()),
-                version: Some("v2".to_string()),
-                display_name: None,
-                file_fingerprint: None,
-            }],
-            &[InstallBackupRow {
-                replacement_repo_id: replacement_id,
-                path: "Shared.dll".to_string(),
-                backup_path: ".wuddle/backups/shared.dll".to_string(),
-                kind: "dll".to_string(),
-                sha256: Some("old".to_string()),
-                displaced_repo_id: Some(owner_id),
-            }],
-            &InstalledAssetState {
-                version: Some("v2".to_string()),
-                ..InstalledAssetState::default()
+            &ReleaseReplacement {
+                repo_id: replacement_id,
+                installs: vec![InstallEntry {
+                    path: "Shared.dll".to_string(),
+                    kind: "dll".to_string(),
+                    sha256: Some("new".to_string()),
+                    version: Some("v2".to_string()),
+                    display_name: None,
+                    file_fingerprint: None,
+                }],
+                backups: vec![InstallBackupRow {
+                    replacement_repo_id: replacement_id,
+                    path: "Shared.dll".to_string(),
+                    backup_path: ".wuddle/backups/shared.dll".to_string(),
+                    kind: "dll".to_string(),
+                    sha256: Some("old".to_string()),
+                    displaced_repo_id: Some(owner_id),
+                }],
+                installed_asset: InstalledAssetState {
+                    version: Some("v2".to_string()),
+                    ..InstalledAssetState::default()
+                },
+                merge_installs: false,
+                mark_manual: false,
             },
-            false,
-            false,
+            None,
         );
         assert!(result.is_err());
         assert_eq!(db.list_installs(replacement_id).unwrap()[0].path, "Old.dll");
@@ -2862,13 +3857,13 @@ mod tests {
             )
             .unwrap();
 
-        db.remove_repo_with_displaced_backups(owner_id, false)
+        db.remove_repo_with_displaced_backups(owner_id, false, None)
             .unwrap();
         let retained = db.list_install_backups(replacement_id).unwrap();
         assert_eq!(retained.len(), 1);
         assert_eq!(retained[0].displaced_repo_id, None);
 
-        db.remove_repo_with_displaced_backups(replacement_id, true)
+        db.remove_repo_with_displaced_backups(replacement_id, true, None)
             .unwrap();
         let count: i64 = db
             .conn
@@ -2877,6 +3872,161 @@ mod tests {
         assert_eq!(count, 0);
     }
 
+    #[test]
+    fn install_history_keeps_the_newest_per_repo() {
+        let temp = tempfile::tempdir().unwrap();
+        let db = Db::open(&temp.path().join("install-history-kept.sqlite")).unwrap();
+        db.conn
+            .execute(
+                r#"
+                INSERT INTO repos(url, forge, host, owner, name, mode)
+                VALUES ('https://github.com/tests/addon', 'github', 'github.com', 'tests', 'addon', 'addon')
+                "#,
+                [],
+            )
+            .unwrap();
+        let repo_id = db.conn.last_insert_rowid();
+        let mut last = 0;
+        for index in 0..(INSTALL_HISTORY_KEPT + 2) {
+            last = db
+                .add_history_entry(&InstallHistoryEntry {
+                    id: 0,
+                    repo_id,
+                    action: if index == 0 {
+                        HistoryAction::Install
+                    } else {
+                        HistoryAction::Update
+                    },
+                    created_unix: 1_700_000_000 + index,
+                    from_version: (index > 0).then(|| format!("v{}", index - 1)),
+                    to_version: Some(format!("v{index}")),
+                    asset_id: None,
+                    asset_name: None,
+                    asset_url: None,
+                    asset_sha256: None,
+                    extra_assets: Vec::new(),
+                    settings_snapshot: (index % 2 == 0).then(|| index.to_string()),
+                })
+                .unwrap();
+        }
+
+        let history = db.list_install_history(repo_id).unwrap();
+        assert_eq!(history.len() as i64, INSTALL_HISTORY_KEPT);
+        assert_eq!(history[0].id, last);
+        assert_eq!(
+            history[0].from_version.as_deref(),
+            Some(format!("v{}", INSTALL_HISTORY_KEPT).as_str())
+        );
+        assert_eq!(history[0].settings_snapshot, None);
+        assert_eq!(history.last().unwrap().to_version.as_deref(), Some("v2"));
+        assert_eq!(
+            history.last().unwrap().settings_snapshot.as_deref(),
+            Some("2")
+        );
+        assert_eq!(db.get_history_entry(last).unwrap(), history[0]);
+    }
+
+    #[test]
+    fn install_history_survives_removing_the_repo() {
+        let temp = tempfile::tempdir().unwrap();
+        let db = Db::open(&temp.path().join("install-history.sqlite")).unwrap();
+        db.conn
+            .execute(
+                r#"
+                INSERT INTO repos(url, forge, host, owner, name, mode)
+                VALUES ('https://github.com/tests/mod', 'github', 'github.com', 'tests', 'mod', 'dll')
+                "#,
+                [],
+            )
+            .unwrap();
+        let repo_id = db.conn.last_insert_rowid();
+        let entry = InstallHistoryEntry {
+            id: 0,
+            repo_id,
+            action: HistoryAction::Update,
+            created_unix: 1_700_000_000,
+            from_version: Some("v1".to_string()),
+            to_version: Some("v2".to_string()),
+            asset_id: Some("42".to_string()),
+            asset_name: Some("mod.dll".to_string()),
+            asset_url: Some(
+                "https://github.com/tests/mod/releases/download/v2/mod.dll".to_string(),
+            ),
+            asset_sha256: Some("ab".repeat(32)),
+            extra_assets: vec![ReleaseAsset {
+                id: None,
+                name: "helper.dll".to_string(),
+                download_url: "https://github.com/tests/mod/releases/download/v2/helper.dll"
+                    .to_string(),
+                size: Some(3),
+                content_type: None,
+                sha256: None,
+            }],
+            settings_snapshot: Some("1700000000".to_string()),
+        };
+        let id = db.add_history_entry(&entry).unwrap();
+        db.add_history_entry(&InstallHistoryEntry {
+            action: HistoryAction::Remove,
+            from_version: Some("v2".to_string()),
+            to_version: None,
+            extra_assets: Vec::new(),
+            ..entry.clone()
+        })
+        .unwrap();
+
+        db.conn
+            .execute("DELETE FROM repos WHERE id=?1", params![repo_id])
+            .unwrap();
+        let history = db.list_install_history(repo_id).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].action, HistoryAction::Remove);
+        let stored = db.get_history_entry(id).unwrap();
+        assert_eq!(stored, InstallHistoryEntry { id, ..entry });
+    }
+
+    #[test]
+    fn addon_index_import_replaces_a_catalogs_entries() {
+        let temp = tempfile::tempdir().unwrap();
+        let db = Db::open(&temp.path().join("addon-index.sqlite")).unwrap();
+        let entry = |name: &str| IndexEntry {
+            name: name.to_string(),
+            url: format!("https://github.com/guild/{name}"),
+            description: String::new(),
+            kind: IndexKind::Mod,
+            clients: vec![ClientFamily::Vanilla],
+            categories: vec!["Raid".to_string()],
+            folders: Vec::new(),
+        };
+        let file = |names: &[&str]| IndexFile {
+            name: String::new(),
+            entries: names.iter().map(|name| entry(name)).collect(),
+        };
+        let id = db
+            .replace_addon_index("Guild", None, 1, &file(&["A", "B"]))
+            .unwrap();
+        let again = db
+            .replace_addon_index(
+                "guild",
+                Some("https://example.org/a.json"),
+                2,
+                &file(&["C"]),
+            )
+            .unwrap();
+        assert_eq!(id, again);
+
+        let sources = db.list_addon_index_sources().unwrap();
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].entries, 1);
+        assert_eq!(
+            sources[0].url.as_deref(),
+            Some("https://example.org/a.json")
+        );
+        assert_eq!(db.list_addon_index_entries().unwrap(), vec![entry("C")]);
+
+        assert!(db.remove_addon_index_source(id).unwrap());
+        assert!(db.list_addon_index_entries().unwrap().is_empty());
+    }
+
     #[test]
     fn fresh_empty_database_does_not_request_casing_recovery() {
         let temp = tempfile::tempdir().unwrap();
@@ -2911,4 +4061,54 @@ mod tests {
             .unwrap();
         assert_eq!(version, SCHEMA_VERSION);
     }
+
+    #[test]
+    fn release_cache_round_trips_and_tracks_validation() {
+        let temp = tempfile::tempdir().unwrap();
+        let db = Db::open(&temp.path().join("release-cache.sqlite")).unwrap();
+        let release = crate::model::LatestRelease {
+            tag: "v2.0.0".to_string(),
+            name: Some("Two".to_string()),
+            prerelease: false,
+            assets: vec![crate::model::ReleaseAsset {
+                id: Some("9".to_string()),
+                name: "Addon.zip".to_string(),
+                download_url: "https://example.invalid/Addon.zip".to_string(),
+                size: Some(42),
+                content_type: None,
+                sha256: None,
+            }],
+            published_at: Some(1_700_000_000),
+            body: None,
+        };
+
+        assert!(db
+            .get_cached_release("github|github.com|o/r")
+            .unwrap()
+            .is_none());
+        db.put_cached_release(
+            "github|github.com|o/r",
+            Some("\"abc\""),
+            Some("Tue, 01 Oct 2024 10:00:00 GMT"),
+            &release,
+            100,
+        )
+        .unwrap();
+        db.mark_cached_release_validated("github|github.com|o/r", 250)
+            .unwrap();
+
+        let row = db
+            .get_cached_release("github|github.com|o/r")
+            .unwrap()
+            .unwrap();
+        assert_eq!(row.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(
+            row.last_modified.as_deref(),
+            Some("Tue, 01 Oct 2024 10:00:00 GMT")
+        );
+        assert_eq!(row.release.tag, "v2.0.0");
+        assert_eq!(row.release.assets[0].size, Some(42));
+        assert_eq!(row.fetched_at_unix, 100);
+        assert_eq!(row.validated_at_unix, 250);
+    }
 }
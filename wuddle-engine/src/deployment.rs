@@ -1,60 +1,306 @@
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::{
     fs,
+    io::Write,
     path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    sync::Mutex,
 };
 
 use crate::{diagnostics, install};
 
-struct BackupArea {
-    parent: PathBuf,
-    directory: tempfile::TempDir,
-}
+static NEXT_JOURNAL: AtomicU64 = AtomicU64::new(0);
+static RECOVERED: Mutex<Vec<RecoveredTransaction>> = Mutex::new(Vec::new());
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Backup {
     original: PathBuf,
     stored: PathBuf,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 struct Deployment {
     target: PathBuf,
     restore_source: Option<PathBuf>,
 }
 
-/// A best-effort filesystem transaction for replacing live install targets.
+/// A rename recorded before it is attempted. Recovery inspects the
+/// filesystem to tell whether it happened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "snake_case")]
+enum PendingStep {
+    Backup {
+        original: PathBuf,
+        stored: PathBuf,
+    },
+    Deploy {
+        staged: PathBuf,
+        target: PathBuf,
+    },
+    Return {
+        source: PathBuf,
+        target: PathBuf,
+    },
+    Promote {
+        original: PathBuf,
+        persistent: PathBuf,
+    },
+}
+
+/// The write-ahead journal of one transaction, saved before every change to
+/// live files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Journal {
+    id: String,
+    operation: String,
+    repo_id: i64,
+    backup_areas: Vec<PathBuf>,
+    backups: Vec<Backup>,
+    deployments: Vec<Deployment>,
+    pending: Option<PendingStep>,
+}
+
+fn exists(path: &Path) -> bool {
+    path.exists() || path.is_symlink()
+}
+
+impl Journal {
+    /// Settle a step that was journaled but may not have run.
+    fn resolve_pending(&mut self) {
+        match self.pending.take() {
+            Some(PendingStep::Backup { original, stored }) if exists(&stored) => {
+                self.backups.push(Backup { original, stored });
+            }
+            Some(PendingStep::Deploy { staged, target }) if exists(&target) && !exists(&staged) => {
+                self.deployments.push(Deployment {
+                    target,
+                    restore_source: None,
+                });
+            }
+            Some(PendingStep::Return { source, target }) if exists(&target) && !exists(&source) => {
+                self.deployments.push(Deployment {
+                    target,
+                    restore_source: Some(source),
+                });
+            }
+            Some(PendingStep::Promote {
+                original,
+                persistent,
+            }) => {
+                if let Some(backup) = self
+                    .backups
+                    .iter_mut()
+                    .find(|backup| same_path(&backup.original, &original))
+                {
+                    if exists(&persistent) && !exists(&backup.stored) {
+                        backup.stored = persistent;
+                    }
+                }
+            }
+            // The step never ran.
+            _ => {}
+        }
+    }
+
+    /// Remove the deployments and put every displaced target back, newest
+    /// first. Steps that were already undone are skipped, so an interrupted
+    /// rollback can be run again. Returns the number of steps undone.
+    fn rollback(&self) -> Result<usize> {
+        let mut failures = Vec::new();
+        let mut undone = 0usize;
+        for deployment in self.deployments.iter().rev() {
+            let result = if let Some(source) = deployment.restore_source.as_deref() {
+                if exists(source) || !exists(&deployment.target) {
+                    Ok(false)
+                } else {
+                    fs::rename(&deployment.target, source)
+                        .map(|()| true)
+                        .context("return restored target to its backup")
+                }
+            } else if exists(&deployment.target) {
+                install::remove_any_target(&deployment.target).map(|()| true)
+            } else {
+                Ok(false)
+            };
+            match result {
+                Ok(true) => undone += 1,
+                Ok(false) => {}
+                Err(error) => failures.push(error.to_string()),
+            }
+        }
+        for backup in self.backups.iter().rev() {
+            if !exists(&backup.stored) {
+                if !exists(&backup.original) {
+                    failures.push("a rollback backup was missing".to_string());
+                }
+                continue;
+            }
+            if exists(&backup.original) {
+                if let Err(error) = install::remove_any_target(&backup.original) {
+                    failures.push(error.to_string());
+                    continue;
+                }
+            }
+            if let Some(parent) = backup.original.parent() {
+                if let Err(error) = fs::create_dir_all(parent) {
+                    failures.push(error.to_string());
+                    continue;
+                }
+            }
+            match fs::rename(&backup.stored, &backup.original) {
+                Ok(()) => undone += 1,
+                Err(error) => failures.push(error.to_string()),
+            }
+        }
+        if failures.is_empty() {
+            Ok(undone)
+        } else {
+            anyhow::bail!(
+                "Filesystem replacement rollback was incomplete ({} failed step(s))",
+                failures.len()
+            )
+        }
+    }
+
+    /// Delete the rollback directories. Promoted backups live elsewhere and
+    /// are kept.
+    fn remove_backup_areas(&self) {
+        for area in &self.backup_areas {
+            if exists(area) {
+                let _ = fs::remove_dir_all(area);
+            }
+        }
+    }
+}
+
+fn same_path(left: &Path, right: &Path) -> bool {
+    left.to_string_lossy()
+        .eq_ignore_ascii_case(&right.to_string_lossy())
+}
+
+fn lock_path(journal_path: &Path) -> PathBuf {
+    let mut name = journal_path.as_os_str().to_owned();
+    name.push(".lock");
+    PathBuf::from(name)
+}
+
+/// Flush `dir`'s entries so a file created or renamed in it survives a power
+/// loss. Windows has no directory handle for this; NTFS journals its own
+/// metadata.
+pub(crate) fn sync_dir(dir: &Path) -> Result<()> {
+    #[cfg(unix)]
+    fs::File::open(dir)
+        .and_then(|handle| handle.sync_all())
+        .with_context(|| format!("flush {}", dir.display()))?;
+    #[cfg(not(unix))]
+    let _ = dir;
+    Ok(())
+}
+
+/// Flush both directories a rename from `from` to `to` touched.
+fn sync_rename(from: &Path, to: &Path) -> Result<()> {
+    let from_parent = from.parent();
+    let to_parent = to.parent();
+    if let Some(parent) = to_parent {
+        sync_dir(parent)?;
+    }
+    match from_parent {
+        Some(parent) if to_parent != Some(parent) => sync_dir(parent),
+        _ => Ok(()),
+    }
+}
+
+fn remove_journal_files(journal_path: &Path) {
+    let _ = fs::remove_file(journal_path);
+    let _ = fs::remove_file(lock_path(journal_path));
+}
+
+/// A filesystem transaction for replacing live install targets.
 ///
 /// Every existing target is first renamed into a hidden directory on the same
 /// filesystem. New targets can then be deployed and SQLite committed. Dropping
 /// an armed transaction removes the new targets and restores every displaced
 /// entry in reverse order.
+///
+/// Each step is written to a journal under `.wuddle/journal` before it runs,
+/// so a transaction cut short by a crash or power loss is finished or rolled
+/// back by [`recover`] when the profile is next opened. The journal and every
+/// rename are flushed to disk, directory entries included, before the next
+/// step. The journal file stays locked while the transaction is alive.
 pub(crate) struct ReplacementTransaction {
-    operation: &'static str,
-    repo_id: i64,
-    backup_areas: Vec<BackupArea>,
-    backups: Vec<Backup>,
-    deployments: Vec<Deployment>,
+    journal: Journal,
+    journal_path: PathBuf,
+    _lock: fs::File,
     armed: bool,
 }
 
 impl ReplacementTransaction {
-    pub(crate) fn new(operation: &'static str, repo_id: i64) -> Self {
-        Self {
-            operation,
-            repo_id,
-            backup_areas: Vec::new(),
-            backups: Vec::new(),
-            deployments: Vec::new(),
+    pub(crate) fn new(operation: &'static str, repo_id: i64, journal_dir: &Path) -> Result<Self> {
+        fs::create_dir_all(journal_dir).context("create install journal directory")?;
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_nanos())
+            .unwrap_or_default();
+        let id = format!(
+            "{nanos:x}-{:x}-{}",
+            std::process::id(),
+            NEXT_JOURNAL.fetch_add(1, Ordering::Relaxed)
+        );
+        let journal_path = journal_dir.join(format!("{id}.json"));
+        let lock =
+            fs::File::create(lock_path(&journal_path)).context("create install journal lock")?;
+        lock.lock().context("lock install journal")?;
+        Ok(Self {
+            journal: Journal {
+                id,
+                operation: operation.to_string(),
+                repo_id,
+                backup_areas: Vec::new(),
+                backups: Vec::new(),
+                deployments: Vec::new(),
+                pending: None,
+            },
+            journal_path,
+            _lock: lock,
             armed: true,
-        }
+        })
+    }
+
+    pub(crate) fn id(&self) -> &str {
+        &self.journal.id
+    }
+
+    pub(crate) fn journal_path(&self) -> &Path {
+        &self.journal_path
+    }
+
+    fn save(&self) -> Result<()> {
+        let dir = self
+            .journal_path
+            .parent()
+            .ok_or_else(|| anyhow::anyhow!("Install journal has no parent directory"))?;
+        let mut staged = tempfile::NamedTempFile::new_in(dir).context("stage install journal")?;
+        serde_json::to_writer(&mut staged, &self.journal)?;
+        staged.flush()?;
+        staged
+            .as_file()
+            .sync_all()
+            .context("flush install journal")?;
+        staged
+            .persist(&self.journal_path)
+            .map_err(|error| error.error)
+            .context("write install journal")?;
+        sync_dir(dir)
     }
 
-    fn same_path(left: &Path, right: &Path) -> bool {
-        left.to_string_lossy()
-            .eq_ignore_ascii_case(&right.to_string_lossy())
+    fn begin(&mut self, step: PendingStep) -> Result<()> {
+        self.journal.pending = Some(step);
+        self.save()
     }
 
     fn actual_case(path: &Path) -> Option<PathBuf> {
-        if path.exists() || path.is_symlink() {
+        if exists(path) {
             return Some(path.to_path_buf());
         }
         let parent = path.parent()?;
@@ -71,37 +317,32 @@ impl ReplacementTransaction {
             .map(|entry| entry.path())
     }
 
-    fn backup_area(&mut self, parent: &Path) -> Result<&Path> {
-        if let Some(index) = self
-            .backup_areas
-            .iter()
-            .position(|area| Self::same_path(&area.parent, parent))
-        {
-            return Ok(self.backup_areas[index].directory.path());
+    fn backup_area(&mut self, parent: &Path) -> Result<PathBuf> {
+        if let Some(area) = self.journal.backup_areas.iter().find(|area| {
+            area.parent()
+                .is_some_and(|area_parent| same_path(area_parent, parent))
+        }) {
+            return Ok(area.clone());
         }
         fs::create_dir_all(parent).context("create replacement target parent")?;
-        let directory = tempfile::Builder::new()
-            .prefix(".wuddle-rollback-")
-            .tempdir_in(parent)
-            .context("create replacement rollback directory")?;
-        self.backup_areas.push(BackupArea {
-            parent: parent.to_path_buf(),
-            directory,
-        });
-        Ok(self
-            .backup_areas
-            .last()
-            .expect("backup area was just inserted")
-            .directory
-            .path())
+        let area = parent.join(format!(
+            ".wuddle-rollback-{}-{}",
+            self.journal.id,
+            self.journal.backup_areas.len()
+        ));
+        self.journal.backup_areas.push(area.clone());
+        self.save()?;
+        fs::create_dir(&area).context("create replacement rollback directory")?;
+        Ok(area)
     }
 
     pub(crate) fn backup_target(&mut self, path: &Path) -> Result<bool> {
         let actual = Self::actual_case(path).unwrap_or_else(|| path.to_path_buf());
         if self
+            .journal
             .backups
             .iter()
-            .any(|backup| Self::same_path(&backup.original, &actual))
+            .any(|backup| same_path(&backup.original, &actual))
         {
             return Ok(true);
         }
@@ -113,13 +354,19 @@ impl ReplacementTransaction {
         if !(metadata.file_type().is_symlink() || metadata.is_file() || metadata.is_dir()) {
             anyhow::bail!("Refusing to replace an unsupported filesystem entry");
         }
-        let index = self.backups.len();
+        let index = self.journal.backups.len();
         let parent = actual
             .parent()
             .ok_or_else(|| anyhow::anyhow!("Replacement target has no parent directory"))?;
         let stored = self.backup_area(parent)?.join(format!("target-{index}"));
+        self.begin(PendingStep::Backup {
+            original: actual.clone(),
+            stored: stored.clone(),
+        })?;
         fs::rename(&actual, &stored).context("stage existing target for rollback")?;
-        self.backups.push(Backup {
+        sync_rename(&actual, &stored)?;
+        self.journal.pending = None;
+        self.journal.backups.push(Backup {
             original: actual,
             stored,
         });
@@ -132,8 +379,14 @@ impl ReplacementTransaction {
             .parent()
             .ok_or_else(|| anyhow::anyhow!("Replacement target has no parent directory"))?;
         fs::create_dir_all(parent).context("create replacement target parent")?;
+        self.begin(PendingStep::Deploy {
+            staged: staged.to_path_buf(),
+            target: target.to_path_buf(),
+        })?;
         fs::rename(staged, target).context("commit staged replacement target")?;
-        self.deployments.push(Deployment {
+        sync_rename(staged, target)?;
+        self.journal.pending = None;
+        self.journal.deployments.push(Deployment {
             target: target.to_path_buf(),
             restore_source: None,
         });
@@ -149,8 +402,14 @@ impl ReplacementTransaction {
             .parent()
             .ok_or_else(|| anyhow::anyhow!("Replacement target has no parent directory"))?;
         fs::create_dir_all(parent).context("create restored target parent")?;
+        self.begin(PendingStep::Return {
+            source: source.to_path_buf(),
+            target: target.to_path_buf(),
+        })?;
         fs::rename(source, target).context("restore displaced install target")?;
-        self.deployments.push(Deployment {
+        sync_rename(source, target)?;
+        self.journal.pending = None;
+        self.journal.deployments.push(Deployment {
             target: target.to_path_buf(),
             restore_source: Some(source.to_path_buf()),
         });
@@ -161,76 +420,53 @@ impl ReplacementTransaction {
     /// restore it later. Rollback still treats the promoted path as the source
     /// of the original live target.
     pub(crate) fn promote_backup(&mut self, original: &Path, persistent: &Path) -> Result<()> {
-        let backup = self
+        let stored = self
+            .journal
             .backups
-            .iter_mut()
-            .find(|backup| Self::same_path(&backup.original, original))
+            .iter()
+            .find(|backup| same_path(&backup.original, original))
+            .map(|backup| backup.stored.clone())
             .ok_or_else(|| anyhow::anyhow!("No rollback backup exists for this target"))?;
-        if persistent.exists() || persistent.is_symlink() {
+        if exists(persistent) {
             anyhow::bail!("A displaced-file backup already exists");
         }
         let parent = persistent
             .parent()
             .ok_or_else(|| anyhow::anyhow!("Persistent backup has no parent directory"))?;
         fs::create_dir_all(parent).context("create displaced-file backup directory")?;
-        fs::rename(&backup.stored, persistent).context("retain displaced-file backup")?;
-        backup.stored = persistent.to_path_buf();
+        self.begin(PendingStep::Promote {
+            original: original.to_path_buf(),
+            persistent: persistent.to_path_buf(),
+        })?;
+        fs::rename(&stored, persistent).context("retain displaced-file backup")?;
+        sync_rename(&stored, persistent)?;
+        self.journal.pending = None;
+        if let Some(backup) = self
+            .journal
+            .backups
+            .iter_mut()
+            .find(|backup| same_path(&backup.original, original))
+        {
+            backup.stored = persistent.to_path_buf();
+        }
         Ok(())
     }
 
+    /// Keep the replacement once SQLite has committed, and discard the
+    /// rollback directories and the journal.
     pub(crate) fn disarm(&mut self) {
         self.armed = false;
+        self.journal.remove_backup_areas();
+        remove_journal_files(&self.journal_path);
     }
 
     pub(crate) fn rollback(&mut self) -> Result<()> {
-        let mut failures = Vec::new();
-        for deployment in self.deployments.iter().rev() {
-            let result = if let Some(source) = deployment.restore_source.as_deref() {
-                if source.exists() || source.is_symlink() {
-                    install::remove_any_target(source)
-                } else if deployment.target.exists() || deployment.target.is_symlink() {
-                    fs::rename(&deployment.target, source)
-                        .context("return restored target to its backup")
-                } else {
-                    Ok(())
-                }
-            } else {
-                install::remove_any_target(&deployment.target)
-            };
-            if let Err(error) = result {
-                failures.push(error.to_string());
-            }
-        }
-        for backup in self.backups.iter().rev() {
-            if !(backup.stored.exists() || backup.stored.is_symlink()) {
-                failures.push("a rollback backup was missing".to_string());
-                continue;
-            }
-            if backup.original.exists() || backup.original.is_symlink() {
-                if let Err(error) = install::remove_any_target(&backup.original) {
-                    failures.push(error.to_string());
-                    continue;
-                }
-            }
-            if let Some(parent) = backup.original.parent() {
-                if let Err(error) = fs::create_dir_all(parent) {
-                    failures.push(error.to_string());
-                    continue;
-                }
-            }
-            if let Err(error) = fs::rename(&backup.stored, &backup.original) {
-                failures.push(error.to_string());
-            }
-        }
-        if failures.is_empty() {
-            self.armed = false;
-            Ok(())
-        } else {
-            anyhow::bail!(
-                "Filesystem replacement rollback was incomplete ({} failed step(s))",
-                failures.len()
-            )
-        }
+        self.journal.resolve_pending();
+        self.journal.rollback()?;
+        self.armed = false;
+        self.journal.remove_backup_areas();
+        remove_journal_files(&self.journal_path);
+        Ok(())
     }
 }
 
@@ -244,32 +480,186 @@ impl Drop for ReplacementTransaction {
             "engine.rollback",
             format!(
                 "{} rollback started: repo_id={}; target_count={}",
-                self.operation,
-                self.repo_id,
-                self.deployments.len()
+                self.journal.operation,
+                self.journal.repo_id,
+                self.journal.deployments.len()
             ),
         );
         if let Err(error) = self.rollback() {
-            let backup_count = self.backups.len();
-            for area in self.backup_areas.drain(..) {
-                let _ = area.directory.keep();
-            }
+            // The backups and the journal stay behind so recovery can retry.
             diagnostics::emit(
                 diagnostics::DiagnosticLevel::Debug,
                 "engine.rollback",
                 format!(
-                    "{} rollback failed: repo_id={}; preserved_backup_count={backup_count}; error={error}",
-                    self.operation, self.repo_id
+                    "{} rollback failed: repo_id={}; preserved_backup_count={}; error={error}",
+                    self.journal.operation,
+                    self.journal.repo_id,
+                    self.journal.backups.len()
                 ),
             );
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryAction {
+    /// The database had committed; the leftover backups were removed.
+    Completed,
+    /// The database had not committed; the previous files were put back.
+    RolledBack,
+    /// Rolling back failed; the journal and backups are kept for next time.
+    Failed,
+}
+
+/// What recovery did with one interrupted install transaction.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RecoveredTransaction {
+    pub operation: String,
+    pub repo_id: i64,
+    pub action: RecoveryAction,
+    /// Deployments removed and displaced targets put back.
+    pub restored: usize,
+    pub error: Option<String>,
+}
+
+impl RecoveredTransaction {
+    pub fn describe(&self) -> String {
+        // Transactions spanning several repos are journaled under repo 0.
+        let subject = if self.repo_id == 0 {
+            self.operation.clone()
+        } else {
+            format!("{} of repo {}", self.operation, self.repo_id)
+        };
+        match self.action {
+            RecoveryAction::Completed => format!("Finished an interrupted {subject}."),
+            RecoveryAction::RolledBack => format!(
+                "Rolled back an interrupted {subject} ({} file(s) restored).",
+                self.restored
+            ),
+            RecoveryAction::Failed => format!(
+                "Could not roll back an interrupted {subject}: {}",
+                self.error.as_deref().unwrap_or("unknown error")
+            ),
+        }
+    }
+}
+
+pub(crate) enum JournalRecovery {
+    /// The transaction is still running in some process.
+    Running,
+    /// The journal's folder cannot be reached, e.g. an unmounted drive.
+    Unavailable,
+    /// Nothing was left to do.
+    Clean,
+    Recovered(RecoveredTransaction),
+}
+
+/// Finish or roll back the transaction journaled at `journal_path`.
+/// `committed` tells whether SQLite committed the transaction's records.
+pub(crate) fn recover(journal_path: &Path, committed: bool) -> Result<JournalRecovery> {
+    if !journal_path.parent().is_some_and(Path::is_dir) {
+        return Ok(JournalRecovery::Unavailable);
+    }
+    let lock = match fs::OpenOptions::new()
+        .write(true)
+        .open(lock_path(journal_path))
+    {
+        Ok(lock) => Some(lock),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => None,
+        Err(error) => return Err(error).context("open install journal lock"),
+    };
+    if let Some(lock) = &lock {
+        match lock.try_lock() {
+            Ok(()) => {}
+            Err(fs::TryLockError::WouldBlock) => return Ok(JournalRecovery::Running),
+            Err(fs::TryLockError::Error(error)) => {
+                return Err(error).context("lock install journal")
+            }
+        }
+    }
+    let raw = match fs::read(journal_path) {
+        Ok(raw) => raw,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+            // Nothing was journaled, or the transaction cleaned up after
+            // itself before its record was removed.
+            drop(lock);
+            remove_journal_files(journal_path);
+            return Ok(JournalRecovery::Clean);
+        }
+        Err(error) => return Err(error).context("read install journal"),
+    };
+    let mut journal: Journal = serde_json::from_slice(&raw).context("parse install journal")?;
+    journal.resolve_pending();
+    let mut recovered = RecoveredTransaction {
+        operation: journal.operation.clone(),
+        repo_id: journal.repo_id,
+        action: RecoveryAction::Completed,
+        restored: 0,
+        error: None,
+    };
+    if !committed {
+        match journal.rollback() {
+            Ok(restored) => {
+                recovered.action = RecoveryAction::RolledBack;
+                recovered.restored = restored;
+            }
+            Err(error) => {
+                recovered.action = RecoveryAction::Failed;
+                recovered.error = Some(error.to_string());
+                return Ok(JournalRecovery::Recovered(recovered));
+            }
+        }
+    }
+    journal.remove_backup_areas();
+    drop(lock);
+    remove_journal_files(journal_path);
+    Ok(JournalRecovery::Recovered(recovered))
+}
+
+pub(crate) fn record_recovered(recovered: RecoveredTransaction) {
+    RECOVERED
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .push(recovered);
+}
+
+/// Interrupted install transactions recovered since the last call, in the
+/// order they were handled.
+pub fn take_recovered_transactions() -> Vec<RecoveredTransaction> {
+    std::mem::take(
+        &mut *RECOVERED
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()),
+    )
+}
+
 #[cfg(test)]
 mod tests {
-    use super::ReplacementTransaction;
+    use super::{recover, JournalRecovery, PendingStep, RecoveryAction, ReplacementTransaction};
     use std::fs;
+    use std::path::Path;
+
+    /// Leave the journal behind as a killed process would.
+    fn crash(mut transaction: ReplacementTransaction) -> std::path::PathBuf {
+        let journal = transaction.journal_path().to_path_buf();
+        transaction.armed = false;
+        drop(transaction);
+        journal
+    }
+
+    fn rollback_dirs(dir: &Path) -> usize {
+        fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(".wuddle-rollback-")
+            })
+            .count()
+    }
 
     #[test]
     fn dropping_armed_transaction_restores_every_replaced_target() {
@@ -280,7 +670,8 @@ mod tests {
         fs::write(&staged, b"new").unwrap();
 
         {
-            let mut transaction = ReplacementTransaction::new("test", 1);
+            let mut transaction =
+                ReplacementTransaction::new("test", 1, &temp.path().join("journal")).unwrap();
             transaction.deploy(&staged, &target).unwrap();
             assert_eq!(fs::read(&target).unwrap(), b"new");
         }
@@ -298,7 +689,8 @@ mod tests {
         fs::write(&persistent, b"original").unwrap();
 
         {
-            let mut transaction = ReplacementTransaction::new("test", 2);
+            let mut transaction =
+                ReplacementTransaction::new("test", 2, &temp.path().join("journal")).unwrap();
             transaction.deploy_returnable(&persistent, &target).unwrap();
             assert_eq!(fs::read(&target).unwrap(), b"original");
         }
@@ -316,7 +708,8 @@ mod tests {
         fs::write(&target, b"old").unwrap();
         fs::write(&staged, b"new").unwrap();
 
-        let mut transaction = ReplacementTransaction::new("test", 3);
+        let mut transaction =
+            ReplacementTransaction::new("test", 3, &temp.path().join("journal")).unwrap();
         transaction.backup_target(&target).unwrap();
         transaction.promote_backup(&target, &persistent).unwrap();
         transaction.deploy(&staged, &target).unwrap();
@@ -326,4 +719,79 @@ mod tests {
         assert_eq!(fs::read(&target).unwrap(), b"new");
         assert_eq!(fs::read(&persistent).unwrap(), b"old");
     }
+
+    #[test]
+    fn recovery_rolls_back_an_uncommitted_transaction() {
+        let temp = tempfile::tempdir().unwrap();
+        let first = temp.path().join("first.dll");
+        let second = temp.path().join("second.dll");
+        let staged = temp.path().join("staged");
+        fs::create_dir_all(&staged).unwrap();
+        fs::write(&first, b"old first").unwrap();
+        fs::write(&second, b"old second").unwrap();
+        fs::write(staged.join("first.dll"), b"new first").unwrap();
+        fs::write(staged.join("second.dll"), b"new second").unwrap();
+
+        let mut transaction =
+            ReplacementTransaction::new("test", 4, &temp.path().join("journal")).unwrap();
+        transaction
+            .deploy(&staged.join("first.dll"), &first)
+            .unwrap();
+        transaction.backup_target(&second).unwrap();
+        // Killed after journaling the second deployment but before running it.
+        transaction
+            .begin(PendingStep::Deploy {
+                staged: staged.join("second.dll"),
+                target: second.clone(),
+            })
+            .unwrap();
+        let journal = crash(transaction);
+        assert!(journal.exists());
+        assert!(!second.exists());
+
+        let JournalRecovery::Recovered(recovered) = recover(&journal, false).unwrap() else {
+            panic!("expected the journal to be recovered");
+        };
+        assert_eq!(recovered.action, RecoveryAction::RolledBack);
+        assert_eq!(recovered.restored, 3);
+        assert_eq!(fs::read(&first).unwrap(), b"old first");
+        assert_eq!(fs::read(&second).unwrap(), b"old second");
+        assert_eq!(fs::read(staged.join("second.dll")).unwrap(), b"new second");
+        assert_eq!(rollback_dirs(temp.path()), 0);
+        assert!(!journal.exists());
+        assert!(matches!(
+            recover(&journal, false).unwrap(),
+            JournalRecovery::Clean
+        ));
+    }
+
+    #[test]
+    fn recovery_completes_a_committed_transaction_and_skips_running_ones() {
+        let temp = tempfile::tempdir().unwrap();
+        let target = temp.path().join("shared.dll");
+        let staged = temp.path().join("new.dll");
+        let persistent = temp.path().join("backups").join("shared.dll");
+        fs::write(&target, b"old").unwrap();
+        fs::write(&staged, b"new").unwrap();
+
+        let mut transaction =
+            ReplacementTransaction::new("test", 5, &temp.path().join("journal")).unwrap();
+        transaction.backup_target(&target).unwrap();
+        transaction.promote_backup(&target, &persistent).unwrap();
+        transaction.deploy(&staged, &target).unwrap();
+        assert!(matches!(
+            recover(transaction.journal_path(), true).unwrap(),
+            JournalRecovery::Running
+        ));
+        let journal = crash(transaction);
+
+        let JournalRecovery::Recovered(recovered) = recover(&journal, true).unwrap() else {
+            panic!("expected the journal to be recovered");
+        };
+        assert_eq!(recovered.action, RecoveryAction::Completed);
+        assert_eq!(fs::read(&target).unwrap(), b"new");
+        assert_eq!(fs::read(&persistent).unwrap(), b"old");
+        assert_eq!(rollback_dirs(temp.path()), 0);
+        assert!(!journal.exists());
+    }
 }
@@ -0,0 +1,319 @@
+//! Fingerprints of addon folders and the catalog of known sources they are
+//! matched against.
+//!
+//! A fingerprint hashes an addon's `.toc` and its whole file tree. The
+//! catalog maps those hashes and folder names to repository URLs: tracked
+//! repos add an entry for every version they are seen at, the Quick Add
+//! presets name the folders they install, and catalog files exported by
+//! other users can be imported. An untracked folder that matches an entry can
+//! be linked to that source, so the next update replaces it.
+
+use crate::util::{sha256_file_hex, sha256_hex};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Folders with more files than this are not fingerprinted.
+const MAX_FILES: usize = 20_000;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddonFingerprint {
+    pub folder: String,
+    /// SHA-256 of the addon's main `.toc`.
+    pub toc_sha256: Option<String>,
+    /// SHA-256 over every file's relative path and contents.
+    pub tree_sha256: String,
+    pub files: usize,
+}
+
+/// Where a catalog entry came from. Declared strongest first: when two
+/// sources give the same kind of match, the earlier one wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogSource {
+    Tracked,
+    Imported,
+    Preset,
+}
+
+impl CatalogSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Tracked => "tracked",
+            Self::Imported => "imported",
+            Self::Preset => "preset",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "tracked" => Some(Self::Tracked),
+            "imported" => Some(Self::Imported),
+            "preset" => Some(Self::Preset),
+            _ => None,
+        }
+    }
+}
+
+/// One addon folder of one known version of a repo.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub url: String,
+    pub folder: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    /// Install mode the repo is tracked with, e.g. `addon` or `addon_git`.
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub toc_sha256: Option<String>,
+    #[serde(default)]
+    pub tree_sha256: Option<String>,
+}
+
+/// The shareable catalog file: `{"entries": [...]}`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CatalogFile {
+    pub entries: Vec<CatalogEntry>,
+}
+
+/// How well a folder fits a catalog entry, strongest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchKind {
+    /// Every file is identical to a known version.
+    Exact,
+    /// The `.toc` is identical to a known version; other files differ.
+    Toc,
+    /// Only the folder name is known.
+    Name,
+}
+
+impl MatchKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Exact => "same files",
+            Self::Toc => "same .toc",
+            Self::Name => "same folder name",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SourceMatch {
+    pub url: String,
+    /// The matched version, when the files identify one.
+    pub version: Option<String>,
+    pub mode: Option<String>,
+    pub kind: MatchKind,
+    pub source: CatalogSource,
+}
+
+/// An untracked addon folder and the sources it may come from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddonIdentification {
+    pub folder: String,
+    /// The `manual` repo that records this folder, if any.
+    pub manual_repo_id: Option<i64>,
+    pub fingerprint: AddonFingerprint,
+    /// Best match first, one per URL.
+    pub matches: Vec<SourceMatch>,
+}
+
+fn is_noise(name: &str) -> bool {
+    name.starts_with('.')
+        || name.eq_ignore_ascii_case("Thumbs.db")
+        || name.eq_ignore_ascii_case("desktop.ini")
+}
+
+fn collect_files(
+    dir: &Path,
+    prefix: &str,
+    out: &mut Vec<(String, std::path::PathBuf)>,
+) -> Result<()> {
+    let entries = fs::read_dir(dir).with_context(|| format!("read {}", dir.display()))?;
+    for entry in entries.flatten() {
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        if is_noise(&name) {
+            continue;
+        }
+        let Ok(file_type) = entry.file_type() else {
+            continue;
+        };
+        let relative = if prefix.is_empty() {
+            name
+        } else {
+            format!("{prefix}/{name}")
+        };
+        if file_type.is_dir() {
+            collect_files(&entry.path(), &relative, out)?;
+        } else if file_type.is_file() {
+            anyhow::ensure!(
+                out.len() < MAX_FILES,
+                "{} has more than {MAX_FILES} files",
+                dir.display()
+            );
+            out.push((relative, entry.path()));
+        }
+    }
+    Ok(())
+}
+
+/// Hash the `.toc` and file tree of the addon folder `dir`. Paths are
+/// compared case-insensitively, since archives extracted on Windows may
+/// change their casing; hidden files and OS thumbnails are ignored.
+pub fn fingerprint_folder(dir: &Path) -> Result<AddonFingerprint> {
+    let folder = dir
+        .file_name()
+        .and_then(|name| name.to_str())
+        .with_context(|| format!("{} has no folder name", dir.display()))?
+        .to_string();
+    let mut files = Vec::new();
+    collect_files(dir, "", &mut files)?;
+    let mut lines = files
+        .iter()
+        .map(|(relative, path)| {
+            Ok(format!(
+                "{}\0{}\n",
+                relative.to_lowercase(),
+                sha256_file_hex(path)?
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    lines.sort();
+    let toc_sha256 = crate::toc::find_addon_toc(dir)
+        .map(|toc| sha256_file_hex(&toc))
+        .transpose()?;
+    Ok(AddonFingerprint {
+        folder,
+        toc_sha256,
+        tree_sha256: sha256_hex(&lines.concat()),
+        files: files.len(),
+    })
+}
+
+fn url_key(url: &str) -> String {
+    let trimmed = url.trim().trim_end_matches('/');
+    trimmed
+        .strip_suffix(".git")
+        .unwrap_or(trimmed)
+        .to_ascii_lowercase()
+}
+
+/// Catalog entries that fit `fingerprint`, best first and one per URL.
+pub fn match_fingerprint(
+    fingerprint: &AddonFingerprint,
+    catalog: &[(CatalogSource, CatalogEntry)],
+) -> Vec<SourceMatch> {
+    let mut matches = catalog
+        .iter()
+        .filter_map(|(source, entry)| {
+            let kind = if entry.tree_sha256.as_deref() == Some(fingerprint.tree_sha256.as_str()) {
+                MatchKind::Exact
+            } else if entry.toc_sha256.is_some() && entry.toc_sha256 == fingerprint.toc_sha256 {
+                MatchKind::Toc
+            } else if entry.folder.eq_ignore_ascii_case(&fingerprint.folder) {
+                MatchKind::Name
+            } else {
+                return None;
+            };
+            Some(SourceMatch {
+                url: entry.url.clone(),
+                version: (kind != MatchKind::Name)
+                    .then(|| entry.version.clone())
+                    .flatten(),
+                mode: entry.mode.clone(),
+                kind,
+                source: *source,
+            })
+        })
+        .collect::<Vec<_>>();
+    matches.sort_by_key(|candidate| (candidate.kind, candidate.source));
+    let mut seen = std::collections::HashSet::new();
+    matches.retain(|candidate| seen.insert(url_key(&candidate.url)));
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fingerprint_folder, match_fingerprint, CatalogEntry, CatalogSource, MatchKind};
+    use std::fs;
+
+    fn entry(url: &str, folder: &str) -> CatalogEntry {
+        CatalogEntry {
+            url: url.to_string(),
+            folder: folder.to_string(),
+            version: Some("1.0".to_string()),
+            mode: None,
+            toc_sha256: None,
+            tree_sha256: None,
+        }
+    }
+
+    #[test]
+    fn fingerprints_ignore_case_and_noise_and_rank_matches() {
+        let temp = tempfile::tempdir().unwrap();
+        let first = temp.path().join("one/Atlas");
+        let second = temp.path().join("two/Atlas");
+        for dir in [&first, &second] {
+            fs::create_dir_all(dir.join("Images")).unwrap();
+            fs::write(dir.join("Atlas.toc"), "## Title: Atlas\nAtlas.lua\n").unwrap();
+            fs::write(dir.join("Atlas.lua"), "Atlas = {}\n").unwrap();
+        }
+        fs::write(first.join("Images/map.tga"), "map").unwrap();
+        fs::write(second.join("Images/MAP.tga"), "map").unwrap();
+        fs::write(second.join("Thumbs.db"), "noise").unwrap();
+
+        let one = fingerprint_folder(&first).unwrap();
+        let two = fingerprint_folder(&second).unwrap();
+        assert_eq!(one.files, 3);
+        assert_eq!(one.tree_sha256, two.tree_sha256);
+
+        fs::write(second.join("Atlas.lua"), "Atlas = { patched = true }\n").unwrap();
+        let patched = fingerprint_folder(&second).unwrap();
+        assert_ne!(patched.tree_sha256, one.tree_sha256);
+        assert_eq!(patched.toc_sha256, one.toc_sha256);
+
+        let catalog = vec![
+            (
+                CatalogSource::Preset,
+                entry("https://github.com/a/Atlas", "atlas"),
+            ),
+            (
+                CatalogSource::Imported,
+                CatalogEntry {
+                    toc_sha256: one.toc_sha256.clone(),
+                    ..entry("https://github.com/b/Atlas", "Atlas")
+                },
+            ),
+            (
+                CatalogSource::Tracked,
+                CatalogEntry {
+                    tree_sha256: Some(one.tree_sha256.clone()),
+                    ..entry("https://github.com/b/Atlas/", "Atlas")
+                },
+            ),
+        ];
+        let exact = match_fingerprint(&one, &catalog);
+        let kinds = exact
+            .iter()
+            .map(|candidate| (candidate.kind, candidate.source))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                (MatchKind::Exact, CatalogSource::Tracked),
+                (MatchKind::Name, CatalogSource::Preset),
+            ]
+        );
+        assert_eq!(exact[0].version.as_deref(), Some("1.0"));
+        assert_eq!(exact[1].version, None);
+
+        let by_toc = match_fingerprint(&patched, &catalog);
+        assert_eq!(by_toc[0].kind, MatchKind::Toc);
+        assert_eq!(by_toc[0].url, "https://github.com/b/Atlas");
+    }
+}
@@ -2,7 +2,10 @@ use anyhow::{Context, Result};
 use reqwest::{Client, StatusCode};
 use serde::Deserialize;
 
-use super::{apply_if_none_match, etag_from_headers, handle_304, DetectedRepo};
+use super::{
+    apply_conditional_headers, authorize_request, handle_304, validators_from_headers,
+    DetectedRepo, ReleaseValidators,
+};
 use crate::model::{LatestRelease, ReleaseAsset};
 
 #[derive(Debug, Deserialize)]
@@ -11,6 +14,7 @@ struct GiteaRelease {
     name: Option<String>,
     published_at: Option<String>,
     assets: Vec<GiteaAsset>,
+    body: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -30,11 +34,10 @@ pub async fn list_releases(
     let mut all = Vec::new();
     loop {
         let url = format!(
-            "https://{}/api/v1/repos/{}/releases?limit=50&page={}",
-            repo.host, repo.project_path, page
+            "{}/repos/{}/releases?limit=50&page={}",
+            repo.api_base, repo.project_path, page
         );
-        let resp = client
-            .get(&url)
+        let resp = authorize_request(client.get(&url), &url)
             .header("User-Agent", "wuddle-engine")
             .header("Accept", "application/json")
             .send()
@@ -72,6 +75,7 @@ pub async fn list_releases(
                     .published_at
                     .as_deref()
                     .and_then(super::parse_rfc3339_unix),
+                body: rel.body.clone(),
             });
         }
         if rels.len() < 50 {
@@ -85,27 +89,26 @@ pub async fn list_releases(
 pub async fn latest_release(
     client: &Client,
     repo: &DetectedRepo,
-    etag: Option<&str>,
-) -> Result<(Option<String>, Option<LatestRelease>, bool)> {
+    validators: &ReleaseValidators,
+) -> Result<(ReleaseValidators, Option<LatestRelease>, bool)> {
     // Gitea API: /api/v1/repos/{owner}/{repo}/releases/latest
     let url = format!(
-        "https://{}/api/v1/repos/{}/releases/latest",
-        repo.host, repo.project_path
+        "{}/repos/{}/releases/latest",
+        repo.api_base, repo.project_path
     );
 
-    let mut req = client
-        .get(url)
+    let mut req = authorize_request(client.get(&url), &url)
         .header("User-Agent", "wuddle-engine")
         .header("Accept", "application/json");
-    req = apply_if_none_match(req, etag);
+    req = apply_conditional_headers(req, validators);
 
     let resp = req.send().await.context("gitea request failed")?;
 
-    if let Some(x) = handle_304(resp.status(), etag) {
+    if let Some(x) = handle_304(resp.status(), validators) {
         return Ok(x);
     }
 
-    let new_etag = etag_from_headers(&resp);
+    let new_validators = validators_from_headers(&resp);
 
     if resp.status() == StatusCode::NOT_FOUND {
         anyhow::bail!("Gitea repo/release not found (no latest release?)");
@@ -128,7 +131,7 @@ pub async fn latest_release(
         .collect();
 
     Ok((
-        new_etag,
+        new_validators,
         Some(LatestRelease {
             tag: rel.tag_name,
             name: rel.name,
@@ -138,6 +141,7 @@ pub async fn latest_release(
                 .published_at
                 .as_deref()
                 .and_then(super::parse_rfc3339_unix),
+            body: rel.body,
         }),
         false,
     ))
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use reqwest::{Client, StatusCode};
 use serde::Deserialize;
+use std::collections::HashMap;
 
 use crate::model::{LatestRelease, ReleaseAsset};
 
@@ -70,6 +71,7 @@ struct GhRelease {
     prerelease: bool,
     published_at: Option<String>,
     assets: Vec<GhAsset>,
+    body: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -106,9 +108,9 @@ impl GitHub {
         client: &Client,
         owner: &str,
         repo: &str,
-        etag: Option<&str>,
-    ) -> Result<(Option<String>, Option<LatestRelease>, bool)> {
-        // returns (new_etag, release_or_none, not_modified)
+        validators: &ReleaseValidators,
+    ) -> Result<(ReleaseValidators, Option<LatestRelease>, bool)> {
+        // returns (new_validators, release_or_none, not_modified)
         let url = format!("https://api.github.com/repos/{owner}/{repo}/releases/latest");
 
         let mut req = client
@@ -121,23 +123,17 @@ impl GitHub {
             req = req.bearer_auth(token);
         }
 
-        if let Some(et) = etag {
-            req = req.header("If-None-Match", et);
-        }
+        req = super::apply_conditional_headers(req, validators);
 
         let resp = req.send().await.context("github request failed")?;
         let status = resp.status();
 
-        if status == StatusCode::NOT_MODIFIED {
+        if let Some(x) = super::handle_304(status, validators) {
             // 304 - no changes
-            return Ok((etag.map(|s| s.to_string()), None, true));
+            return Ok(x);
         }
 
-        let new_etag = resp
-            .headers()
-            .get("etag")
-            .and_then(|v| v.to_str().ok())
-            .map(|s| s.to_string());
+        let new_validators = super::validators_from_headers(&resp);
 
         if status == StatusCode::NOT_FOUND {
             anyhow::bail!("GitHub repo/release not found (no latest release?)");
@@ -161,7 +157,7 @@ impl GitHub {
             .collect();
 
         Ok((
-            new_etag,
+            new_validators,
             Some(LatestRelease {
                 tag: gh.tag_name,
                 name: gh.name,
@@ -171,20 +167,21 @@ impl GitHub {
                     .published_at
                     .as_deref()
                     .and_then(super::parse_rfc3339_unix),
+                body: gh.body,
             }),
             false,
         ))
     }
 }
 
-use super::DetectedRepo;
+use super::{DetectedRepo, ReleaseValidators};
 
 pub async fn latest_release(
     client: &Client,
     repo: &DetectedRepo,
-    etag: Option<&str>,
-) -> Result<(Option<String>, Option<LatestRelease>, bool)> {
-    GitHub::latest_release(client, &repo.owner, &repo.name, etag).await
+    validators: &ReleaseValidators,
+) -> Result<(ReleaseValidators, Option<LatestRelease>, bool)> {
+    GitHub::latest_release(client, &repo.owner, &repo.name, validators).await
 }
 
 /// Fetch all releases for a GitHub repo (paginated, newest first).
@@ -237,6 +234,7 @@ pub async fn list_releases(client: &Client, repo: &DetectedRepo) -> Result<Vec<L
                     .published_at
                     .as_deref()
                     .and_then(super::parse_rfc3339_unix),
+                body: gh.body.clone(),
             });
         }
         if rels.len() < 100 {
@@ -247,6 +245,192 @@ pub async fn list_releases(client: &Client, repo: &DetectedRepo) -> Result<Vec<L
     Ok(all)
 }
 
+const GRAPHQL_URL: &str = "https://api.github.com/graphql";
+
+/// Repositories per GraphQL query. Each alias may expand up to 100 asset
+/// nodes, which keeps a full batch far below GitHub's node limit.
+pub const GRAPHQL_BATCH_SIZE: usize = 50;
+
+const GRAPHQL_RELEASE_FRAGMENT: &str = r#"
+fragment LatestReleaseFields on Repository {
+  latestRelease {
+    tagName
+    name
+    isPrerelease
+    publishedAt
+    releaseAssets(first: 100) {
+      pageInfo { hasNextPage }
+      nodes { databaseId name downloadUrl size contentType digest }
+    }
+  }
+}"#;
+
+#[derive(Debug, Deserialize)]
+struct GqlResponse {
+    data: Option<HashMap<String, Option<GqlRepository>>>,
+    #[serde(default)]
+    errors: Vec<GqlError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GqlError {
+    message: String,
+    #[serde(default)]
+    path: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlRepository {
+    latest_release: Option<GqlRelease>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlRelease {
+    tag_name: String,
+    name: Option<String>,
+    is_prerelease: bool,
+    published_at: Option<String>,
+    release_assets: GqlAssetConnection,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlAssetConnection {
+    page_info: GqlPageInfo,
+    nodes: Vec<GqlAsset>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlPageInfo {
+    has_next_page: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GqlAsset {
+    database_id: Option<u64>,
+    name: String,
+    download_url: String,
+    size: Option<u64>,
+    content_type: Option<String>,
+    digest: Option<String>,
+}
+
+fn batch_query(count: usize) -> String {
+    let mut variables = Vec::with_capacity(count);
+    let mut fields = Vec::with_capacity(count);
+    for index in 0..count {
+        variables.push(format!("$o{index}: String!, $n{index}: String!"));
+        fields.push(format!(
+            "  r{index}: repository(owner: $o{index}, name: $n{index}) {{ ...LatestReleaseFields }}"
+        ));
+    }
+    format!(
+        "query({}) {{\n{}\n}}\n{}",
+        variables.join(", "),
+        fields.join("\n"),
+        GRAPHQL_RELEASE_FRAGMENT
+    )
+}
+
+/// Convert one aliased result. Releases that cannot be represented exactly
+/// as the REST endpoint would (truncated asset lists or assets without a
+/// numeric id) are left to the per-repository REST path.
+fn release_from_graphql(release: GqlRelease) -> Option<LatestRelease> {
+    if release.release_assets.page_info.has_next_page {
+        return None;
+    }
+    let assets = release
+        .release_assets
+        .nodes
+        .into_iter()
+        .map(|asset| {
+            Some(ReleaseAsset {
+                id: Some(asset.database_id?.to_string()),
+                name: asset.name,
+                download_url: asset.download_url,
+                size: asset.size,
+                content_type: asset.content_type,
+                sha256: parse_sha256_digest(asset.digest.as_deref()),
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(LatestRelease {
+        tag: release.tag_name,
+        name: release.name,
+        prerelease: release.is_prerelease,
+        assets,
+        published_at: release
+            .published_at
+            .as_deref()
+            .and_then(super::parse_rfc3339_unix),
+        // Batched checks leave bodies out to keep responses small;
+        // `list_releases` carries them.
+        body: None,
+    })
+}
+
+fn parse_batch_response(response: GqlResponse, count: usize) -> Result<Vec<Option<LatestRelease>>> {
+    let Some(mut data) = response.data else {
+        let message = response
+            .errors
+            .first()
+            .map(|error| error.message.as_str())
+            .unwrap_or("no data");
+        anyhow::bail!("GitHub GraphQL request failed: {message}");
+    };
+    // Errors scoped to one alias (for example a renamed or private repo) only
+    // affect that entry; anything else invalidates the whole batch.
+    if response.errors.iter().any(|error| error.path.is_empty()) {
+        anyhow::bail!(
+            "GitHub GraphQL request failed: {}",
+            response.errors[0].message
+        );
+    }
+    Ok((0..count)
+        .map(|index| {
+            data.remove(&format!("r{index}"))
+                .flatten()
+                .and_then(|repository| repository.latest_release)
+                .and_then(release_from_graphql)
+        })
+        .collect())
+}
+
+/// Fetch the latest release of several repositories in one GraphQL query.
+///
+/// Requires a token. The result is index-aligned with `repos`; `None` means
+/// the caller should fall back to the REST endpoint for that repository.
+pub async fn latest_releases_batch(
+    client: &Client,
+    repos: &[&DetectedRepo],
+) -> Result<Vec<Option<LatestRelease>>> {
+    let token = crate::github_token().context("GitHub GraphQL requires a token")?;
+    let mut variables = serde_json::Map::new();
+    for (index, repo) in repos.iter().enumerate() {
+        variables.insert(format!("o{index}"), repo.owner.clone().into());
+        variables.insert(format!("n{index}"), repo.name.clone().into());
+    }
+    let body = serde_json::json!({
+        "query": batch_query(repos.len()),
+        "variables": variables,
+    });
+    let resp = client
+        .post(GRAPHQL_URL)
+        .header("User-Agent", "wuddle-engine")
+        .bearer_auth(token)
+        .json(&body)
+        .send()
+        .await
+        .context("github graphql request failed")?;
+    let resp = checked_response(resp, "GitHub GraphQL request failed").await?;
+    let response: GqlResponse = resp.json().await.context("invalid github graphql json")?;
+    parse_batch_response(response, repos.len())
+}
+
 #[derive(Debug, Deserialize)]
 struct GhTreeResponse {
     tree: Vec<GhTreeEntry>,
@@ -318,7 +502,10 @@ pub async fn list_files_recursive(
 
 #[cfg(test)]
 mod tests {
-    use super::{complete_tree_files, GhTreeEntry, GhTreeResponse};
+    use super::{
+        batch_query, complete_tree_files, parse_batch_response, GhTreeEntry, GhTreeResponse,
+        GqlResponse,
+    };
 
     #[test]
     fn truncated_recursive_trees_are_never_treated_as_authoritative() {
@@ -343,4 +530,54 @@ mod tests {
         assert_eq!(files[0].path, "Complete/Complete.toc");
         assert!(!files[0].is_dir);
     }
+
+    #[test]
+    fn batch_query_aliases_every_repository() {
+        let query = batch_query(2);
+        assert!(query.starts_with("query($o0: String!, $n0: String!, $o1: String!, $n1: String!)"));
+        assert!(query.contains("r1: repository(owner: $o1, name: $n1) { ...LatestReleaseFields }"));
+        assert!(query.contains("fragment LatestReleaseFields on Repository"));
+    }
+
+    #[test]
+    fn batch_response_falls_back_per_repository() {
+        let response: GqlResponse = serde_json::from_str(
+            r#"{
+              "data": {
+                "r0": {"latestRelease": {
+                  "tagName": "v1.2.0", "name": "One", "isPrerelease": false,
+                  "publishedAt": "2024-01-15T10:30:00Z",
+                  "releaseAssets": {"pageInfo": {"hasNextPage": false}, "nodes": [
+                    {"databaseId": 77, "name": "Addon.zip",
+                     "downloadUrl": "https://github.com/o/a/releases/download/v1.2.0/Addon.zip",
+                     "size": 10, "contentType": "application/zip",
+                     "digest": "sha256:0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"}
+                  ]}
+                }},
+                "r1": null,
+                "r2": {"latestRelease": null},
+                "r3": {"latestRelease": {
+                  "tagName": "v2", "name": null, "isPrerelease": false, "publishedAt": null,
+                  "releaseAssets": {"pageInfo": {"hasNextPage": true}, "nodes": []}
+                }}
+              },
+              "errors": [{"message": "Could not resolve to a Repository", "path": ["r1"]}]
+            }"#,
+        )
+        .unwrap();
+
+        let releases = parse_batch_response(response, 4).unwrap();
+        let first = releases[0].as_ref().unwrap();
+        assert_eq!(first.tag, "v1.2.0");
+        assert_eq!(first.assets[0].id.as_deref(), Some("77"));
+        assert_eq!(first.assets[0].sha256.as_deref().map(str::len), Some(64));
+        assert_eq!(first.published_at, Some(1_705_314_600));
+        assert!(releases[1].is_none());
+        assert!(releases[2].is_none());
+        assert!(releases[3].is_none());
+
+        let failed: GqlResponse =
+            serde_json::from_str(r#"{"errors": [{"message": "Bad credentials"}]}"#).unwrap();
+        assert!(parse_batch_response(failed, 1).is_err());
+    }
 }
@@ -2,7 +2,10 @@ use anyhow::{Context, Result};
 use reqwest::{Client, StatusCode};
 use serde::Deserialize;
 
-use super::{apply_if_none_match, etag_from_headers, handle_304, DetectedRepo};
+use super::{
+    apply_conditional_headers, authorize_request, handle_304, validators_from_headers,
+    DetectedRepo, ReleaseValidators,
+};
 use crate::model::{LatestRelease, ReleaseAsset};
 
 #[derive(Debug, Deserialize)]
@@ -11,6 +14,7 @@ struct GitLabRelease {
     name: Option<String>,
     released_at: Option<String>,
     assets: GitLabAssets,
+    description: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -36,11 +40,10 @@ pub async fn list_releases(client: &Client, repo: &DetectedRepo) -> Result<Vec<L
     let mut all = Vec::new();
     loop {
         let url = format!(
-            "https://{}/api/v4/projects/{}/releases?per_page=100&page={}",
-            repo.host, encoded, page
+            "{}/projects/{}/releases?per_page=100&page={}",
+            repo.api_base, encoded, page
         );
-        let resp = client
-            .get(&url)
+        let resp = authorize_request(client.get(&url), &url)
             .header("User-Agent", "wuddle-engine")
             .header("Accept", "application/json")
             .send()
@@ -82,6 +85,7 @@ pub async fn list_releases(client: &Client, repo: &DetectedRepo) -> Result<Vec<L
                     .released_at
                     .as_deref()
                     .and_then(super::parse_rfc3339_unix),
+                body: rel.description.clone(),
             });
         }
         if rels.len() < 100 {
@@ -95,27 +99,26 @@ pub async fn list_releases(client: &Client, repo: &DetectedRepo) -> Result<Vec<L
 pub async fn latest_release(
     client: &Client,
     repo: &DetectedRepo,
-    etag: Option<&str>,
-) -> Result<(Option<String>, Option<LatestRelease>, bool)> {
+    validators: &ReleaseValidators,
+) -> Result<(ReleaseValidators, Option<LatestRelease>, bool)> {
     let encoded = urlencoding::encode(&repo.project_path);
     let url = format!(
-        "https://{}/api/v4/projects/{}/releases/permalink/latest",
-        repo.host, encoded
+        "{}/projects/{}/releases/permalink/latest",
+        repo.api_base, encoded
     );
 
-    let mut req = client
-        .get(url)
+    let mut req = authorize_request(client.get(&url), &url)
         .header("User-Agent", "wuddle-engine")
         .header("Accept", "application/json");
-    req = apply_if_none_match(req, etag);
+    req = apply_conditional_headers(req, validators);
 
     let resp = req.send().await.context("gitlab request failed")?;
 
-    if let Some(x) = handle_304(resp.status(), etag) {
+    if let Some(x) = handle_304(resp.status(), validators) {
         return Ok(x);
     }
 
-    let new_etag = etag_from_headers(&resp);
+    let new_validators = validators_from_headers(&resp);
 
     if resp.status() == StatusCode::NOT_FOUND {
         anyhow::bail!("GitLab project/release not found (no latest release?)");
@@ -142,7 +145,7 @@ pub async fn latest_release(
         .collect();
 
     Ok((
-        new_etag,
+        new_validators,
         Some(LatestRelease {
             tag: rel.tag_name,
             name: rel.name,
@@ -152,6 +155,7 @@ pub async fn latest_release(
                 .released_at
                 .as_deref()
                 .and_then(super::parse_rfc3339_unix),
+            body: rel.description,
         }),
         false,
     ))
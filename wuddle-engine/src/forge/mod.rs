@@ -1,8 +1,9 @@
 use anyhow::{Context, Result};
 use reqwest::{Client, StatusCode};
+use serde::Serialize;
 use std::{
     collections::HashMap,
-    sync::{Mutex, OnceLock},
+    sync::{Mutex, OnceLock, RwLock},
     time::{Duration, Instant},
 };
 use url::Url;
@@ -19,17 +20,32 @@ const RELEASE_CACHE_TTL: Duration = Duration::from_secs(45);
 #[derive(Clone)]
 struct CachedRelease {
     fetched_at: Instant,
-    etag: Option<String>,
+    validators: ReleaseValidators,
     release: LatestRelease,
 }
 
+/// Conditional-request validators remembered for a release endpoint.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseValidators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl ReleaseValidators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
 static RELEASE_CACHE: OnceLock<Mutex<HashMap<String, CachedRelease>>> = OnceLock::new();
 
 fn release_cache() -> &'static Mutex<HashMap<String, CachedRelease>> {
     RELEASE_CACHE.get_or_init(|| Mutex::new(HashMap::new()))
 }
 
-fn cache_key(repo: &DetectedRepo) -> String {
+/// Stable identity of a release endpoint, shared by the in-memory and the
+/// persistent release caches.
+pub(crate) fn cache_key(repo: &DetectedRepo) -> String {
     let forge = match repo.kind {
         ForgeKind::GitHub => "github",
         ForgeKind::GitLab => "gitlab",
@@ -46,8 +62,8 @@ fn cache_key(repo: &DetectedRepo) -> String {
 
 fn cache_read(
     repo: &DetectedRepo,
-    etag: Option<&str>,
-) -> Option<(Option<String>, Option<LatestRelease>, bool)> {
+    validators: &ReleaseValidators,
+) -> Option<(ReleaseValidators, Option<LatestRelease>, bool)> {
     let key = cache_key(repo);
     let mut guard = release_cache().lock().ok()?;
     let entry = guard.get(&key)?;
@@ -56,34 +72,212 @@ fn cache_read(
         return None;
     }
 
-    if etag.is_some() && entry.etag.as_deref() == etag {
-        return Some((entry.etag.clone(), None, true));
+    if !validators.is_empty() && entry.validators == *validators {
+        return Some((entry.validators.clone(), None, true));
     }
-    Some((entry.etag.clone(), Some(entry.release.clone()), false))
+    Some((entry.validators.clone(), Some(entry.release.clone()), false))
 }
 
-fn cache_write(repo: &DetectedRepo, etag: Option<String>, release: LatestRelease) {
+fn cache_write(repo: &DetectedRepo, validators: ReleaseValidators, release: LatestRelease) {
     let key = cache_key(repo);
     if let Ok(mut guard) = release_cache().lock() {
         guard.insert(
             key,
             CachedRelease {
                 fetched_at: Instant::now(),
-                etag,
+                validators,
                 release,
             },
         );
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ForgeKind {
     GitHub,
     GitLab,
-    Gitea, // includes Codeberg (Gitea)
+    Gitea, // includes Codeberg and Forgejo (Gitea-compatible API)
     Generic,
 }
 
+impl ForgeKind {
+    /// Parse the kind of a user-registered host. Only forges whose release
+    /// API Wuddle implements can be registered.
+    pub fn parse_custom(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "gitlab" => Some(Self::GitLab),
+            "gitea" | "forgejo" => Some(Self::Gitea),
+            _ => None,
+        }
+    }
+
+    fn default_api_path(self) -> &'static str {
+        match self {
+            Self::GitLab => "/api/v4",
+            Self::Gitea => "/api/v1",
+            Self::GitHub | Self::Generic => "",
+        }
+    }
+}
+
+/// A self-hosted GitLab or Gitea/Forgejo instance registered by the user.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CustomForgeHost {
+    /// Lower-case host name, with `:port` when the instance uses one.
+    pub host: String,
+    pub kind: ForgeKind,
+    /// API root below the host; `None` uses the forge's standard path.
+    pub api_base_path: Option<String>,
+}
+
+impl CustomForgeHost {
+    pub fn api_path(&self) -> &str {
+        self.api_base_path
+            .as_deref()
+            .unwrap_or_else(|| self.kind.default_api_path())
+    }
+}
+
+static CUSTOM_FORGE_HOSTS: OnceLock<RwLock<Vec<CustomForgeHost>>> = OnceLock::new();
+
+fn custom_forge_hosts() -> &'static RwLock<Vec<CustomForgeHost>> {
+    CUSTOM_FORGE_HOSTS.get_or_init(|| RwLock::new(Vec::new()))
+}
+
+/// Replace the process-wide set of registered hosts. The engine calls this
+/// whenever it opens a profile database or the stored list changes.
+pub(crate) fn set_custom_forge_hosts(hosts: Vec<CustomForgeHost>) {
+    if let Ok(mut guard) = custom_forge_hosts().write() {
+        *guard = hosts;
+    }
+}
+
+fn find_custom_forge_host(
+    hosts: &[CustomForgeHost],
+    host: &str,
+    port: Option<u16>,
+) -> Option<CustomForgeHost> {
+    let authority = match port {
+        Some(port) => format!("{host}:{port}"),
+        None => host.to_string(),
+    };
+    hosts
+        .iter()
+        .find(|entry| entry.host.eq_ignore_ascii_case(&authority))
+        .cloned()
+}
+
+/// Forge and HTTPS API root of a host that accepts per-host access tokens.
+fn token_forge_for_authority(authority: &str) -> Option<(ForgeKind, String)> {
+    match authority {
+        "gitlab.com" => return Some((ForgeKind::GitLab, "https://gitlab.com/api/v4".into())),
+        "codeberg.org" => {
+            return Some((ForgeKind::Gitea, "https://codeberg.org/api/v1".into()));
+        }
+        _ => {}
+    }
+    let guard = custom_forge_hosts().read().ok()?;
+    let entry = guard
+        .iter()
+        .find(|entry| entry.host.eq_ignore_ascii_case(authority))?;
+    Some((
+        entry.kind,
+        format!("https://{}{}", entry.host, entry.api_path()),
+    ))
+}
+
+/// Attach the access token registered for the request's host, if any.
+///
+/// The token travels in the `Authorization` header, which is dropped on
+/// cross-host redirects, and only when [`url_safety::credential_authority`]
+/// scopes the URL to exactly the host the token was saved for.
+///
+/// [`url_safety::credential_authority`]: crate::url_safety::credential_authority
+pub(crate) fn authorize_request(
+    request: reqwest::RequestBuilder,
+    url: &str,
+) -> reqwest::RequestBuilder {
+    let Some(authority) = crate::url_safety::credential_authority(url) else {
+        return request;
+    };
+    let Some(token) = crate::forge_token(&authority) else {
+        return request;
+    };
+    match token_forge_for_authority(&authority) {
+        Some((kind, _)) => attach_token(request, kind, &token),
+        None => request,
+    }
+}
+
+/// Put `token` in the `Authorization` header the way `kind` expects it.
+fn attach_token(
+    request: reqwest::RequestBuilder,
+    kind: ForgeKind,
+    token: &str,
+) -> reqwest::RequestBuilder {
+    match kind {
+        // GitLab accepts personal access tokens as OAuth bearer tokens.
+        ForgeKind::GitLab => request.bearer_auth(token),
+        ForgeKind::Gitea => request.header("Authorization", format!("token {token}")),
+        ForgeKind::GitHub | ForgeKind::Generic => request,
+    }
+}
+
+/// HTTPS Git credentials for `url`: GitLab and Gitea both accept an access
+/// token as the password of any user name.
+pub(crate) fn git_credential(url: &str) -> Option<(&'static str, String)> {
+    let authority = crate::url_safety::credential_authority(url)?;
+    token_forge_for_authority(&authority)?;
+    Some(("oauth2", crate::forge_token(&authority)?))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ForgeTokenValidation {
+    Valid,
+    Invalid,
+    Unverified(String),
+}
+
+/// Check a token against the `/user` endpoint of the forge serving `host`.
+/// Hosts must be built-in forges or registered forge hosts so the request
+/// shape is known.
+pub async fn validate_forge_token(host: &str, token: &str) -> ForgeTokenValidation {
+    let Ok(authority) = crate::normalize_forge_token_host(host) else {
+        return ForgeTokenValidation::Invalid;
+    };
+    let Some((kind, api_base)) = token_forge_for_authority(&authority) else {
+        return ForgeTokenValidation::Unverified(format!(
+            "{authority} is not a known GitLab or Gitea host, so the token has not been verified."
+        ));
+    };
+    let request = Client::new()
+        .get(format!("{api_base}/user"))
+        .header("User-Agent", "wuddle-engine")
+        .header("Accept", "application/json");
+    let request = match kind {
+        ForgeKind::GitLab => request.bearer_auth(token),
+        _ => request.header("Authorization", format!("token {token}")),
+    };
+    match request.send().await {
+        Ok(response) => classify_token_validation_status(response.status(), &authority),
+        Err(_) => ForgeTokenValidation::Unverified(format!(
+            "{authority} could not be reached, so the token has not been verified."
+        )),
+    }
+}
+
+fn classify_token_validation_status(status: StatusCode, authority: &str) -> ForgeTokenValidation {
+    match status {
+        StatusCode::OK => ForgeTokenValidation::Valid,
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => ForgeTokenValidation::Invalid,
+        status => ForgeTokenValidation::Unverified(format!(
+            "{authority} returned HTTP {}, so the token has not been verified.",
+            status.as_u16()
+        )),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct DetectedRepo {
     pub kind: ForgeKind,
@@ -93,10 +287,19 @@ pub struct DetectedRepo {
     pub name: String,  // repo/project name
     pub canonical_url: String, // scheme://host/<project_path>
     pub project_path: String, // GitHub/Gitea: owner/name. GitLab: full path group/sub/project
+    pub api_base: String, // GitLab/Gitea REST root, e.g. https://gitlab.com/api/v4
 }
 
 /// Accepts repo URLs with or without /releases and normalizes them.
 pub fn detect_repo(input: &str) -> Result<DetectedRepo> {
+    let hosts = custom_forge_hosts()
+        .read()
+        .map(|guard| guard.clone())
+        .unwrap_or_default();
+    detect_repo_with_hosts(input, &hosts)
+}
+
+fn detect_repo_with_hosts(input: &str, hosts: &[CustomForgeHost]) -> Result<DetectedRepo> {
     let input = input.trim();
 
     let url = Url::parse(input).context("invalid URL")?;
@@ -121,17 +324,35 @@ pub fn detect_repo(input: &str) -> Result<DetectedRepo> {
     }
 
     // Recognize only hosts whose release API contract Wuddle explicitly
-    // supports. Unknown/self-hosted servers remain generic Git rather than
-    // being guessed from path syntax.
+    // supports: the public forges and self-hosted instances the user has
+    // registered. Other servers remain generic Git rather than being guessed
+    // from path syntax.
+    let custom = find_custom_forge_host(hosts, &host, url.port());
     let kind = if host.eq_ignore_ascii_case("github.com") {
         ForgeKind::GitHub
     } else if host.eq_ignore_ascii_case("gitlab.com") {
         ForgeKind::GitLab
     } else if host.eq_ignore_ascii_case("codeberg.org") {
         ForgeKind::Gitea
+    } else if let Some(custom) = &custom {
+        custom.kind
     } else {
         ForgeKind::Generic
     };
+    // Registered hosts may listen on a non-default port, which must survive
+    // into the canonical and API URLs.
+    let authority = match (&custom, url.port()) {
+        (Some(_), Some(port)) => format!("{host}:{port}"),
+        _ => host.clone(),
+    };
+    let api_base = match (&custom, kind) {
+        (Some(custom), _) => format!("{scheme}://{authority}{}", custom.api_path()),
+        (None, ForgeKind::GitLab | ForgeKind::Gitea) => {
+            format!("https://{host}{}", kind.default_api_path())
+        }
+        (None, ForgeKind::GitHub) => "https://api.github.com".to_string(),
+        (None, ForgeKind::Generic) => String::new(),
+    };
 
     // Normalize only the known forge-specific browsing suffixes. A generic
     // repository may legitimately contain segments named `-`, `releases`, or
@@ -181,7 +402,7 @@ pub fn detect_repo(input: &str) -> Result<DetectedRepo> {
                 name.truncate(name.len() - 4);
             }
             let project_path = format!("{}/{}", owner, name);
-            let canonical_url = format!("{scheme}://{host}/{project_path}");
+            let canonical_url = format!("{scheme}://{authority}/{project_path}");
             Ok(DetectedRepo {
                 kind,
                 forge_str: if kind == ForgeKind::GitHub {
@@ -196,6 +417,7 @@ pub fn detect_repo(input: &str) -> Result<DetectedRepo> {
                 name,
                 canonical_url,
                 project_path,
+                api_base,
             })
         }
         ForgeKind::GitLab | ForgeKind::Generic => {
@@ -219,7 +441,7 @@ pub fn detect_repo(input: &str) -> Result<DetectedRepo> {
                 .unwrap_or_else(|| "project".into());
             let owner = project_segs[..project_segs.len().saturating_sub(1)].join("/");
             let project_path = project_segs.join("/");
-            let canonical_url = format!("{scheme}://{host}/{project_path}");
+            let canonical_url = format!("{scheme}://{authority}/{project_path}");
             Ok(DetectedRepo {
                 kind,
                 forge_str: if kind == ForgeKind::GitLab {
@@ -232,29 +454,30 @@ pub fn detect_repo(input: &str) -> Result<DetectedRepo> {
                 name,
                 canonical_url,
                 project_path,
+                api_base,
             })
         }
     }
 }
 
-/// Unified "latest release" fetch with optional ETag.
-/// Returns: (new_etag, release_or_none, not_modified)
+/// Unified "latest release" fetch with optional ETag/Last-Modified validators.
+/// Returns: (new_validators, release_or_none, not_modified)
 pub async fn latest_release(
     client: &Client,
     repo: &DetectedRepo,
-    etag: Option<&str>,
-) -> Result<(Option<String>, Option<LatestRelease>, bool)> {
-    if let Some(hit) = cache_read(repo, etag) {
+    validators: &ReleaseValidators,
+) -> Result<(ReleaseValidators, Option<LatestRelease>, bool)> {
+    if let Some(hit) = cache_read(repo, validators) {
         return Ok(hit);
     }
 
     let out = match repo.kind {
-        ForgeKind::GitHub => github::latest_release(client, repo, etag).await,
-        ForgeKind::GitLab => gitlab::latest_release(client, repo, etag).await,
-        ForgeKind::Gitea => gitea::latest_release(client, repo, etag).await,
+        ForgeKind::GitHub => github::latest_release(client, repo, validators).await,
+        ForgeKind::GitLab => gitlab::latest_release(client, repo, validators).await,
+        ForgeKind::Gitea => gitea::latest_release(client, repo, validators).await,
         ForgeKind::Generic => {
             anyhow::bail!(
-                "This generic Git host has no configured release API. Add it as a Git addon or use a direct archive URL."
+                "This generic Git host has no configured release API. Register it as a GitLab or Gitea forge host, add it as a Git addon, or use a direct archive URL."
             )
         }
     }?;
@@ -274,28 +497,37 @@ pub async fn list_releases(client: &Client, repo: &DetectedRepo) -> Result<Vec<L
         ForgeKind::Gitea => gitea::list_releases(client, repo).await,
         ForgeKind::Generic => {
             anyhow::bail!(
-                "This generic Git host has no configured release API. Add it as a Git addon or use a direct archive URL."
+                "This generic Git host has no configured release API. Register it as a GitLab or Gitea forge host, add it as a Git addon, or use a direct archive URL."
             )
         }
     }
 }
 
 /// Helper for forges that support 304 Not Modified.
-pub(crate) fn etag_from_headers(resp: &reqwest::Response) -> Option<String> {
-    resp.headers()
-        .get("etag")
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string())
+pub(crate) fn validators_from_headers(resp: &reqwest::Response) -> ReleaseValidators {
+    let header = |name: &str| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+    };
+    ReleaseValidators {
+        etag: header("etag"),
+        last_modified: header("last-modified"),
+    }
 }
 
 /// Convenience for building conditional GET requests.
-fn apply_if_none_match(
+fn apply_conditional_headers(
     mut req: reqwest::RequestBuilder,
-    etag: Option<&str>,
+    validators: &ReleaseValidators,
 ) -> reqwest::RequestBuilder {
-    if let Some(et) = etag {
+    if let Some(et) = validators.etag.as_deref() {
         req = req.header("If-None-Match", et);
     }
+    if let Some(modified) = validators.last_modified.as_deref() {
+        req = req.header("If-Modified-Since", modified);
+    }
     req
 }
 
@@ -352,17 +584,21 @@ pub(crate) fn parse_rfc3339_unix(s: &str) -> Option<i64> {
 /// Common handler for 304.
 fn handle_304(
     status: StatusCode,
-    etag: Option<&str>,
-) -> Option<(Option<String>, Option<LatestRelease>, bool)> {
+    validators: &ReleaseValidators,
+) -> Option<(ReleaseValidators, Option<LatestRelease>, bool)> {
     if status == StatusCode::NOT_MODIFIED {
-        return Some((etag.map(|s| s.to_string()), None, true));
+        return Some((validators.clone(), None, true));
     }
     None
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{detect_repo, ForgeKind};
+    use super::{
+        attach_token, classify_token_validation_status, detect_repo, detect_repo_with_hosts,
+        CustomForgeHost, ForgeKind, ForgeTokenValidation,
+    };
+    use reqwest::StatusCode;
 
     #[test]
     fn unknown_hosts_are_generic_and_preserve_nested_namespaces() {
@@ -392,4 +628,124 @@ mod tests {
         assert_eq!(gitlab.owner, "group/subgroup");
         assert_eq!(gitlab.name, "Project");
     }
+
+    #[test]
+    fn registered_hosts_use_their_forge_api() {
+        let hosts = vec![
+            CustomForgeHost {
+                host: "git.example.invalid:3000".to_string(),
+                kind: ForgeKind::Gitea,
+                api_base_path: None,
+            },
+            CustomForgeHost {
+                host: "code.example.invalid".to_string(),
+                kind: ForgeKind::GitLab,
+                api_base_path: Some("/gitlab/api/v4".to_string()),
+            },
+        ];
+
+        let gitea = detect_repo_with_hosts(
+            "http://GIT.example.invalid:3000/Owner/Addon/releases",
+            &hosts,
+        )
+        .unwrap();
+        assert_eq!(gitea.kind, ForgeKind::Gitea);
+        assert_eq!(gitea.forge_str, "gitea");
+        assert_eq!(gitea.api_base, "http://git.example.invalid:3000/api/v1");
+        assert_eq!(
+            gitea.canonical_url,
+            "http://git.example.invalid:3000/Owner/Addon"
+        );
+
+        let gitlab = detect_repo_with_hosts(
+            "https://code.example.invalid/group/sub/Addon/-/releases",
+            &hosts,
+        )
+        .unwrap();
+        assert_eq!(gitlab.kind, ForgeKind::GitLab);
+        assert_eq!(gitlab.owner, "group/sub");
+        assert_eq!(
+            gitlab.api_base,
+            "https://code.example.invalid/gitlab/api/v4"
+        );
+
+        // The port is part of the registration.
+        let other_port =
+            detect_repo_with_hosts("https://git.example.invalid/Owner/Addon", &hosts).unwrap();
+        assert_eq!(other_port.kind, ForgeKind::Generic);
+    }
+
+    #[test]
+    fn host_tokens_are_only_attached_to_their_own_https_host() {
+        const HOST: &str = "tokens-test.invalid";
+        let previous = crate::forge_token(HOST);
+        crate::set_forge_token(
+            "https://Tokens-Test.invalid/group/project",
+            Some(" secret ".into()),
+        )
+        .unwrap();
+        let token_for = |url: &str| {
+            crate::url_safety::credential_authority(url)
+                .and_then(|authority| crate::forge_token(&authority))
+        };
+        let authorization = |kind: ForgeKind| {
+            let client = reqwest::Client::new();
+            attach_token(
+                client.get("https://tokens-test.invalid/api"),
+                kind,
+                "secret",
+            )
+            .build()
+            .unwrap()
+            .headers()
+            .get("Authorization")
+            .map(|value| value.to_str().unwrap().to_string())
+        };
+
+        assert_eq!(
+            token_for("https://tokens-test.invalid/api/v4/projects/1").as_deref(),
+            Some("secret")
+        );
+        assert_eq!(
+            token_for("http://tokens-test.invalid/api/v4/projects/1"),
+            None
+        );
+        assert_eq!(token_for("https://tokens-test.invalid.example/x"), None);
+        assert_eq!(token_for("https://cdn.tokens-test.invalid/x"), None);
+        assert_eq!(
+            authorization(ForgeKind::GitLab).as_deref(),
+            Some("Bearer secret")
+        );
+        assert_eq!(
+            authorization(ForgeKind::Gitea).as_deref(),
+            Some("token secret")
+        );
+        assert_eq!(authorization(ForgeKind::Generic), None);
+        assert!(crate::set_forge_token("github.com", Some("x".into())).is_err());
+
+        crate::set_forge_token(HOST, None).unwrap();
+        assert_eq!(
+            token_for("https://tokens-test.invalid/api/v4/projects/1"),
+            None
+        );
+        crate::set_forge_token(HOST, previous).unwrap();
+
+        assert_eq!(
+            crate::parse_forge_token_list(
+                " Tokens-Test.invalid = abc=1 ,bad, other.invalid=x",
+                HOST
+            )
+            .as_deref(),
+            Some("abc=1")
+        );
+
+        assert_eq!(
+            classify_token_validation_status(StatusCode::FORBIDDEN, "gitlab.com"),
+            ForgeTokenValidation::Invalid
+        );
+        assert!(matches!(
+            classify_token_validation_status(StatusCode::BAD_GATEWAY, "gitlab.com"),
+            ForgeTokenValidation::Unverified(_)
+        ));
+    }
 }
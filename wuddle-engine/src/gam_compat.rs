@@ -107,14 +107,13 @@ pub(crate) fn identity_from_remote(raw: &str) -> Option<GitIdentity> {
         return None;
     }
 
-    // SCP-style SSH URL: git@example.org:group/subgroup/project.git
+    // SCP-style SSH URL: git@example.org:group/subgroup/project.git. The
+    // identity matches the same project added over HTTPS or `ssh://`.
     if !raw.contains("://") {
-        if let Some((user_host, path)) = raw.split_once(':') {
-            if let Some((_, host)) = user_host.rsplit_once('@') {
-                if !host.trim().is_empty() {
-                    return identity_from_parts(raw.to_string(), host.trim().to_string(), path);
-                }
-            }
+        if crate::url_safety::is_ssh_remote(raw) {
+            let (user_host, path) = raw.split_once(':')?;
+            let (_, host) = user_host.rsplit_once('@')?;
+            return identity_from_parts(raw.to_string(), host.trim().to_ascii_lowercase(), path);
         }
 
         // Local and relative remotes are valid git remotes too.
@@ -623,6 +622,22 @@ mod tests {
         assert_eq!(identity.name, "project");
     }
 
+    #[test]
+    fn ssh_and_https_remotes_share_an_identity() {
+        let https = identity_from_remote("https://github.com/Owner/Addon").unwrap();
+        for remote in [
+            "git@GitHub.com:Owner/Addon.git",
+            "ssh://git@github.com/Owner/Addon.git",
+        ] {
+            let ssh = identity_from_remote(remote).unwrap();
+            assert_eq!(
+                (&ssh.forge, &ssh.host, &ssh.owner, &ssh.name),
+                (&https.forge, &https.host, &https.owner, &https.name)
+            );
+            assert_eq!(ssh.url, remote);
+        }
+    }
+
     #[test]
     fn strips_http_credentials_from_stored_identity() {
         let identity =
@@ -51,6 +51,9 @@ pub struct InstallOptions {
     pub replace_local_changes: bool,
     /// Number of cached release versions to retain per repo (0 = only current).
     pub cache_keep_versions: usize,
+    /// The profile's client. Collections without a selection install only the
+    /// addon variants built for it.
+    pub client_family: crate::toc::ClientFamily,
 }
 
 #[derive(Debug, Clone)]
@@ -1050,7 +1053,7 @@ fn addon_folder_names_from_toc(dir: &Path, scan_root: &Path) -> Vec<String> {
     resolve_addon_names_from_stems(stems, dir_name, is_root)
 }
 
-fn normalize_toc_stem(stem: &str) -> String {
+pub(crate) fn normalize_toc_stem(stem: &str) -> String {
     let mut out = stem.trim().to_string();
     if out.is_empty() {
         return out;
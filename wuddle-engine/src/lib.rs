@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use git2::Repository;
 use reqwest::Client;
+use serde::Serialize;
 use std::{
     collections::{HashMap, HashSet},
     fs,
@@ -19,9 +20,15 @@ use std::os::windows::fs::MetadataExt;
 use std::process::Command;
 use url::Url;
 
+pub mod addon_deps;
+pub mod addon_index;
+pub mod addon_sets;
 mod archive;
+pub mod changes;
+pub mod character_addons;
 mod db;
 mod direct;
+pub mod fingerprint;
 mod forge;
 mod gam_compat;
 mod install;
@@ -32,16 +39,34 @@ mod url_safety;
 mod util;
 
 pub mod mpq;
+pub mod presets;
+pub mod profiles;
+pub mod release_notes;
+pub mod saved_variables;
+pub mod toc;
 
 #[cfg(feature = "auto-login")]
 pub mod auto_login;
 mod deployment;
 pub mod diagnostics;
 
+pub use addon_deps::{DependencyProblem, DependencySource, UnmetDependency};
+pub use addon_index::{IndexEntry, IndexFile, IndexKind, IndexQuery, IndexSource};
+pub use addon_sets::{AddonSet, AddonSetExport, AddonSetFolder, AddonSetRepo, AddonSetReport};
+pub use changes::ChangeSet;
+pub use character_addons::WtfCharacter;
 pub use db::Db;
+pub use deployment::{take_recovered_transactions, RecoveredTransaction, RecoveryAction};
 pub use direct::{is_direct_archive_candidate, is_direct_archive_url};
+pub use fingerprint::{AddonIdentification, CatalogEntry, CatalogFile, MatchKind, SourceMatch};
+pub use forge::{validate_forge_token, CustomForgeHost, ForgeKind, ForgeTokenValidation};
 pub use install::InstallOptions;
-pub use model::{InstallMode, LatestRelease, ReleaseAsset, Repo};
+pub use model::{
+    GitTagPolicy, HistoryAction, InstallHistoryEntry, InstallMode, LatestRelease, ReleaseAsset,
+    Repo,
+};
+pub use release_notes::{NotesSource, ReleaseNoteEntry, ReleaseNotes};
+pub use toc::{ClientFamily, ClientMismatch};
 
 /// Configure the directory used by [`Engine::open_default`]. The native
 /// frontend sets this after selecting its single authoritative storage root.
@@ -57,9 +82,9 @@ pub fn initialize_git_transport() -> Result<()> {
     git_sync::initialize_transport_timeouts()
 }
 
+use crate::changes::Deploy;
 use crate::forge::detect_repo;
 use crate::forge::git_sync;
-use crate::forge::ForgeKind;
 // LatestRelease and ReleaseAsset re-exported via `pub use model::` above.
 
 #[cfg(windows)]
@@ -256,7 +281,7 @@ fn remove_windows_dir_link(path: &Path) -> Result<()> {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct UpdatePlan {
     pub repo_id: i64,
     pub forge: String,
@@ -287,6 +312,10 @@ pub struct UpdatePlan {
     pub not_modified: bool,
     pub applied: bool,
     pub error: Option<String>,
+    /// Seconds since the release data behind this plan was downloaded. Zero
+    /// for a fresh download, larger when a conditional request confirmed the
+    /// persistent cache, and `None` when no release data was consulted.
+    pub release_cache_age_secs: Option<u64>,
 
     /// Additional assets to install alongside the primary one.
     /// Only populated for Dll-mode repos that publish multiple individual .dll files.
@@ -299,6 +328,26 @@ pub struct UpdatePlan {
     pub is_manual: bool,
 }
 
+/// `.toc` metadata of one installed addon folder.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstalledAddonToc {
+    /// Install path relative to the WoW directory, as recorded for the repo.
+    pub path: String,
+    /// Folder name under `Interface/AddOns`.
+    pub folder: String,
+    pub toc: toc::AddonToc,
+}
+
+/// Outcome of [`Engine::rollback_to_history`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RollbackReport {
+    pub repo_id: i64,
+    /// The version that was redeployed and is now pinned.
+    pub version: String,
+    /// One entry per addon whose settings were put back.
+    pub settings: Vec<saved_variables::RestoreReport>,
+}
+
 /// Local-only comparison result for a tracked Git addon.
 ///
 /// This is intentionally derived from the installed worktree and its checked-out
@@ -416,6 +465,32 @@ fn should_skip_adaptive(
     }
 }
 
+/// Reduce user input to the `host[:port]` key of a registered forge host.
+fn normalize_forge_host(input: &str) -> Result<String> {
+    let authority = url_safety::normalize_host_authority(input)?;
+    let host = authority.split(':').next().unwrap_or_default();
+    if ["github.com", "gitlab.com", "codeberg.org"].contains(&host) {
+        anyhow::bail!("{host} is already supported and cannot be registered");
+    }
+    Ok(authority)
+}
+
+/// Validate an API root such as `/gitlab/api/v4`. Empty input selects the
+/// forge's default path.
+fn normalize_forge_api_path(input: &str) -> Result<Option<String>> {
+    let path = input.trim().trim_end_matches('/');
+    if path.is_empty() {
+        return Ok(None);
+    }
+    if !path.starts_with('/')
+        || path.contains(['?', '#', '\\', '@'])
+        || path.split('/').any(|segment| segment == "..")
+    {
+        anyhow::bail!("API base path must be an absolute path like /api/v1");
+    }
+    Ok(Some(path.to_string()))
+}
+
 pub struct Engine {
     db: std::sync::Mutex<Db>,
     client: Client,
@@ -465,6 +540,15 @@ fn normalize_collection_entry_key(name: &str) -> String {
     key
 }
 
+/// Whether two repository URLs name the same repo, ignoring case, a
+/// trailing slash and a `.git` suffix.
+fn repo_urls_match(left: &str, right: &str) -> bool {
+    fn trim_url(url: &str) -> &str {
+        url.trim().trim_end_matches('/').trim_end_matches(".git")
+    }
+    trim_url(left).eq_ignore_ascii_case(trim_url(right))
+}
+
 fn addon_git_name_matches_selection(addon_name: &str, selected_keys: &HashSet<String>) -> bool {
     selected_keys.contains(&normalize_collection_entry_key(addon_name))
 }
@@ -579,6 +663,8 @@ pub struct AddonProbeConflict {
 pub struct AddonProbeEntry {
     pub addon_name: String,
     pub source_path: String,
+    /// The `.toc`'s `## Interface` versions, when the probe could read it.
+    pub interface: Vec<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -611,6 +697,22 @@ struct PreparedReleaseTarget {
     sha256: Option<String>,
 }
 
+/// A release built beneath `.wuddle/cache/release-staging`, ready to be
+/// moved into the game folder. A dry run builds it in the system temp
+/// directory instead.
+struct StagedRelease {
+    _staging: tempfile::TempDir,
+    _raw_staging: Option<tempfile::TempDir>,
+    repo: Repo,
+    existing_installs: Vec<db::InstallEntry>,
+    final_backups: Vec<db::InstallBackupRow>,
+    targets: Vec<PreparedReleaseTarget>,
+    conflicts: Vec<ReleaseTargetConflict>,
+    staged_dlls_txt: PathBuf,
+    dlls_txt_relevant: bool,
+    is_local_asset: bool,
+}
+
 #[derive(Debug)]
 struct ReleaseTargetConflict {
     target_index: usize,
@@ -623,6 +725,8 @@ struct ReleaseTargetConflict {
 }
 
 static GITHUB_TOKEN: OnceLock<Mutex<Option<String>>> = OnceLock::new();
+static FORGE_TOKENS: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+static SSH_KEY_PATH: OnceLock<Mutex<Option<PathBuf>>> = OnceLock::new();
 
 static RE_GITHUB_RESET: LazyLock<regex::Regex> =
     LazyLock::new(|| regex::Regex::new(r"(?:reset |GITHUB_RATE_LIMIT:)(\d+)").unwrap());
@@ -631,6 +735,10 @@ const REMOTE_CHECK_TIMEOUT: Duration = Duration::from_secs(20);
 const MAX_CONCURRENT_GIT_UPDATE_CHECKS: usize = 4;
 const MAX_CONCURRENT_RELEASE_UPDATE_CHECKS: usize = 4;
 
+/// Latest releases fetched ahead of an update check, keyed by
+/// `forge::cache_key`.
+type PrefetchedReleases = HashMap<String, LatestRelease>;
+
 static RE_VERSION_FROM_ASSET: LazyLock<regex::Regex> = LazyLock::new(|| {
     // Suffix character class deliberately excludes '.' to avoid consuming file
     // extensions (e.g. "2.1-1.tar.gz" should match "2.1-1", not "2.1-1.tar.gz").
@@ -650,6 +758,102 @@ pub fn set_github_token(token: Option<String>) {
     }
 }
 
+fn forge_token_state() -> &'static Mutex<HashMap<String, String>> {
+    FORGE_TOKENS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Normalize a host or URL to the `host[:port]` key access tokens are stored
+/// under. GitHub has its own token and cannot be used here.
+pub fn normalize_forge_token_host(input: &str) -> Result<String> {
+    let authority = url_safety::normalize_host_authority(input)?;
+    let host = authority.split(':').next().unwrap_or_default();
+    if host == "github.com" || host.ends_with(".github.com") {
+        anyhow::bail!("GitHub uses the GitHub token setting");
+    }
+    Ok(authority)
+}
+
+/// Activate or clear the access token for a GitLab or Gitea/Forgejo host.
+/// The token is only attached to HTTPS requests to exactly this host.
+pub fn set_forge_token(host: &str, token: Option<String>) -> Result<()> {
+    let host = normalize_forge_token_host(host)?;
+    let token = token
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    if let Ok(mut guard) = forge_token_state().lock() {
+        match token {
+            Some(token) => {
+                guard.insert(host, token);
+            }
+            None => {
+                guard.remove(&host);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The active token for `host`, a normalized `host[:port]` key. Without an
+/// activated token, falls back to `WUDDLE_FORGE_TOKENS`, a comma-separated
+/// list of `host=token` pairs, as `github_token` falls back to the
+/// environment.
+pub fn forge_token(host: &str) -> Option<String> {
+    let host = host.to_ascii_lowercase();
+    if let Some(token) = forge_token_state()
+        .lock()
+        .ok()
+        .and_then(|guard| guard.get(&host).cloned())
+    {
+        return Some(token);
+    }
+    parse_forge_token_list(&std::env::var("WUDDLE_FORGE_TOKENS").ok()?, &host)
+}
+
+/// The token for `host` in a `host=token[,host=token...]` list.
+pub(crate) fn parse_forge_token_list(list: &str, host: &str) -> Option<String> {
+    list.split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| normalize_forge_token_host(key.trim()).is_ok_and(|key| key == host))
+        .map(|(_, token)| token.trim().to_string())
+        .filter(|token| !token.is_empty())
+}
+
+/// Hosts that currently have an active token, sorted.
+pub fn forge_token_hosts() -> Vec<String> {
+    let Ok(guard) = forge_token_state().lock() else {
+        return Vec::new();
+    };
+    let mut hosts: Vec<String> = guard.keys().cloned().collect();
+    hosts.sort();
+    hosts
+}
+
+fn ssh_key_state() -> &'static Mutex<Option<PathBuf>> {
+    SSH_KEY_PATH.get_or_init(|| Mutex::new(None))
+}
+
+/// Select the private key offered to SSH Git remotes when ssh-agent has no
+/// accepted identity. Only the path is kept; the key is read by libgit2 at
+/// connection time and passphrase-protected keys must be loaded into an agent.
+pub fn set_ssh_key_path(path: Option<PathBuf>) {
+    let normalized = path.filter(|p| !p.as_os_str().is_empty());
+    if let Ok(mut guard) = ssh_key_state().lock() {
+        *guard = normalized;
+    }
+}
+
+/// The configured SSH key, falling back to `WUDDLE_SSH_KEY`.
+pub fn ssh_key_path() -> Option<PathBuf> {
+    if let Ok(guard) = ssh_key_state().lock() {
+        if let Some(path) = guard.clone() {
+            return Some(path);
+        }
+    }
+    std::env::var_os("WUDDLE_SSH_KEY")
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+}
+
 pub fn github_token() -> Option<String> {
     if let Ok(guard) = github_token_state().lock() {
         if let Some(token) = guard.clone() {
@@ -714,8 +918,13 @@ impl Engine {
 
     pub fn open(db_path: &Path) -> Result<Self> {
         let _diagnostic = diagnostics::OperationGuard::new("open_engine");
+        let db = Db::open(db_path)?;
+        Self::recover_install_journals(&db);
+        // Repository detection is a pure function of the URL, so registered
+        // hosts of the opened profile are published process-wide.
+        forge::set_custom_forge_hosts(db.list_forge_hosts()?);
         Ok(Self {
-            db: std::sync::Mutex::new(Db::open(db_path)?),
+            db: std::sync::Mutex::new(db),
             client: Client::builder()
                 .user_agent("wuddle-engine")
                 .connect_timeout(Duration::from_secs(10))
@@ -731,6 +940,81 @@ impl Engine {
         })
     }
 
+    /// Finish or roll back replacement transactions a crash or power loss
+    /// interrupted. Results are queued for [`take_recovered_transactions`].
+    fn recover_install_journals(db: &Db) {
+        let journals = match db.list_install_journals() {
+            Ok(journals) => journals,
+            Err(error) => {
+                diagnostics::emit(
+                    diagnostics::DiagnosticLevel::Debug,
+                    "engine.journal",
+                    format!("install journals could not be listed: {error}"),
+                );
+                return;
+            }
+        };
+        for journal in journals {
+            let recovered = match deployment::recover(Path::new(&journal.path), journal.committed) {
+                Ok(
+                    deployment::JournalRecovery::Running | deployment::JournalRecovery::Unavailable,
+                ) => continue,
+                Ok(deployment::JournalRecovery::Clean) => None,
+                Ok(deployment::JournalRecovery::Recovered(recovered)) => Some(recovered),
+                Err(error) => Some(RecoveredTransaction {
+                    operation: journal.operation.clone(),
+                    repo_id: journal.repo_id,
+                    action: RecoveryAction::Failed,
+                    restored: 0,
+                    error: Some(format!("{error:#}")),
+                }),
+            };
+            let settled = recovered
+                .as_ref()
+                .is_none_or(|recovered| recovered.action != RecoveryAction::Failed);
+            if settled {
+                let _ = db.remove_install_journal(&journal.id);
+            }
+            if let Some(recovered) = recovered {
+                diagnostics::emit(
+                    diagnostics::DiagnosticLevel::Debug,
+                    "engine.journal",
+                    recovered.describe(),
+                );
+                deployment::record_recovered(recovered);
+            }
+        }
+    }
+
+    /// Start a journaled replacement of live files under `wow_dir`.
+    fn begin_replacement(
+        &self,
+        operation: &'static str,
+        repo_id: i64,
+        wow_dir: &Path,
+    ) -> Result<deployment::ReplacementTransaction> {
+        let transaction = deployment::ReplacementTransaction::new(
+            operation,
+            repo_id,
+            &wow_dir.join(".wuddle").join("journal"),
+        )?;
+        self.db().register_install_journal(
+            transaction.id(),
+            &transaction.journal_path().to_string_lossy(),
+            operation,
+            repo_id,
+            Self::now_unix(),
+        )?;
+        Ok(transaction)
+    }
+
+    /// Keep a replacement whose records SQLite committed.
+    fn finish_replacement(&self, mut transaction: deployment::ReplacementTransaction) {
+        transaction.disarm();
+        // A leftover record is removed by the next recovery pass.
+        let _ = self.db().remove_install_journal(transaction.id());
+    }
+
     pub fn open_default() -> Result<Self> {
         let db_path = util::db_path()?;
         Self::open(&db_path)
@@ -755,6 +1039,78 @@ impl Engine {
         })
     }
 
+    pub fn list_forge_hosts(&self) -> Result<Vec<CustomForgeHost>> {
+        self.db().list_forge_hosts()
+    }
+
+    /// Register a self-hosted GitLab or Gitea/Forgejo instance so its
+    /// repositories are checked through the release API instead of as plain
+    /// Git. `host` may be a bare host (`git.example.org:3000`) or a URL.
+    pub fn add_forge_host(
+        &self,
+        host: &str,
+        kind: ForgeKind,
+        api_base_path: Option<&str>,
+    ) -> Result<CustomForgeHost> {
+        let _diagnostic = diagnostics::OperationGuard::new("add_forge_host");
+        if !matches!(kind, ForgeKind::GitLab | ForgeKind::Gitea) {
+            anyhow::bail!("only GitLab and Gitea hosts can be registered");
+        }
+        let entry = CustomForgeHost {
+            host: normalize_forge_host(host)?,
+            kind,
+            api_base_path: api_base_path
+                .map(normalize_forge_api_path)
+                .transpose()?
+                .flatten(),
+        };
+        let bare_host = entry.host.split(':').next().unwrap_or_default().to_string();
+        {
+            let db = self.db();
+            db.upsert_forge_host(&entry)?;
+            let relabelled = db.relabel_repo_forge(
+                &bare_host,
+                "git",
+                if kind == ForgeKind::GitLab {
+                    "gitlab"
+                } else {
+                    "gitea"
+                },
+            )?;
+            forge::set_custom_forge_hosts(db.list_forge_hosts()?);
+            diagnostics::emit(
+                diagnostics::DiagnosticLevel::Debug,
+                "engine.forge",
+                format!("registered custom forge host; relabelled_repos={relabelled}"),
+            );
+        }
+        Ok(entry)
+    }
+
+    /// Forget a registered host. Its repositories fall back to generic Git.
+    pub fn remove_forge_host(&self, host: &str) -> Result<bool> {
+        let _diagnostic = diagnostics::OperationGuard::new("remove_forge_host");
+        let host = normalize_forge_host(host)?;
+        let db = self.db();
+        let Some(entry) = db
+            .list_forge_hosts()?
+            .into_iter()
+            .find(|entry| entry.host.eq_ignore_ascii_case(&host))
+        else {
+            return Ok(false);
+        };
+        db.delete_forge_host(&entry.host)?;
+        let bare_host = entry.host.split(':').next().unwrap_or_default();
+        let label = if entry.kind == ForgeKind::GitLab {
+            "gitlab"
+        } else {
+            "gitea"
+        };
+        db.relabel_repo_forge(bare_host, label, "git")?;
+        forge::set_custom_forge_hosts(db.list_forge_hosts()?);
+        Ok(true)
+    }
+
     pub fn add_repo(
         &self,
         url: &str,
@@ -774,6 +1130,9 @@ impl Engine {
             ),
         );
         url_safety::reject_embedded_credentials(url)?;
+        if url_safety::is_ssh_remote(url) && !matches!(&mode, InstallMode::AddonGit) {
+            anyhow::bail!("SSH remotes can only be tracked as addon_git repositories");
+        }
         let identity = if matches!(&mode, InstallMode::AddonGit) {
             gam_compat::identity_from_remote(url)
                 .context("Could not identify git repository URL")?
@@ -831,6 +1190,7 @@ impl Engine {
             merge_installs: false,
             pinned_version,
             selected_addons_json,
+            git_tag_policy: None,
         };
 
         self.db().add_repo(&repo)
@@ -884,6 +1244,7 @@ impl Engine {
             merge_installs: false,
             pinned_version: None,
             selected_addons_json: None,
+            git_tag_policy: None,
         };
 
         self.db().add_repo(&repo)
@@ -1072,6 +1433,7 @@ impl Engine {
                                 addon_entries.push(AddonProbeEntry {
                                     addon_name: stem.to_string(),
                                     source_path: parent.to_str().unwrap_or("").replace('\\', "/"),
+                                    interface: Vec::new(),
                                 });
                             }
                         }
@@ -1092,8 +1454,15 @@ impl Engine {
         }
 
         let probe_dir = tempfile::tempdir().context("create addon probe dir")?;
-        let synced = git_sync::sync_repo(url, probe_dir.path(), preferred_branch_inner)
-            .with_context(|| format!("git sync {}", url_safety::safe_remote_label(url)))?;
+        // Detection reads the Git tree, so the probe never writes files.
+        let synced = git_sync::sync_repo(
+            url,
+            probe_dir.path(),
+            preferred_branch_inner,
+            None,
+            git_sync::WorktreeCheckout::Deferred,
+        )
+        .with_context(|| format!("git sync {}", url_safety::safe_remote_label(url)))?;
 
         let mut detected = install::detect_addons_in_tree(probe_dir.path());
         detected.sort_by_key(|(src, name)| (src.components().count(), name.clone()));
@@ -1113,9 +1482,14 @@ impl Engine {
                             .replace('\\', "/")
                     })
                     .unwrap_or_else(|| addon_name.clone());
+                let interface =
+                    toc::read_git_addon_toc(probe_dir.path(), &source_path, &addon_name)
+                        .map(|toc| toc.interface)
+                        .unwrap_or_default();
                 addon_entries.push(AddonProbeEntry {
                     addon_name: addon_name.clone(),
                     source_path,
+                    interface,
                 });
                 addon_names.push(addon_name);
             }
@@ -1190,6 +1564,7 @@ impl Engine {
             not_modified: false,
             applied: false,
             error: None,
+            release_cache_age_secs: None,
             extra_assets: Vec::new(),
             previous_dll_count: 0,
             new_dll_count: 0,
@@ -1539,6 +1914,7 @@ impl Engine {
             .include_untracked(true)
             .recurse_untracked_dirs(true)
             .include_ignored(false);
+        let sparse_dirs = git_sync::sparse_checkout_dirs(worktree);
         let statuses = repository
             .statuses(Some(&mut options))
             .context("inspect installed addon worktree changes")?;
@@ -1548,11 +1924,18 @@ impl Engine {
                 .path()
                 .map(Path::new)
                 .context("read installed addon Git status path")?;
-            let is_expected_moved_deletion = status.bits() & !git2::Status::WT_DELETED.bits() == 0
+            let only_deleted = status.bits() & !git2::Status::WT_DELETED.bits() == 0;
+            let is_expected_moved_deletion = only_deleted
                 && allowed_moved_paths
                     .iter()
                     .any(|allowed| path == allowed || path.starts_with(allowed));
-            if !is_expected_moved_deletion {
+            // libgit2 reports skip-worktree entries outside a sparse checkout
+            // as deleted.
+            let is_outside_sparse_checkout = only_deleted
+                && sparse_dirs
+                    .as_deref()
+                    .is_some_and(|dirs| !git_sync::sparse_includes(dirs, &path.to_string_lossy()));
+            if !is_expected_moved_deletion && !is_outside_sparse_checkout {
                 return Ok(Some("the Git worktree contains unexpected changes"));
             }
         }
@@ -1984,6 +2367,7 @@ impl Engine {
                         merge_installs: false,
                         pinned_version: None,
                         selected_addons_json: None,
+                        git_tag_policy: None,
                     };
 
                     if let Ok(id) = self.db().add_repo(&tracked) {
@@ -2073,6 +2457,7 @@ impl Engine {
                     merge_installs: false,
                     pinned_version: None,
                     selected_addons_json: None,
+                    git_tag_policy: None,
                 };
 
                 let add_repo_result = { self.db().add_repo(&tracked) };
@@ -2094,6 +2479,346 @@ impl Engine {
         Ok(imported)
     }
 
+    /// Fingerprint the installed addon folders of every tracked repo and add
+    /// them to the catalog at the repo's current version, so the folders can
+    /// be recognised elsewhere later. Returns how many folders were recorded.
+    pub fn refresh_addon_catalog(&self, wow_dir: &Path) -> Result<usize> {
+        let _diagnostic = diagnostics::OperationGuard::new("refresh_addon_catalog");
+        let repos = self.db().list_repos()?;
+        let mut entries = Vec::new();
+        for repo in repos {
+            if matches!(repo.mode, InstallMode::Manual | InstallMode::Mpq) || repo.url.is_empty() {
+                continue;
+            }
+            for folder in self.repo_addon_folders(repo.id)? {
+                let addons_dir = wow_dir.join("Interface").join("AddOns");
+                let Some(dir) = mpq::find_case_insensitive_child(&addons_dir, &folder) else {
+                    continue;
+                };
+                match fingerprint::fingerprint_folder(&dir) {
+                    Ok(print) => entries.push(fingerprint::CatalogEntry {
+                        url: repo.url.clone(),
+                        folder: print.folder,
+                        version: repo.last_version.clone(),
+                        mode: Some(repo.mode.as_str().to_string()),
+                        toc_sha256: print.toc_sha256,
+                        tree_sha256: Some(print.tree_sha256),
+                    }),
+                    Err(error) => diagnostics::emit(
+                        diagnostics::DiagnosticLevel::Debug,
+                        "engine.catalog",
+                        format!(
+                            "could not fingerprint tracked addon: repo_id={}; folder={folder}; error={error:#}",
+                            repo.id
+                        ),
+                    ),
+                }
+            }
+        }
+        self.db()
+            .upsert_catalog_entries(fingerprint::CatalogSource::Tracked, &entries)?;
+        Ok(entries.len())
+    }
+
+    /// Add the entries of a catalog file shared by another user. Returns how
+    /// many were added or refreshed.
+    pub fn import_addon_catalog(&self, file: &fingerprint::CatalogFile) -> Result<usize> {
+        for entry in &file.entries {
+            url_safety::reject_embedded_credentials(&entry.url)?;
+            if entry.url.trim().is_empty() || entry.folder.trim().is_empty() {
+                anyhow::bail!("Catalog entries need both a url and a folder.");
+            }
+        }
+        self.db()
+            .upsert_catalog_entries(fingerprint::CatalogSource::Imported, &file.entries)?;
+        Ok(file.entries.len())
+    }
+
+    /// The catalog entries recorded from tracked repos, as a file others can
+    /// import.
+    pub fn export_addon_catalog(&self) -> Result<fingerprint::CatalogFile> {
+        let entries = self
+            .db()
+            .list_catalog_entries(Some(fingerprint::CatalogSource::Tracked))?
+            .into_iter()
+            .map(|(_, entry)| entry)
+            .collect();
+        Ok(fingerprint::CatalogFile { entries })
+    }
+
+    /// Fingerprint every addon folder that no repo with a known source owns,
+    /// whether untracked or imported as a `manual` repo, and match it against
+    /// the catalog and the folders listed by imported addon catalogs.
+    /// `known_sources` are further `(folder, url)` pairs such as the Quick Add
+    /// presets. Only folders with at least one match are returned.
+    pub fn identify_untracked_addons(
+        &self,
+        wow_dir: &Path,
+        known_sources: &[(String, String)],
+    ) -> Result<Vec<fingerprint::AddonIdentification>> {
+        let _diagnostic = diagnostics::OperationGuard::new("identify_untracked_addons");
+        self.refresh_addon_catalog(wow_dir)?;
+        let mut catalog = self.db().list_catalog_entries(None)?;
+        let name_only = |source, (folder, url): (String, String)| {
+            (
+                source,
+                fingerprint::CatalogEntry {
+                    url,
+                    folder,
+                    version: None,
+                    mode: None,
+                    toc_sha256: None,
+                    tree_sha256: None,
+                },
+            )
+        };
+        catalog.extend(
+            self.indexed_addon_sources()?
+                .into_iter()
+                .map(|pair| name_only(fingerprint::CatalogSource::Imported, pair)),
+        );
+        catalog.extend(
+            known_sources
+                .iter()
+                .cloned()
+                .map(|pair| name_only(fingerprint::CatalogSource::Preset, pair)),
+        );
+
+        let mut owned = HashMap::<String, Option<i64>>::new();
+        let repos = self.db().list_repos()?;
+        for repo in repos {
+            let manual = matches!(repo.mode, InstallMode::Manual).then_some(repo.id);
+            for folder in self.repo_addon_folders(repo.id)? {
+                let owner = owned.entry(folder.to_ascii_lowercase()).or_insert(manual);
+                if manual.is_none() {
+                    *owner = None;
+                }
+            }
+        }
+
+        let addons_dir = wow_dir.join("Interface").join("AddOns");
+        let mut identified = Vec::new();
+        for (folder, _) in addon_deps::scan_addon_folders(&addons_dir) {
+            if addon_deps::is_builtin(&folder)
+                || Self::has_local_git_marker(&addons_dir.join(&folder))
+            {
+                continue;
+            }
+            let manual_repo_id = match owned.get(&folder.to_ascii_lowercase()) {
+                Some(None) => continue,
+                Some(Some(id)) => Some(*id),
+                None => None,
+            };
+            let print = match fingerprint::fingerprint_folder(&addons_dir.join(&folder)) {
+                Ok(print) => print,
+                Err(error) => {
+                    diagnostics::emit(
+                        diagnostics::DiagnosticLevel::Debug,
+                        "engine.catalog",
+                        format!("could not fingerprint addon: folder={folder}; error={error:#}"),
+                    );
+                    continue;
+                }
+            };
+            let matches = fingerprint::match_fingerprint(&print, &catalog);
+            if !matches.is_empty() {
+                identified.push(fingerprint::AddonIdentification {
+                    folder,
+                    manual_repo_id,
+                    fingerprint: print,
+                    matches,
+                });
+            }
+        }
+        Ok(identified)
+    }
+
+    /// Make `source` the owner of the untracked addon folder `folder`, adding
+    /// its repo when it is not tracked yet. A `manual` repo that recorded the
+    /// folder is dropped; the files stay in place until the next update
+    /// replaces them. Returns the repo id.
+    pub fn link_addon_folder(
+        &self,
+        wow_dir: &Path,
+        folder: &str,
+        source: &fingerprint::SourceMatch,
+    ) -> Result<i64> {
+        let _diagnostic = diagnostics::OperationGuard::new("link_addon_folder");
+        let addons_dir = wow_dir.join("Interface").join("AddOns");
+        let dir = mpq::find_case_insensitive_child(&addons_dir, folder)
+            .ok_or_else(|| anyhow::anyhow!("{folder} is not in Interface/AddOns."))?;
+        let path = Self::to_manifest_path(&dir, wow_dir);
+        let owners = self.db().find_addon_install_owners(&path, None)?;
+        let mut manual_owners = Vec::new();
+        for owner in owners {
+            let repo = self.db().get_repo(owner.repo_id)?;
+            if !matches!(repo.mode, InstallMode::Manual) {
+                anyhow::bail!(
+                    "{folder} is already tracked by {}/{}.",
+                    repo.owner,
+                    repo.name
+                );
+            }
+            manual_owners.push(repo.id);
+        }
+
+        let mode = source
+            .mode
+            .as_deref()
+            .and_then(InstallMode::parse)
+            .filter(|mode| matches!(mode, InstallMode::Addon | InstallMode::AddonGit))
+            .unwrap_or_else(|| {
+                if release_tag_from_url(&source.url).is_some() {
+                    InstallMode::Addon
+                } else {
+                    InstallMode::AddonGit
+                }
+            });
+        let repo_id = self.add_repo(&source.url, mode, None, None)?;
+        let repo = self.db().get_repo(repo_id)?;
+        if repo.last_version.is_none() {
+            self.db()
+                .set_last_version(repo_id, source.version.as_deref())?;
+        }
+        self.db()
+            .add_install(repo_id, &path, "addon", source.version.as_deref())?;
+        for manual_id in manual_owners {
+            self.db().remove_install(manual_id, &path)?;
+            let remaining = self.db().list_installs(manual_id)?;
+            if remaining.is_empty() {
+                self.db().remove_repo(manual_id)?;
+            }
+        }
+        if let Err(error) = self.refresh_addon_metadata(repo_id, wow_dir) {
+            diagnostics::emit(
+                diagnostics::DiagnosticLevel::Debug,
+                "engine",
+                format!("could not refresh addon metadata: repo_id={repo_id}; error={error}"),
+            );
+        }
+        if let Err(error) = self.refresh_addon_dependencies(wow_dir) {
+            diagnostics::emit(
+                diagnostics::DiagnosticLevel::Debug,
+                "engine",
+                format!("could not refresh addon dependencies: error={error}"),
+            );
+        }
+        diagnostics::emit(
+            diagnostics::DiagnosticLevel::Debug,
+            "engine.catalog",
+            format!(
+                "addon folder linked: repo_id={repo_id}; match={}; source={}",
+                source.kind.label(),
+                source.source.as_str()
+            ),
+        );
+        Ok(repo_id)
+    }
+
+    fn store_addon_index(&self, raw: &str, name: &str, url: Option<&str>) -> Result<IndexSource> {
+        let file = addon_index::parse_index(raw)?;
+        let name = if file.name.is_empty() {
+            name.trim()
+        } else {
+            file.name.as_str()
+        };
+        anyhow::ensure!(!name.is_empty(), "Name the addon catalog.");
+        let id = self
+            .db()
+            .replace_addon_index(name, url, Self::now_unix(), &file)?;
+        diagnostics::emit(
+            diagnostics::DiagnosticLevel::Debug,
+            "engine.index",
+            format!(
+                "addon catalog stored: id={id}; entries={}; remote={}",
+                file.entries.len(),
+                url.is_some()
+            ),
+        );
+        self.db()
+            .list_addon_index_sources()?
+            .into_iter()
+            .find(|source| source.id == id)
+            .context("addon catalog disappeared after import")
+    }
+
+    /// Import a catalog file. `fallback_name` names it when the file has no
+    /// `name`; importing a catalog again replaces its entries.
+    pub fn import_addon_index(&self, raw: &str, fallback_name: &str) -> Result<IndexSource> {
+        let _diagnostic = diagnostics::OperationGuard::new("import_addon_index");
+        self.store_addon_index(raw, fallback_name, None)
+    }
+
+    /// Fetch a catalog from `url` and remember the URL for refreshes.
+    pub async fn add_addon_index_url(&self, url: &str) -> Result<IndexSource> {
+        let _diagnostic = diagnostics::OperationGuard::new("add_addon_index_url");
+        let raw = addon_index::fetch_index(url).await?;
+        let fallback = url
+            .trim_end_matches('/')
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .trim_end_matches(".json");
+        self.store_addon_index(&raw, fallback, Some(url.trim()))
+    }
+
+    /// Fetch a catalog again from the URL it was added from.
+    pub async fn refresh_addon_index(&self, id: i64) -> Result<IndexSource> {
+        let _diagnostic = diagnostics::OperationGuard::new("refresh_addon_index");
+        let source = self
+            .db()
+            .list_addon_index_sources()?
+            .into_iter()
+            .find(|source| source.id == id)
+            .with_context(|| format!("No addon catalog with id {id}."))?;
+        let url = source.url.with_context(|| {
+            format!("{} was imported from a file; import it again.", source.name)
+        })?;
+        let raw = addon_index::fetch_index(&url).await?;
+        self.store_addon_index(&raw, &source.name, Some(&url))
+    }
+
+    pub fn addon_index_sources(&self) -> Result<Vec<IndexSource>> {
+        self.db().list_addon_index_sources()
+    }
+
+    /// Returns whether a catalog with that id existed.
+    pub fn remove_addon_index(&self, id: i64) -> Result<bool> {
+        self.db().remove_addon_index_source(id)
+    }
+
+    /// Search every imported catalog offline. An addon listed by several
+    /// catalogs appears once, as the oldest catalog describes it.
+    pub fn search_addon_index(&self, query: &IndexQuery) -> Result<Vec<IndexEntry>> {
+        Ok(addon_index::search(&self.addon_index_entries()?, query))
+    }
+
+    fn addon_index_entries(&self) -> Result<Vec<IndexEntry>> {
+        let mut seen = HashSet::new();
+        Ok(self
+            .db()
+            .list_addon_index_entries()?
+            .into_iter()
+            .filter(|entry| seen.insert(entry.url.trim_end_matches('/').to_ascii_lowercase()))
+            .collect())
+    }
+
+    /// `(folder, url)` pairs for the addon folders imported catalogs list.
+    fn indexed_addon_sources(&self) -> Result<Vec<(String, String)>> {
+        Ok(self
+            .addon_index_entries()?
+            .into_iter()
+            .filter(|entry| entry.kind == IndexKind::Addon)
+            .flat_map(|entry| {
+                let url = entry.url;
+                entry
+                    .folders
+                    .into_iter()
+                    .map(move |folder| (folder, url.clone()))
+            })
+            .collect())
+    }
+
     /// Remove duplicate addon_git repos that share the same on-disk addon
     /// folders. Keeps the repo whose git remote matches what's actually
     /// cloned on disk; removes the other(s).
@@ -2504,6 +3229,34 @@ impl Engine {
         Ok(pruned)
     }
 
+    /// The tag an addon_git repo should track: a pinned version names one
+    /// exactly, otherwise the repo's tag policy picks the newest match.
+    fn git_tag_target(r: &Repo) -> Option<git_sync::TagTarget<'_>> {
+        let pinned = r
+            .pinned_version
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty());
+        match pinned {
+            Some(tag) => Some(git_sync::TagTarget::Exact(tag)),
+            None => r.git_tag_policy.as_ref().map(git_sync::TagTarget::Latest),
+        }
+    }
+
+    /// A full commit id an addon_git repo is pinned to, as left by rolling
+    /// back to an untagged install. Its state is known without asking the
+    /// remote.
+    fn git_pinned_commit(r: &Repo) -> Option<Result<git_sync::GitHeadState>> {
+        let pinned = r.pinned_version.as_deref().map(str::trim)?;
+        git_sync::is_commit_id(pinned).then(|| {
+            let branch = r
+                .installed_asset_name
+                .as_deref()
+                .and_then(|name| name.strip_prefix("git:"));
+            git_sync::pinned_commit_state(pinned, branch)
+        })
+    }
+
     fn build_git_addon_plan_for_repo(
         &self,
         r: &Repo,
@@ -2555,7 +3308,10 @@ impl Engine {
             );
             return Ok(p);
         };
-        let remote = match git_sync::remote_head_for_branch(&remote_url, preferred_branch) {
+        let remote = Self::git_pinned_commit(r).unwrap_or_else(|| {
+            git_sync::remote_head_for_branch(&remote_url, preferred_branch, Self::git_tag_target(r))
+        });
+        let remote = match remote {
             Ok(v) => v,
             Err(e) => {
                 let mut p = Self::blank_plan(r);
@@ -2570,10 +3326,14 @@ impl Engine {
 
         let current = local
             .as_ref()
-            .map(|h| h.short_oid.clone())
+            .map(|h| h.tag.clone().unwrap_or_else(|| h.short_oid.clone()))
             .or_else(|| Self::normalized_current_version(r));
         let installed_matches = local.as_ref().map(|h| h.oid == remote.oid).unwrap_or(false);
         let needs_sync = !installed_matches;
+        let remote_version = remote
+            .tag
+            .clone()
+            .unwrap_or_else(|| remote.short_oid.clone());
 
         Ok(UpdatePlan {
             repo_id: r.id,
@@ -2584,8 +3344,8 @@ impl Engine {
             url: remote_url.clone(),
             mode: r.mode.clone(),
             current,
-            install_version: remote.short_oid.clone(),
-            latest: remote.short_oid.clone(),
+            install_version: remote_version.clone(),
+            latest: remote_version,
             asset_id: remote.oid.clone(),
             asset_name: format!("git:{}", remote.branch),
             asset_url: if needs_sync {
@@ -2600,6 +3360,7 @@ impl Engine {
             not_modified: false,
             applied: false,
             error: None,
+            release_cache_age_secs: None,
             extra_assets: Vec::new(),
             previous_dll_count: 0,
             new_dll_count: 0,
@@ -2664,16 +3425,23 @@ impl Engine {
         };
         let preferred_for_task = preferred_branch.clone();
         let url_for_task = url.clone();
+        let repo_for_task = r.clone();
         let worker_cancelled = Arc::clone(cancelled);
-        let remote = tokio::task::spawn_blocking(move || {
-            git_sync::remote_head_for_branch_bounded(
-                &url_for_task,
-                preferred_for_task.as_deref(),
-                REMOTE_CHECK_TIMEOUT,
-                worker_cancelled,
-            )
-        })
-        .await;
+        let remote = match Self::git_pinned_commit(r) {
+            Some(pinned) => Ok(pinned),
+            None => {
+                tokio::task::spawn_blocking(move || {
+                    git_sync::remote_head_for_branch_bounded(
+                        &url_for_task,
+                        preferred_for_task.as_deref(),
+                        Self::git_tag_target(&repo_for_task),
+                        REMOTE_CHECK_TIMEOUT,
+                        worker_cancelled,
+                    )
+                })
+                .await
+            }
+        };
         let remote = match remote {
             Ok(Ok(v)) => v,
             Ok(Err(e)) => {
@@ -2698,10 +3466,14 @@ impl Engine {
 
         let current = local
             .as_ref()
-            .map(|h| h.short_oid.clone())
+            .map(|h| h.tag.clone().unwrap_or_else(|| h.short_oid.clone()))
             .or_else(|| Self::normalized_current_version(r));
         let installed_matches = local.as_ref().map(|h| h.oid == remote.oid).unwrap_or(false);
         let needs_sync = !installed_matches;
+        let remote_version = remote
+            .tag
+            .clone()
+            .unwrap_or_else(|| remote.short_oid.clone());
 
         Ok(UpdatePlan {
             repo_id: r.id,
@@ -2712,8 +3484,8 @@ impl Engine {
             url: url.clone(),
             mode: r.mode.clone(),
             current,
-            install_version: remote.short_oid.clone(),
-            latest: remote.short_oid.clone(),
+            install_version: remote_version.clone(),
+            latest: remote_version,
             asset_id: remote.oid.clone(),
             asset_name: format!("git:{}", remote.branch),
             asset_url: if needs_sync { url } else { "".to_string() },
@@ -2724,6 +3496,7 @@ impl Engine {
             not_modified: false,
             applied: false,
             error: None,
+            release_cache_age_secs: None,
             extra_assets: Vec::new(),
             previous_dll_count: 0,
             new_dll_count: 0,
@@ -2768,6 +3541,7 @@ impl Engine {
             not_modified: !needs_download,
             applied: false,
             error: None,
+            release_cache_age_secs: None,
             extra_assets: Vec::new(),
             previous_dll_count: 0,
             new_dll_count: 0,
@@ -2775,12 +3549,20 @@ impl Engine {
         })
     }
 
+    /// `prefetched` holds release payloads a batched query already fetched
+    /// for this check; a repository found there is not requested again.
+    /// Without `persist` the check records nothing in the profile: no
+    /// validators, cached releases or rate limits. Dry runs and read-only
+    /// views plan this way.
+    #[allow(clippy::too_many_arguments)]
     async fn build_update_plan_for_repo(
         &self,
         r: &Repo,
         use_cached_etag: bool,
+        persist: bool,
         wow_dir: Option<&Path>,
         check_mode: CheckMode,
+        prefetched: Option<&PrefetchedReleases>,
         progress_tx: Option<&tokio::sync::mpsc::UnboundedSender<UpdateCheckProgress>>,
         cancelled: &Arc<AtomicBool>,
     ) -> Result<UpdatePlan> {
@@ -2838,28 +3620,61 @@ impl Engine {
 
         if det.kind == ForgeKind::GitHub {
             if Self::has_github_token() {
-                let _ = self.db().clear_rate_limit(&r.host);
+                if persist {
+                    let _ = self.db().clear_rate_limit(&r.host);
+                }
             } else if let Some(reset_epoch) = self.active_stored_github_rate_limit(&r.host, now)? {
                 return Ok(Self::rate_limited_plan(r, reset_epoch));
             }
         }
 
-        let mut etag = if use_cached_etag {
-            r.etag.as_deref()
+        // The persistent release cache is keyed by forge endpoint and keeps the
+        // payload behind its validators, so a 304 after a restart still yields
+        // a complete plan. Profiles that predate it fall back to the per-repo
+        // ETag, whose 304 only confirms the installed release.
+        let cache_key = forge::cache_key(&det);
+        let mut cached = if use_cached_etag {
+            self.db().get_cached_release(&cache_key).unwrap_or(None)
         } else {
             None
         };
+        let mut validators = match &cached {
+            Some(row) => forge::ReleaseValidators {
+                etag: row.etag.clone(),
+                last_modified: row.last_modified.clone(),
+            },
+            None if use_cached_etag => forge::ReleaseValidators {
+                etag: r.etag.clone(),
+                last_modified: None,
+            },
+            None => forge::ReleaseValidators::default(),
+        };
         let mut attempted_uncached = !use_cached_etag;
+        let mut prefetched = prefetched
+            .and_then(|releases| releases.get(&cache_key))
+            .cloned();
 
-        let rel = loop {
+        let (rel, release_cache_age_secs) = loop {
             Self::send_update_progress(progress_tx, r, UpdateCheckProgressStage::FetchingRelease);
             let remote_started = Instant::now();
-            let (new_etag, rel_opt, not_modified) = match tokio::time::timeout(
-                REMOTE_CHECK_TIMEOUT,
-                forge::latest_release(&self.client, &det, etag),
-            )
-            .await
-            {
+            let fetched = match prefetched.take() {
+                // A batch result that matches the stored payload confirms it
+                // like a 304 would, keeping the stored validators.
+                Some(release) => Ok(Ok(match &cached {
+                    Some(row) if Self::same_release(&row.release, &release) => {
+                        (validators.clone(), None, true)
+                    }
+                    _ => (forge::ReleaseValidators::default(), Some(release), false),
+                })),
+                None => {
+                    tokio::time::timeout(
+                        REMOTE_CHECK_TIMEOUT,
+                        forge::latest_release(&self.client, &det, &validators),
+                    )
+                    .await
+                }
+            };
+            let (new_validators, rel_opt, not_modified) = match fetched {
                 Ok(Ok(v)) => v,
                 Ok(Err(e)) => {
                     let msg = e.to_string();
@@ -2879,7 +3694,9 @@ impl Engine {
                     );
                     if det.kind == ForgeKind::GitHub {
                         if let Some(reset_epoch) = Self::parse_github_reset_epoch(&msg) {
-                            let _ = self.db().set_rate_limit(&r.host, reset_epoch);
+                            if persist {
+                                let _ = self.db().set_rate_limit(&r.host, reset_epoch);
+                            }
                             return Ok(Self::rate_limited_plan(r, reset_epoch));
                         }
                     }
@@ -2922,14 +3739,23 @@ impl Engine {
             );
             Self::send_update_progress(progress_tx, r, UpdateCheckProgressStage::SelectingRelease);
 
-            if let Some(ref et) = new_etag {
-                let _ = self.db().update_etag(r.id, Some(et.as_str()));
-            }
-            if det.kind == ForgeKind::GitHub {
-                let _ = self.db().clear_rate_limit(&r.host);
+            if persist {
+                if let Some(ref et) = new_validators.etag {
+                    let _ = self.db().update_etag(r.id, Some(et.as_str()));
+                }
+                if det.kind == ForgeKind::GitHub {
+                    let _ = self.db().clear_rate_limit(&r.host);
+                }
             }
 
             if not_modified {
+                if let Some(row) = cached.take() {
+                    if persist {
+                        let _ = self.db().mark_cached_release_validated(&cache_key, now);
+                    }
+                    let age = u64::try_from(now - row.fetched_at_unix).unwrap_or(0);
+                    break (row.release, Some(age));
+                }
                 let has_known_install = r.installed_asset_id.is_some() || r.last_version.is_some();
                 let needs_uncached_refresh = !attempted_uncached
                     && (!has_known_install
@@ -2937,7 +3763,7 @@ impl Engine {
                             && r.installed_asset_url.as_deref().unwrap_or("").is_empty()));
 
                 if needs_uncached_refresh {
-                    etag = None;
+                    validators = forge::ReleaseValidators::default();
                     attempted_uncached = true;
                     continue;
                 }
@@ -2964,10 +3790,19 @@ impl Engine {
 
             match rel_opt {
                 Some(x) => {
-                    if let Some(pub_at) = x.published_at {
-                        let _ = self.db().set_published_at(r.id, Some(pub_at));
+                    if persist {
+                        if let Some(pub_at) = x.published_at {
+                            let _ = self.db().set_published_at(r.id, Some(pub_at));
+                        }
+                        let _ = self.db().put_cached_release(
+                            &cache_key,
+                            new_validators.etag.as_deref(),
+                            new_validators.last_modified.as_deref(),
+                            &x,
+                            now,
+                        );
                     }
-                    break x;
+                    break (x, Some(0));
                 }
                 None => {
                     let mut p = Self::blank_plan(r);
@@ -3107,7 +3942,7 @@ impl Engine {
         // Clear the cached ETag when an update or repair is pending so that the
         // next check re-fetches the release instead of getting a 304 (which would
         // incorrectly report "up to date" while the update remains uninstalled).
-        if needs_download {
+        if needs_download && persist {
             let _ = self.db().update_etag(r.id, None);
         }
 
@@ -3145,6 +3980,7 @@ impl Engine {
             not_modified: false,
             applied: false,
             error: None,
+            release_cache_age_secs,
             extra_assets,
             previous_dll_count,
             new_dll_count,
@@ -3162,13 +3998,23 @@ impl Engine {
         repo: &Repo,
         wow_dir: Option<&Path>,
         check_mode: CheckMode,
+        prefetched: Option<&PrefetchedReleases>,
         progress_tx: Option<&tokio::sync::mpsc::UnboundedSender<UpdateCheckProgress>>,
         cancelled: &Arc<AtomicBool>,
     ) -> Result<UpdatePlan> {
         Self::ensure_update_check_active(cancelled)?;
         Self::send_update_progress(progress_tx, repo, UpdateCheckProgressStage::Started);
         let result = self
-            .build_update_plan_for_repo(repo, true, wow_dir, check_mode, progress_tx, cancelled)
+            .build_update_plan_for_repo(
+                repo,
+                true,
+                true,
+                wow_dir,
+                check_mode,
+                prefetched,
+                progress_tx,
+                cancelled,
+            )
             .await
             .with_context(|| format!("checking updates for '{}'", repo.name));
         // Publish the terminal event before returning so one slow peer cannot
@@ -3199,7 +4045,7 @@ impl Engine {
         // the pool whenever one completes. The scheduler waits for every
         // started probe before returning, including when one reports an error.
         update_scheduler::run_bounded_ordered(repos, MAX_CONCURRENT_GIT_UPDATE_CHECKS, |repo| {
-            self.check_one_update_plan(repo, wow_dir, check_mode, progress_tx, cancelled)
+            self.check_one_update_plan(repo, wow_dir, check_mode, None, progress_tx, cancelled)
         })
         .await
     }
@@ -3222,15 +4068,105 @@ impl Engine {
                 MAX_CONCURRENT_RELEASE_UPDATE_CHECKS
             ),
         );
+        let prefetched = self.prefetch_github_releases(repos, cancelled).await;
         // Keep release API checks bounded to avoid bursty rate-limit pressure,
         // but do not make faster repositories wait for the slowest member of a
         // fixed batch before another check can begin.
         update_scheduler::run_bounded_ordered(repos, MAX_CONCURRENT_RELEASE_UPDATE_CHECKS, |repo| {
-            self.check_one_update_plan(repo, wow_dir, check_mode, progress_tx, cancelled)
+            self.check_one_update_plan(
+                repo,
+                wow_dir,
+                check_mode,
+                Some(&prefetched),
+                progress_tx,
+                cancelled,
+            )
         })
         .await
     }
 
+    /// With a token, fetch the latest release of every GitHub repository in a
+    /// few GraphQL queries. Any repository missing from the result keeps the
+    /// per-repo REST path.
+    async fn prefetch_github_releases(
+        &self,
+        repos: &[Repo],
+        cancelled: &Arc<AtomicBool>,
+    ) -> PrefetchedReleases {
+        let mut prefetched = PrefetchedReleases::new();
+        if !Self::has_github_token() {
+            return prefetched;
+        }
+        let mut seen = HashSet::new();
+        let targets = repos
+            .iter()
+            .filter(|r| r.enabled && !r.forge.eq_ignore_ascii_case("direct"))
+            .filter(|r| !matches!(r.mode, InstallMode::Manual | InstallMode::Mpq))
+            .filter_map(|r| detect_repo(&r.url).ok())
+            .filter(|det| det.kind == ForgeKind::GitHub)
+            .filter(|det| seen.insert(forge::cache_key(det)))
+            .collect::<Vec<_>>();
+        // A single repository costs one REST request either way.
+        if targets.len() < 2 {
+            return prefetched;
+        }
+
+        for chunk in targets.chunks(forge::github::GRAPHQL_BATCH_SIZE) {
+            if Self::ensure_update_check_active(cancelled).is_err() {
+                break;
+            }
+            let started = Instant::now();
+            let refs = chunk.iter().collect::<Vec<_>>();
+            let releases = match tokio::time::timeout(
+                REMOTE_CHECK_TIMEOUT,
+                forge::github::latest_releases_batch(&self.client, &refs),
+            )
+            .await
+            {
+                Ok(Ok(releases)) => releases,
+                outcome => {
+                    diagnostics::emit(
+                        diagnostics::DiagnosticLevel::Debug,
+                        "engine.update_check",
+                        format!(
+                            "stage=graphql_batch_finished; repo_count={}; elapsed_ms={}; outcome={}; falling back to REST",
+                            chunk.len(),
+                            started.elapsed().as_millis(),
+                            if outcome.is_err() { "timeout" } else { "error" }
+                        ),
+                    );
+                    continue;
+                }
+            };
+            let mut answered = 0usize;
+            for (det, release) in chunk.iter().zip(releases) {
+                if let Some(release) = release {
+                    prefetched.insert(forge::cache_key(det), release);
+                    answered += 1;
+                }
+            }
+            diagnostics::emit(
+                diagnostics::DiagnosticLevel::Debug,
+                "engine.update_check",
+                format!(
+                    "stage=graphql_batch_finished; repo_count={}; answered={answered}; elapsed_ms={}",
+                    chunk.len(),
+                    started.elapsed().as_millis()
+                ),
+            );
+        }
+        prefetched
+    }
+
+    fn same_release(a: &LatestRelease, b: &LatestRelease) -> bool {
+        a.tag == b.tag
+            && a.assets.len() == b.assets.len()
+            && a.assets
+                .iter()
+                .zip(&b.assets)
+                .all(|(x, y)| x.id == y.id && x.name == y.name && x.size == y.size)
+    }
+
     pub async fn check_updates_with_wow(
         &self,
         wow_dir: Option<&Path>,
@@ -3717,10 +4653,7 @@ impl Engine {
         if !path.exists() {
             return Ok(true);
         }
-
-        let validation = Self::validate_downloaded_asset_fields(path, asset_name, asset_size)
-            .and_then(|_| Self::verify_asset_digest(path, asset_sha256));
-        if validation.is_ok() {
+        if Self::cached_asset_is_valid(path, asset_name, asset_size, asset_sha256) {
             return Ok(false);
         }
 
@@ -3738,6 +4671,39 @@ impl Engine {
         Ok(true)
     }
 
+    /// Whether a cached asset exists and matches the release metadata.
+    fn cached_asset_is_valid(
+        path: &Path,
+        asset_name: &str,
+        asset_size: Option<u64>,
+        asset_sha256: Option<&str>,
+    ) -> bool {
+        path.is_file()
+            && Self::validate_downloaded_asset_fields(path, asset_name, asset_size)
+                .and_then(|_| Self::verify_asset_digest(path, asset_sha256))
+                .is_ok()
+    }
+
+    /// Where to read or download a release asset whose cached copy is
+    /// `cached`. With a dry run's `scratch` directory, the cache is only used
+    /// when it already holds a valid copy.
+    fn release_asset_path(
+        cached: PathBuf,
+        scratch: Option<&Path>,
+        asset_name: &str,
+        asset_size: Option<u64>,
+        asset_sha256: Option<&str>,
+    ) -> PathBuf {
+        match scratch {
+            Some(scratch)
+                if !Self::cached_asset_is_valid(&cached, asset_name, asset_size, asset_sha256) =>
+            {
+                scratch.join(cached.file_name().unwrap_or_default())
+            }
+            _ => cached,
+        }
+    }
+
     fn sanitize_for_fs(s: &str) -> String {
         let mut out = String::with_capacity(s.len());
         for c in s.chars() {
@@ -3755,16 +4721,21 @@ impl Engine {
     }
 
     fn release_cache_dir(plan: &UpdatePlan, wow_dir: Option<&Path>) -> Result<PathBuf> {
-        let dir = util::cache_dir(wow_dir)?
+        let dir = Self::release_cache_path(plan, &util::cache_dir(wow_dir)?);
+        std::fs::create_dir_all(&dir)?;
+        Ok(dir)
+    }
+
+    /// Where `plan`'s assets are cached beneath `cache`.
+    fn release_cache_path(plan: &UpdatePlan, cache: &Path) -> PathBuf {
+        cache
             .join("releases")
             .join(Self::sanitize_for_fs(&plan.forge))
             .join(Self::sanitize_for_fs(&plan.host))
             .join(Self::sanitize_for_fs(&plan.owner))
             .join(Self::sanitize_for_fs(&plan.name))
             .join(Self::sanitize_for_fs(&plan.install_version))
-            .join(Self::sanitize_for_fs(&plan.asset_id));
-        std::fs::create_dir_all(&dir)?;
-        Ok(dir)
+            .join(Self::sanitize_for_fs(&plan.asset_id))
     }
 
     fn local_file_asset_path(plan: &UpdatePlan) -> Result<Option<PathBuf>> {
@@ -4252,81 +5223,557 @@ impl Engine {
         Ok(plan)
     }
 
-    fn resolve_install_path(path: &str, wow_dir: Option<&Path>) -> Option<PathBuf> {
-        let p = PathBuf::from(path);
-        if p.is_absolute() {
-            return Some(p);
-        }
-        let base = wow_dir?;
-        if p.components().any(|c| {
-            matches!(
-                c,
-                Component::ParentDir | Component::RootDir | Component::Prefix(_)
-            )
-        }) {
-            return None;
-        }
-        Some(base.join(p))
+    /// Re-read the `.toc` of every installed addon folder of a repo and store
+    /// the result. Folders that are missing or have no readable `.toc` are
+    /// stored too, without metadata.
+    pub fn refresh_addon_metadata(
+        &self,
+        repo_id: i64,
+        wow_dir: &Path,
+    ) -> Result<Vec<InstalledAddonToc>> {
+        let entries = self
+            .db()
+            .list_installs(repo_id)?
+            .into_iter()
+            .filter(|install| install.kind == "addon")
+            .map(|install| {
+                let toc = Self::resolve_install_path(&install.path, Some(wow_dir))
+                    .and_then(|dir| toc::read_addon_toc(&dir));
+                (install.path, toc)
+            })
+            .collect::<Vec<_>>();
+        self.db().replace_addon_metadata(repo_id, &entries)?;
+        Ok(Self::installed_addon_tocs(entries))
     }
 
-    fn remove_dlls_txt_entries(wow_dir: &Path, dll_names: &[String]) -> Result<()> {
-        if dll_names.is_empty() {
-            return Ok(());
+    /// Stored `.toc` metadata of a repo's installed addon folders. Refreshed
+    /// from disk first when the installed folders changed since it was read.
+    pub fn addon_metadata(&self, repo_id: i64, wow_dir: &Path) -> Result<Vec<InstalledAddonToc>> {
+        let stored = self.db().list_addon_metadata(repo_id)?;
+        let mut installed = self
+            .db()
+            .list_installs(repo_id)?
+            .into_iter()
+            .filter(|install| install.kind == "addon")
+            .map(|install| install.path)
+            .collect::<Vec<_>>();
+        installed.sort();
+        installed.dedup();
+        let covered = stored.len() == installed.len()
+            && stored
+                .iter()
+                .zip(&installed)
+                .all(|((path, _), installed)| path == installed);
+        if !covered {
+            return self.refresh_addon_metadata(repo_id, wow_dir);
         }
-        let path = wow_dir.join("dlls.txt");
-        if !path.exists() {
-            return Ok(());
+        Ok(Self::installed_addon_tocs(stored))
+    }
+
+    fn installed_addon_tocs(
+        entries: Vec<(String, Option<toc::AddonToc>)>,
+    ) -> Vec<InstalledAddonToc> {
+        entries
+            .into_iter()
+            .filter_map(|(path, toc)| Some(Self::installed_addon_toc((path, toc?))))
+            .collect()
+    }
+
+    /// Installed addon folders of `repo_id` whose `.toc` targets another
+    /// client than `client`.
+    pub fn addon_client_mismatches(
+        &self,
+        repo_id: i64,
+        wow_dir: &Path,
+        client: ClientFamily,
+    ) -> Result<Vec<ClientMismatch>> {
+        if !client.is_legacy() {
+            return Ok(Vec::new());
         }
+        Ok(self
+            .addon_metadata(repo_id, wow_dir)?
+            .iter()
+            .filter_map(|addon| ClientMismatch::check(&addon.folder, &addon.toc.interface, client))
+            .collect())
+    }
 
-        let remove_set: HashSet<String> = dll_names.iter().map(|n| n.to_lowercase()).collect();
-        let existing = fs::read_to_string(&path).unwrap_or_default();
-        let mut kept = Vec::new();
+    /// Check the hard dependencies of every addon folder in `Interface/AddOns`.
+    /// Read-only: the links between tracked repos are kept current by
+    /// [`Engine::refresh_addon_dependencies`].
+    ///
+    /// `known_sources` maps addon folder names to repository URLs that provide
+    /// them. An unmet dependency is resolved from a tracked repo that installs
+    /// the folder first, then from that mapping and finally from the folders
+    /// imported addon catalogs list.
+    pub fn addon_dependency_report(
+        &self,
+        wow_dir: &Path,
+        known_sources: &[(String, String)],
+    ) -> Result<Vec<UnmetDependency>> {
+        let _diagnostic = diagnostics::OperationGuard::new("addon_dependency_report");
+        let folders = addon_deps::scan_addon_folders(&wow_dir.join("Interface").join("AddOns"));
+        let repos = self.db().list_repos()?;
+        let owners = self.addon_folder_owners(&repos)?;
+        let indexed = self.indexed_addon_sources()?;
 
-        for line in existing.lines() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
-                kept.push(line.to_string());
-                continue;
-            }
-            let rest = if let Some(stripped) = trimmed.strip_prefix('#') {
-                stripped.trim()
-            } else {
-                trimmed
+        let mut unmet = addon_deps::unmet_dependencies(&folders, &owners);
+        for entry in &mut unmet {
+            let known_url = known_sources
+                .iter()
+                .chain(&indexed)
+                .find(|(name, _)| name.eq_ignore_ascii_case(&entry.dependency))
+                .map(|(_, url)| url.as_str());
+            let tracked = owners
+                .get(&entry.dependency.to_ascii_lowercase())
+                .map(|owner| owner.repo_id)
+                .or_else(|| {
+                    repos
+                        .iter()
+                        .find(|repo| {
+                            repo.name.eq_ignore_ascii_case(&entry.dependency)
+                                || known_url.is_some_and(|url| repo_urls_match(url, &repo.url))
+                        })
+                        .map(|repo| repo.id)
+                });
+            entry.source = match (tracked, known_url) {
+                (Some(repo_id), _) => Some(addon_deps::DependencySource::TrackedRepo { repo_id }),
+                (None, Some(url)) => Some(addon_deps::DependencySource::Known {
+                    url: url.to_string(),
+                }),
+                (None, None) => None,
             };
-            if remove_set.contains(&rest.to_lowercase()) {
-                continue;
+        }
+        diagnostics::emit(
+            diagnostics::DiagnosticLevel::Trace,
+            "engine",
+            format!(
+                "addon dependency report: addon_count={}; unmet_count={}",
+                folders.len(),
+                unmet.len()
+            ),
+        );
+        Ok(unmet)
+    }
+
+    /// Rebuild the `addon-dependency` links between tracked repos from the
+    /// `.toc` files in `Interface/AddOns`. Runs after every install and
+    /// before a removal warns about dependents.
+    pub fn refresh_addon_dependencies(&self, wow_dir: &Path) -> Result<()> {
+        let folders = addon_deps::scan_addon_folders(&wow_dir.join("Interface").join("AddOns"));
+        let repos = self.db().list_repos()?;
+        let owners = self.addon_folder_owners(&repos)?;
+        let mut links = addon_deps::dependency_edges(&folders)
+            .into_iter()
+            .filter_map(|(addon, dependency)| {
+                let parent = owners.get(&addon.to_ascii_lowercase())?;
+                let child = owners.get(&dependency.to_ascii_lowercase())?;
+                (parent.repo_id != child.repo_id).then_some((parent.repo_id, child.repo_id))
+            })
+            .collect::<Vec<_>>();
+        links.sort_unstable();
+        links.dedup();
+        self.db()
+            .replace_repo_dependencies(addon_deps::ADDON_DEPENDENCY, &links)
+    }
+
+    /// Tracked repos by the lowercased addon folder names they installed.
+    fn addon_folder_owners(
+        &self,
+        repos: &[Repo],
+    ) -> Result<HashMap<String, addon_deps::FolderOwner>> {
+        let mut owners = HashMap::new();
+        for repo in repos {
+            for folder in self.repo_addon_folders(repo.id)? {
+                owners.insert(
+                    folder.to_ascii_lowercase(),
+                    addon_deps::FolderOwner {
+                        repo_id: repo.id,
+                        enabled: repo.enabled,
+                    },
+                );
             }
-            kept.push(line.to_string());
         }
+        Ok(owners)
+    }
 
-        let mut out = kept.join("\n");
-        out.push('\n');
-        fs::write(path, out)?;
-        Ok(())
+    /// Repos whose addons require a folder installed by `repo_id`, as of the
+    /// last [`Engine::refresh_addon_dependencies`].
+    pub fn repo_dependents(&self, repo_id: i64) -> Result<Vec<i64>> {
+        Ok(self
+            .db()
+            .list_repo_dependents(repo_id)?
+            .into_iter()
+            .filter(|(_, relationship)| relationship == addon_deps::ADDON_DEPENDENCY)
+            .map(|(parent, _)| parent)
+            .collect())
     }
 
-    fn set_dlls_txt_entries_commented(
+    /// Names of the addon folders a repo installed.
+    fn repo_addon_folders(&self, repo_id: i64) -> Result<Vec<String>> {
+        Ok(self
+            .db()
+            .list_installs(repo_id)?
+            .into_iter()
+            .filter(|install| install.kind == "addon")
+            .filter_map(|install| {
+                Path::new(&install.path)
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .map(str::to_string)
+            })
+            .collect())
+    }
+
+    /// Enable or disable a repo's installed addon folders in the characters'
+    /// `AddOns.txt`, for one character or, with `None`, for every character
+    /// under `wow_dir`. Installed files stay where they are. Returns how many
+    /// `AddOns.txt` files were rewritten.
+    pub fn set_repo_character_enabled(
+        &self,
+        repo_id: i64,
         wow_dir: &Path,
-        dll_names: &[String],
-        commented: bool,
+        character: Option<&WtfCharacter>,
+        enabled: bool,
     ) -> Result<usize> {
-        if dll_names.is_empty() {
-            return Ok(0);
+        let _diagnostic = diagnostics::OperationGuard::new("set_repo_character_enabled");
+        let addons = self.repo_addon_folders(repo_id)?;
+        if addons.is_empty() {
+            anyhow::bail!("This repository has no installed addon folders.");
+        }
+        character_addons::ensure_game_closed(wow_dir)?;
+        let changed = match character {
+            Some(character) => usize::from(character_addons::set_character_addons_enabled(
+                character, &addons, enabled,
+            )?),
+            None => character_addons::set_addons_enabled_for_all(wow_dir, &addons, enabled)?,
+        };
+        diagnostics::emit(
+            diagnostics::DiagnosticLevel::Debug,
+            "engine.addon",
+            format!(
+                "character addon state changed: repo_id={repo_id}; enabled={enabled}; addon_count={}; files_written={changed}",
+                addons.len()
+            ),
+        );
+        Ok(changed)
+    }
+
+    pub fn list_addon_sets(&self) -> Result<Vec<AddonSet>> {
+        self.db().list_addon_sets()
+    }
+
+    pub fn addon_set(&self, name: &str) -> Result<AddonSet> {
+        self.list_addon_sets()?
+            .into_iter()
+            .find(|set| set.name.eq_ignore_ascii_case(name.trim()))
+            .ok_or_else(|| anyhow::anyhow!("No addon set named \"{}\".", name.trim()))
+    }
+
+    /// Create or replace a named addon set. Repo entries must name tracked
+    /// repos.
+    pub fn save_addon_set(&self, set: &AddonSet) -> Result<()> {
+        let name = set.name.trim();
+        if name.is_empty() {
+            anyhow::bail!("Addon set name cannot be empty.");
         }
-        let path = wow_dir.join("dlls.txt");
-        if !path.exists() {
-            return Ok(0);
+        for entry in &set.repos {
+            self.db().get_repo(entry.repo_id)?;
         }
+        self.db().save_addon_set(&AddonSet {
+            name: name.to_string(),
+            ..set.clone()
+        })
+    }
 
-        let wanted: HashSet<String> = dll_names.iter().map(|n| n.to_lowercase()).collect();
-        let existing = fs::read_to_string(&path).unwrap_or_default();
-        let mut lines: Vec<String> = existing.lines().map(|l| l.to_string()).collect();
-        let mut changed = 0usize;
-        let mut seen = HashSet::<String>::new();
+    pub fn delete_addon_set(&self, name: &str) -> Result<bool> {
+        self.db().delete_addon_set(name.trim())
+    }
 
-        for line in lines.iter_mut() {
-            let trimmed = line.trim();
-            if trimmed.is_empty() {
+    /// Save the current state of every installed addon, as recorded in one
+    /// character's `AddOns.txt`, as the set `name`. Tracked repos count as
+    /// enabled unless all of their folders are disabled.
+    pub fn capture_addon_set(
+        &self,
+        name: &str,
+        wow_dir: &Path,
+        character: &WtfCharacter,
+    ) -> Result<AddonSet> {
+        let file = character_addons::AddonsTxt::read(&character.addons_txt())?;
+        let is_enabled = |folder: &str| file.is_enabled(folder).unwrap_or(true);
+        let mut owned = HashSet::new();
+        let mut repos = Vec::new();
+        let tracked = self.db().list_repos()?;
+        for repo in tracked {
+            let folders = self.repo_addon_folders(repo.id)?;
+            if folders.is_empty() {
+                continue;
+            }
+            repos.push(AddonSetRepo {
+                repo_id: repo.id,
+                enabled: folders.iter().any(|folder| is_enabled(folder)),
+            });
+            owned.extend(folders.iter().map(|folder| folder.to_ascii_lowercase()));
+        }
+        let folders = addon_deps::scan_addon_folders(&wow_dir.join("Interface").join("AddOns"))
+            .into_iter()
+            .filter(|(folder, _)| {
+                !owned.contains(&folder.to_ascii_lowercase()) && !addon_deps::is_builtin(folder)
+            })
+            .map(|(folder, _)| AddonSetFolder {
+                enabled: is_enabled(&folder),
+                folder,
+            })
+            .collect();
+        let set = AddonSet {
+            name: name.trim().to_string(),
+            repos,
+            folders,
+        };
+        self.save_addon_set(&set)?;
+        Ok(set)
+    }
+
+    /// Switch every character, or only `character`, to the addon set `name`.
+    /// The set's repos are enabled or disabled like [`Engine::set_repo_enabled`]
+    /// does, then the `AddOns.txt` files are written. Entries whose folders
+    /// are not installed are skipped and reported; everything else changes
+    /// together or not at all.
+    pub fn apply_addon_set(
+        &self,
+        name: &str,
+        wow_dir: &Path,
+        character: Option<&WtfCharacter>,
+        use_dlls_txt: bool,
+    ) -> Result<AddonSetReport> {
+        let _diagnostic = diagnostics::OperationGuard::new("apply_addon_set");
+        let set = self.addon_set(name)?;
+        let addons_dir = wow_dir.join("Interface").join("AddOns");
+        let installed = |folder: &str| mpq::find_case_insensitive_child(&addons_dir, folder);
+        let mut report = AddonSetReport::default();
+        let mut states = Vec::<(String, bool)>::new();
+        let mut toggles = Vec::<(i64, bool)>::new();
+        for entry in &set.repos {
+            let Ok(repo) = self.db().get_repo(entry.repo_id) else {
+                report.skipped.push(addon_sets::SkippedSetEntry {
+                    entry: format!("repo id={}", entry.repo_id),
+                    reason: "is no longer tracked".to_string(),
+                });
+                continue;
+            };
+            if repo.enabled != entry.enabled {
+                toggles.push((repo.id, entry.enabled));
+            }
+            let label = format!("{}/{}", repo.owner, repo.name);
+            let folders = self.repo_addon_folders(repo.id)?;
+            for folder in folders {
+                if installed(&folder).is_some() {
+                    states.push((folder, entry.enabled));
+                } else {
+                    report.skipped.push(addon_sets::SkippedSetEntry {
+                        entry: format!("{label}: {folder}"),
+                        reason: "is missing from Interface/AddOns".to_string(),
+                    });
+                }
+            }
+        }
+        for entry in &set.folders {
+            if installed(&entry.folder).is_some() {
+                states.push((entry.folder.clone(), entry.enabled));
+            } else {
+                report.skipped.push(addon_sets::SkippedSetEntry {
+                    entry: entry.folder.clone(),
+                    reason: "is missing from Interface/AddOns".to_string(),
+                });
+            }
+        }
+        let characters = match character {
+            Some(character) => vec![character.clone()],
+            None => character_addons::discover_characters(wow_dir),
+        };
+        if characters.is_empty() {
+            anyhow::bail!(
+                "No characters were found in the WTF folder. Log in with each character once before applying an addon set."
+            );
+        }
+        character_addons::ensure_game_closed(wow_dir)?;
+
+        let mut switched = Vec::new();
+        let mut outcome = Ok(0);
+        for &(repo_id, enabled) in &toggles {
+            if let Err(error) = self.set_repo_enabled(repo_id, enabled, Some(wow_dir), use_dlls_txt)
+            {
+                let label = self
+                    .db()
+                    .get_repo(repo_id)
+                    .map(|repo| format!("{}/{}", repo.owner, repo.name))
+                    .unwrap_or_else(|_| format!("repo id={repo_id}"));
+                outcome = Err(error.context(format!("could not switch {label}")));
+                break;
+            }
+            switched.push((repo_id, enabled));
+        }
+        if outcome.is_ok() {
+            outcome = addon_sets::apply_states(&characters, &states);
+        }
+        let written = match outcome {
+            Ok(written) => written,
+            Err(error) => {
+                for &(repo_id, enabled) in switched.iter().rev() {
+                    self.set_repo_enabled(repo_id, !enabled, Some(wow_dir), use_dlls_txt)
+                        .with_context(|| {
+                            format!("{error:#}; restoring repo id={repo_id} also failed")
+                        })?;
+                }
+                return Err(error);
+            }
+        };
+        report.folders = states.len();
+        report.repos_changed = switched.len();
+        report.characters_updated = written;
+        diagnostics::emit(
+            diagnostics::DiagnosticLevel::Debug,
+            "engine.addon",
+            format!(
+                "addon set applied: folder_count={}; repo_count={}; character_count={}; files_written={}; skipped_count={}",
+                report.folders,
+                report.repos_changed,
+                characters.len(),
+                report.characters_updated,
+                report.skipped.len()
+            ),
+        );
+        Ok(report)
+    }
+
+    /// The set `name` with repos identified by URL, for use in another
+    /// database.
+    pub fn export_addon_set(&self, name: &str) -> Result<AddonSetExport> {
+        let set = self.addon_set(name)?;
+        let mut repos = Vec::with_capacity(set.repos.len());
+        for entry in &set.repos {
+            repos.push(addon_sets::ExportedSetRepo {
+                url: self.db().get_repo(entry.repo_id)?.url,
+                enabled: entry.enabled,
+            });
+        }
+        Ok(AddonSetExport {
+            name: set.name,
+            repos,
+            folders: set.folders,
+        })
+    }
+
+    /// Save an exported set, matching its repos to tracked repos by URL.
+    /// Returns the URLs that no tracked repo matches; those entries are left
+    /// out.
+    pub fn import_addon_set(&self, export: &AddonSetExport) -> Result<Vec<String>> {
+        let tracked = self.db().list_repos()?;
+        let mut set = AddonSet {
+            name: export.name.clone(),
+            repos: Vec::new(),
+            folders: export.folders.clone(),
+        };
+        let mut unmatched = Vec::new();
+        for entry in &export.repos {
+            match tracked
+                .iter()
+                .find(|repo| repo_urls_match(&repo.url, &entry.url))
+            {
+                Some(repo) => set.repos.push(AddonSetRepo {
+                    repo_id: repo.id,
+                    enabled: entry.enabled,
+                }),
+                None => unmatched.push(entry.url.clone()),
+            }
+        }
+        self.save_addon_set(&set)?;
+        Ok(unmatched)
+    }
+
+    fn installed_addon_toc((path, toc): (String, toc::AddonToc)) -> InstalledAddonToc {
+        let folder = Path::new(&path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or_default()
+            .to_string();
+        InstalledAddonToc { path, folder, toc }
+    }
+
+    fn resolve_install_path(path: &str, wow_dir: Option<&Path>) -> Option<PathBuf> {
+        let p = PathBuf::from(path);
+        if p.is_absolute() {
+            return Some(p);
+        }
+        let base = wow_dir?;
+        if p.components().any(|c| {
+            matches!(
+                c,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        }) {
+            return None;
+        }
+        Some(base.join(p))
+    }
+
+    fn remove_dlls_txt_entries(wow_dir: &Path, dll_names: &[String]) -> Result<()> {
+        if dll_names.is_empty() {
+            return Ok(());
+        }
+        let path = wow_dir.join("dlls.txt");
+        if !path.exists() {
+            return Ok(());
+        }
+
+        let remove_set: HashSet<String> = dll_names.iter().map(|n| n.to_lowercase()).collect();
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        let mut kept = Vec::new();
+
+        for line in existing.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                kept.push(line.to_string());
+                continue;
+            }
+            let rest = if let Some(stripped) = trimmed.strip_prefix('#') {
+                stripped.trim()
+            } else {
+                trimmed
+            };
+            if remove_set.contains(&rest.to_lowercase()) {
+                continue;
+            }
+            kept.push(line.to_string());
+        }
+
+        let mut out = kept.join("\n");
+        out.push('\n');
+        fs::write(path, out)?;
+        Ok(())
+    }
+
+    fn set_dlls_txt_entries_commented(
+        wow_dir: &Path,
+        dll_names: &[String],
+        commented: bool,
+    ) -> Result<usize> {
+        if dll_names.is_empty() {
+            return Ok(0);
+        }
+        let path = wow_dir.join("dlls.txt");
+        if !path.exists() {
+            return Ok(0);
+        }
+
+        let wanted: HashSet<String> = dll_names.iter().map(|n| n.to_lowercase()).collect();
+        let existing = fs::read_to_string(&path).unwrap_or_default();
+        let mut lines: Vec<String> = existing.lines().map(|l| l.to_string()).collect();
+        let mut changed = 0usize;
+        let mut seen = HashSet::<String>::new();
+
+        for line in lines.iter_mut() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
                 continue;
             }
 
@@ -4606,6 +6053,38 @@ impl Engine {
         Ok(())
     }
 
+    /// Make an addon_git repo follow the newest tag admitted by `policy`
+    /// instead of its branch head. `None` returns to following the branch.
+    pub fn set_repo_git_tag_policy(
+        &self,
+        repo_id: i64,
+        policy: Option<GitTagPolicy>,
+    ) -> Result<()> {
+        let repo = self.db().get_repo(repo_id)?;
+        if !matches!(repo.mode, InstallMode::AddonGit) {
+            anyhow::bail!("Tag tracking is only supported for addon_git repos.");
+        }
+        if let Some(GitTagPolicy::Pattern(pattern)) = &policy {
+            if pattern.trim().is_empty() {
+                anyhow::bail!("Tag pattern cannot be empty.");
+            }
+            regex::Regex::new(pattern)
+                .with_context(|| format!("invalid tag pattern: {pattern}"))?;
+        }
+        self.db()
+            .set_repo_git_tag_policy(repo_id, policy.as_ref())?;
+        Ok(())
+    }
+
+    /// Tag names on an addon_git repo's remote, newest first.
+    pub fn list_repo_tags(&self, repo_id: i64) -> Result<Vec<String>> {
+        let repo = self.db().get_repo(repo_id)?;
+        if !matches!(repo.mode, InstallMode::AddonGit) {
+            anyhow::bail!("Tags can only be listed for addon_git repos.");
+        }
+        git_sync::remote_tags(&repo.url)
+    }
+
     pub fn set_repo_merge_installs(&self, repo_id: i64, merge: bool) -> Result<()> {
         self.db().set_merge_installs(repo_id, merge)?;
         Ok(())
@@ -4718,8 +6197,8 @@ impl Engine {
         installs: &[db::InstallEntry],
         owned_backups: &[db::InstallBackupRow],
         displaced_backups: &[db::InstallBackupRow],
+        record: Option<&mut ChangeSet>,
     ) -> Result<usize> {
-        let mut transaction = deployment::ReplacementTransaction::new("release removal", repo.id);
         let backup_by_path = owned_backups
             .iter()
             .map(|backup| (Self::install_path_key(&backup.path), backup))
@@ -4728,9 +6207,6 @@ impl Engine {
             .iter()
             .map(|backup| Self::install_path_key(&backup.path))
             .collect::<HashSet<_>>();
-        let mut restored_keys = HashSet::new();
-        let mut removed_paths = 0usize;
-        let mut removed_dlls = Vec::new();
 
         // Validate every live replacement before changing anything. A locally
         // modified file is user data and must not be discarded merely because
@@ -4758,6 +6234,55 @@ impl Engine {
             }
         }
 
+        if let Some(changes) = record {
+            let removed = self.move_release_repo_files(
+                repo,
+                wow_dir,
+                installs,
+                &backup_by_path,
+                displaced_backups,
+                &mut Deploy::DryRun(changes),
+            )?;
+            changes.database.push(format!(
+                "remove {} displaced-file backup row(s)",
+                owned_backups.len() + displaced_backups.len()
+            ));
+            return Ok(removed);
+        }
+        let mut transaction = self.begin_replacement("release removal", repo.id, wow_dir)?;
+        let removed_paths = self.move_release_repo_files(
+            repo,
+            wow_dir,
+            installs,
+            &backup_by_path,
+            displaced_backups,
+            &mut Deploy::Live(&mut transaction),
+        )?;
+        self.db()
+            .remove_repo_with_displaced_backups(repo.id, true, Some(transaction.id()))?;
+        self.finish_replacement(transaction);
+        Ok(removed_paths)
+    }
+
+    /// Move a removed release's files out of the game folder, restoring the
+    /// files it displaced.
+    fn move_release_repo_files(
+        &self,
+        repo: &Repo,
+        wow_dir: &Path,
+        installs: &[db::InstallEntry],
+        backup_by_path: &HashMap<String, &db::InstallBackupRow>,
+        displaced_backups: &[db::InstallBackupRow],
+        to: &mut Deploy<'_>,
+    ) -> Result<usize> {
+        let displaced_keys = displaced_backups
+            .iter()
+            .map(|backup| Self::install_path_key(&backup.path))
+            .collect::<HashSet<_>>();
+        let mut restored_keys = HashSet::new();
+        let mut removed_paths = 0usize;
+        let mut removed_dlls = Vec::new();
+
         for install in installs {
             let key = Self::install_path_key(&install.path);
             if displaced_keys.contains(&key) {
@@ -4769,10 +6294,10 @@ impl Engine {
             if let Some(backup) = backup_by_path.get(&key) {
                 let persistent = Self::resolve_install_path(&backup.backup_path, Some(wow_dir))
                     .unwrap_or_else(|| PathBuf::from(&backup.backup_path));
-                transaction.deploy_returnable(&persistent, &live)?;
+                to.restore(&persistent, &live, &install.path)?;
                 restored_keys.insert(key);
                 removed_paths += 1;
-            } else if transaction.backup_target(&live)? {
+            } else if to.remove(&live, &install.path)? {
                 removed_paths += 1;
             }
 
@@ -4794,7 +6319,7 @@ impl Engine {
         for backup in displaced_backups {
             let persistent = Self::resolve_install_path(&backup.backup_path, Some(wow_dir))
                 .unwrap_or_else(|| PathBuf::from(&backup.backup_path));
-            if transaction.backup_target(&persistent)? {
+            if to.remove(&persistent, &backup.backup_path)? {
                 removed_paths += 1;
             }
         }
@@ -4802,20 +6327,15 @@ impl Engine {
         if !removed_dlls.is_empty() {
             let live_dlls_txt = wow_dir.join("dlls.txt");
             if live_dlls_txt.is_file() {
-                let staging = tempfile::Builder::new()
-                    .prefix(".wuddle-dlls-remove-")
-                    .tempdir_in(wow_dir)
+                let staging = to
+                    .staging_dir(wow_dir, ".wuddle-dlls-remove-")
                     .context("create DLL configuration staging directory")?;
                 fs::copy(&live_dlls_txt, staging.path().join("dlls.txt"))
                     .context("stage DLL launcher configuration")?;
                 Self::remove_dlls_txt_entries(staging.path(), &removed_dlls)?;
-                transaction.deploy(&staging.path().join("dlls.txt"), &live_dlls_txt)?;
+                to.replace_dlls_txt(&staging.path().join("dlls.txt"), &live_dlls_txt)?;
             }
         }
-
-        self.db()
-            .remove_repo_with_displaced_backups(repo.id, true)?;
-        transaction.disarm();
         Ok(removed_paths)
     }
 
@@ -4824,6 +6344,43 @@ impl Engine {
         repo_id: i64,
         wow_dir: Option<&Path>,
         remove_local_files: bool,
+    ) -> Result<usize> {
+        let repo = self.db().get_repo(repo_id)?;
+        let removed =
+            self.remove_repo_records_and_files(repo_id, wow_dir, remove_local_files, None)?;
+        let entry = InstallHistoryEntry {
+            id: 0,
+            repo_id,
+            action: HistoryAction::Remove,
+            created_unix: Self::now_unix(),
+            from_version: repo.last_version,
+            to_version: None,
+            asset_id: repo.installed_asset_id,
+            asset_name: repo.installed_asset_name,
+            asset_url: repo.installed_asset_url,
+            asset_sha256: None,
+            extra_assets: Vec::new(),
+            settings_snapshot: None,
+        };
+        if let Err(error) = self.db().add_history_entry(&entry) {
+            diagnostics::emit(
+                diagnostics::DiagnosticLevel::Debug,
+                "engine.remove",
+                format!("could not record install history: repo_id={repo_id}; error={error}"),
+            );
+        }
+        Ok(removed)
+    }
+
+    /// Remove a repo's records and, with `remove_local_files`, its files.
+    /// With `record`, nothing is removed: the files and records the removal
+    /// would change are added to the change set instead.
+    fn remove_repo_records_and_files(
+        &self,
+        repo_id: i64,
+        wow_dir: Option<&Path>,
+        remove_local_files: bool,
+        mut record: Option<&mut ChangeSet>,
     ) -> Result<usize> {
         let _diagnostic = diagnostics::OperationGuard::new("remove_repo");
         diagnostics::emit(
@@ -4832,6 +6389,13 @@ impl Engine {
             format!("remove_repo: repo_id={repo_id}; remove_local_files={remove_local_files}"),
         );
         let repo = self.db().get_repo(repo_id)?;
+        let installs = self.db().list_installs(repo_id)?;
+        if let Some(changes) = record.as_deref_mut() {
+            changes.database.push(format!(
+                "remove the repo and its {} install row(s)",
+                installs.len()
+            ));
+        }
         if repo.mode == InstallMode::Mpq {
             let wow_dir = wow_dir.ok_or_else(|| {
                 anyhow::anyhow!("The WoW directory is required to remove an MPQ package")
@@ -4841,10 +6405,9 @@ impl Engine {
                     "MPQ packages must be removed with their deployed files so protected backups can be restored"
                 );
             }
-            return self.remove_mpq_package(repo_id, wow_dir, false);
+            return self.remove_mpq_package_internal(repo_id, wow_dir, false, record);
         }
 
-        let installs = self.db().list_installs(repo_id)?;
         let owned_backups = self.db().list_install_backups(repo_id)?;
         let displaced_backups = self.db().list_install_backups_displacing(repo_id)?;
         if !owned_backups.is_empty() && !remove_local_files {
@@ -4867,6 +6430,7 @@ impl Engine {
                 &installs,
                 &owned_backups,
                 &displaced_backups,
+                record,
             );
         }
 
@@ -4888,7 +6452,7 @@ impl Engine {
                         addon_worktree.as_deref(),
                     )?;
                     let removed = if safe_to_remove {
-                        Self::remove_any_target(&full)?
+                        Self::remove_or_record(&full, &entry.path, record.as_deref_mut())?
                     } else {
                         diagnostics::emit(
                             diagnostics::DiagnosticLevel::Debug,
@@ -4908,7 +6472,11 @@ impl Engine {
                             &disabled,
                             addon_worktree.as_deref(),
                         )? {
-                            Self::remove_any_target(&disabled)?
+                            Self::remove_or_record(
+                                &disabled,
+                                &format!("{}.disabled", entry.path),
+                                record.as_deref_mut(),
+                            )?
                         } else {
                             diagnostics::emit(
                                 diagnostics::DiagnosticLevel::Debug,
@@ -4934,7 +6502,12 @@ impl Engine {
                 }
             }
             if let Some(base) = wow_dir {
-                let _ = Self::remove_dlls_txt_entries(base, &removed_dlls);
+                match record.as_deref_mut() {
+                    Some(changes) => Self::record_dlls_txt_removal(base, &removed_dlls, changes)?,
+                    None => {
+                        let _ = Self::remove_dlls_txt_entries(base, &removed_dlls);
+                    }
+                }
 
                 // 2. Remove the repository folder itself (AddonName or AddonName.repo).
                 // Step 1 above already removes symlinks that point INTO the worktree, but
@@ -4959,7 +6532,8 @@ impl Engine {
                             );
                             continue;
                         }
-                        if Self::remove_any_target(&actual)? {
+                        let manifest = Self::to_manifest_path(&actual, base);
+                        if Self::remove_or_record(&actual, &manifest, record.as_deref_mut())? {
                             removed_paths += 1;
                         }
                     }
@@ -4967,6 +6541,9 @@ impl Engine {
             }
         }
 
+        if record.is_some() {
+            return Ok(removed_paths);
+        }
         self.db().remove_repo(repo_id)?;
         diagnostics::emit(
             diagnostics::DiagnosticLevel::Debug,
@@ -4994,9 +6571,11 @@ impl Engine {
                 .build_update_plan_for_repo(
                     &r,
                     true,
+                    true,
                     Some(wow_dir),
                     CheckMode::Force,
                     None,
+                    None,
                     &cancelled,
                 )
                 .await?;
@@ -5054,9 +6633,11 @@ impl Engine {
             .build_update_plan_for_repo(
                 &repo,
                 true,
+                true,
                 Some(wow_dir),
                 CheckMode::Force,
                 None,
+                None,
                 &cancelled,
             )
             .await?;
@@ -5074,24 +6655,261 @@ impl Engine {
         Ok(Some(plan))
     }
 
-    /// One-time migration: if a repo was previously cloned into the legacy
-    /// `.wuddle/addon_git/…` staging area, move it to the new direct location
-    /// (`Interface/AddOns/{name}`) so it becomes cross-compatible with GAM and
-    /// the TurtleWoW launcher.  Updates the DB install entries in-place.
-    /// Safe to call repeatedly — does nothing when the legacy path doesn't exist
-    /// or the target already exists.
-    fn migrate_staging_clone_if_needed(&self, wow_dir: &Path, repo: &Repo) -> Result<()> {
-        let legacy =
-            git_sync::addon_repo_legacy_staging_dir(wow_dir, &repo.host, &repo.owner, &repo.name);
-        if !legacy.is_dir() || !Self::has_local_git_marker(&legacy) {
-            return Ok(());
+    /// Like [`Engine::apply_updates`] for the enabled repos, but the release
+    /// updates commit or roll back as a unit: a failure in any of them leaves
+    /// every release repo on its old version, and each of their plans carries
+    /// the error. A repo whose check fails carries its own error and stays
+    /// out of the batch. Git addons sync through their own worktrees and
+    /// still update one by one, after the releases committed; when the batch
+    /// rolls back they are left untouched and their plans say so.
+    pub async fn apply_updates_atomic(
+        &self,
+        wow_dir: &Path,
+        raw_dest: Option<&Path>,
+        opts: InstallOptions,
+    ) -> Result<Vec<UpdatePlan>> {
+        let _diagnostic = diagnostics::OperationGuard::new("apply_updates_atomic");
+        let repos = self.db().list_repos()?;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut plans = Vec::new();
+        for r in repos.iter().filter(|r| r.enabled) {
+            // A repo that cannot be checked reports the error on its own plan
+            // and stays out of the batch.
+            let plan = match self
+                .build_update_plan_for_repo(
+                    r,
+                    true,
+                    true,
+                    Some(wow_dir),
+                    CheckMode::Force,
+                    None,
+                    None,
+                    &cancelled,
+                )
+                .await
+            {
+                Ok(plan) => plan,
+                Err(error) => {
+                    let mut plan = Self::blank_plan(r);
+                    plan.error = Some(format!("{error:#}"));
+                    plan
+                }
+            };
+            plans.push(plan);
         }
-        let direct = git_sync::addon_direct_dir(wow_dir, &repo.name);
 
-        // If the target already exists (even as a broken symlink), remove it if it's a symlink.
-        if let Ok(meta) = fs::symlink_metadata(&direct) {
-            if meta.file_type().is_symlink() {
-                let _ = fs::remove_file(&direct);
+        let due = |plan: &UpdatePlan| {
+            plan.error.is_none() && !plan.asset_url.is_empty() && !plan.externally_modified
+        };
+        let batch = plans
+            .iter()
+            .enumerate()
+            .filter(|(_, plan)| due(plan) && !matches!(plan.mode, InstallMode::AddonGit))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let mut batch_plans = batch
+            .iter()
+            .map(|&index| plans[index].clone())
+            .collect::<Vec<_>>();
+        let outcome = self
+            .apply_release_batch(&mut batch_plans, wow_dir, raw_dest, opts)
+            .await;
+        for (index, mut plan) in batch.into_iter().zip(batch_plans) {
+            if let Err(error) = &outcome {
+                plan.error = Some(format!("Atomic update rolled back: {error}"));
+            }
+            plans[index] = plan;
+        }
+
+        for plan in plans.iter_mut() {
+            if due(plan) && matches!(plan.mode, InstallMode::AddonGit) {
+                if outcome.is_err() {
+                    plan.error = Some(
+                        "Skipped because the atomic update of the release repos rolled back."
+                            .to_string(),
+                    );
+                    continue;
+                }
+                match self.apply_one(plan, wow_dir, raw_dest, opts).await {
+                    Ok(()) => plan.applied = true,
+                    Err(e) => plan.error = Some(format!("Install failed: {}", e)),
+                }
+            }
+        }
+        Ok(plans)
+    }
+
+    /// Update the release repos `repo_ids` as one unit. Every release is
+    /// downloaded and staged before any live file changes, then all of them
+    /// are deployed under a single journaled transaction and their records
+    /// committed together. On any error nothing changes. Repos that are up to
+    /// date are returned unapplied.
+    ///
+    /// Git addons cannot join; update them with [`Engine::update_repo`].
+    pub async fn update_repos_atomically(
+        &self,
+        repo_ids: &[i64],
+        wow_dir: &Path,
+        raw_dest: Option<&Path>,
+        opts: InstallOptions,
+    ) -> Result<Vec<UpdatePlan>> {
+        let _diagnostic = diagnostics::OperationGuard::new("update_repos_atomically");
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let mut plans = Vec::with_capacity(repo_ids.len());
+        for &repo_id in repo_ids {
+            let repo = self.db().get_repo(repo_id)?;
+            if matches!(repo.mode, InstallMode::AddonGit) {
+                anyhow::bail!(
+                    "{}/{} is a Git addon; Git addons cannot join an atomic update.",
+                    repo.owner,
+                    repo.name
+                );
+            }
+            let plan = self
+                .build_update_plan_for_repo(
+                    &repo,
+                    true,
+                    true,
+                    Some(wow_dir),
+                    CheckMode::Force,
+                    None,
+                    None,
+                    &cancelled,
+                )
+                .await
+                .with_context(|| format!("{}/{}", repo.owner, repo.name))?;
+            if let Some(error) = &plan.error {
+                anyhow::bail!("{}/{}: {error}", plan.owner, plan.name);
+            }
+            plans.push(plan);
+        }
+        let (mut due, current): (Vec<_>, Vec<_>) = plans
+            .into_iter()
+            .partition(|plan| !plan.asset_url.is_empty());
+        self.apply_release_batch(&mut due, wow_dir, raw_dest, opts)
+            .await?;
+        due.extend(current);
+        Ok(due)
+    }
+
+    /// Stage every plan, then deploy them all under one transaction. Sets
+    /// `applied` on each plan once the batch committed.
+    async fn apply_release_batch(
+        &self,
+        plans: &mut [UpdatePlan],
+        wow_dir: &Path,
+        raw_dest: Option<&Path>,
+        opts: InstallOptions,
+    ) -> Result<()> {
+        if plans.is_empty() {
+            return Ok(());
+        }
+        let live_dlls_txt = wow_dir.join("dlls.txt");
+        let mut staged: Vec<StagedRelease> = Vec::with_capacity(plans.len());
+        for plan in plans.iter() {
+            // Each release edits the launcher entries its predecessors staged.
+            let dlls_txt_base = staged
+                .last()
+                .map(|previous| previous.staged_dlls_txt.clone())
+                .filter(|path| path.is_file())
+                .unwrap_or_else(|| live_dlls_txt.clone());
+            let release = match self
+                .stage_release(plan, wow_dir, raw_dest, opts, &dlls_txt_base, false)
+                .await
+            {
+                Ok(release) => Self::check_release_conflicts(&release, opts).map(|()| release),
+                Err(error) => Err(error),
+            }
+            .map_err(|error| anyhow::anyhow!("{}/{}: {error:#}", plan.owner, plan.name))?;
+            staged.push(release);
+        }
+
+        // Every release was staged against the profile as it was before the
+        // batch, so two repos that touch the same file cannot share one.
+        let mut claimed = HashMap::new();
+        for (plan, release) in plans.iter().zip(&staged) {
+            let paths = release
+                .targets
+                .iter()
+                .map(|target| target.manifest_path.as_str())
+                .chain(
+                    release
+                        .existing_installs
+                        .iter()
+                        .map(|install| install.path.as_str()),
+                )
+                .filter(|path| {
+                    !Path::new(path)
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .is_some_and(|name| name.eq_ignore_ascii_case("dlls.txt"))
+                });
+            for path in paths {
+                if let Some(other) = claimed.insert(Self::install_path_key(path), plan) {
+                    if other.repo_id != plan.repo_id {
+                        anyhow::bail!(
+                            "{}/{} and {}/{} both install {path}; update them separately.",
+                            other.owner,
+                            other.name,
+                            plan.owner,
+                            plan.name
+                        );
+                    }
+                }
+            }
+        }
+
+        // Snapshot settings only once every release staged and passed its
+        // checks, so a refused batch leaves no snapshots behind.
+        let settings_snapshots = plans
+            .iter()
+            .map(|plan| self.snapshot_settings_before_deploy(plan.repo_id, wow_dir))
+            .collect::<Vec<_>>();
+        let mut transaction = self.begin_replacement("atomic update", 0, wow_dir)?;
+        for (plan, release) in plans.iter().zip(staged.iter_mut()) {
+            self.deploy_staged_release(plan, wow_dir, release, &mut Deploy::Live(&mut transaction))
+                .map_err(|error| anyhow::anyhow!("{}/{}: {error:#}", plan.owner, plan.name))?;
+        }
+        let replacements = plans
+            .iter()
+            .zip(&staged)
+            .map(|(plan, release)| Self::release_replacement(plan, release))
+            .collect::<Vec<_>>();
+        self.db()
+            .commit_release_replacements(&replacements, Some(transaction.id()))?;
+        self.finish_replacement(transaction);
+        diagnostics::emit(
+            diagnostics::DiagnosticLevel::Debug,
+            "engine",
+            format!("atomic update committed: repo_count={}", plans.len()),
+        );
+
+        for (plan, settings_snapshot) in plans.iter_mut().zip(settings_snapshots) {
+            plan.applied = true;
+            self.after_install(plan, wow_dir, settings_snapshot, Self::history_action(plan));
+            self.prune_release_cache(plan, opts.cache_keep_versions, Some(wow_dir));
+        }
+        Ok(())
+    }
+
+    /// One-time migration: if a repo was previously cloned into the legacy
+    /// `.wuddle/addon_git/…` staging area, move it to the new direct location
+    /// (`Interface/AddOns/{name}`) so it becomes cross-compatible with GAM and
+    /// the TurtleWoW launcher.  Updates the DB install entries in-place.
+    /// Safe to call repeatedly — does nothing when the legacy path doesn't exist
+    /// or the target already exists.
+    fn migrate_staging_clone_if_needed(&self, wow_dir: &Path, repo: &Repo) -> Result<()> {
+        let legacy =
+            git_sync::addon_repo_legacy_staging_dir(wow_dir, &repo.host, &repo.owner, &repo.name);
+        if !legacy.is_dir() || !Self::has_local_git_marker(&legacy) {
+            return Ok(());
+        }
+        let direct = git_sync::addon_direct_dir(wow_dir, &repo.name);
+
+        // If the target already exists (even as a broken symlink), remove it if it's a symlink.
+        if let Ok(meta) = fs::symlink_metadata(&direct) {
+            if meta.file_type().is_symlink() {
+                let _ = fs::remove_file(&direct);
             } else if meta.is_dir() {
                 // It's a real directory, assume it's already migrated or manually moved.
                 return Ok(());
@@ -5142,8 +6960,15 @@ impl Engine {
         raw_dest: Option<&Path>,
         opts: InstallOptions,
     ) -> Result<()> {
-        self.apply_one_internal(plan, wow_dir, raw_dest, opts, false)
-            .await
+        self.apply_one_internal(
+            plan,
+            wow_dir,
+            raw_dest,
+            opts,
+            false,
+            Self::history_action(plan),
+        )
+        .await
     }
 
     async fn apply_one_internal(
@@ -5153,6 +6978,346 @@ impl Engine {
         raw_dest: Option<&Path>,
         opts: InstallOptions,
         force_clean_git_reinstall: bool,
+        action: HistoryAction,
+    ) -> Result<()> {
+        let settings_snapshot = self.snapshot_settings_before_deploy(plan.repo_id, wow_dir);
+        self.apply_one_files(
+            plan,
+            wow_dir,
+            raw_dest,
+            opts,
+            force_clean_git_reinstall,
+            None,
+        )
+        .await?;
+        self.after_install(plan, wow_dir, settings_snapshot, action);
+        Ok(())
+    }
+
+    fn history_action(plan: &UpdatePlan) -> HistoryAction {
+        if plan
+            .current
+            .as_deref()
+            .is_some_and(|version| !version.trim().is_empty())
+        {
+            HistoryAction::Update
+        } else {
+            HistoryAction::Install
+        }
+    }
+
+    /// Bookkeeping once an install committed.
+    fn after_install(
+        &self,
+        plan: &UpdatePlan,
+        wow_dir: &Path,
+        settings_snapshot: Option<String>,
+        action: HistoryAction,
+    ) {
+        self.record_history(plan, wow_dir, action, settings_snapshot);
+        // Metadata is a convenience for display; it never fails an update.
+        if let Err(error) = self.refresh_addon_metadata(plan.repo_id, wow_dir) {
+            diagnostics::emit(
+                diagnostics::DiagnosticLevel::Debug,
+                "engine",
+                format!(
+                    "could not refresh addon metadata: repo_id={}; error={error}",
+                    plan.repo_id
+                ),
+            );
+        }
+        if let Err(error) = self.refresh_addon_dependencies(wow_dir) {
+            diagnostics::emit(
+                diagnostics::DiagnosticLevel::Debug,
+                "engine",
+                format!("could not refresh addon dependencies: error={error}"),
+            );
+        }
+    }
+
+    /// Snapshot the SavedVariables of the repo's installed addons whose `.toc`
+    /// declares saved settings, before new files replace them. A failed
+    /// snapshot is logged and never blocks the install.
+    fn snapshot_settings_before_deploy(&self, repo_id: i64, wow_dir: &Path) -> Option<String> {
+        let installs = self.db().list_installs(repo_id).ok()?;
+        let addons = installs
+            .into_iter()
+            .filter(|install| install.kind == "addon")
+            .filter_map(|install| {
+                let dir = Self::resolve_install_path(&install.path, Some(wow_dir))?;
+                let toc = toc::read_addon_toc(&dir)?;
+                let declares_settings = !toc.saved_variables.is_empty()
+                    || !toc.saved_variables_per_character.is_empty();
+                let folder = Path::new(&install.path).file_name()?.to_str()?;
+                declares_settings.then(|| folder.to_string())
+            })
+            .collect::<Vec<_>>();
+        if addons.is_empty() {
+            return None;
+        }
+        match saved_variables::take_addon_snapshot(
+            wow_dir,
+            saved_variables::SnapshotTrigger::BeforeUpdate,
+            &addons,
+        ) {
+            Ok(snapshot) => snapshot.map(|snapshot| snapshot.id),
+            Err(error) => {
+                diagnostics::emit(
+                    diagnostics::DiagnosticLevel::Debug,
+                    "engine.saved_variables",
+                    format!(
+                        "pre-update settings snapshot failed: repo_id={repo_id}; error={error:#}"
+                    ),
+                );
+                None
+            }
+        }
+    }
+
+    /// Append `plan`'s install to the repo's history, with the hashes of the
+    /// cached assets so a rollback can trust them without the network.
+    fn record_history(
+        &self,
+        plan: &UpdatePlan,
+        wow_dir: &Path,
+        action: HistoryAction,
+        settings_snapshot: Option<String>,
+    ) {
+        let Ok(repo) = self.db().get_repo(plan.repo_id) else {
+            return;
+        };
+        let is_release = !matches!(plan.mode, InstallMode::AddonGit);
+        let cached_sha256 = |name: &str| {
+            Self::cached_release_asset(plan, wow_dir, name)
+                .and_then(|path| util::sha256_file_hex(&path).ok())
+        };
+        let entry = InstallHistoryEntry {
+            id: 0,
+            repo_id: plan.repo_id,
+            action,
+            created_unix: Self::now_unix(),
+            from_version: plan
+                .current
+                .clone()
+                .filter(|version| !version.trim().is_empty()),
+            to_version: Some(
+                repo.last_version
+                    .clone()
+                    .unwrap_or_else(|| plan.install_version.clone()),
+            ),
+            asset_id: repo.installed_asset_id.clone(),
+            asset_name: repo.installed_asset_name.clone(),
+            asset_url: repo.installed_asset_url.clone(),
+            asset_sha256: if is_release {
+                cached_sha256(&plan.asset_name)
+            } else {
+                None
+            },
+            extra_assets: plan
+                .extra_assets
+                .iter()
+                .map(|extra| ReleaseAsset {
+                    sha256: extra.sha256.clone().or_else(|| cached_sha256(&extra.name)),
+                    ..extra.clone()
+                })
+                .collect(),
+            settings_snapshot,
+        };
+        if let Err(error) = self.db().add_history_entry(&entry) {
+            diagnostics::emit(
+                diagnostics::DiagnosticLevel::Debug,
+                "engine",
+                format!(
+                    "could not record install history: repo_id={}; error={error}",
+                    plan.repo_id
+                ),
+            );
+        }
+    }
+
+    /// Where the release cache holds `plan`'s asset `name`, if it does.
+    fn cached_release_asset(plan: &UpdatePlan, wow_dir: &Path, name: &str) -> Option<PathBuf> {
+        if name == plan.asset_name {
+            if let Ok(Some(path)) = Self::local_file_asset_path(plan) {
+                return Some(path);
+            }
+        }
+        let file_name = install::validate_asset_filename(name).ok()?;
+        let path = Self::release_cache_dir(plan, Some(wow_dir))
+            .ok()?
+            .join(file_name);
+        path.is_file().then_some(path)
+    }
+
+    /// Installs, updates, reinstalls, rollbacks and removals of a repo,
+    /// newest first. Entries remain after the repo is removed.
+    pub fn install_history(&self, repo_id: i64) -> Result<Vec<InstallHistoryEntry>> {
+        self.db().list_install_history(repo_id)
+    }
+
+    /// Redeploy the version an install history entry recorded, put back the
+    /// addon settings snapshotted when that version was replaced, and pin
+    /// the repo to it so the next update does not bring the newer one back.
+    /// Clearing the pin resumes updates.
+    ///
+    /// Release assets come from the release cache when the cached file still
+    /// matches the recorded hash, so no network is needed; otherwise they are
+    /// downloaded again. Git addons are pinned to the recorded commit, tagged
+    /// or not, which is only fetched if the installed worktree lacks it.
+    /// Entries that predate asset details are reinstalled by version, which
+    /// for a Git addon needs a tag. The previous pin is restored if the
+    /// deploy fails.
+    pub async fn rollback_to_history(
+        &self,
+        entry_id: i64,
+        wow_dir: &Path,
+        raw_dest: Option<&Path>,
+        opts: InstallOptions,
+    ) -> Result<RollbackReport> {
+        let _diagnostic = diagnostics::OperationGuard::new("rollback_to_history");
+        let entry = self.db().get_history_entry(entry_id)?;
+        let repo = self
+            .db()
+            .get_repo(entry.repo_id)
+            .context("This repo is no longer tracked; add it again before rolling back")?;
+        if entry.action == HistoryAction::Remove {
+            anyhow::bail!("A removal has no version to roll back to.");
+        }
+        let Some(version) = entry.to_version.clone() else {
+            anyhow::bail!("This history entry did not record a version.");
+        };
+        if repo.forge.eq_ignore_ascii_case("direct") {
+            anyhow::bail!("Direct downloads cannot be reinstalled at an earlier version.");
+        }
+
+        // The entry that replaced this version snapshotted its settings.
+        let snapshot = self
+            .db()
+            .list_install_history(repo.id)?
+            .into_iter()
+            .filter(|later| later.id > entry.id)
+            .min_by_key(|later| later.id)
+            .and_then(|later| later.settings_snapshot)
+            .map(|id| {
+                saved_variables::load_snapshot(wow_dir, &id).context(
+                    "The settings snapshot taken when this version was replaced no longer exists",
+                )
+            })
+            .transpose()?;
+        if snapshot.is_some() && saved_variables::game_running(wow_dir) {
+            anyhow::bail!(
+                "Close the game before rolling back; it rewrites SavedVariables when you log out."
+            );
+        }
+
+        let is_git = matches!(repo.mode, InstallMode::AddonGit);
+        let (pin, plan, force_clean) = match (entry.asset_name.clone(), entry.asset_url.clone()) {
+            (Some(asset_name), Some(asset_url)) if !is_git => {
+                let plan = UpdatePlan {
+                    repo_id: repo.id,
+                    forge: repo.forge.clone(),
+                    host: repo.host.clone(),
+                    owner: repo.owner.clone(),
+                    name: repo.name.clone(),
+                    url: repo.url.clone(),
+                    mode: repo.mode.clone(),
+                    current: Self::normalized_current_version(&repo),
+                    install_version: version.clone(),
+                    latest: version.clone(),
+                    asset_id: entry.asset_id.clone().unwrap_or_default(),
+                    asset_name,
+                    asset_url,
+                    asset_size: None,
+                    asset_sha256: entry.asset_sha256.clone(),
+                    repair_needed: false,
+                    externally_modified: false,
+                    not_modified: false,
+                    applied: false,
+                    error: None,
+                    release_cache_age_secs: None,
+                    extra_assets: entry.extra_assets.clone(),
+                    previous_dll_count: 0,
+                    new_dll_count: 0,
+                    is_manual: false,
+                };
+                (version.clone(), plan, false)
+            }
+            _ => {
+                let pin = match entry
+                    .asset_id
+                    .clone()
+                    .filter(|id| is_git && git_sync::is_commit_id(id))
+                {
+                    Some(commit) => commit,
+                    None if !is_git || self.list_repo_tags(repo.id)?.contains(&version) => {
+                        version.clone()
+                    }
+                    None => anyhow::bail!(
+                        "{version} is a commit without a tag that this install did not record; it cannot be reinstalled."
+                    ),
+                };
+                let pinned = Repo {
+                    pinned_version: Some(pin.clone()),
+                    ..repo.clone()
+                };
+                let (plan, force_clean) = self.reinstall_plan(&pinned, wow_dir, true).await?;
+                (pin, plan, force_clean)
+            }
+        };
+
+        let previous_pin = repo.pinned_version.clone();
+        self.set_repo_pinned_version(repo.id, Some(pin))?;
+        if let Err(error) = self
+            .apply_one_internal(
+                &plan,
+                wow_dir,
+                raw_dest,
+                opts,
+                force_clean,
+                HistoryAction::Rollback,
+            )
+            .await
+        {
+            let _ = self.set_repo_pinned_version(repo.id, previous_pin);
+            return Err(error);
+        }
+        let mut settings = Vec::new();
+        if let Some(snapshot) = snapshot {
+            for addon in snapshot.addons() {
+                settings.push(saved_variables::restore_addon(
+                    wow_dir,
+                    &snapshot.id,
+                    &addon,
+                )?);
+            }
+        }
+        diagnostics::emit(
+            diagnostics::DiagnosticLevel::Debug,
+            "engine",
+            format!(
+                "rolled back from history: repo_id={}; entry_id={entry_id}; version={version}; settings_restored={}",
+                repo.id,
+                settings.len()
+            ),
+        );
+        Ok(RollbackReport {
+            repo_id: repo.id,
+            version,
+            settings,
+        })
+    }
+
+    /// Install `plan`'s files and records. With `record`, nothing is
+    /// changed: the files and records the install would change are added to
+    /// the change set instead.
+    async fn apply_one_files(
+        &self,
+        plan: &UpdatePlan,
+        wow_dir: &Path,
+        raw_dest: Option<&Path>,
+        opts: InstallOptions,
+        force_clean_git_reinstall: bool,
+        mut record: Option<&mut ChangeSet>,
     ) -> Result<()> {
         let _diagnostic = diagnostics::OperationGuard::new("apply_one");
         diagnostics::emit(
@@ -5171,7 +7336,9 @@ impl Engine {
             let repo = self.db().get_repo(plan.repo_id)?;
 
             // Migrate legacy staging clones to the direct AddOns location on first encounter.
-            self.migrate_staging_clone_if_needed(wow_dir, &repo)?;
+            if record.is_none() {
+                self.migrate_staging_clone_if_needed(wow_dir, &repo)?;
+            }
 
             let installed_worktree_dir = self.addon_git_worktree_dir(plan.repo_id, wow_dir, &repo);
             if let Some(installed) = installed_worktree_dir.as_deref() {
@@ -5184,7 +7351,10 @@ impl Engine {
                     } else {
                         actual_name
                     };
-                    if base_name != repo.name && base_name.eq_ignore_ascii_case(&repo.name) {
+                    if record.is_none()
+                        && base_name != repo.name
+                        && base_name.eq_ignore_ascii_case(&repo.name)
+                    {
                         let _ = self
                             .db()
                             .update_repo_casing(repo.id, &repo.owner, base_name);
@@ -5211,13 +7381,29 @@ impl Engine {
                 }
             }
 
-            let staging_dir = util::cache_dir(Some(wow_dir))?
-                .join("addon_git_staging")
-                .join(format!("repo-{}", plan.repo_id));
-            Self::remove_any_target(&staging_dir)?;
-            diagnostics::emit(
-                diagnostics::DiagnosticLevel::Trace,
-                "engine",
+            // A dry run syncs into the system temp directory, not the cache.
+            let dry_run_staging = match record {
+                Some(_) => Some(
+                    tempfile::Builder::new()
+                        .prefix("wuddle-dry-run-")
+                        .tempdir()
+                        .context("create dry-run staging directory")?,
+                ),
+                None => None,
+            };
+            let staging_dir = match &dry_run_staging {
+                Some(scratch) => scratch.path().join(format!("repo-{}", plan.repo_id)),
+                None => {
+                    let staging_dir = util::cache_dir(Some(wow_dir))?
+                        .join("addon_git_staging")
+                        .join(format!("repo-{}", plan.repo_id));
+                    Self::remove_any_target(&staging_dir)?;
+                    staging_dir
+                }
+            };
+            diagnostics::emit(
+                diagnostics::DiagnosticLevel::Trace,
+                "engine",
                 format!(
                     "apply_one: repo_id={}; using staged git replacement",
                     plan.repo_id
@@ -5231,17 +7417,52 @@ impl Engine {
                 .as_deref()
                 .map(str::trim)
                 .filter(|b| !b.is_empty());
-            let synced = git_sync::sync_repo_to_staging(
-                &plan.url,
-                installed_worktree_dir.as_deref(),
-                &worktree_dir,
-                preferred_branch,
-            )
+            let selected_addons = selected_addons_from_json(repo.selected_addons_json.as_deref());
+            // Collections with a selection only materialize the chosen folders;
+            // the files are written once the layout below is known.
+            let mut sparse = !selected_addons.is_empty();
+            let checkout = if sparse {
+                git_sync::WorktreeCheckout::Deferred
+            } else {
+                git_sync::WorktreeCheckout::Full
+            };
+            let pinned_commit = repo
+                .pinned_version
+                .as_deref()
+                .map(str::trim)
+                .filter(|pinned| git_sync::is_commit_id(pinned));
+            let synced = match pinned_commit {
+                Some(commit) => git_sync::stage_commit(
+                    &plan.url,
+                    installed_worktree_dir.as_deref(),
+                    &worktree_dir,
+                    commit,
+                    plan.asset_name.strip_prefix("git:"),
+                    checkout,
+                ),
+                None => git_sync::sync_repo_to_staging(
+                    &plan.url,
+                    installed_worktree_dir.as_deref(),
+                    &worktree_dir,
+                    preferred_branch,
+                    Self::git_tag_target(&repo),
+                    checkout,
+                ),
+            }
             .with_context(|| format!("git sync {}", url_safety::safe_remote_label(&plan.url)))?;
 
             // Detect addon folders inside the cloned repo.
             // detect_addons_in_tree returns (src_path, toc_name) pairs.
             let mut detected = install::detect_addons_in_tree(&worktree_dir);
+            if selected_addons.is_empty() {
+                // Without a selection, install only the client's variants.
+                toc::retain_client_variants(&mut detected, opts.client_family, |(_, name)| name);
+            }
+            if detected.is_empty() && sparse {
+                // The legacy rescue below inspects files on disk.
+                git_sync::checkout_full(&worktree_dir)?;
+                sparse = false;
+            }
 
             // [Legacy Reconciliation] If strict detection found nothing, but we have
             // previous installs in the DB, try to "rescue" them by looking for their
@@ -5275,7 +7496,6 @@ impl Engine {
                 }
             }
 
-            let selected_addons = selected_addons_from_json(repo.selected_addons_json.as_deref());
             let selected_paths: HashSet<String> = selected_addons
                 .iter()
                 .map(|name| name.trim().trim_matches('/').to_ascii_lowercase())
@@ -5332,6 +7552,27 @@ impl Engine {
                     "No addon .toc files found in synced repo. Expected at least one addon folder."
                 );
             }
+            if sparse {
+                let sparse_dirs = chosen
+                    .iter()
+                    .filter_map(|(src, _)| src.strip_prefix(&worktree_dir).ok())
+                    .map(|rel| {
+                        rel.components()
+                            .filter_map(|part| match part {
+                                Component::Normal(part) => part.to_str(),
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>()
+                            .join("/")
+                    })
+                    .collect::<Vec<_>>();
+                // An addon at the repository root needs the whole tree.
+                if sparse_dirs.iter().any(String::is_empty) {
+                    git_sync::checkout_full(&worktree_dir)?;
+                } else {
+                    git_sync::checkout_sparse(&worktree_dir, &sparse_dirs)?;
+                }
+            }
 
             // GAM subfolder collision: if a subfolder has the same name as the repo
             // directory, rename the repo dir to "{name}.repo" first — exactly as
@@ -5373,12 +7614,58 @@ impl Engine {
             let mut conflicts = Vec::new();
             if !conflict_names.is_empty() {
                 conflicts = self.addon_install_conflicts(plan.repo_id, wow_dir, &conflict_names)?;
+                if let Some(changes) = record.as_deref_mut() {
+                    changes.conflicts = conflicts
+                        .iter()
+                        .map(|conflict| {
+                            let owners = conflict
+                                .owners
+                                .iter()
+                                .map(|owner| format!("{}/{}", owner.owner, owner.name))
+                                .collect::<Vec<_>>();
+                            if owners.is_empty() {
+                                format!("{} [untracked folder]", conflict.addon_name)
+                            } else {
+                                format!("{} [{}]", conflict.addon_name, owners.join(", "))
+                            }
+                        })
+                        .collect();
+                }
                 if !conflicts.is_empty() && !opts.replace_addon_conflicts {
                     anyhow::bail!(Self::format_addon_conflict_message(&conflicts));
                 }
             }
             let conflict_plan =
                 self.plan_conflicting_addon_replacements(plan.repo_id, wow_dir, &conflicts)?;
+            if let Some(changes) = record {
+                let exposed = chosen
+                    .iter()
+                    .map(|(src, addon_name)| {
+                        if src == &worktree_dir {
+                            addon_name.clone()
+                        } else {
+                            src.file_name()
+                                .and_then(|name| name.to_str())
+                                .unwrap_or(addon_name)
+                                .to_string()
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let version = synced
+                    .tag
+                    .clone()
+                    .unwrap_or_else(|| synced.short_oid.clone());
+                return self.record_addon_git_replacement(
+                    plan.repo_id,
+                    wow_dir,
+                    &exposed,
+                    &staged_final_name,
+                    installed_worktree_dir.as_deref(),
+                    &conflict_plan,
+                    &version,
+                    changes,
+                );
+            }
 
             // Only disturb the live installation after the replacement clone has
             // synced, its addon layout has been detected, and all conflicts have
@@ -5481,13 +7768,17 @@ impl Engine {
             // No kind='raw' worktree entry — GAM doesn't track anything beyond the
             // addon folders themselves. The .git dir inside the addon folder is the
             // ground truth; import_existing_addon_git_repos() will re-discover it.
+            let synced_version = synced
+                .tag
+                .clone()
+                .unwrap_or_else(|| synced.short_oid.clone());
             let install_entries = records
                 .iter()
                 .map(|record| db::InstallEntry {
                     path: Self::to_manifest_path(&record.path, wow_dir),
                     kind: record.kind.to_string(),
                     sha256: None,
-                    version: Some(synced.short_oid.clone()),
+                    version: Some(synced_version.clone()),
                     display_name: None,
                     file_fingerprint: None,
                 })
@@ -5496,7 +7787,7 @@ impl Engine {
                 plan.repo_id,
                 &install_entries,
                 &db::InstalledAssetState {
-                    version: Some(synced.short_oid.clone()),
+                    version: Some(synced_version),
                     asset_id: Some(synced.oid.clone()),
                     asset_name: Some(format!("git:{}", synced.branch)),
                     asset_size: None,
@@ -5514,11 +7805,101 @@ impl Engine {
             return Ok(());
         }
 
+        let live_dlls_txt = wow_dir.join("dlls.txt");
+        let mut staged = self
+            .stage_release(
+                plan,
+                wow_dir,
+                raw_dest,
+                opts,
+                &live_dlls_txt,
+                record.is_some(),
+            )
+            .await?;
+        if let Some(changes) = record {
+            // Record the whole deployment even when the conflict check would
+            // refuse it, so the dry run shows what the approval would allow.
+            let checked = Self::check_release_conflicts(&staged, opts);
+            changes.conflicts = staged
+                .conflicts
+                .iter()
+                .map(|conflict| format!("{} [{}]", conflict.display_name, conflict.owner_label))
+                .collect();
+            self.deploy_staged_release(plan, wow_dir, &mut staged, &mut Deploy::DryRun(changes))?;
+            changes.record_release_replacement(
+                plan,
+                &Self::release_replacement(plan, &staged),
+                staged.conflicts.len(),
+            );
+            return checked;
+        }
+        Self::check_release_conflicts(&staged, opts)?;
+        let mut transaction =
+            self.begin_replacement("release replacement", plan.repo_id, wow_dir)?;
+        self.deploy_staged_release(
+            plan,
+            wow_dir,
+            &mut staged,
+            &mut Deploy::Live(&mut transaction),
+        )?;
+        self.db().commit_release_replacement(
+            &Self::release_replacement(plan, &staged),
+            Some(transaction.id()),
+        )?;
+        self.finish_replacement(transaction);
+
+        self.prune_release_cache(plan, opts.cache_keep_versions, Some(wow_dir));
+
+        Ok(())
+    }
+
+    /// Download `plan`'s release and build it beneath `.wuddle` without
+    /// touching live files. The staged `dlls.txt` starts from `dlls_txt_base`,
+    /// so releases staged for one transaction can build on each other's
+    /// launcher entries.
+    ///
+    /// A `dry_run` only reads the cache: it builds the release in the system
+    /// temp directory, downloading there any asset the cache lacks.
+    async fn stage_release(
+        &self,
+        plan: &UpdatePlan,
+        wow_dir: &Path,
+        raw_dest: Option<&Path>,
+        opts: InstallOptions,
+        dlls_txt_base: &Path,
+        dry_run: bool,
+    ) -> Result<StagedRelease> {
         if plan.asset_url.is_empty() {
             anyhow::bail!("No downloadable asset in update plan");
         }
 
-        let release_dir = Self::release_cache_dir(plan, Some(wow_dir))?;
+        // Build the entire release beneath .wuddle first. The staging root is
+        // on the same filesystem as the game so final renames and rollback
+        // backups never require a destructive cross-device copy.
+        let staging = if dry_run {
+            tempfile::Builder::new()
+                .prefix("wuddle-dry-run-")
+                .tempdir()
+                .context("create dry-run staging directory")?
+        } else {
+            let staging_parent = util::cache_dir(Some(wow_dir))?.join("release-staging");
+            fs::create_dir_all(&staging_parent).context("create release staging directory")?;
+            tempfile::Builder::new()
+                .prefix(&format!("repo-{}-", plan.repo_id))
+                .tempdir_in(&staging_parent)
+                .context("create release staging transaction")?
+        };
+        let (release_dir, scratch) = if dry_run {
+            let scratch = staging.path().join("assets");
+            fs::create_dir_all(&scratch).context("create dry-run download directory")?;
+            (
+                Self::release_cache_path(plan, &util::wow_cache_path(wow_dir)),
+                Some(scratch),
+            )
+        } else {
+            (Self::release_cache_dir(plan, Some(wow_dir))?, None)
+        };
+
         let asset_name_fs = install::validate_asset_filename(&plan.asset_name)?.to_string();
         Self::validate_asset_url(plan)?;
         let local_asset_path = Self::local_file_asset_path(plan)?;
@@ -5526,7 +7907,13 @@ impl Engine {
         let asset_path = if let Some(path) = local_asset_path {
             path
         } else {
-            let asset_path = release_dir.join(asset_name_fs);
+            let asset_path = Self::release_asset_path(
+                release_dir.join(asset_name_fs),
+                scratch.as_deref(),
+                &plan.asset_name,
+                plan.asset_size,
+                plan.asset_sha256.as_deref(),
+            );
             let should_download = Self::cached_asset_needs_download(
                 &asset_path,
                 &plan.asset_name,
@@ -5538,7 +7925,10 @@ impl Engine {
             }
             asset_path
         };
-        let extract_dir = release_dir.join("extract");
+        let extract_dir = match &scratch {
+            Some(_) => staging.path().join("extract"),
+            None => release_dir.join("extract"),
+        };
         Self::validate_downloaded_asset(&asset_path, plan)?;
         Self::verify_asset_digest(&asset_path, plan.asset_sha256.as_deref())?;
 
@@ -5547,28 +7937,18 @@ impl Engine {
             plan.owner, plan.name, plan.install_version
         );
 
-        // Build the entire release beneath .wuddle first. The staging root is
-        // on the same filesystem as the game so final renames and rollback
-        // backups never require a destructive cross-device copy.
-        let staging_parent = util::cache_dir(Some(wow_dir))?.join("release-staging");
-        fs::create_dir_all(&staging_parent).context("create release staging directory")?;
-        let staging = tempfile::Builder::new()
-            .prefix(&format!("repo-{}-", plan.repo_id))
-            .tempdir_in(&staging_parent)
-            .context("create release staging transaction")?;
         let staging_wow = staging.path().join("wow");
         fs::create_dir_all(&staging_wow).context("create staged WoW root")?;
 
         let repo = self.db().get_repo(plan.repo_id)?;
         let existing_installs = self.db().list_installs(plan.repo_id)?;
-        let mut final_backups = self.db().list_install_backups(plan.repo_id)?;
+        let final_backups = self.db().list_install_backups(plan.repo_id)?;
 
         // Preserve every unrelated launcher entry while calculating this
         // package's new dlls.txt state. Stale entries owned solely by this
         // package are removed before the new DLL list is added.
-        let live_dlls_txt = wow_dir.join("dlls.txt");
-        if live_dlls_txt.is_file() {
-            fs::copy(&live_dlls_txt, staging_wow.join("dlls.txt"))
+        if dlls_txt_base.is_file() {
+            fs::copy(dlls_txt_base, staging_wow.join("dlls.txt"))
                 .context("stage existing DLL launcher configuration")?;
         }
         let retained_backup_keys = final_backups
@@ -5592,11 +7972,16 @@ impl Engine {
 
         let mut raw_staging = None;
         if let Some(destination) = raw_dest {
-            fs::create_dir_all(destination).context("create raw destination directory")?;
+            let parent = if dry_run {
+                staging.path()
+            } else {
+                fs::create_dir_all(destination).context("create raw destination directory")?;
+                destination
+            };
             raw_staging = Some(
                 tempfile::Builder::new()
                     .prefix(".wuddle-release-stage-")
-                    .tempdir_in(destination)
+                    .tempdir_in(parent)
                     .context("create raw release staging directory")?,
             );
         }
@@ -5643,7 +8028,13 @@ impl Engine {
         // Download and install any additional .dll assets (multi-DLL repos like WeirdUtils).
         for extra in &plan.extra_assets {
             let extra_name_fs = install::validate_asset_filename(&extra.name)?.to_string();
-            let extra_path = release_dir.join(&extra_name_fs);
+            let extra_path = Self::release_asset_path(
+                release_dir.join(&extra_name_fs),
+                scratch.as_deref(),
+                &extra.name,
+                extra.size,
+                extra.sha256.as_deref(),
+            );
             Self::validate_asset_url_for(plan, &extra.download_url)?;
             let needs_dl = Self::cached_asset_needs_download(
                 &extra_path,
@@ -5710,23 +8101,64 @@ impl Engine {
             &existing_installs,
             &final_backups,
         )?;
+        let dlls_txt_relevant =
+            !old_dll_names.is_empty() || records.iter().any(|record| record.kind == "dll");
+        Ok(StagedRelease {
+            staged_dlls_txt: staging_wow.join("dlls.txt"),
+            _staging: staging,
+            _raw_staging: raw_staging,
+            repo,
+            existing_installs,
+            final_backups,
+            targets,
+            conflicts,
+            dlls_txt_relevant,
+            is_local_asset,
+        })
+    }
+
+    /// Refuse a staged release whose targets belong to other installs unless
+    /// `opts` allows replacing them.
+    fn check_release_conflicts(staged: &StagedRelease, opts: InstallOptions) -> Result<()> {
+        let conflicts = &staged.conflicts;
         if !conflicts.is_empty() && !opts.replace_file_conflicts {
-            anyhow::bail!(Self::format_release_target_conflicts(&conflicts));
+            anyhow::bail!(Self::format_release_target_conflicts(conflicts));
         }
         if conflicts.iter().any(|conflict| conflict.owner_count > 1) {
             anyhow::bail!(
                 "Multiple tracked mods claim the same install target. Remove the ambiguous owners before replacing it."
             );
         }
-        for conflict in &conflicts {
-            let target = &targets[conflict.target_index].target_path;
+        for conflict in conflicts {
+            let target = &staged.targets[conflict.target_index].target_path;
             if Self::find_actual_case(target).is_none() {
                 anyhow::bail!(
                     "A conflicting tracked file is missing. Remove or repair its owning mod before replacement."
                 );
             }
         }
+        Ok(())
+    }
 
+    /// Move a staged release into place, backing up what it replaces, or
+    /// record those moves for a dry run.
+    fn deploy_staged_release(
+        &self,
+        plan: &UpdatePlan,
+        wow_dir: &Path,
+        staged: &mut StagedRelease,
+        to: &mut Deploy<'_>,
+    ) -> Result<()> {
+        let StagedRelease {
+            repo,
+            existing_installs,
+            final_backups,
+            targets,
+            conflicts,
+            staged_dlls_txt,
+            dlls_txt_relevant,
+            ..
+        } = staged;
         let conflict_by_target = conflicts
             .iter()
             .map(|conflict| (conflict.target_index, conflict))
@@ -5735,18 +8167,32 @@ impl Engine {
             .iter()
             .map(|target| Self::install_path_key(&target.manifest_path))
             .collect::<HashSet<_>>();
-        let mut transaction =
-            deployment::ReplacementTransaction::new("release replacement", plan.repo_id);
 
         for (index, target) in targets.iter().enumerate() {
-            transaction.backup_target(&target.target_path)?;
-            if let Some(conflict) = conflict_by_target.get(&index) {
-                let persistent = Self::persistent_install_backup_path(
-                    plan.repo_id,
-                    wow_dir,
-                    &target.manifest_path,
-                );
-                transaction.promote_backup(&target.target_path, &persistent)?;
+            let conflict = conflict_by_target.get(&index);
+            let persistent = conflict.map(|_| {
+                Self::persistent_install_backup_path(plan.repo_id, wow_dir, &target.manifest_path)
+            });
+            match to {
+                Deploy::Live(transaction) => {
+                    transaction.backup_target(&target.target_path)?;
+                    if let Some(persistent) = &persistent {
+                        transaction.promote_backup(&target.target_path, persistent)?;
+                    }
+                    transaction.deploy(&target.staged_path, &target.target_path)?;
+                }
+                Deploy::DryRun(changes) => {
+                    if persistent.is_some() {
+                        changes.backed_up.push(target.manifest_path.clone());
+                    }
+                    if Self::find_actual_case(&target.target_path).is_some() {
+                        changes.replaced.push(target.manifest_path.clone());
+                    } else {
+                        changes.created.push(target.manifest_path.clone());
+                    }
+                }
+            }
+            if let (Some(conflict), Some(persistent)) = (conflict, persistent) {
                 final_backups.push(db::InstallBackupRow {
                     replacement_repo_id: plan.repo_id,
                     path: target.manifest_path.clone(),
@@ -5756,11 +8202,10 @@ impl Engine {
                     displaced_repo_id: conflict.displaced_repo_id,
                 });
             }
-            transaction.deploy(&target.staged_path, &target.target_path)?;
         }
 
         if !repo.merge_installs {
-            for install in &existing_installs {
+            for install in existing_installs {
                 let key = Self::install_path_key(&install.path);
                 if target_keys.contains(&key)
                     || Path::new(&install.path)
@@ -5786,27 +8231,29 @@ impl Engine {
                         Some(wow_dir),
                     )
                     .ok_or_else(|| anyhow::anyhow!("Invalid displaced-file backup path"))?;
-                    if !Self::tracked_install_is_safe_to_remove(&repo, install, &live, None)? {
+                    if !Self::tracked_install_is_safe_to_remove(repo, install, &live, None)? {
                         anyhow::bail!(
                             "A replaced file was modified outside Wuddle. No files were changed."
                         );
                     }
-                    transaction.deploy_returnable(&backup_path, &live)?;
+                    to.restore(&backup_path, &live, &install.path)?;
                     final_backups.remove(backup_index);
-                } else if Self::tracked_install_is_safe_to_remove(&repo, install, &live, None)? {
-                    transaction.backup_target(&live)?;
+                } else if Self::tracked_install_is_safe_to_remove(repo, install, &live, None)? {
+                    to.remove(&live, &install.path)?;
                 }
             }
         }
 
-        let dlls_txt_relevant =
-            !old_dll_names.is_empty() || records.iter().any(|record| record.kind == "dll");
-        let staged_dlls_txt = staging_wow.join("dlls.txt");
-        if dlls_txt_relevant && staged_dlls_txt.is_file() {
-            transaction.deploy(&staged_dlls_txt, &live_dlls_txt)?;
+        if *dlls_txt_relevant && staged_dlls_txt.is_file() {
+            to.replace_dlls_txt(staged_dlls_txt, &wow_dir.join("dlls.txt"))?;
         }
 
-        let install_entries = targets
+        Ok(())
+    }
+
+    fn release_replacement(plan: &UpdatePlan, staged: &StagedRelease) -> db::ReleaseReplacement {
+        let install_entries = staged
+            .targets
             .iter()
             .map(|target| db::InstallEntry {
                 path: target.manifest_path.clone(),
@@ -5817,31 +8264,21 @@ impl Engine {
                 file_fingerprint: None,
             })
             .collect::<Vec<_>>();
-        self.db().commit_release_replacement(
-            plan.repo_id,
-            &install_entries,
-            &final_backups,
-            &db::InstalledAssetState {
+        db::ReleaseReplacement {
+            repo_id: plan.repo_id,
+            installs: install_entries,
+            backups: staged.final_backups.clone(),
+            installed_asset: db::InstalledAssetState {
                 version: Some(plan.install_version.clone()),
                 asset_id: Some(plan.asset_id.clone()),
                 asset_name: Some(plan.asset_name.clone()),
                 asset_size: Self::size_u64_to_i64(plan.asset_size),
                 asset_url: Some(plan.asset_url.clone()),
-                installed_at_unix: Some(
-                    std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs() as i64,
-                ),
+                installed_at_unix: Some(Self::now_unix()),
             },
-            repo.merge_installs,
-            is_local_asset,
-        )?;
-        transaction.disarm();
-
-        self.prune_release_cache(plan, opts.cache_keep_versions, Some(wow_dir));
-
-        Ok(())
+            merge_installs: staged.repo.merge_installs,
+            mark_manual: staged.is_local_asset,
+        }
     }
 
     /// Remove old cached release versions for a repo, keeping the `keep_versions`
@@ -5920,38 +8357,63 @@ impl Engine {
             format!("reinstall_repo: repo_id={repo_id}"),
         );
         let r = self.db().get_repo(repo_id)?;
+        let (mut plan, force_clean_git_reinstall) = self.reinstall_plan(&r, wow_dir, true).await?;
+        self.apply_one_internal(
+            &plan,
+            wow_dir,
+            raw_dest,
+            opts,
+            force_clean_git_reinstall,
+            HistoryAction::Reinstall,
+        )
+        .await?;
+        plan.applied = true;
+        Ok(plan)
+    }
 
+    /// The plan a reinstall applies, and whether a Git addon is recloned
+    /// rather than synced. Without `persist` the fetched release is not
+    /// recorded in the profile.
+    async fn reinstall_plan(
+        &self,
+        r: &Repo,
+        wow_dir: &Path,
+        persist: bool,
+    ) -> Result<(UpdatePlan, bool)> {
         if r.forge.eq_ignore_ascii_case("direct") {
-            let mut plan = self.build_direct_archive_plan_for_repo(&r, Some(wow_dir), true)?;
-            self.apply_one(&plan, wow_dir, raw_dest, opts).await?;
-            plan.applied = true;
-            return Ok(plan);
+            let plan = self.build_direct_archive_plan_for_repo(r, Some(wow_dir), true)?;
+            return Ok((plan, false));
         }
 
         if matches!(r.mode, InstallMode::AddonGit) {
-            let mut plan = self.build_git_addon_plan_for_repo(&r, Some(wow_dir))?;
+            let mut plan = self.build_git_addon_plan_for_repo(r, Some(wow_dir))?;
             if let Some(err) = plan.error.clone() {
                 anyhow::bail!(err);
             }
             // Force sync even if already up to date.
             plan.asset_url = r.url.clone();
-            self.apply_one_internal(&plan, wow_dir, raw_dest, opts, true)
-                .await?;
-            plan.applied = true;
-            return Ok(plan);
+            return Ok((plan, true));
         }
 
         let det = detect_repo(&r.url)?;
 
         // force fetch (no ETag) so we always get asset URLs
-        let (etag, rel_opt, _not_modified) =
-            forge::latest_release(&self.client, &det, None).await?;
-
-        if let Some(ref et) = etag {
-            let _ = self.db().update_etag(r.id, Some(et.as_str()));
-        }
+        let (validators, rel_opt, _not_modified) =
+            forge::latest_release(&self.client, &det, &forge::ReleaseValidators::default()).await?;
 
         let rel = rel_opt.ok_or_else(|| anyhow::anyhow!("No releases found for {}", r.url))?;
+        if persist {
+            if let Some(ref et) = validators.etag {
+                let _ = self.db().update_etag(r.id, Some(et.as_str()));
+            }
+            let _ = self.db().put_cached_release(
+                &forge::cache_key(&det),
+                validators.etag.as_deref(),
+                validators.last_modified.as_deref(),
+                &rel,
+                Self::now_unix(),
+            );
+        }
         // Reinstall/Repair must honor the same pinned-release selection as a
         // normal update check. Previously it always reinstalled `latest`, which
         // made the version picker appear to succeed while leaving the newer
@@ -5981,7 +8443,7 @@ impl Engine {
         let new_dll_count =
             usize::from(asset.name.to_ascii_lowercase().ends_with(".dll")) + extra_assets.len();
 
-        let mut plan = UpdatePlan {
+        let plan = UpdatePlan {
             repo_id: r.id,
             forge: r.forge.clone(),
             host: r.host.clone(),
@@ -5989,7 +8451,7 @@ impl Engine {
             name: r.name.clone(),
             url: r.url.clone(),
             mode,
-            current: Self::normalized_current_version(&r),
+            current: Self::normalized_current_version(r),
             install_version: latest.clone(),
             latest,
             asset_id: Self::effective_asset_id(&asset),
@@ -6002,28 +8464,30 @@ impl Engine {
             not_modified: false,
             applied: false,
             error: None,
+            release_cache_age_secs: Some(0),
             extra_assets,
             previous_dll_count: 0,
             new_dll_count,
             is_manual: false,
         };
-
-        self.apply_one(&plan, wow_dir, raw_dest, opts).await?;
-        plan.applied = true;
-        Ok(plan)
+        Ok((plan, false))
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{
-        selected_addons_from_json, should_skip_adaptive, CheckMode, Engine, InstallMode,
-        InstallOptions, LatestRelease, ReleaseAsset, Repo, StagedGitWorktree, UpdatePlan,
+        character_addons, fingerprint, forge, selected_addons_from_json, should_skip_adaptive,
+        AddonSet, AddonSetFolder, AddonSetRepo, CheckMode, ClientFamily, DependencyProblem,
+        DependencySource, Engine, GitTagPolicy, HistoryAction, InstallMode, InstallOptions,
+        LatestRelease, PrefetchedReleases, RecoveryAction, ReleaseAsset, Repo, StagedGitWorktree,
+        UpdatePlan,
     };
     use git2::Repository;
     use std::collections::HashSet;
     use std::fs;
     use std::path::Path;
+    use std::sync::atomic::AtomicBool;
     use std::sync::Arc;
 
     #[test]
@@ -6065,6 +8529,7 @@ mod tests {
             name: None,
             prerelease: false,
             published_at: None,
+            body: None,
             assets: ["Core.dll", "Helper.dll", "Plugin.dll"]
                 .into_iter()
                 .enumerate()
@@ -6338,6 +8803,7 @@ mod tests {
             prerelease: false,
             assets,
             published_at: None,
+            body: None,
         }
     }
 
@@ -6364,6 +8830,7 @@ mod tests {
             merge_installs: false,
             pinned_version: Some(pin.to_string()),
             selected_addons_json: None,
+            git_tag_policy: None,
         }
     }
 
@@ -6410,6 +8877,55 @@ mod tests {
         assert!(error.contains(&repo_id.to_string()));
     }
 
+    #[tokio::test]
+    async fn prefetched_releases_answer_the_check_without_a_request() {
+        let tmp = tempfile::tempdir().unwrap();
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let repo_id = engine
+            .add_repo(
+                "https://github.com/example/Prefetched",
+                InstallMode::Dll,
+                None,
+                None,
+            )
+            .unwrap();
+        let repo = engine.db().get_repo(repo_id).unwrap();
+        let det = forge::detect_repo(&repo.url).unwrap();
+        let release = LatestRelease {
+            tag: "v2.0.0".to_string(),
+            name: None,
+            prerelease: false,
+            assets: vec![ReleaseAsset {
+                id: Some("7".to_string()),
+                name: "Prefetched.dll".to_string(),
+                download_url: "https://example.invalid/Prefetched.dll".to_string(),
+                size: Some(4),
+                content_type: None,
+                sha256: None,
+            }],
+            published_at: None,
+            body: None,
+        };
+        let prefetched = PrefetchedReleases::from([(forge::cache_key(&det), release)]);
+
+        let plan = engine
+            .build_update_plan_for_repo(
+                &repo,
+                true,
+                true,
+                None,
+                CheckMode::Force,
+                Some(&prefetched),
+                None,
+                &Arc::new(AtomicBool::new(false)),
+            )
+            .await
+            .unwrap();
+        assert_eq!(plan.error, None);
+        assert_eq!(plan.latest, "v2.0.0");
+        assert_eq!(plan.asset_name, "Prefetched.dll");
+    }
+
     fn plan_for_asset(asset_name: &str, size: Option<u64>) -> UpdatePlan {
         UpdatePlan {
             repo_id: 1,
@@ -6434,6 +8950,7 @@ mod tests {
             not_modified: false,
             applied: false,
             error: None,
+            release_cache_age_secs: None,
             extra_assets: Vec::new(),
             previous_dll_count: 0,
             new_dll_count: 0,
@@ -6601,6 +9118,7 @@ mod tests {
                 merge_installs: false,
                 pinned_version: None,
                 selected_addons_json: None,
+                git_tag_policy: None,
             })
             .unwrap()
     }
@@ -6631,6 +9149,7 @@ mod tests {
                 merge_installs: false,
                 pinned_version: None,
                 selected_addons_json: None,
+                git_tag_policy: None,
             })
             .unwrap()
     }
@@ -6657,6 +9176,7 @@ mod tests {
             not_modified: false,
             applied: false,
             error: None,
+            release_cache_age_secs: None,
             extra_assets: Vec::new(),
             previous_dll_count: 0,
             new_dll_count: 0,
@@ -6728,6 +9248,35 @@ mod tests {
             .all(|repo| repo.name != "NewCollection"));
     }
 
+    #[test]
+    fn tag_policy_is_validated_and_persisted_for_git_addons() {
+        let tmp = tempfile::tempdir().unwrap();
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let repo_id = add_local_git_repo(&engine, "/nonexistent/addon".to_string(), "Addon");
+
+        assert!(engine
+            .set_repo_git_tag_policy(repo_id, Some(GitTagPolicy::Pattern("(".to_string())))
+            .is_err());
+        engine
+            .set_repo_git_tag_policy(repo_id, Some(GitTagPolicy::Pattern("^r\\d+$".to_string())))
+            .unwrap();
+        assert_eq!(
+            engine.db().get_repo(repo_id).unwrap().git_tag_policy,
+            Some(GitTagPolicy::Pattern("^r\\d+$".to_string()))
+        );
+        engine.set_repo_git_tag_policy(repo_id, None).unwrap();
+        assert_eq!(engine.db().get_repo(repo_id).unwrap().git_tag_policy, None);
+
+        assert!(GitTagPolicy::Semver.admits("v1.10.2"));
+        assert!(GitTagPolicy::Semver.admits("2.4"));
+        assert!(!GitTagPolicy::Semver.admits("v2.0-beta"));
+        assert!(!GitTagPolicy::Semver.admits("release"));
+        assert_eq!(
+            GitTagPolicy::from_storage(&GitTagPolicy::Pattern("a:b".to_string()).to_storage()),
+            Some(GitTagPolicy::Pattern("a:b".to_string()))
+        );
+    }
+
     #[test]
     fn new_git_addon_is_finalized_only_after_staged_checks_pass() {
         let tmp = tempfile::tempdir().unwrap();
@@ -6795,6 +9344,53 @@ mod tests {
         assert!(!addons.join("RepositorySlug").exists());
     }
 
+    #[test]
+    fn git_addon_dry_runs_leave_the_worktree_and_profile_untouched() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        let addons = wow.join("Interface").join("AddOns");
+        let remote = tmp.path().join("remote-addon");
+        let remote_url = create_local_git_root_addon_repo(&remote, "ActualAddon");
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let repo_id = add_local_git_repo(&engine, remote_url.clone(), "RepositorySlug");
+        let plan = local_git_plan(repo_id, remote_url, "RepositorySlug");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let opts = InstallOptions::default();
+
+        let install =
+            runtime.block_on(engine.dry_run_plan(&plan, &wow, None, opts, "update", false));
+        assert!(install.blocked.is_none(), "{:?}", install.blocked);
+        assert!(!install.created.is_empty());
+        assert!(!addons.join("ActualAddon").exists());
+        assert!(!wow.join(".wuddle").exists());
+        assert!(engine.db().list_installs(repo_id).unwrap().is_empty());
+        assert_eq!(
+            engine.db().get_repo(repo_id).unwrap().installed_asset_id,
+            None
+        );
+
+        runtime
+            .block_on(engine.apply_one(&plan, &wow, None, opts))
+            .unwrap();
+        commit_local_git_file(&remote, "notes.txt", b"new", "add notes");
+        let before = engine.db().get_repo(repo_id).unwrap();
+        let installs = engine.db().list_installs(repo_id).unwrap().len();
+
+        let reinstall = runtime
+            .block_on(engine.dry_run_reinstall(repo_id, &wow, None, opts))
+            .unwrap();
+        assert!(reinstall.blocked.is_none(), "{:?}", reinstall.blocked);
+        assert!(!addons.join("ActualAddon").join("notes.txt").exists());
+        let after = engine.db().get_repo(repo_id).unwrap();
+        assert_eq!(after.installed_asset_id, before.installed_asset_id);
+        assert_eq!(after.etag, before.etag);
+        assert_eq!(engine.db().list_installs(repo_id).unwrap().len(), installs);
+        assert_eq!(engine.db().list_repos().unwrap().len(), 1);
+    }
+
     #[test]
     fn same_name_fork_replacement_uses_new_remote_and_survives_dedup() {
         let tmp = tempfile::tempdir().unwrap();
@@ -7302,27 +9898,401 @@ mod tests {
     }
 
     #[test]
-    fn conflict_replacement_removes_only_matching_collection_addons() {
+    fn installs_record_toc_metadata_per_addon_folder() {
         let tmp = tempfile::tempdir().unwrap();
         let wow = tmp.path().join("wow");
-        let addons = wow.join("Interface").join("AddOns");
+        let remote_url = create_local_git_addon_repo(
+            &tmp.path().join("remote-pair"),
+            &["PairCore", "PairOptions"],
+        );
         let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
-        let old_repo_id = engine
-            .add_repo(
-                "https://github.com/example/collection",
-                InstallMode::AddonGit,
-                None,
-                Some(vec![
-                    "AddonOne".to_string(),
-                    "AddonTwo".to_string(),
-                    "AddonThree".to_string(),
-                ]),
-            )
+        let repo_id = add_local_git_repo(&engine, remote_url.clone(), "Pair");
+        let plan = local_git_plan(repo_id, remote_url, "Pair");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime
+            .block_on(engine.apply_one(&plan, &wow, None, InstallOptions::default()))
             .unwrap();
 
-        for addon_name in ["AddonOne", "AddonTwo", "AddonThree"] {
-            let addon_dir = addons.join(addon_name);
-            fs::create_dir_all(&addon_dir).unwrap();
+        let stored = engine.db().list_addon_metadata(repo_id).unwrap();
+        assert!(!stored.is_empty());
+        let metadata = engine.addon_metadata(repo_id, &wow).unwrap();
+        let folders = metadata
+            .iter()
+            .map(|entry| entry.folder.as_str())
+            .collect::<HashSet<_>>();
+        assert!(folders.contains("PairCore") && folders.contains("PairOptions"));
+        assert!(metadata
+            .iter()
+            .all(|entry| entry.toc.interface == vec![30300]));
+
+        // A folder without a .toc is remembered, so listings stay off disk
+        // until the installed folders change again.
+        let bare = wow.join("Interface").join("AddOns").join("PairBare");
+        fs::create_dir_all(&bare).unwrap();
+        engine
+            .db()
+            .add_install(repo_id, "Interface/AddOns/PairBare", "addon", None)
+            .unwrap();
+        assert_eq!(engine.addon_metadata(repo_id, &wow).unwrap().len(), 2);
+        let stored = engine.db().list_addon_metadata(repo_id).unwrap();
+        assert!(stored
+            .iter()
+            .any(|(path, toc)| path == "Interface/AddOns/PairBare" && toc.is_none()));
+        fs::write(bare.join("PairBare.toc"), b"## Interface: 30300\n").unwrap();
+        assert_eq!(engine.addon_metadata(repo_id, &wow).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn collections_install_the_clients_variant_and_report_mismatches() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        let addons = wow.join("Interface").join("AddOns");
+        let remote_url = create_local_git_addon_repo(
+            &tmp.path().join("remote-quests"),
+            &["Quests", "Quests-tbc", "Quests-wotlk"],
+        );
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let repo_id = add_local_git_repo(&engine, remote_url.clone(), "Quests");
+        let plan = local_git_plan(repo_id, remote_url, "Quests");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime
+            .block_on(engine.apply_one(
+                &plan,
+                &wow,
+                None,
+                InstallOptions {
+                    client_family: ClientFamily::Wotlk,
+                    ..InstallOptions::default()
+                },
+            ))
+            .unwrap();
+
+        assert!(addons
+            .join("Quests-wotlk")
+            .join("Quests-wotlk.toc")
+            .is_file());
+        assert!(!addons.join("Quests-tbc").exists());
+        assert!(!addons.join("Quests").join("Quests.toc").exists());
+
+        assert!(engine
+            .addon_client_mismatches(repo_id, &wow, ClientFamily::Wotlk)
+            .unwrap()
+            .is_empty());
+        let mismatches = engine
+            .addon_client_mismatches(repo_id, &wow, ClientFamily::Vanilla)
+            .unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].addon_name, "Quests-wotlk");
+        assert_eq!(mismatches[0].addon_client, ClientFamily::Wotlk);
+        assert!(engine
+            .addon_client_mismatches(repo_id, &wow, ClientFamily::Unknown)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn addon_sets_capture_apply_and_round_trip_through_export() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        let pack_url = create_local_git_addon_repo(&tmp.path().join("pack"), &["Alpha", "Beta"]);
+        let solo_url = create_local_git_addon_repo(&tmp.path().join("solo"), &["Gamma"]);
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let pack_id = add_local_git_repo(&engine, pack_url.clone(), "Pack");
+        let solo_id = add_local_git_repo(&engine, solo_url.clone(), "Solo");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        for (id, url, name) in [(pack_id, pack_url, "Pack"), (solo_id, solo_url, "Solo")] {
+            runtime
+                .block_on(engine.apply_one(
+                    &local_git_plan(id, url, name),
+                    &wow,
+                    None,
+                    InstallOptions::default(),
+                ))
+                .unwrap();
+        }
+        fs::create_dir_all(wow.join("Interface/AddOns/Loose")).unwrap();
+        fs::write(
+            wow.join("Interface/AddOns/Loose/Loose.toc"),
+            "## Title: Loose\n",
+        )
+        .unwrap();
+        let realm = wow.join("WTF/Account/ACC/Realm");
+        fs::create_dir_all(realm.join("Hero")).unwrap();
+        fs::create_dir_all(realm.join("Alt")).unwrap();
+        fs::write(realm.join("Alt/AddOns.txt"), "Gamma: disabled\n").unwrap();
+        let characters = character_addons::discover_characters(&wow);
+        let (alt, hero) = (&characters[0], &characters[1]);
+
+        let pvp = engine.capture_addon_set("PvP", &wow, alt).unwrap();
+        assert_eq!(
+            pvp.repos,
+            vec![
+                AddonSetRepo {
+                    repo_id: pack_id,
+                    enabled: true
+                },
+                AddonSetRepo {
+                    repo_id: solo_id,
+                    enabled: false
+                },
+            ]
+        );
+        assert_eq!(
+            pvp.folders,
+            vec![AddonSetFolder {
+                folder: "Loose".to_string(),
+                enabled: true
+            }]
+        );
+
+        let raid = AddonSet {
+            name: "Raid".to_string(),
+            repos: vec![AddonSetRepo {
+                repo_id: pack_id,
+                enabled: false,
+            }],
+            folders: vec![
+                AddonSetFolder {
+                    folder: "Gone".to_string(),
+                    enabled: true,
+                },
+                AddonSetFolder {
+                    folder: "Loose".to_string(),
+                    enabled: false,
+                },
+            ],
+        };
+        engine.save_addon_set(&raid).unwrap();
+        let report = engine.apply_addon_set("raid", &wow, None, false).unwrap();
+        assert_eq!(report.folders, 3);
+        assert_eq!(report.repos_changed, 1);
+        assert!(!engine.db().get_repo(pack_id).unwrap().enabled);
+        assert_eq!(report.characters_updated, 2);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].entry, "Gone");
+        assert_eq!(
+            fs::read_to_string(hero.addons_txt()).unwrap(),
+            "Alpha: disabled\nBeta: disabled\nLoose: disabled\n"
+        );
+
+        let report = engine
+            .apply_addon_set("PvP", &wow, Some(hero), false)
+            .unwrap();
+        assert_eq!(report.repos_changed, 2);
+        assert!(engine.db().get_repo(pack_id).unwrap().enabled);
+        assert!(!engine.db().get_repo(solo_id).unwrap().enabled);
+        assert_eq!(
+            fs::read_to_string(hero.addons_txt()).unwrap(),
+            "Alpha: enabled\nBeta: enabled\nLoose: enabled\nGamma: disabled\n"
+        );
+        assert!(wow.join("Interface/AddOns/Gamma").is_dir());
+
+        // A folder in place of Alt's AddOns.txt fails the write; the repo
+        // switch made before it is undone.
+        fs::remove_file(alt.addons_txt()).unwrap();
+        fs::create_dir_all(alt.addons_txt()).unwrap();
+        assert!(engine.apply_addon_set("raid", &wow, None, false).is_err());
+        assert!(engine.db().get_repo(pack_id).unwrap().enabled);
+        assert_eq!(
+            fs::read_to_string(hero.addons_txt()).unwrap(),
+            "Alpha: enabled\nBeta: enabled\nLoose: enabled\nGamma: disabled\n"
+        );
+
+        let export = engine.export_addon_set("Raid").unwrap();
+        assert!(engine.delete_addon_set("RAID").unwrap());
+        assert!(engine.apply_addon_set("Raid", &wow, None, false).is_err());
+        assert!(engine.import_addon_set(&export).unwrap().is_empty());
+        assert_eq!(engine.addon_set("raid").unwrap(), raid);
+    }
+
+    #[test]
+    fn character_toggle_rewrites_addons_txt_without_moving_folders() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        let url = create_local_git_addon_repo(&tmp.path().join("remote"), &["Alpha", "Beta"]);
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let repo_id = add_local_git_repo(&engine, url.clone(), "Pack");
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap()
+            .block_on(engine.apply_one(
+                &local_git_plan(repo_id, url, "Pack"),
+                &wow,
+                None,
+                InstallOptions::default(),
+            ))
+            .unwrap();
+        let character_dir = wow.join("WTF/Account/ACC/Realm/Hero");
+        fs::create_dir_all(&character_dir).unwrap();
+        fs::write(character_dir.join("AddOns.txt"), "Other: enabled\n").unwrap();
+
+        assert_eq!(
+            engine
+                .set_repo_character_enabled(repo_id, &wow, None, false)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            fs::read_to_string(character_dir.join("AddOns.txt")).unwrap(),
+            "Other: enabled\nAlpha: disabled\nBeta: disabled\n"
+        );
+        assert!(wow.join("Interface/AddOns/Alpha").is_dir());
+        assert!(engine.db().get_repo(repo_id).unwrap().enabled);
+    }
+
+    #[test]
+    fn dependency_report_finds_unmet_dependencies_and_links_repos() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        let lib_url = create_local_git_addon_repo(&tmp.path().join("remote-lib"), &["LibCore"]);
+        let raids_remote = tmp.path().join("remote-raids");
+        fs::create_dir_all(raids_remote.join("RaidsCore")).unwrap();
+        fs::write(
+            raids_remote.join("RaidsCore").join("RaidsCore.toc"),
+            b"## Interface: 30300\n## Dependencies: LibCore, LibMissing, Blizzard_RaidUI\n",
+        )
+        .unwrap();
+        let raids_url = create_local_git_addon_repo(&raids_remote, &[]);
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let lib_id = add_local_git_repo(&engine, lib_url.clone(), "Lib");
+        let raids_id = add_local_git_repo(&engine, raids_url.clone(), "Raids");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        for (id, url, name) in [(lib_id, lib_url, "Lib"), (raids_id, raids_url, "Raids")] {
+            runtime
+                .block_on(engine.apply_one(
+                    &local_git_plan(id, url, name),
+                    &wow,
+                    None,
+                    InstallOptions::default(),
+                ))
+                .unwrap();
+        }
+
+        let known = vec![(
+            "libmissing".to_string(),
+            "https://example.invalid/libmissing".to_string(),
+        )];
+        // Installing linked the repos; the report itself writes nothing.
+        assert_eq!(engine.repo_dependents(lib_id).unwrap(), vec![raids_id]);
+        engine
+            .db()
+            .replace_repo_dependencies(crate::addon_deps::ADDON_DEPENDENCY, &[])
+            .unwrap();
+        let unmet = engine.addon_dependency_report(&wow, &known).unwrap();
+        assert!(engine.repo_dependents(lib_id).unwrap().is_empty());
+        engine.refresh_addon_dependencies(&wow).unwrap();
+        assert_eq!(unmet.len(), 1);
+        assert_eq!(unmet[0].addon, "RaidsCore");
+        assert_eq!(unmet[0].repo_id, Some(raids_id));
+        assert_eq!(unmet[0].problem, DependencyProblem::Missing);
+        assert_eq!(
+            unmet[0].source,
+            Some(DependencySource::Known {
+                url: "https://example.invalid/libmissing".to_string()
+            })
+        );
+        assert_eq!(engine.repo_dependents(lib_id).unwrap(), vec![raids_id]);
+        assert!(engine.repo_dependents(raids_id).unwrap().is_empty());
+
+        engine.db().set_repo_enabled(lib_id, false).unwrap();
+        let unmet = engine.addon_dependency_report(&wow, &[]).unwrap();
+        let disabled = unmet
+            .iter()
+            .find(|entry| entry.dependency == "LibCore")
+            .unwrap();
+        assert_eq!(disabled.problem, DependencyProblem::Disabled);
+        assert_eq!(
+            disabled.source,
+            Some(DependencySource::TrackedRepo { repo_id: lib_id })
+        );
+
+        engine
+            .import_addon_index(
+                r#"{"entries": [{"name": "LibMissing", "url": "https://example.invalid/indexed",
+                                 "folders": ["LibMissing"]}]}"#,
+                "Guild",
+            )
+            .unwrap();
+        let unmet = engine.addon_dependency_report(&wow, &[]).unwrap();
+        let missing = unmet
+            .iter()
+            .find(|entry| entry.dependency == "LibMissing")
+            .unwrap();
+        assert_eq!(
+            missing.source,
+            Some(DependencySource::Known {
+                url: "https://example.invalid/indexed".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn selected_collection_installs_check_out_only_chosen_folders() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        let addons = wow.join("Interface").join("AddOns");
+        let remote_url = create_local_git_addon_repo(
+            &tmp.path().join("remote-suite"),
+            &["SuiteCore", "SuiteExtras"],
+        );
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let repo_id = add_local_git_repo(&engine, remote_url.clone(), "Suite");
+        engine
+            .set_repo_selected_addons(repo_id, Some(vec!["SuiteCore".to_string()]))
+            .unwrap();
+        let plan = local_git_plan(repo_id, remote_url, "Suite");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        runtime
+            .block_on(engine.apply_one(&plan, &wow, None, InstallOptions::default()))
+            .unwrap();
+
+        assert!(addons.join("SuiteCore").join("SuiteCore.toc").is_file());
+        assert!(!addons.join("Suite").join("SuiteExtras").exists());
+        assert!(!addons.join("SuiteExtras").exists());
+        let scan = engine.scan_addon_git_local_changes(&wow).unwrap();
+        assert_eq!(scan.inspected, 1);
+        assert!(scan.modified.is_empty());
+    }
+
+    #[test]
+    fn conflict_replacement_removes_only_matching_collection_addons() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        let addons = wow.join("Interface").join("AddOns");
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let old_repo_id = engine
+            .add_repo(
+                "https://github.com/example/collection",
+                InstallMode::AddonGit,
+                None,
+                Some(vec![
+                    "AddonOne".to_string(),
+                    "AddonTwo".to_string(),
+                    "AddonThree".to_string(),
+                ]),
+            )
+            .unwrap();
+
+        for addon_name in ["AddonOne", "AddonTwo", "AddonThree"] {
+            let addon_dir = addons.join(addon_name);
+            fs::create_dir_all(&addon_dir).unwrap();
             fs::write(
                 addon_dir.join(format!("{addon_name}.toc")),
                 b"## Interface: 30300\n",
@@ -7756,6 +10726,69 @@ mod tests {
         );
     }
 
+    #[test]
+    fn untracked_addon_matched_by_fingerprint_links_to_its_source() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        let addons = wow.join("Interface").join("AddOns");
+        for name in ["pfQuest", "Unknown"] {
+            fs::create_dir_all(addons.join(name)).unwrap();
+            fs::write(
+                addons.join(name).join(format!("{name}.toc")),
+                format!("## Interface: 11200\n## Title: {name}\n"),
+            )
+            .unwrap();
+        }
+        fs::write(addons.join("pfQuest/pfQuest.lua"), b"-- quests\n").unwrap();
+
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        assert_eq!(engine.import_existing_addons(&wow).unwrap(), 2);
+        let print = fingerprint::fingerprint_folder(&addons.join("pfQuest")).unwrap();
+        let catalog = fingerprint::CatalogFile {
+            entries: vec![fingerprint::CatalogEntry {
+                url: "https://github.com/shagu/pfQuest".to_string(),
+                folder: "pfQuest".to_string(),
+                version: Some("7.0.1".to_string()),
+                mode: Some("addon_git".to_string()),
+                toc_sha256: print.toc_sha256.clone(),
+                tree_sha256: Some(print.tree_sha256.clone()),
+            }],
+        };
+        assert_eq!(engine.import_addon_catalog(&catalog).unwrap(), 1);
+
+        let found = engine.identify_untracked_addons(&wow, &[]).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].folder, "pfQuest");
+        assert!(found[0].manual_repo_id.is_some());
+        let best = found[0].matches[0].clone();
+        assert_eq!(best.kind, fingerprint::MatchKind::Exact);
+
+        let repo_id = engine.link_addon_folder(&wow, "pfQuest", &best).unwrap();
+        let repo = engine.db().get_repo(repo_id).unwrap();
+        assert!(matches!(repo.mode, InstallMode::AddonGit));
+        assert_eq!(repo.last_version.as_deref(), Some("7.0.1"));
+        let repos = engine.db().list_repos().unwrap();
+        assert_eq!(repos.len(), 2, "the pfQuest manual repo is dropped");
+        assert!(engine
+            .identify_untracked_addons(&wow, &[])
+            .unwrap()
+            .is_empty());
+        assert!(engine.link_addon_folder(&wow, "pfQuest", &best).is_err());
+
+        engine
+            .import_addon_index(
+                r#"{"entries": [{"name": "Unknown", "url": "https://example.invalid/unknown",
+                                 "folders": ["Unknown"]}]}"#,
+                "Guild",
+            )
+            .unwrap();
+        let found = engine.identify_untracked_addons(&wow, &[]).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].folder, "Unknown");
+        assert_eq!(found[0].matches[0].url, "https://example.invalid/unknown");
+        assert_eq!(found[0].matches[0].kind, fingerprint::MatchKind::Name);
+    }
+
     #[test]
     fn dropped_local_archive_installs_then_becomes_manual() {
         let tmp = tempfile::tempdir().unwrap();
@@ -8300,4 +11333,535 @@ mod tests {
             None
         );
     }
+
+    #[test]
+    fn opening_the_engine_rolls_back_an_interrupted_replacement() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        fs::create_dir_all(&wow).unwrap();
+        fs::write(wow.join("nampower.dll"), b"old").unwrap();
+        fs::write(wow.join("staged.dll"), b"new").unwrap();
+        let db_path = tmp.path().join("wuddle.sqlite");
+
+        {
+            let engine = Engine::open(&db_path).unwrap();
+            let mut transaction = engine
+                .begin_replacement("release replacement", 7301, &wow)
+                .unwrap();
+            transaction
+                .deploy(&wow.join("staged.dll"), &wow.join("nampower.dll"))
+                .unwrap();
+            // The process dies before SQLite commits: nothing rolls back.
+            std::mem::forget(transaction);
+        }
+        assert_eq!(fs::read(wow.join("nampower.dll")).unwrap(), b"new");
+
+        // The forgotten transaction still holds its lock in this process, so
+        // release it the way process exit would.
+        let journal_dir = wow.join(".wuddle").join("journal");
+        for entry in fs::read_dir(&journal_dir).unwrap().flatten() {
+            if entry.path().extension().is_some_and(|ext| ext == "lock") {
+                fs::remove_file(entry.path()).unwrap();
+            }
+        }
+
+        let engine = Engine::open(&db_path).unwrap();
+        let recovered = super::take_recovered_transactions()
+            .into_iter()
+            .filter(|recovered| recovered.repo_id == 7301)
+            .collect::<Vec<_>>();
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].action, RecoveryAction::RolledBack);
+        assert_eq!(fs::read(wow.join("nampower.dll")).unwrap(), b"old");
+        assert!(engine.db().list_install_journals().unwrap().is_empty());
+        assert_eq!(fs::read_dir(&journal_dir).unwrap().count(), 0);
+    }
+
+    fn local_dll_repo(url: &str, name: &str) -> Repo {
+        Repo {
+            id: 0,
+            url: url.to_string(),
+            forge: "direct".to_string(),
+            host: "local".to_string(),
+            owner: "tests".to_string(),
+            name: name.to_string(),
+            mode: InstallMode::Dll,
+            enabled: true,
+            git_branch: None,
+            asset_regex: None,
+            last_version: None,
+            etag: None,
+            installed_asset_id: None,
+            installed_asset_name: None,
+            installed_asset_size: None,
+            installed_asset_url: None,
+            installed_at_unix: None,
+            published_at_unix: None,
+            merge_installs: false,
+            pinned_version: None,
+            selected_addons_json: None,
+            git_tag_policy: None,
+        }
+    }
+
+    fn local_dll_plan(engine: &Engine, name: &str, asset: &Path) -> UpdatePlan {
+        let name = name.to_string();
+        let url = url::Url::from_file_path(asset).unwrap().to_string();
+        let repo_id = engine.db().add_repo(&local_dll_repo(&url, &name)).unwrap();
+        let file_name = asset.file_name().unwrap().to_string_lossy().into_owned();
+        UpdatePlan {
+            repo_id,
+            forge: "direct".to_string(),
+            host: "local".to_string(),
+            owner: "tests".to_string(),
+            name,
+            url: url.clone(),
+            mode: InstallMode::Dll,
+            asset_id: file_name.clone(),
+            asset_name: file_name,
+            asset_url: url,
+            asset_size: fs::metadata(asset).ok().map(|metadata| metadata.len()),
+            ..plan_for_asset("unused.dll", None)
+        }
+    }
+
+    #[test]
+    fn release_batch_deploys_every_repo_or_none() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        let assets = tmp.path().join("assets");
+        fs::create_dir_all(&wow).unwrap();
+        fs::create_dir_all(&assets).unwrap();
+        fs::write(assets.join("first.dll"), b"MZfirst").unwrap();
+        fs::write(assets.join("second.dll"), b"MZsecond").unwrap();
+        fs::write(wow.join("dlls.txt"), "other.dll\n").unwrap();
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let first = local_dll_plan(&engine, "first", &assets.join("first.dll"));
+        let second = local_dll_plan(&engine, "second", &assets.join("second.dll"));
+        let mut missing = local_dll_plan(&engine, "missing", &assets.join("missing.dll"));
+        missing.asset_size = Some(1);
+        let mut failing = vec![first.clone(), second.clone(), missing];
+        assert!(runtime
+            .block_on(engine.apply_release_batch(
+                &mut failing,
+                &wow,
+                None,
+                InstallOptions::default()
+            ))
+            .is_err());
+        assert!(!wow.join("first.dll").exists());
+        assert!(!wow.join("second.dll").exists());
+        assert_eq!(
+            fs::read_to_string(wow.join("dlls.txt")).unwrap(),
+            "other.dll\n"
+        );
+        assert!(engine.db().list_installs(first.repo_id).unwrap().is_empty());
+        assert!(failing.iter().all(|plan| !plan.applied));
+
+        let mut plans = vec![first.clone(), second.clone()];
+        runtime
+            .block_on(engine.apply_release_batch(&mut plans, &wow, None, InstallOptions::default()))
+            .unwrap();
+        assert!(plans.iter().all(|plan| plan.applied));
+        assert_eq!(fs::read(wow.join("first.dll")).unwrap(), b"MZfirst");
+        assert_eq!(fs::read(wow.join("second.dll")).unwrap(), b"MZsecond");
+        let dlls_txt = fs::read_to_string(wow.join("dlls.txt")).unwrap();
+        for name in ["other.dll", "first.dll", "second.dll"] {
+            assert!(dlls_txt.contains(name), "{name} missing from {dlls_txt}");
+        }
+        for plan in [&first, &second] {
+            assert_eq!(engine.db().list_installs(plan.repo_id).unwrap().len(), 1);
+        }
+        assert!(engine.db().list_install_journals().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn atomic_updates_report_unplannable_repos_and_skip_disabled_ones() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        fs::create_dir_all(&wow).unwrap();
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let broken_id = engine
+            .db()
+            .add_repo(&Repo {
+                url: "not a repository url".to_string(),
+                pinned_version: None,
+                ..repo_with_pin("v1.0.0")
+            })
+            .unwrap();
+        engine
+            .db()
+            .add_repo(&Repo {
+                url: "https://github.com/owner/disabled".to_string(),
+                name: "disabled".to_string(),
+                enabled: false,
+                pinned_version: None,
+                ..repo_with_pin("v1.0.0")
+            })
+            .unwrap();
+
+        let plans = engine
+            .apply_updates_atomic(&wow, None, InstallOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(plans.len(), 1);
+        assert_eq!(plans[0].repo_id, broken_id);
+        assert!(plans[0].error.is_some());
+        assert!(!plans[0].applied);
+    }
+
+    #[tokio::test]
+    async fn atomic_updates_leave_git_addons_alone_when_the_batch_rolls_back() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        fs::create_dir_all(&wow).unwrap();
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        for name in ["a", "b"] {
+            let archive = tmp.path().join(format!("{name}.zip"));
+            let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+            zip.start_file("shared.dll", zip::write::SimpleFileOptions::default())
+                .unwrap();
+            std::io::Write::write_all(&mut zip, name.as_bytes()).unwrap();
+            zip.finish().unwrap();
+            let url = url::Url::from_file_path(archive.canonicalize().unwrap())
+                .unwrap()
+                .to_string();
+            engine.db().add_repo(&local_dll_repo(&url, name)).unwrap();
+        }
+        let remote_url =
+            create_local_git_root_addon_repo(&tmp.path().join("remote-addon"), "GitAddon");
+        let git_id = add_local_git_repo(&engine, remote_url, "GitAddon");
+
+        let plans = engine
+            .apply_updates_atomic(&wow, None, InstallOptions::default())
+            .await
+            .unwrap();
+        assert!(!wow.join("shared.dll").exists());
+        assert!(!wow
+            .join("Interface")
+            .join("AddOns")
+            .join("GitAddon")
+            .exists());
+        assert!(engine.db().list_installs(git_id).unwrap().is_empty());
+        let git_plan = plans.iter().find(|plan| plan.repo_id == git_id).unwrap();
+        assert!(!git_plan.applied);
+        assert!(git_plan
+            .error
+            .as_deref()
+            .is_some_and(|error| error.contains("rolled back")));
+        assert!(plans
+            .iter()
+            .all(|plan| !plan.applied && plan.error.is_some()));
+    }
+
+    #[test]
+    fn release_batch_refuses_repos_that_install_the_same_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        fs::create_dir_all(tmp.path().join("a")).unwrap();
+        fs::create_dir_all(tmp.path().join("b")).unwrap();
+        fs::write(tmp.path().join("a").join("shared.dll"), b"MZa").unwrap();
+        fs::write(tmp.path().join("b").join("shared.dll"), b"MZb").unwrap();
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let mut plans = vec![
+            local_dll_plan(&engine, "a", &tmp.path().join("a").join("shared.dll")),
+            local_dll_plan(&engine, "b", &tmp.path().join("b").join("shared.dll")),
+        ];
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let error = runtime
+            .block_on(engine.apply_release_batch(&mut plans, &wow, None, InstallOptions::default()))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("both install"), "{error}");
+        assert!(!wow.join("shared.dll").exists());
+    }
+
+    #[test]
+    fn dry_runs_report_changes_without_applying_them() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        fs::create_dir_all(&wow).unwrap();
+        fs::write(tmp.path().join("first.dll"), b"MZfirst").unwrap();
+        fs::write(wow.join("dlls.txt"), "other.dll\n").unwrap();
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let plan = local_dll_plan(&engine, "first", &tmp.path().join("first.dll"));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let opts = InstallOptions::default();
+
+        let install =
+            runtime.block_on(engine.dry_run_plan(&plan, &wow, None, opts, "update", false));
+        assert_eq!(install.created, vec!["first.dll"]);
+        assert_eq!(install.dlls_txt_added, vec!["first.dll"]);
+        assert!(install.blocked.is_none());
+        assert!(!wow.join("first.dll").exists());
+        assert_eq!(
+            fs::read_to_string(wow.join("dlls.txt")).unwrap(),
+            "other.dll\n"
+        );
+        assert!(engine.db().list_installs(plan.repo_id).unwrap().is_empty());
+
+        runtime
+            .block_on(engine.apply_one(&plan, &wow, None, opts))
+            .unwrap();
+        let removal = engine
+            .dry_run_remove(plan.repo_id, Some(&wow), true)
+            .unwrap();
+        assert_eq!(removal.deleted, vec!["first.dll"]);
+        assert_eq!(removal.dlls_txt_removed, vec!["first.dll"]);
+        assert!(wow.join("first.dll").exists());
+        assert_eq!(engine.db().list_installs(plan.repo_id).unwrap().len(), 1);
+
+        let records_only = engine
+            .dry_run_remove(plan.repo_id, Some(&wow), false)
+            .unwrap();
+        assert!(records_only.deleted.is_empty());
+        assert!(!records_only.database.is_empty());
+    }
+
+    #[test]
+    fn reinstall_dry_run_records_nothing_in_the_profile() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        fs::create_dir_all(&wow).unwrap();
+        let archive = tmp.path().join("Mod.zip");
+        let mut zip = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        zip.start_file("Mod.dll", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        std::io::Write::write_all(&mut zip, b"MZmod").unwrap();
+        zip.finish().unwrap();
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let url = url::Url::from_file_path(archive.canonicalize().unwrap())
+            .unwrap()
+            .to_string();
+        let repo_id = engine
+            .db()
+            .add_repo(&Repo {
+                etag: Some("\"v1\"".to_string()),
+                ..local_dll_repo(&url, "Mod")
+            })
+            .unwrap();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let changes = runtime
+            .block_on(engine.dry_run_reinstall(repo_id, &wow, None, InstallOptions::default()))
+            .unwrap();
+        assert!(changes.blocked.is_none(), "{:?}", changes.blocked);
+        assert_eq!(changes.created, vec!["Mod.dll"]);
+        assert!(!wow.join("Mod.dll").exists());
+        assert!(!wow.join(".wuddle").exists());
+        let repo = engine.db().get_repo(repo_id).unwrap();
+        assert_eq!(repo.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(repo.installed_asset_id, None);
+        assert_eq!(engine.db().list_repos().unwrap().len(), 1);
+        assert!(engine.db().list_installs(repo_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn history_rolls_back_to_a_cached_release_without_the_network() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        fs::create_dir_all(&wow).unwrap();
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let repo_id = engine
+            .db()
+            .add_repo(&Repo {
+                id: 0,
+                url: "https://github.com/owner/repo".to_string(),
+                forge: "github".to_string(),
+                host: "github.com".to_string(),
+                owner: "owner".to_string(),
+                name: "repo".to_string(),
+                mode: InstallMode::Dll,
+                enabled: true,
+                git_branch: None,
+                asset_regex: None,
+                last_version: None,
+                etag: None,
+                installed_asset_id: None,
+                installed_asset_name: None,
+                installed_asset_size: None,
+                installed_asset_url: None,
+                installed_at_unix: None,
+                published_at_unix: None,
+                merge_installs: false,
+                pinned_version: None,
+                selected_addons_json: None,
+                git_tag_policy: None,
+            })
+            .unwrap();
+        // Both versions are already cached, so nothing below downloads.
+        let cached_plan = |version: &str, current: Option<&str>, bytes: &[u8]| {
+            let plan = UpdatePlan {
+                repo_id,
+                mode: InstallMode::Dll,
+                current: current.map(str::to_string),
+                install_version: version.to_string(),
+                latest: version.to_string(),
+                asset_id: format!("asset-{version}"),
+                asset_url: format!(
+                    "https://github.com/owner/repo/releases/download/{version}/tool.dll"
+                ),
+                ..plan_for_asset("tool.dll", None)
+            };
+            let cache = Engine::release_cache_dir(&plan, Some(&wow)).unwrap();
+            fs::write(cache.join("tool.dll"), bytes).unwrap();
+            plan
+        };
+        let first = cached_plan("v1", None, b"MZfirst");
+        let second = cached_plan("v2", Some("v1"), b"MZsecond");
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let opts = InstallOptions {
+            cache_keep_versions: 3,
+            ..InstallOptions::default()
+        };
+        runtime
+            .block_on(engine.apply_one(&first, &wow, None, opts))
+            .unwrap();
+        runtime
+            .block_on(engine.apply_one(&second, &wow, None, opts))
+            .unwrap();
+        assert_eq!(fs::read(wow.join("tool.dll")).unwrap(), b"MZsecond");
+
+        let history = engine.install_history(repo_id).unwrap();
+        assert_eq!(
+            history.iter().map(|entry| entry.action).collect::<Vec<_>>(),
+            vec![HistoryAction::Update, HistoryAction::Install]
+        );
+        let installed = &history[1];
+        assert_eq!(installed.to_version.as_deref(), Some("v1"));
+        assert_eq!(
+            installed.asset_sha256.as_deref(),
+            Some(crate::util::sha256_hex("MZfirst").as_str())
+        );
+
+        let report = runtime
+            .block_on(engine.rollback_to_history(installed.id, &wow, None, opts))
+            .unwrap();
+        assert_eq!(report.version, "v1");
+        assert!(report.settings.is_empty());
+        assert_eq!(fs::read(wow.join("tool.dll")).unwrap(), b"MZfirst");
+        let repo = engine.db().get_repo(repo_id).unwrap();
+        assert_eq!(repo.pinned_version.as_deref(), Some("v1"));
+        assert_eq!(repo.last_version.as_deref(), Some("v1"));
+        let latest = &engine.install_history(repo_id).unwrap()[0];
+        assert_eq!(latest.action, HistoryAction::Rollback);
+        assert_eq!(latest.from_version.as_deref(), Some("v2"));
+
+        engine.remove_repo(repo_id, Some(&wow), true).unwrap();
+        let history = engine.install_history(repo_id).unwrap();
+        assert_eq!(history[0].action, HistoryAction::Remove);
+        assert!(runtime
+            .block_on(engine.rollback_to_history(installed.id, &wow, None, opts))
+            .is_err());
+    }
+
+    #[test]
+    fn rollback_restores_the_settings_saved_when_the_version_was_replaced() {
+        let tmp = tempfile::tempdir().unwrap();
+        let wow = tmp.path().join("wow");
+        fs::create_dir_all(&wow).unwrap();
+        let engine = Engine::open(&tmp.path().join("wuddle.sqlite")).unwrap();
+        let repo_id = engine
+            .db()
+            .add_repo(&Repo {
+                forge: "github".to_string(),
+                host: "github.com".to_string(),
+                owner: "owner".to_string(),
+                mode: InstallMode::Addon,
+                ..local_dll_repo("https://github.com/owner/repo", "repo")
+            })
+            .unwrap();
+        let cached_plan = |version: &str, current: Option<&str>| {
+            let plan = UpdatePlan {
+                repo_id,
+                mode: InstallMode::Addon,
+                current: current.map(str::to_string),
+                install_version: version.to_string(),
+                latest: version.to_string(),
+                asset_id: format!("asset-{version}"),
+                asset_url: format!(
+                    "https://github.com/owner/repo/releases/download/{version}/Keeper.zip"
+                ),
+                ..plan_for_asset("Keeper.zip", None)
+            };
+            let cache = Engine::release_cache_dir(&plan, Some(&wow)).unwrap();
+            let mut zip = zip::ZipWriter::new(fs::File::create(cache.join("Keeper.zip")).unwrap());
+            zip.start_file(
+                "Keeper/Keeper.toc",
+                zip::write::SimpleFileOptions::default(),
+            )
+            .unwrap();
+            std::io::Write::write_all(
+                &mut zip,
+                b"## Interface: 11200\n## SavedVariables: KeeperDB\n",
+            )
+            .unwrap();
+            zip.start_file(
+                "Keeper/Keeper.lua",
+                zip::write::SimpleFileOptions::default(),
+            )
+            .unwrap();
+            std::io::Write::write_all(&mut zip, version.as_bytes()).unwrap();
+            zip.finish().unwrap();
+            plan
+        };
+        let first = cached_plan("v1", None);
+        let second = cached_plan("v2", Some("v1"));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        let opts = InstallOptions {
+            cache_keep_versions: 3,
+            ..InstallOptions::default()
+        };
+        let saved = wow.join("WTF/Account/ACC/SavedVariables");
+        fs::create_dir_all(&saved).unwrap();
+        runtime
+            .block_on(engine.apply_one(&first, &wow, None, opts))
+            .unwrap();
+        fs::write(saved.join("Keeper.lua"), "KeeperDB = 1\n").unwrap();
+        runtime
+            .block_on(engine.apply_one(&second, &wow, None, opts))
+            .unwrap();
+        fs::write(saved.join("Keeper.lua"), "KeeperDB = 2\n").unwrap();
+
+        let history = engine.install_history(repo_id).unwrap();
+        assert!(history[0].settings_snapshot.is_some());
+        assert_eq!(history[1].settings_snapshot, None);
+        let report = runtime
+            .block_on(engine.rollback_to_history(history[1].id, &wow, None, opts))
+            .unwrap();
+        assert_eq!(report.version, "v1");
+        assert_eq!(report.settings.len(), 1);
+        assert_eq!(
+            fs::read_to_string(wow.join("Interface/AddOns/Keeper/Keeper.lua")).unwrap(),
+            "v1"
+        );
+        assert_eq!(
+            fs::read_to_string(saved.join("Keeper.lua")).unwrap(),
+            "KeeperDB = 1\n"
+        );
+        let latest = &engine.install_history(repo_id).unwrap()[0];
+        assert_eq!(latest.action, HistoryAction::Rollback);
+        assert!(latest.settings_snapshot.is_some());
+    }
 }
@@ -1,15 +1,92 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
-use wuddle_engine::{Engine, InstallMode, InstallOptions};
+use anyhow::{Context, Result};
+use clap::{Parser, Subcommand, ValueEnum};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use wuddle_engine::character_addons::{self, WtfCharacter};
+use wuddle_engine::mpq::{
+    MpqDestination, MpqError, MpqInspection, MpqInstallSelection, MpqInstalledFile,
+    MpqProtectionEntry, MpqTargetPreview, MpqTargetStatus,
+};
+use wuddle_engine::presets;
+use wuddle_engine::profiles::{GuiSettings, ProfileSettings};
+use wuddle_engine::saved_variables::{
+    self, DiffLineKind, FileDiff, SavedVariablesSnapshot, SnapshotRetention, SnapshotTrigger,
+};
+use wuddle_engine::{
+    AddonIdentification, AddonSet, AddonSetExport, AddonSetFolder, AddonSetRepo, AddonSetReport,
+    CatalogFile, ChangeSet, ClientFamily, ClientMismatch, CustomForgeHost, Engine, ForgeKind,
+    GitTagPolicy, IndexEntry, IndexKind, IndexQuery, IndexSource, InstallHistoryEntry, InstallMode,
+    InstallOptions, Repo, UnmetDependency, UpdatePlan,
+};
+
+/// Exit status used when `check` finds at least one pending update or repair
+/// and no errors. Matches the `check-update` convention of common package
+/// managers so cron jobs can tell "clean" from "needs attention".
+const EXIT_UPDATES_AVAILABLE: u8 = 100;
+/// Exit status used when at least one repository reported an error.
+const EXIT_ERRORS: u8 = 1;
+/// Exit status used when an MPQ operation was refused by a conflict or
+/// protection check rather than failing outright, or when a dry run found an
+/// operation that would stop.
+const EXIT_BLOCKED: u8 = 3;
 
 #[derive(Debug, Parser)]
-#[command(name = "wuddle", version, about = "WoW addon/dll updater")]
+#[command(
+    name = "wuddle",
+    version,
+    about = "WoW addon/dll updater",
+    after_help = "Exit status: 0 = clean, 1 = errors, 3 = blocked by an MPQ conflict or protection (or a dry run that would stop), 100 = updates available (check only).\n\nAccess tokens: WUDDLE_GITHUB_TOKEN (or GITHUB_TOKEN) for GitHub, and WUDDLE_FORGE_TOKENS=host=token[,host=token...] for GitLab and Gitea/Forgejo hosts."
+)]
 struct Cli {
+    /// Output format: human-readable text, one JSON document, or one JSON
+    /// object per line.
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    format: OutputFormat,
+    /// Desktop app profile (id or name) whose database and WoW directory to
+    /// use. Reads the app's settings, including portable mode.
+    #[arg(long, global = true, conflicts_with = "db")]
+    profile: Option<String>,
+    /// The desktop app's data directory, for `--profile` and `profiles`.
+    /// Needed when this binary is not installed beside the app in portable
+    /// mode or on Windows, where the app keeps its data next to itself.
+    #[arg(long, global = true, conflicts_with = "db")]
+    data_dir: Option<PathBuf>,
+    /// Open this database file instead of the default one.
+    #[arg(long, global = true)]
+    db: Option<PathBuf>,
+    /// Private key for SSH Git remotes when ssh-agent has no accepted
+    /// identity. Defaults to the profile's setting, then `WUDDLE_SSH_KEY`.
+    #[arg(long, global = true)]
+    ssh_key: Option<PathBuf>,
     #[command(subcommand)]
     cmd: Cmd,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+    Ndjson,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ClientArg {
+    Vanilla,
+    Tbc,
+    Wotlk,
+}
+
+impl From<ClientArg> for ClientFamily {
+    fn from(client: ClientArg) -> Self {
+        match client {
+            ClientArg::Vanilla => ClientFamily::Vanilla,
+            ClientArg::Tbc => ClientFamily::Tbc,
+            ClientArg::Wotlk => ClientFamily::Wotlk,
+        }
+    }
+}
+
 #[derive(Debug, Subcommand)]
 enum Cmd {
     Add {
@@ -28,14 +105,18 @@ enum Cmd {
         remove_local_files: bool,
         #[arg(long)]
         wow_dir: Option<PathBuf>,
+        /// Print what would be deleted and restored without removing anything.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
     },
     Check {
         #[arg(long)]
         wow_dir: Option<PathBuf>,
     },
     Update {
+        /// Defaults to the selected profile's WoW directory.
         #[arg(long)]
-        wow_dir: PathBuf,
+        wow_dir: Option<PathBuf>,
         /// Only used for Raw mode (or Auto fallback when asset isn't archive/dll)
         #[arg(long)]
         raw_dest: Option<PathBuf>,
@@ -43,20 +124,766 @@ enum Cmd {
         symlink_targets: bool,
         #[arg(long, default_value_t = false)]
         set_xattr_comment: bool,
+        /// The game client. Collections then install only the addon variants
+        /// built for it, and addons written for another client are reported.
+        #[arg(long, value_enum)]
+        client: Option<ClientArg>,
+        /// Install the release updates as one unit: if any fails, none is
+        /// applied. Git addons still update one by one.
+        #[arg(long, default_value_t = false)]
+        atomic: bool,
+        /// Print what each update would change without applying it.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Reinstall a repo even if it is up to date.
+    Reinstall {
+        id: i64,
+        /// Defaults to the selected profile's WoW directory.
+        #[arg(long)]
+        wow_dir: Option<PathBuf>,
+        /// Only used for Raw mode (or Auto fallback when asset isn't archive/dll)
+        #[arg(long)]
+        raw_dest: Option<PathBuf>,
+        #[arg(long, value_enum)]
+        client: Option<ClientArg>,
+        /// Print what the reinstall would change without applying it.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// Manage MPQ patches in a game's Data directory.
+    Mpq {
+        /// Defaults to the selected profile's WoW directory.
+        #[arg(long, global = true)]
+        wow_dir: Option<PathBuf>,
+        #[command(subcommand)]
+        cmd: MpqCmd,
+    },
+    /// List the desktop app's profiles and their databases.
+    Profiles,
+    /// List an addon_git repo's remote tags, newest first.
+    Tags {
+        id: i64,
+    },
+    /// Update an addon_git repo to its newest tag instead of its branch head.
+    /// Pinning a version still takes precedence.
+    FollowTags {
+        id: i64,
+        /// Follow tags matching this regex instead of semver tags.
+        #[arg(long, conflicts_with = "off")]
+        pattern: Option<String>,
+        /// Follow the branch head again.
+        #[arg(long, default_value_t = false)]
+        off: bool,
+    },
+    /// List every install, update, reinstall, rollback and removal of a
+    /// repo, newest first, with the SavedVariables snapshot taken before
+    /// each. Entries outlive the repo.
+    History {
+        id: i64,
+    },
+    /// Redeploy the version a history entry recorded and restore the addon
+    /// settings snapshotted when it was replaced. The repo stays pinned to
+    /// that version. Cached release assets and commits already in the
+    /// addon's Git worktree are used without the network.
+    Rollback {
+        /// Entry id, as listed by `history`.
+        entry: i64,
+        /// Defaults to the selected profile's WoW directory.
+        #[arg(long)]
+        wow_dir: Option<PathBuf>,
+        /// Only used for Raw mode (or Auto fallback when asset isn't archive/dll)
+        #[arg(long)]
+        raw_dest: Option<PathBuf>,
+    },
+    /// Print the release notes, or for addon_git repos the commit log,
+    /// between a repo's installed version and the one an update installs.
+    WhatsNew {
+        id: i64,
+        /// Defaults to the selected profile's WoW directory. Git addons read
+        /// their installed commit from it.
+        #[arg(long)]
+        wow_dir: Option<PathBuf>,
+    },
+    /// Manage self-hosted GitLab and Gitea/Forgejo instances.
+    ForgeHosts {
+        #[command(subcommand)]
+        cmd: ForgeHostsCmd,
+    },
+    /// Switch between named addon sets through the characters' AddOns.txt.
+    Sets {
+        /// Defaults to the selected profile's WoW directory.
+        #[arg(long, global = true)]
+        wow_dir: Option<PathBuf>,
+        #[command(subcommand)]
+        cmd: SetsCmd,
+    },
+    /// Recognise untracked addon folders by their files and link them to
+    /// the repos they came from.
+    Catalog {
+        /// Defaults to the selected profile's WoW directory.
+        #[arg(long, global = true)]
+        wow_dir: Option<PathBuf>,
+        #[command(subcommand)]
+        cmd: CatalogCmd,
+    },
+    /// Search the local addon catalog and manage the catalogs it is built
+    /// from. The addon folders they list also feed `catalog identify` and
+    /// the dependency check.
+    Index {
+        #[command(subcommand)]
+        cmd: IndexCmd,
+    },
+    /// Show the Quick Add preset catalog and sign catalogs for publishing.
+    Presets {
+        #[command(subcommand)]
+        cmd: PresetsCmd,
+    },
+    /// Snapshot, compare and restore the addon settings kept in WTF.
+    SavedVars {
+        /// Defaults to the selected profile's WoW directory.
+        #[arg(long, global = true)]
+        wow_dir: Option<PathBuf>,
+        #[command(subcommand)]
+        cmd: SavedVarsCmd,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SavedVarsCmd {
+    /// List snapshots, newest first.
+    List,
+    /// Copy every SavedVariables file into a new snapshot.
+    Snapshot {
+        /// Record the snapshot as scheduled and skip it when nothing changed
+        /// since the newest one, for use from cron or Task Scheduler.
+        #[arg(long)]
+        if_changed: bool,
+        /// Prune afterwards, keeping this many snapshots.
+        #[arg(long)]
+        keep: Option<usize>,
+        /// Prune afterwards, removing snapshots older than this.
+        #[arg(long)]
+        max_age_days: Option<u32>,
+    },
+    /// Show how an addon's SavedVariables changed since a snapshot.
+    Diff {
+        addon: String,
+        /// Older snapshot id.
+        from: String,
+        /// Newer snapshot id; defaults to the current files.
+        #[arg(long)]
+        to: Option<String>,
+    },
+    /// Put one addon's SavedVariables back from a snapshot. The game must be
+    /// closed.
+    Restore { addon: String, snapshot: String },
+    /// Remove old snapshots. The newest one is always kept.
+    Prune {
+        #[arg(long, default_value_t = 20)]
+        keep: usize,
+        #[arg(long)]
+        max_age_days: Option<u32>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum CatalogCmd {
+    /// List untracked or manually imported addon folders that match a known
+    /// source, best match first.
+    Identify,
+    /// Track an untracked addon folder as coming from one of its matches.
+    Link {
+        folder: String,
+        /// Link to this matched URL instead of the best match.
+        #[arg(long)]
+        url: Option<String>,
+    },
+    /// Add the entries of a catalog file written by `export`.
+    Import { file: PathBuf },
+    /// Print the fingerprints of the tracked addons as a catalog file.
+    Export,
+}
+
+#[derive(Debug, Subcommand)]
+enum IndexCmd {
+    /// Search every imported catalog, best match first. No network access.
+    Search {
+        /// Words that must all appear in an entry; omit to list everything.
+        query: Vec<String>,
+        #[arg(long, value_enum)]
+        client: Option<ClientArg>,
+        #[arg(long)]
+        category: Option<String>,
+        /// addon|mod
+        #[arg(long)]
+        kind: Option<String>,
+    },
+    /// Import a catalog from a JSON file or an HTTPS URL. Importing a catalog
+    /// of the same name again replaces its entries.
+    Import {
+        source: String,
+        /// Name for a catalog whose file does not name it.
+        #[arg(long)]
+        name: Option<String>,
+    },
+    /// Fetch a catalog again from its URL, or every catalog added by URL.
+    Refresh {
+        id: Option<i64>,
+    },
+    /// List imported catalogs.
+    List,
+    Remove {
+        id: i64,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum PresetsCmd {
+    /// List the presets in use and the catalog they come from.
+    List,
+    /// Fetch the published catalog and keep it when its signature verifies
+    /// and it is newer than the one in use. Keys listed in
+    /// WUDDLE_PRESET_KEYS are trusted alongside the built-in ones.
+    Update,
+    /// Write a new PKCS#8 signing key and print its public key.
+    Keygen { out: PathBuf },
+    /// Sign a catalog file, writing the signature next to it as `<file>.sig`.
+    Sign {
+        file: PathBuf,
+        /// PKCS#8 key written by `keygen`.
+        #[arg(long)]
+        key: PathBuf,
+    },
+    /// Check a catalog file and its `<file>.sig` against a public key.
+    Verify {
+        file: PathBuf,
+        /// Base64 public key printed by `keygen`.
+        #[arg(long)]
+        public_key: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SetsCmd {
+    /// List saved sets.
+    List,
+    /// Save the addon states one character has in game as a set.
+    Capture {
+        name: String,
+        /// `Name`, `Realm/Name` or `Account/Realm/Name`.
+        #[arg(long)]
+        character: String,
+    },
+    /// Save a set from explicit entries, replacing a set of the same name.
+    Save {
+        name: String,
+        /// Tracked repo id to enable (repeatable).
+        #[arg(long)]
+        enable_repo: Vec<i64>,
+        /// Tracked repo id to disable (repeatable).
+        #[arg(long)]
+        disable_repo: Vec<i64>,
+        /// Untracked addon folder to enable (repeatable).
+        #[arg(long)]
+        enable: Vec<String>,
+        /// Untracked addon folder to disable (repeatable).
+        #[arg(long)]
+        disable: Vec<String>,
+    },
+    /// Apply a set to every character, or only to `--character`.
+    Apply {
+        name: String,
+        /// `Name`, `Realm/Name` or `Account/Realm/Name`.
+        #[arg(long)]
+        character: Option<String>,
+        /// The game client. Vanilla switches DLL mods through dlls.txt.
+        #[arg(long, value_enum)]
+        client: Option<ClientArg>,
+    },
+    Remove {
+        name: String,
+    },
+    /// Print a set as JSON with repos identified by URL.
+    Export {
+        name: String,
+    },
+    /// Save a set from a file written by `export`.
+    Import {
+        file: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ForgeHostsCmd {
+    /// List registered hosts.
+    List,
+    /// Register a host, e.g. `git.example.org` or `code.example.org:8443`.
+    Add {
+        host: String,
+        /// gitlab|gitea|forgejo
+        #[arg(long)]
+        kind: String,
+        /// API root when the instance is served below a sub-path, e.g.
+        /// `/gitlab/api/v4`. Defaults to the forge's standard path.
+        #[arg(long)]
+        api_path: Option<String>,
+    },
+    /// Forget a host. Its repositories are checked as plain Git again.
+    Remove { host: String },
+}
+
+#[derive(Debug, Subcommand)]
+enum MpqCmd {
+    /// Show the MPQ files inside a local .mpq, .zip, or .7z source.
+    Inspect { source: PathBuf },
+    /// Install MPQ files from a local source as one tracked package.
+    Install {
+        source: PathBuf,
+        /// Install only this source key or file name (repeatable). Defaults
+        /// to every MPQ in the source.
+        #[arg(long = "only")]
+        only: Vec<String>,
+        /// `data` or a client locale such as `enUS`. Defaults to the
+        /// suggested destination of each file.
+        #[arg(long)]
+        destination: Option<String>,
+        /// Version label recorded for the installed files.
+        #[arg(long)]
+        version: Option<String>,
+        /// Back up and replace existing unprotected MPQs with the same name.
+        #[arg(long, default_value_t = false)]
+        replace_unprotected: bool,
+        #[arg(long, default_value_t = false)]
+        set_xattr_comment: bool,
+        /// Print what the install would change without installing.
+        #[arg(long, default_value_t = false)]
+        dry_run: bool,
+    },
+    /// List tracked MPQ packages, or untracked archives with `--untracked`.
+    List {
+        /// Only list files of this package id.
+        #[arg(long, conflicts_with = "untracked")]
+        package: Option<i64>,
+        #[arg(long, default_value_t = false)]
+        untracked: bool,
+    },
+    /// Enable a package, or one of its files with `--path`.
+    Enable {
+        id: i64,
+        #[arg(long)]
+        path: Option<String>,
+    },
+    /// Disable a package, or one of its files with `--path`.
+    Disable {
+        id: i64,
+        #[arg(long)]
+        path: Option<String>,
+    },
+    /// Protect an MPQ from replacement (or lift protection with `--off`).
+    Protect {
+        /// Manifest path such as `Data/patch-Z.MPQ`.
+        path: String,
+        /// Package id when the MPQ is tracked by Wuddle.
+        #[arg(long)]
+        package: Option<i64>,
+        #[arg(long, default_value_t = false)]
+        off: bool,
+    },
+    /// Unlock an MPQ for renaming (or lock it again with `--lock`).
+    Unlock {
+        path: String,
+        #[arg(long)]
+        package: Option<i64>,
+        #[arg(long, default_value_t = false)]
+        lock: bool,
+    },
+    /// Rename a tracked MPQ's display name and, optionally, its file.
+    Rename {
+        id: i64,
+        path: String,
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        file_name: Option<String>,
+        #[arg(long, default_value_t = false)]
+        set_xattr_comment: bool,
+    },
+    /// Remove a tracked MPQ package and restore any replaced archives.
+    Remove {
+        id: i64,
+        /// Also delete files that changed after Wuddle installed them.
+        #[arg(long, default_value_t = false)]
+        force_modified: bool,
     },
 }
 
-fn main() -> Result<()> {
-    wuddle_engine::initialize_git_transport()?;
-    tokio::runtime::Builder::new_multi_thread()
-        .enable_all()
-        .build()?
-        .block_on(run())
+/// Stable classification of one update-check result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum PlanStatus {
+    Error,
+    /// The update was installed by this run.
+    Updated,
+    RepairNeeded,
+    Unchanged,
+    UpToDate,
+    UpdateAvailable,
+}
+
+impl PlanStatus {
+    fn of(plan: &UpdatePlan) -> Self {
+        if plan.error.is_some() {
+            PlanStatus::Error
+        } else if plan.applied {
+            PlanStatus::Updated
+        } else if plan.repair_needed {
+            PlanStatus::RepairNeeded
+        } else if plan.not_modified {
+            PlanStatus::Unchanged
+        } else if plan.asset_url.is_empty() {
+            PlanStatus::UpToDate
+        } else {
+            PlanStatus::UpdateAvailable
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PlanOutput<'a> {
+    status: PlanStatus,
+    #[serde(flatten)]
+    plan: &'a UpdatePlan,
+}
+
+#[derive(Serialize)]
+struct AddOutput {
+    id: i64,
+}
+
+#[derive(Serialize)]
+struct TagsOutput<'a> {
+    id: i64,
+    tags: &'a [String],
+}
+
+#[derive(Serialize)]
+struct HistoryOutput<'a> {
+    id: i64,
+    entries: &'a [InstallHistoryEntry],
+}
+
+#[derive(Serialize)]
+struct FollowTagsOutput<'a> {
+    id: i64,
+    policy: Option<&'a GitTagPolicy>,
+}
+
+#[derive(Serialize)]
+struct RemoveOutput<'a> {
+    id: i64,
+    remove_local_files: bool,
+    removed_paths: usize,
+    /// Repos whose addons required folders this repo installed.
+    dependents: &'a [i64],
+}
+
+#[derive(Serialize)]
+struct CheckOutput<'a> {
+    plans: Vec<PlanOutput<'a>>,
+    updates_available: usize,
+    errors: usize,
+    missing_dependencies: &'a [UnmetDependency],
+}
+
+#[derive(Serialize)]
+struct UpdateOutput<'a> {
+    plans: Vec<PlanOutput<'a>>,
+    updated: usize,
+    failed: usize,
+    client_mismatches: &'a [ClientMismatch],
+    missing_dependencies: &'a [UnmetDependency],
+}
+
+#[derive(Serialize)]
+struct DryRunOutput<'a> {
+    changes: &'a [ChangeSet],
+}
+
+#[derive(Serialize)]
+struct ListOutput<'a> {
+    repos: &'a [Repo],
+}
+
+#[derive(Serialize)]
+struct MpqTargetOutput<'a> {
+    #[serde(flatten)]
+    preview: &'a MpqTargetPreview,
+    /// Why this target blocks the install, if it does.
+    reason: Option<String>,
+}
+
+#[derive(Serialize)]
+struct MpqInstallOutput<'a> {
+    repo_id: Option<i64>,
+    targets: Vec<MpqTargetOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct MpqFileOutput<'a> {
+    repo_id: i64,
+    package: &'a str,
+    #[serde(flatten)]
+    file: &'a MpqInstalledFile,
+}
+
+#[derive(Serialize)]
+struct MpqListOutput<'a> {
+    files: Vec<MpqFileOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct MpqUntrackedOutput<'a> {
+    untracked: &'a [MpqProtectionEntry],
+}
+
+#[derive(Serialize)]
+struct MpqChangeOutput<'a> {
+    action: &'a str,
+    repo_id: Option<i64>,
+    path: Option<&'a str>,
+    changed: usize,
+}
+
+#[derive(Serialize)]
+struct ForgeHostOutput<'a> {
+    #[serde(flatten)]
+    host: &'a CustomForgeHost,
+    api_path: &'a str,
+}
+
+#[derive(Serialize)]
+struct ForgeHostsOutput<'a> {
+    hosts: Vec<ForgeHostOutput<'a>>,
+}
+
+#[derive(Serialize)]
+struct ForgeHostRemovedOutput<'a> {
+    host: &'a str,
+    removed: bool,
+}
+
+#[derive(Serialize)]
+struct SetsOutput<'a> {
+    sets: &'a [AddonSet],
+}
+
+#[derive(Serialize)]
+struct SetAppliedOutput<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    report: &'a AddonSetReport,
 }
 
-async fn run() -> Result<()> {
+#[derive(Serialize)]
+struct SetImportedOutput<'a> {
+    name: &'a str,
+    unmatched_urls: &'a [String],
+}
+
+#[derive(Serialize)]
+struct SetRemovedOutput<'a> {
+    name: &'a str,
+    removed: bool,
+}
+
+#[derive(Serialize)]
+struct IdentifyOutput<'a> {
+    addons: &'a [AddonIdentification],
+}
+
+#[derive(Serialize)]
+struct LinkedOutput<'a> {
+    folder: &'a str,
+    repo_id: i64,
+    url: &'a str,
+}
+
+#[derive(Serialize)]
+struct CatalogImportedOutput {
+    entries: usize,
+}
+
+#[derive(Serialize)]
+struct IndexSearchOutput<'a> {
+    entries: &'a [IndexEntry],
+}
+
+#[derive(Serialize)]
+struct PresetsUpdatedOutput {
+    updated: bool,
+    revision: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct IndexSourcesOutput<'a> {
+    catalogs: &'a [IndexSource],
+}
+
+#[derive(Serialize)]
+struct IndexRemovedOutput {
+    id: i64,
+    removed: bool,
+}
+
+#[derive(Serialize)]
+struct SnapshotsOutput<'a> {
+    snapshots: &'a [SavedVariablesSnapshot],
+}
+
+#[derive(Serialize)]
+struct SnapshotTakenOutput<'a> {
+    snapshot: Option<&'a SavedVariablesSnapshot>,
+    pruned: usize,
+}
+
+#[derive(Serialize)]
+struct SavedVarsDiffOutput<'a> {
+    addon: &'a str,
+    from: &'a str,
+    to: Option<&'a str>,
+    files: &'a [FileDiff],
+}
+
+#[derive(Serialize)]
+struct PrunedOutput {
+    pruned: usize,
+}
+
+#[derive(Serialize)]
+struct ProfileOutput<'a> {
+    #[serde(flatten)]
+    profile: &'a ProfileSettings,
+    active: bool,
+    db_path: PathBuf,
+    db_exists: bool,
+}
+
+#[derive(Serialize)]
+struct ProfilesOutput<'a> {
+    data_dir: &'a Path,
+    profiles: Vec<ProfileOutput<'a>>,
+}
+
+/// Print a single JSON document (`json`) or one JSON object per item
+/// (`ndjson`). Text output is handled by each command.
+fn emit_json<T: Serialize, I: Serialize>(
+    format: OutputFormat,
+    document: &T,
+    items: &[I],
+) -> Result<()> {
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(document)?),
+        OutputFormat::Ndjson => {
+            for item in items {
+                println!("{}", serde_json::to_string(item)?);
+            }
+        }
+        OutputFormat::Text => {}
+    }
+    Ok(())
+}
+
+/// Exit status of `check`: errors win over pending updates.
+fn check_exit_status(errors: usize, updates_available: usize) -> u8 {
+    if errors > 0 {
+        EXIT_ERRORS
+    } else if updates_available > 0 {
+        EXIT_UPDATES_AVAILABLE
+    } else {
+        0
+    }
+}
+
+/// Print dry-run change sets. Exits with [`EXIT_BLOCKED`] when any of the
+/// operations would stop.
+fn emit_change_sets(format: OutputFormat, changes: &[ChangeSet]) -> Result<ExitCode> {
+    if format == OutputFormat::Text {
+        for set in changes {
+            println!("{} {} (dry run):", set.operation, set.label);
+            let lines = set.lines();
+            if lines.is_empty() {
+                println!("  nothing to change");
+            }
+            for line in lines {
+                println!("  {line}");
+            }
+        }
+    } else {
+        emit_json(format, &DryRunOutput { changes }, changes)?;
+    }
+    if changes.iter().any(|set| set.blocked.is_some()) {
+        return Ok(ExitCode::from(EXIT_BLOCKED));
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn main() -> ExitCode {
+    let result = wuddle_engine::initialize_git_transport().and_then(|()| {
+        tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?
+            .block_on(run())
+    });
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("Error: {err:#}");
+            if is_blocking_mpq_error(&err) {
+                ExitCode::from(EXIT_BLOCKED)
+            } else {
+                ExitCode::from(EXIT_ERRORS)
+            }
+        }
+    }
+}
+
+/// Conflict and protection refusals from the MPQ engine are expected
+/// outcomes that a script may want to react to differently from I/O errors.
+fn is_blocking_mpq_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<MpqError>(),
+            Some(
+                MpqError::ProtectedTarget(_)
+                    | MpqError::ManagedTarget(_)
+                    | MpqError::ReplacementNotApproved(_)
+                    | MpqError::ModifiedTarget(_)
+                    | MpqError::MissingTarget(_)
+                    | MpqError::ToggleCollision(_)
+                    | MpqError::RestoredTargetModified(_)
+            )
+        )
+    })
+}
+
+async fn run() -> Result<ExitCode> {
     let cli = Cli::parse();
-    let engine = Engine::open_default()?;
+    let format = cli.format;
+    if let Cmd::Profiles = cli.cmd {
+        return list_profiles(format, cli.data_dir.as_deref());
+    }
+    let (engine, profile_wow_dir) = open_engine(
+        cli.profile.as_deref(),
+        cli.data_dir.as_deref(),
+        cli.db.as_deref(),
+    )?;
+    for recovered in wuddle_engine::take_recovered_transactions() {
+        eprintln!("warning: {}", recovered.describe());
+    }
+    if cli.ssh_key.is_some() {
+        wuddle_engine::set_ssh_key_path(cli.ssh_key.clone());
+    }
 
     match cli.cmd {
         Cmd::Add {
@@ -70,57 +897,156 @@ async fn run() -> Result<()> {
             } else {
                 engine.add_repo(&url, mode, asset_regex, None)?
             };
-            println!("Added repo id={id}");
-        }
-        Cmd::List => {
-            for r in engine.db().list_repos()? {
-                println!(
-                    "#{:>3} {:<6} {:<18} {}/{} mode={} url={}",
-                    r.id,
-                    r.forge,
-                    r.host,
-                    r.owner,
-                    r.name,
-                    r.mode.as_str(),
-                    r.url
-                );
+            if format == OutputFormat::Text {
+                println!("Added repo id={id}");
+            } else {
+                let output = AddOutput { id };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
             }
         }
-        Cmd::Remove {
-            id,
+        Cmd::List => {
+            let repos = engine.db().list_repos()?;
+            if format == OutputFormat::Text {
+                for r in &repos {
+                    println!(
+                        "#{:>3} {:<6} {:<18} {}/{} mode={} url={}",
+                        r.id,
+                        r.forge,
+                        r.host,
+                        r.owner,
+                        r.name,
+                        r.mode.as_str(),
+                        r.url
+                    );
+                }
+            } else {
+                emit_json(format, &ListOutput { repos: &repos }, &repos)?;
+            }
+        }
+        Cmd::Remove {
+            id,
             remove_local_files,
             wow_dir,
+            dry_run,
         } => {
-            let removed = engine.remove_repo(id, wow_dir.as_deref(), remove_local_files)?;
-            if remove_local_files {
-                println!("Removed repo id={id} and deleted {removed} local path(s).");
+            if dry_run {
+                let changes = engine.dry_run_remove(
+                    id,
+                    wow_dir.or(profile_wow_dir).as_deref(),
+                    remove_local_files,
+                )?;
+                return emit_change_sets(format, &[changes]);
+            }
+            if let Some(wow_dir) = wow_dir.as_ref().or(profile_wow_dir.as_ref()) {
+                engine.refresh_addon_dependencies(wow_dir)?;
+            }
+            let dependents = engine.repo_dependents(id)?;
+            if format == OutputFormat::Text && !dependents.is_empty() {
+                let ids = dependents
+                    .iter()
+                    .map(|dependent| format!("id={dependent}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                eprintln!("Warning: addons of repo(s) {ids} depend on repo id={id}.");
+            }
+            let removed = engine.remove_repo(
+                id,
+                wow_dir.or(profile_wow_dir).as_deref(),
+                remove_local_files,
+            )?;
+            if format == OutputFormat::Text {
+                if remove_local_files {
+                    println!("Removed repo id={id} and deleted {removed} local path(s).");
+                } else {
+                    println!("Removed repo id={id}");
+                }
             } else {
-                println!("Removed repo id={id}");
+                let output = RemoveOutput {
+                    id,
+                    remove_local_files,
+                    removed_paths: removed,
+                    dependents: &dependents,
+                };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
             }
         }
         Cmd::Check { wow_dir } => {
+            let wow_dir = wow_dir.or(profile_wow_dir);
             let plans = engine
                 .check_updates_with_wow(wow_dir.as_deref(), wuddle_engine::CheckMode::Force)
                 .await?;
-            for p in plans {
-                if let Some(err) = p.error.as_deref() {
-                    println!("{}/{}: error ({})", p.owner, p.name, err);
-                } else if p.repair_needed {
-                    println!("{}/{}: repair needed ({})", p.owner, p.name, p.latest);
-                } else if p.not_modified {
-                    println!("{}/{}: (etag) unchanged", p.owner, p.name);
-                } else if p.asset_url.is_empty() {
-                    println!("{}/{}: up-to-date ({})", p.owner, p.name, p.latest);
-                } else {
-                    println!(
-                        "{}/{}: update {} -> {} (asset {})",
-                        p.owner,
-                        p.name,
-                        p.current.clone().unwrap_or("<none>".into()),
-                        p.latest,
-                        p.asset_name
-                    );
+            let missing_dependencies = match &wow_dir {
+                Some(wow_dir) => engine.addon_dependency_report(wow_dir, &preset_sources())?,
+                None => Vec::new(),
+            };
+            let entries = plans
+                .iter()
+                .map(|plan| PlanOutput {
+                    status: PlanStatus::of(plan),
+                    plan,
+                })
+                .collect::<Vec<_>>();
+            let errors = entries
+                .iter()
+                .filter(|entry| entry.status == PlanStatus::Error)
+                .count();
+            let updates_available = entries
+                .iter()
+                .filter(|entry| {
+                    matches!(
+                        entry.status,
+                        PlanStatus::UpdateAvailable | PlanStatus::RepairNeeded
+                    )
+                })
+                .count();
+
+            if format == OutputFormat::Text {
+                for p in &plans {
+                    match PlanStatus::of(p) {
+                        PlanStatus::Error => println!(
+                            "{}/{}: error ({})",
+                            p.owner,
+                            p.name,
+                            p.error.as_deref().unwrap_or_default()
+                        ),
+                        PlanStatus::Updated => {
+                            println!("{}/{}: updated ({})", p.owner, p.name, p.install_version)
+                        }
+                        PlanStatus::RepairNeeded => {
+                            println!("{}/{}: repair needed ({})", p.owner, p.name, p.latest)
+                        }
+                        PlanStatus::Unchanged => {
+                            println!("{}/{}: (etag) unchanged", p.owner, p.name)
+                        }
+                        PlanStatus::UpToDate => {
+                            println!("{}/{}: up-to-date ({})", p.owner, p.name, p.latest)
+                        }
+                        PlanStatus::UpdateAvailable => println!(
+                            "{}/{}: update {} -> {} (asset {})",
+                            p.owner,
+                            p.name,
+                            p.current.clone().unwrap_or("<none>".into()),
+                            p.latest,
+                            p.asset_name
+                        ),
+                    }
+                }
+                for unmet in &missing_dependencies {
+                    eprintln!("Warning: {}", unmet.message());
                 }
+            } else {
+                let output = CheckOutput {
+                    plans: entries,
+                    updates_available,
+                    errors,
+                    missing_dependencies: &missing_dependencies,
+                };
+                emit_json(format, &output, &output.plans)?;
+            }
+
+            let status = check_exit_status(errors, updates_available);
+            if status != 0 {
+                return Ok(ExitCode::from(status));
             }
         }
         Cmd::Update {
@@ -128,7 +1054,11 @@ async fn run() -> Result<()> {
             raw_dest,
             symlink_targets,
             set_xattr_comment,
+            client,
+            atomic,
+            dry_run,
         } => {
+            let wow_dir = require_wow_dir(wow_dir, profile_wow_dir)?;
             let raw_dest_ref = raw_dest.as_deref();
             let opts = InstallOptions {
                 use_symlinks: symlink_targets,
@@ -137,25 +1067,1465 @@ async fn run() -> Result<()> {
                 replace_file_conflicts: false,
                 replace_local_changes: false,
                 cache_keep_versions: 3,
+                client_family: client.map(ClientFamily::from).unwrap_or_default(),
             };
-            let plans = engine.apply_updates(&wow_dir, raw_dest_ref, opts).await?;
-            let mut updated = 0;
-            let mut failed = 0;
-            for p in plans {
-                if p.applied {
-                    updated += 1;
+            if dry_run {
+                let changes = engine.dry_run_updates(&wow_dir, raw_dest_ref, opts).await?;
+                return emit_change_sets(format, &changes);
+            }
+            let plans = if atomic {
+                engine
+                    .apply_updates_atomic(&wow_dir, raw_dest_ref, opts)
+                    .await?
+            } else {
+                engine.apply_updates(&wow_dir, raw_dest_ref, opts).await?
+            };
+            let updated = plans.iter().filter(|p| p.applied).count();
+            let failed = plans.iter().filter(|p| p.error.is_some()).count();
+            let mut client_mismatches = Vec::new();
+            for plan in plans.iter().filter(|p| p.applied) {
+                client_mismatches.extend(engine.addon_client_mismatches(
+                    plan.repo_id,
+                    &wow_dir,
+                    opts.client_family,
+                )?);
+            }
+            let missing_dependencies =
+                engine.addon_dependency_report(&wow_dir, &preset_sources())?;
+            if format == OutputFormat::Text {
+                for mismatch in &client_mismatches {
+                    eprintln!("Warning: {}", mismatch.message());
+                }
+                for unmet in &missing_dependencies {
+                    eprintln!("Warning: {}", unmet.message());
                 }
-                if p.error.is_some() {
-                    failed += 1;
+                if failed > 0 {
+                    println!("Done. Updated {updated} repo(s); {failed} failed.");
+                } else {
+                    println!("Done. Updated {updated} repo(s).");
                 }
+            } else {
+                let entries = plans
+                    .iter()
+                    .map(|plan| PlanOutput {
+                        status: PlanStatus::of(plan),
+                        plan,
+                    })
+                    .collect::<Vec<_>>();
+                let output = UpdateOutput {
+                    plans: entries,
+                    updated,
+                    failed,
+                    client_mismatches: &client_mismatches,
+                    missing_dependencies: &missing_dependencies,
+                };
+                emit_json(format, &output, &output.plans)?;
             }
             if failed > 0 {
-                println!("Done. Updated {updated} repo(s); {failed} failed.");
+                return Ok(ExitCode::from(EXIT_ERRORS));
+            }
+        }
+        Cmd::Reinstall {
+            id,
+            wow_dir,
+            raw_dest,
+            client,
+            dry_run,
+        } => {
+            let wow_dir = require_wow_dir(wow_dir, profile_wow_dir)?;
+            let opts = InstallOptions {
+                cache_keep_versions: 3,
+                client_family: client.map(ClientFamily::from).unwrap_or_default(),
+                ..InstallOptions::default()
+            };
+            if dry_run {
+                let changes = engine
+                    .dry_run_reinstall(id, &wow_dir, raw_dest.as_deref(), opts)
+                    .await?;
+                return emit_change_sets(format, &[changes]);
+            }
+            let plan = engine
+                .reinstall_repo(id, &wow_dir, raw_dest.as_deref(), opts)
+                .await?;
+            if format == OutputFormat::Text {
+                println!(
+                    "Reinstalled {}/{} ({})",
+                    plan.owner, plan.name, plan.install_version
+                );
+            } else {
+                let output = PlanOutput {
+                    status: PlanStatus::of(&plan),
+                    plan: &plan,
+                };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+        Cmd::Mpq { wow_dir, cmd } => {
+            let wow_dir = require_wow_dir(wow_dir, profile_wow_dir)?;
+            return run_mpq(&engine, format, &wow_dir, cmd);
+        }
+        Cmd::ForgeHosts { cmd } => run_forge_hosts(&engine, format, cmd)?,
+        Cmd::SavedVars { wow_dir, cmd } => {
+            let wow_dir = require_wow_dir(wow_dir, profile_wow_dir)?;
+            run_saved_vars(format, &wow_dir, cmd)?;
+        }
+        Cmd::Sets { wow_dir, cmd } => run_sets(&engine, format, wow_dir.or(profile_wow_dir), cmd)?,
+        Cmd::Index { cmd } => run_index(&engine, format, cmd).await?,
+        Cmd::Presets { cmd } => run_presets(format, cmd).await?,
+        Cmd::Catalog { wow_dir, cmd } => {
+            run_catalog(&engine, format, wow_dir.or(profile_wow_dir), cmd)?
+        }
+        Cmd::Tags { id } => {
+            let tags = engine.list_repo_tags(id)?;
+            if format == OutputFormat::Text {
+                for tag in &tags {
+                    println!("{tag}");
+                }
+            } else {
+                emit_json(format, &TagsOutput { id, tags: &tags }, &tags)?;
+            }
+        }
+        Cmd::FollowTags { id, pattern, off } => {
+            let policy = match (off, pattern) {
+                (true, _) => None,
+                (false, Some(pattern)) => Some(GitTagPolicy::Pattern(pattern)),
+                (false, None) => Some(GitTagPolicy::Semver),
+            };
+            engine.set_repo_git_tag_policy(id, policy.clone())?;
+            if format == OutputFormat::Text {
+                match &policy {
+                    None => println!("Repo id={id} follows its branch head"),
+                    Some(GitTagPolicy::Semver) => println!("Repo id={id} follows semver tags"),
+                    Some(GitTagPolicy::Pattern(pattern)) => {
+                        println!("Repo id={id} follows tags matching {pattern}")
+                    }
+                }
+            } else {
+                let output = FollowTagsOutput {
+                    id,
+                    policy: policy.as_ref(),
+                };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+        Cmd::History { id } => {
+            let entries = engine.install_history(id)?;
+            if format == OutputFormat::Text {
+                if entries.is_empty() {
+                    println!("No install history for repo id={id}.");
+                }
+                for entry in &entries {
+                    println!(
+                        "#{:>3} {:<9} {} -> {} settings={} sha256={}",
+                        entry.id,
+                        entry.action.as_str(),
+                        entry.from_version.as_deref().unwrap_or("<none>"),
+                        entry.to_version.as_deref().unwrap_or("<none>"),
+                        entry.settings_snapshot.as_deref().unwrap_or("-"),
+                        entry.asset_sha256.as_deref().unwrap_or("-")
+                    );
+                }
+            } else {
+                emit_json(
+                    format,
+                    &HistoryOutput {
+                        id,
+                        entries: &entries,
+                    },
+                    &entries,
+                )?;
+            }
+        }
+        Cmd::Rollback {
+            entry,
+            wow_dir,
+            raw_dest,
+        } => {
+            let wow_dir = require_wow_dir(wow_dir, profile_wow_dir)?;
+            let opts = InstallOptions {
+                cache_keep_versions: 3,
+                ..InstallOptions::default()
+            };
+            let report = engine
+                .rollback_to_history(entry, &wow_dir, raw_dest.as_deref(), opts)
+                .await?;
+            if format == OutputFormat::Text {
+                println!(
+                    "Redeployed repo id={} at {} and pinned it there.",
+                    report.repo_id, report.version
+                );
+                for restored in &report.settings {
+                    println!(
+                        "Restored {} SavedVariables file(s) of {}.",
+                        restored.restored_files, restored.addon
+                    );
+                }
+            } else {
+                emit_json(format, &report, std::slice::from_ref(&report))?;
+            }
+        }
+        Cmd::WhatsNew { id, wow_dir } => {
+            let notes = engine
+                .release_notes_for_repo(id, wow_dir.or(profile_wow_dir).as_deref())
+                .await?;
+            if format == OutputFormat::Text {
+                print!("{}", notes.to_markdown());
+            } else {
+                emit_json(format, &notes, std::slice::from_ref(&notes))?;
+            }
+        }
+        Cmd::Profiles => unreachable!("handled before opening the database"),
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Open the database selected by `--db` or `--profile`, falling back to the
+/// engine's default. Also returns the profile's WoW directory, if any.
+fn open_engine(
+    profile: Option<&str>,
+    data_dir: Option<&Path>,
+    db: Option<&Path>,
+) -> Result<(Engine, Option<PathBuf>)> {
+    if let Some(db) = db {
+        return Ok((Engine::open(db)?, None));
+    }
+    let Some(selector) = profile else {
+        return Ok((Engine::open_default()?, None));
+    };
+    let settings = load_gui_settings(data_dir)?;
+    let profile = settings.find_profile(selector)?;
+    let db_path = settings.profile_db_path(&profile.id);
+    if !db_path.is_file() {
+        anyhow::bail!(
+            "profile {:?} has no database at {}; open it in Wuddle first, or pass the app's --data-dir",
+            profile.name,
+            db_path.display()
+        );
+    }
+    // Keep caches and other app data beside the GUI's, as the app does.
+    wuddle_engine::set_default_app_dir(settings.data_dir.clone())?;
+    wuddle_engine::set_ssh_key_path(settings.ssh_key_path());
+    Ok((Engine::open(&db_path)?, profile.wow_dir()))
+}
+
+fn require_wow_dir(explicit: Option<PathBuf>, profile: Option<PathBuf>) -> Result<PathBuf> {
+    explicit.or(profile).ok_or_else(|| {
+        anyhow::anyhow!("--wow-dir is required unless --profile has a WoW directory")
+    })
+}
+
+/// The desktop app's settings from `--data-dir`, or from where the app
+/// would keep them if it sat beside this binary.
+fn load_gui_settings(data_dir: Option<&Path>) -> Result<GuiSettings> {
+    match data_dir {
+        Some(dir) => GuiSettings::load(dir),
+        None => GuiSettings::load_default(),
+    }
+}
+
+fn list_profiles(format: OutputFormat, data_dir: Option<&Path>) -> Result<ExitCode> {
+    let settings = load_gui_settings(data_dir)?;
+    let entries = settings
+        .profiles
+        .iter()
+        .map(|profile| {
+            let db_path = settings.profile_db_path(&profile.id);
+            ProfileOutput {
+                active: profile.id == settings.active_profile().id,
+                db_exists: db_path.is_file(),
+                db_path,
+                profile,
+            }
+        })
+        .collect::<Vec<_>>();
+    if format == OutputFormat::Text {
+        println!("Settings: {}", settings.data_dir.display());
+        for entry in &entries {
+            println!(
+                "{} {:<24} {:<20} wow_dir={} launch={} db={}{}",
+                if entry.active { "*" } else { " " },
+                entry.profile.id,
+                entry.profile.name,
+                entry.profile.wow_dir,
+                entry.profile.launch_method,
+                entry.db_path.display(),
+                if entry.db_exists { "" } else { " (missing)" }
+            );
+        }
+    } else {
+        let output = ProfilesOutput {
+            data_dir: &settings.data_dir,
+            profiles: entries,
+        };
+        emit_json(format, &output, &output.profiles)?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run_forge_hosts(engine: &Engine, format: OutputFormat, cmd: ForgeHostsCmd) -> Result<()> {
+    match cmd {
+        ForgeHostsCmd::List => {
+            let hosts = engine.list_forge_hosts()?;
+            let output = ForgeHostsOutput {
+                hosts: hosts
+                    .iter()
+                    .map(|host| ForgeHostOutput {
+                        host,
+                        api_path: host.api_path(),
+                    })
+                    .collect(),
+            };
+            if format == OutputFormat::Text {
+                if output.hosts.is_empty() {
+                    println!("No forge hosts registered.");
+                }
+                for entry in &output.hosts {
+                    println!(
+                        "{:<32} {:?} {}",
+                        entry.host.host, entry.host.kind, entry.api_path
+                    );
+                }
+            } else {
+                emit_json(format, &output, &output.hosts)?;
+            }
+        }
+        ForgeHostsCmd::Add {
+            host,
+            kind,
+            api_path,
+        } => {
+            let kind = ForgeKind::parse_custom(&kind)
+                .ok_or_else(|| anyhow::anyhow!("kind must be gitlab, gitea, or forgejo"))?;
+            let entry = engine.add_forge_host(&host, kind, api_path.as_deref())?;
+            if format == OutputFormat::Text {
+                println!(
+                    "Registered {} ({:?}) at {}",
+                    entry.host,
+                    entry.kind,
+                    entry.api_path()
+                );
+            } else {
+                let output = ForgeHostOutput {
+                    host: &entry,
+                    api_path: entry.api_path(),
+                };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+        ForgeHostsCmd::Remove { host } => {
+            let removed = engine.remove_forge_host(&host)?;
+            if format == OutputFormat::Text {
+                if removed {
+                    println!("Removed {host}");
+                } else {
+                    println!("{host} was not registered");
+                }
+            } else {
+                let output = ForgeHostRemovedOutput {
+                    host: &host,
+                    removed,
+                };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The single character under `wow_dir` that `selector` names, matched from
+/// the end of `Account/Realm/Name`.
+fn find_character(wow_dir: &Path, selector: &str) -> Result<WtfCharacter> {
+    let wanted = selector
+        .split('/')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    let mut matches = character_addons::discover_characters(wow_dir)
+        .into_iter()
+        .filter(|character| {
+            let parts = [&character.account, &character.realm, &character.name];
+            wanted.len() <= parts.len()
+                && wanted
+                    .iter()
+                    .rev()
+                    .zip(parts.iter().rev())
+                    .all(|(want, part)| want.eq_ignore_ascii_case(part))
+        })
+        .collect::<Vec<_>>();
+    match matches.len() {
+        0 => anyhow::bail!("no character matches {selector:?}"),
+        1 => Ok(matches.remove(0)),
+        _ => anyhow::bail!(
+            "{selector:?} matches several characters; use Realm/Name or Account/Realm/Name"
+        ),
+    }
+}
+
+fn run_saved_vars(format: OutputFormat, wow_dir: &Path, cmd: SavedVarsCmd) -> Result<()> {
+    match cmd {
+        SavedVarsCmd::List => {
+            let snapshots = saved_variables::list_snapshots(wow_dir)?;
+            if format == OutputFormat::Text {
+                if snapshots.is_empty() {
+                    println!("No SavedVariables snapshots.");
+                }
+                for snapshot in &snapshots {
+                    println!(
+                        "{:<14} {:<14} {} file(s), {} addon(s)",
+                        snapshot.id,
+                        snapshot.trigger.label(),
+                        snapshot.files.len(),
+                        snapshot.addons().len()
+                    );
+                }
+            } else {
+                emit_json(
+                    format,
+                    &SnapshotsOutput {
+                        snapshots: &snapshots,
+                    },
+                    &snapshots,
+                )?;
+            }
+        }
+        SavedVarsCmd::Snapshot {
+            if_changed,
+            keep,
+            max_age_days,
+        } => {
+            let trigger = if if_changed {
+                SnapshotTrigger::Scheduled
+            } else {
+                SnapshotTrigger::Manual
+            };
+            let snapshot = saved_variables::take_snapshot(wow_dir, trigger)?;
+            let pruned = if keep.is_some() || max_age_days.is_some() {
+                let retention = SnapshotRetention {
+                    keep_last: keep.unwrap_or(usize::MAX),
+                    max_age_days,
+                };
+                saved_variables::prune_snapshots(wow_dir, retention)?
+            } else {
+                0
+            };
+            if format == OutputFormat::Text {
+                match &snapshot {
+                    Some(snapshot) => println!(
+                        "Saved snapshot {} with {} file(s).",
+                        snapshot.id,
+                        snapshot.files.len()
+                    ),
+                    None => {
+                        println!("Nothing to snapshot; SavedVariables are unchanged or absent.")
+                    }
+                }
+                if pruned > 0 {
+                    println!("Removed {pruned} old snapshot(s).");
+                }
+            } else {
+                let output = SnapshotTakenOutput {
+                    snapshot: snapshot.as_ref(),
+                    pruned,
+                };
+                emit_json(format, &output, snapshot.as_slice())?;
+            }
+        }
+        SavedVarsCmd::Diff { addon, from, to } => {
+            let files = saved_variables::diff_addon(wow_dir, &from, to.as_deref(), &addon)?;
+            if format == OutputFormat::Text {
+                if files.is_empty() {
+                    println!("No SavedVariables for {addon} in either version.");
+                }
+                for file in &files {
+                    println!("{} [{}]: {:?}", file.scope, file.path, file.change);
+                    for hunk in &file.hunks {
+                        println!("@@ -{} +{} @@", hunk.old_start, hunk.new_start);
+                        for line in &hunk.lines {
+                            let marker = match line.kind {
+                                DiffLineKind::Context => ' ',
+                                DiffLineKind::Added => '+',
+                                DiffLineKind::Removed => '-',
+                            };
+                            println!("{marker}{}", line.text);
+                        }
+                    }
+                }
+            } else {
+                let output = SavedVarsDiffOutput {
+                    addon: &addon,
+                    from: &from,
+                    to: to.as_deref(),
+                    files: &files,
+                };
+                emit_json(format, &output, &files)?;
+            }
+        }
+        SavedVarsCmd::Restore { addon, snapshot } => {
+            let report = saved_variables::restore_addon(wow_dir, &snapshot, &addon)?;
+            if format == OutputFormat::Text {
+                println!(
+                    "Restored {} file(s) of {} from snapshot {}.",
+                    report.restored_files, report.addon, report.snapshot_id
+                );
+                if let Some(backup) = &report.backup_id {
+                    println!("The previous files were saved as snapshot {backup}.");
+                }
+            } else {
+                emit_json(format, &report, std::slice::from_ref(&report))?;
+            }
+        }
+        SavedVarsCmd::Prune { keep, max_age_days } => {
+            let retention = SnapshotRetention {
+                keep_last: keep,
+                max_age_days,
+            };
+            let pruned = saved_variables::prune_snapshots(wow_dir, retention)?;
+            if format == OutputFormat::Text {
+                println!("Removed {pruned} snapshot(s).");
+            } else {
+                let output = PrunedOutput { pruned };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Addon sources named by the Quick Add presets in use.
+fn preset_sources() -> Vec<(String, String)> {
+    presets::addon_sources(&presets::load_default().catalog.presets)
+}
+
+fn signature_path(file: &Path) -> PathBuf {
+    let mut name = file.as_os_str().to_owned();
+    name.push(".sig");
+    PathBuf::from(name)
+}
+
+async fn run_presets(format: OutputFormat, cmd: PresetsCmd) -> Result<()> {
+    match cmd {
+        PresetsCmd::List => {
+            let loaded = presets::load_default();
+            for problem in &loaded.problems {
+                eprintln!("warning: {problem}");
+            }
+            if format == OutputFormat::Text {
+                println!(
+                    "{} catalog, revision {}:",
+                    loaded.origin.label(),
+                    loaded.catalog.revision
+                );
+                for preset in &loaded.catalog.presets {
+                    let clients = preset
+                        .supported_clients
+                        .iter()
+                        .map(|client| client.label())
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    println!("{:<24} {:<30} {}", preset.name, clients, preset.url);
+                }
+            } else {
+                emit_json(format, &loaded, &loaded.catalog.presets)?;
+            }
+        }
+        PresetsCmd::Update => {
+            let stored = presets::update_default().await?;
+            if format == OutputFormat::Text {
+                match &stored {
+                    Some(catalog) => println!("Updated presets to revision {}.", catalog.revision),
+                    None => println!("Presets are up to date."),
+                }
+            } else {
+                let output = PresetsUpdatedOutput {
+                    updated: stored.is_some(),
+                    revision: stored.map(|catalog| catalog.revision),
+                };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+        PresetsCmd::Keygen { out } => {
+            anyhow::ensure!(!out.exists(), "{} already exists", out.display());
+            let (pkcs8, public_key) = presets::generate_signing_key()?;
+            std::fs::write(&out, pkcs8).with_context(|| format!("write {}", out.display()))?;
+            println!("{public_key}");
+        }
+        PresetsCmd::Sign { file, key } => {
+            let raw = std::fs::read(&file).with_context(|| format!("read {}", file.display()))?;
+            presets::parse_catalog(&raw)?;
+            let pkcs8 = std::fs::read(&key).with_context(|| format!("read {}", key.display()))?;
+            let signature = presets::sign_catalog(&raw, &pkcs8)?;
+            let out = signature_path(&file);
+            std::fs::write(&out, format!("{signature}\n"))
+                .with_context(|| format!("write {}", out.display()))?;
+            println!("Wrote {}.", out.display());
+        }
+        PresetsCmd::Verify { file, public_key } => {
+            let raw = std::fs::read(&file).with_context(|| format!("read {}", file.display()))?;
+            let signature_file = signature_path(&file);
+            let signature = std::fs::read_to_string(&signature_file)
+                .with_context(|| format!("read {}", signature_file.display()))?;
+            presets::verify_signature(&raw, &signature, &[public_key.as_str()])?;
+            let catalog = presets::parse_catalog(&raw)?;
+            println!(
+                "Signature verified: revision {} with {} presets.",
+                catalog.revision,
+                catalog.presets.len()
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn run_index(engine: &Engine, format: OutputFormat, cmd: IndexCmd) -> Result<()> {
+    match cmd {
+        IndexCmd::Search {
+            query,
+            client,
+            category,
+            kind,
+        } => {
+            let kind = kind
+                .map(|kind| {
+                    IndexKind::parse(&kind.to_ascii_lowercase())
+                        .with_context(|| format!("unknown kind {kind:?}; expected addon or mod"))
+                })
+                .transpose()?;
+            let entries = engine.search_addon_index(&IndexQuery {
+                text: query.join(" "),
+                client: client.map(ClientFamily::from),
+                category,
+                kind,
+            })?;
+            if format == OutputFormat::Text {
+                if entries.is_empty() {
+                    println!("No catalog entry matches.");
+                }
+                for entry in &entries {
+                    println!(
+                        "{:<28} {:<5} {}",
+                        entry.name,
+                        entry.kind.as_str(),
+                        entry.url
+                    );
+                    if !entry.description.is_empty() {
+                        println!("    {}", entry.description);
+                    }
+                }
             } else {
-                println!("Done. Updated {updated} repo(s).");
+                emit_json(format, &IndexSearchOutput { entries: &entries }, &entries)?;
+            }
+        }
+        IndexCmd::Import { source, name } => {
+            let catalog = if source.starts_with("https://") || source.starts_with("http://") {
+                engine.add_addon_index_url(&source).await?
+            } else {
+                let path = PathBuf::from(&source);
+                let raw = std::fs::read_to_string(&path)
+                    .with_context(|| format!("read {}", path.display()))?;
+                let fallback = name.clone().unwrap_or_else(|| {
+                    path.file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .unwrap_or_default()
+                        .to_string()
+                });
+                engine.import_addon_index(&raw, &fallback)?
+            };
+            if format == OutputFormat::Text {
+                println!(
+                    "Imported catalog {} (id={}) with {} entries.",
+                    catalog.name, catalog.id, catalog.entries
+                );
+            } else {
+                emit_json(format, &catalog, std::slice::from_ref(&catalog))?;
+            }
+        }
+        IndexCmd::Refresh { id } => {
+            let ids = match id {
+                Some(id) => vec![id],
+                None => engine
+                    .addon_index_sources()?
+                    .into_iter()
+                    .filter(|source| source.url.is_some())
+                    .map(|source| source.id)
+                    .collect(),
+            };
+            let mut refreshed = Vec::new();
+            for id in ids {
+                refreshed.push(engine.refresh_addon_index(id).await?);
+            }
+            if format == OutputFormat::Text {
+                if refreshed.is_empty() {
+                    println!("No catalog was added by URL.");
+                }
+                for catalog in &refreshed {
+                    println!(
+                        "Refreshed catalog {}: {} entries.",
+                        catalog.name, catalog.entries
+                    );
+                }
+            } else {
+                emit_json(
+                    format,
+                    &IndexSourcesOutput {
+                        catalogs: &refreshed,
+                    },
+                    &refreshed,
+                )?;
+            }
+        }
+        IndexCmd::List => {
+            let catalogs = engine.addon_index_sources()?;
+            if format == OutputFormat::Text {
+                if catalogs.is_empty() {
+                    println!("No addon catalogs imported.");
+                }
+                for catalog in &catalogs {
+                    println!(
+                        "#{:>3} {:<24} {:>4} entries {}",
+                        catalog.id,
+                        catalog.name,
+                        catalog.entries,
+                        catalog.url.as_deref().unwrap_or("(file)")
+                    );
+                }
+            } else {
+                emit_json(
+                    format,
+                    &IndexSourcesOutput {
+                        catalogs: &catalogs,
+                    },
+                    &catalogs,
+                )?;
+            }
+        }
+        IndexCmd::Remove { id } => {
+            let removed = engine.remove_addon_index(id)?;
+            if format == OutputFormat::Text {
+                if removed {
+                    println!("Removed catalog id={id}");
+                } else {
+                    println!("No catalog with id={id}");
+                }
+            } else {
+                let output = IndexRemovedOutput { id, removed };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
             }
         }
     }
+    Ok(())
+}
 
+fn run_catalog(
+    engine: &Engine,
+    format: OutputFormat,
+    wow_dir: Option<PathBuf>,
+    cmd: CatalogCmd,
+) -> Result<()> {
+    match cmd {
+        CatalogCmd::Identify => {
+            let wow_dir = require_wow_dir(wow_dir, None)?;
+            let addons = engine.identify_untracked_addons(&wow_dir, &preset_sources())?;
+            if format == OutputFormat::Text {
+                if addons.is_empty() {
+                    println!("No untracked addon matches a known source.");
+                }
+                for addon in &addons {
+                    println!("{}", addon.folder);
+                    for candidate in &addon.matches {
+                        println!(
+                            "  {} ({}, {}{})",
+                            candidate.url,
+                            candidate.kind.label(),
+                            candidate.source.as_str(),
+                            candidate
+                                .version
+                                .as_deref()
+                                .map(|version| format!(", version {version}"))
+                                .unwrap_or_default()
+                        );
+                    }
+                }
+            } else {
+                emit_json(format, &IdentifyOutput { addons: &addons }, &addons)?;
+            }
+        }
+        CatalogCmd::Link { folder, url } => {
+            let wow_dir = require_wow_dir(wow_dir, None)?;
+            let addons = engine.identify_untracked_addons(&wow_dir, &preset_sources())?;
+            let addon = addons
+                .iter()
+                .find(|addon| addon.folder.eq_ignore_ascii_case(&folder))
+                .with_context(|| format!("{folder} does not match any known source"))?;
+            let source = match &url {
+                Some(url) => addon
+                    .matches
+                    .iter()
+                    .find(|candidate| candidate.url.eq_ignore_ascii_case(url))
+                    .with_context(|| format!("{folder} does not match {url}"))?,
+                None => &addon.matches[0],
+            };
+            let repo_id = engine.link_addon_folder(&wow_dir, &addon.folder, source)?;
+            if format == OutputFormat::Text {
+                println!(
+                    "Linked {} to {} as repo id={repo_id}.",
+                    addon.folder, source.url
+                );
+            } else {
+                let output = LinkedOutput {
+                    folder: &addon.folder,
+                    repo_id,
+                    url: &source.url,
+                };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+        CatalogCmd::Import { file } => {
+            let raw = std::fs::read_to_string(&file)
+                .with_context(|| format!("read {}", file.display()))?;
+            let catalog: CatalogFile = serde_json::from_str(&raw)
+                .with_context(|| format!("parse catalog {}", file.display()))?;
+            let entries = engine.import_addon_catalog(&catalog)?;
+            if format == OutputFormat::Text {
+                println!("Imported {entries} catalog entries.");
+            } else {
+                let output = CatalogImportedOutput { entries };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+        CatalogCmd::Export => {
+            // Like `sets export`, the catalog file is the output in every format.
+            if let Some(wow_dir) = &wow_dir {
+                engine.refresh_addon_catalog(wow_dir)?;
+            }
+            let catalog = engine.export_addon_catalog()?;
+            println!("{}", serde_json::to_string_pretty(&catalog)?);
+        }
+    }
     Ok(())
 }
+
+fn run_sets(
+    engine: &Engine,
+    format: OutputFormat,
+    wow_dir: Option<PathBuf>,
+    cmd: SetsCmd,
+) -> Result<()> {
+    match cmd {
+        SetsCmd::List => {
+            let sets = engine.list_addon_sets()?;
+            if format == OutputFormat::Text {
+                if sets.is_empty() {
+                    println!("No addon sets saved.");
+                }
+                for set in &sets {
+                    println!(
+                        "{:<24} {} repo(s), {} folder(s)",
+                        set.name,
+                        set.repos.len(),
+                        set.folders.len()
+                    );
+                }
+            } else {
+                emit_json(format, &SetsOutput { sets: &sets }, &sets)?;
+            }
+        }
+        SetsCmd::Capture { name, character } => {
+            let wow_dir = require_wow_dir(wow_dir, None)?;
+            let character = find_character(&wow_dir, &character)?;
+            let set = engine.capture_addon_set(&name, &wow_dir, &character)?;
+            if format == OutputFormat::Text {
+                println!(
+                    "Saved set {} from {}: {} repo(s), {} folder(s)",
+                    set.name,
+                    character.label(),
+                    set.repos.len(),
+                    set.folders.len()
+                );
+            } else {
+                emit_json(format, &set, std::slice::from_ref(&set))?;
+            }
+        }
+        SetsCmd::Save {
+            name,
+            enable_repo,
+            disable_repo,
+            enable,
+            disable,
+        } => {
+            let repos = enable_repo
+                .into_iter()
+                .map(|repo_id| (repo_id, true))
+                .chain(disable_repo.into_iter().map(|repo_id| (repo_id, false)))
+                .map(|(repo_id, enabled)| AddonSetRepo { repo_id, enabled })
+                .collect();
+            let folders = enable
+                .into_iter()
+                .map(|folder| (folder, true))
+                .chain(disable.into_iter().map(|folder| (folder, false)))
+                .map(|(folder, enabled)| AddonSetFolder { folder, enabled })
+                .collect();
+            let set = AddonSet {
+                name,
+                repos,
+                folders,
+            };
+            engine.save_addon_set(&set)?;
+            if format == OutputFormat::Text {
+                println!("Saved set {}", set.name.trim());
+            } else {
+                emit_json(format, &set, std::slice::from_ref(&set))?;
+            }
+        }
+        SetsCmd::Apply {
+            name,
+            character,
+            client,
+        } => {
+            let wow_dir = require_wow_dir(wow_dir, None)?;
+            let character = character
+                .map(|selector| find_character(&wow_dir, &selector))
+                .transpose()?;
+            let use_dlls_txt = matches!(client, Some(ClientArg::Vanilla));
+            let report =
+                engine.apply_addon_set(&name, &wow_dir, character.as_ref(), use_dlls_txt)?;
+            if format == OutputFormat::Text {
+                for skipped in &report.skipped {
+                    eprintln!("Warning: {} {}", skipped.entry, skipped.reason);
+                }
+                println!(
+                    "Applied set {name}: {} addon folder(s); switched {} repo(s); updated {} AddOns.txt file(s).",
+                    report.folders, report.repos_changed, report.characters_updated
+                );
+            } else {
+                let output = SetAppliedOutput {
+                    name: &name,
+                    report: &report,
+                };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+        SetsCmd::Remove { name } => {
+            let removed = engine.delete_addon_set(&name)?;
+            if format == OutputFormat::Text {
+                if removed {
+                    println!("Removed set {name}");
+                } else {
+                    println!("No set named {name}");
+                }
+            } else {
+                let output = SetRemovedOutput {
+                    name: &name,
+                    removed,
+                };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+        SetsCmd::Export { name } => {
+            // The export document is the output in every format.
+            let export = engine.export_addon_set(&name)?;
+            println!("{}", serde_json::to_string_pretty(&export)?);
+        }
+        SetsCmd::Import { file } => {
+            let raw = std::fs::read_to_string(&file)
+                .with_context(|| format!("read {}", file.display()))?;
+            let export: AddonSetExport = serde_json::from_str(&raw)
+                .with_context(|| format!("parse addon set {}", file.display()))?;
+            let unmatched = engine.import_addon_set(&export)?;
+            if format == OutputFormat::Text {
+                for url in &unmatched {
+                    eprintln!("Warning: {url} is not tracked here and was left out.");
+                }
+                println!("Imported set {}", export.name.trim());
+            } else {
+                let output = SetImportedOutput {
+                    name: &export.name,
+                    unmatched_urls: &unmatched,
+                };
+                emit_json(format, &output, std::slice::from_ref(&output))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn run_mpq(engine: &Engine, format: OutputFormat, wow_dir: &Path, cmd: MpqCmd) -> Result<ExitCode> {
+    match cmd {
+        MpqCmd::Inspect { source } => {
+            let inspection = engine.inspect_local_mpq_source(wow_dir, &source)?;
+            if format == OutputFormat::Text {
+                print_inspection(&inspection);
+            } else {
+                emit_json(format, &inspection, &inspection.candidates)?;
+            }
+        }
+        MpqCmd::Install {
+            source,
+            only,
+            destination,
+            version,
+            replace_unprotected,
+            set_xattr_comment,
+            dry_run,
+        } => {
+            let destination = destination
+                .map(|value| {
+                    MpqDestination::parse(&value).ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "bad destination {value:?}; use `data` or a locale like enUS"
+                        )
+                    })
+                })
+                .transpose()?;
+            let inspection = engine.inspect_local_mpq_source(wow_dir, &source)?;
+            let selections = select_mpq_candidates(
+                &inspection,
+                &only,
+                destination.as_ref(),
+                version.as_deref(),
+                replace_unprotected,
+            )?;
+            if dry_run {
+                let changes = engine.dry_run_local_mpq_package(wow_dir, &source, &selections)?;
+                return emit_change_sets(format, &[changes]);
+            }
+
+            // Preview first so every conflict is reported at once instead of
+            // failing on the first target inside the install transaction.
+            let previews = engine.preview_local_mpq_targets(wow_dir, &source, &selections)?;
+            let targets = previews
+                .iter()
+                .map(|preview| MpqTargetOutput {
+                    preview,
+                    reason: target_block_reason(preview, replace_unprotected)
+                        .map(|err| err.to_string()),
+                })
+                .collect::<Vec<_>>();
+            let blocked = targets
+                .iter()
+                .filter(|target| target.reason.is_some())
+                .count();
+            if blocked > 0 {
+                for target in &targets {
+                    if let Some(reason) = &target.reason {
+                        eprintln!("Blocked: {reason}");
+                    }
+                }
+                if format != OutputFormat::Text {
+                    let output = MpqInstallOutput {
+                        repo_id: None,
+                        targets,
+                    };
+                    emit_json(format, &output, &output.targets)?;
+                }
+                return Ok(ExitCode::from(EXIT_BLOCKED));
+            }
+
+            let repo_id = engine.install_local_mpq_package(
+                wow_dir,
+                &source,
+                &selections,
+                set_xattr_comment,
+            )?;
+            if format == OutputFormat::Text {
+                for target in &targets {
+                    println!(
+                        "{} ({})",
+                        target.preview.manifest_path,
+                        target.preview.status.label()
+                    );
+                }
+                println!("Installed MPQ package id={repo_id}");
+            } else {
+                let output = MpqInstallOutput {
+                    repo_id: Some(repo_id),
+                    targets,
+                };
+                emit_json(format, &output, &output.targets)?;
+            }
+        }
+        MpqCmd::List { package, untracked } => {
+            if untracked {
+                let entries = engine.list_mpq_protection(wow_dir)?;
+                if format == OutputFormat::Text {
+                    for entry in &entries {
+                        let mut flags = Vec::new();
+                        if entry.core {
+                            flags.push("core");
+                        }
+                        if entry.protected {
+                            flags.push("protected");
+                        }
+                        if !entry.enabled {
+                            flags.push("disabled");
+                        }
+                        println!("{:<40} {}", entry.path, flags.join(","));
+                    }
+                } else {
+                    let output = MpqUntrackedOutput {
+                        untracked: &entries,
+                    };
+                    emit_json(format, &output, &entries)?;
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let repos = engine
+                .db()
+                .list_repos()?
+                .into_iter()
+                .filter(|repo| repo.mode == InstallMode::Mpq)
+                .filter(|repo| package.is_none_or(|id| repo.id == id))
+                .collect::<Vec<_>>();
+            if let Some(id) = package {
+                if repos.is_empty() {
+                    anyhow::bail!("No MPQ package with id={id}");
+                }
+            }
+            let mut packages = Vec::with_capacity(repos.len());
+            for repo in &repos {
+                packages.push((repo, engine.list_installed_mpqs(repo.id, wow_dir)?));
+            }
+            if format == OutputFormat::Text {
+                for (repo, files) in &packages {
+                    println!("#{:>3} {}", repo.id, repo.name);
+                    for file in files {
+                        println!(
+                            "     {:<40} {:<9} {}{}",
+                            file.path,
+                            file.status.label(),
+                            if file.enabled { "enabled" } else { "disabled" },
+                            if file.protected { ", protected" } else { "" }
+                        );
+                    }
+                }
+            } else {
+                let output = MpqListOutput {
+                    files: packages
+                        .iter()
+                        .flat_map(|(repo, files)| {
+                            files.iter().map(|file| MpqFileOutput {
+                                repo_id: repo.id,
+                                package: &repo.name,
+                                file,
+                            })
+                        })
+                        .collect(),
+                };
+                emit_json(format, &output, &output.files)?;
+            }
+        }
+        MpqCmd::Enable { id, path } => {
+            let changed = engine.set_mpq_enabled(id, path.as_deref(), true, wow_dir)?;
+            report_mpq_change(format, "enabled", Some(id), path.as_deref(), changed)?;
+        }
+        MpqCmd::Disable { id, path } => {
+            let changed = engine.set_mpq_enabled(id, path.as_deref(), false, wow_dir)?;
+            report_mpq_change(format, "disabled", Some(id), path.as_deref(), changed)?;
+        }
+        MpqCmd::Protect { path, package, off } => {
+            match package {
+                Some(id) => engine.set_tracked_mpq_protected(id, wow_dir, &path, !off)?,
+                None => engine.set_mpq_protected(wow_dir, &path, !off)?,
+            }
+            let action = if off { "unprotected" } else { "protected" };
+            report_mpq_change(format, action, package, Some(&path), 1)?;
+        }
+        MpqCmd::Unlock {
+            path,
+            package,
+            lock,
+        } => {
+            match package {
+                Some(id) => engine.set_tracked_mpq_editor_unlocked(id, wow_dir, &path, !lock)?,
+                None => engine.set_untracked_mpq_editor_unlocked(wow_dir, &path, !lock)?,
+            }
+            let action = if lock { "locked" } else { "unlocked" };
+            report_mpq_change(format, action, package, Some(&path), 1)?;
+        }
+        MpqCmd::Rename {
+            id,
+            path,
+            name,
+            file_name,
+            set_xattr_comment,
+        } => {
+            let new_path = match file_name {
+                Some(file_name) => {
+                    let destination = MpqDestination::from_manifest_path(&path)
+                        .ok_or_else(|| anyhow::anyhow!("{path} is not inside Data/"))?;
+                    engine.edit_tracked_mpq(
+                        id,
+                        wow_dir,
+                        &path,
+                        &name,
+                        &file_name,
+                        &destination,
+                        set_xattr_comment,
+                    )?
+                }
+                None => {
+                    engine.rename_mpq_display_name(id, &path, &name, wow_dir, set_xattr_comment)?;
+                    path
+                }
+            };
+            report_mpq_change(format, "renamed", Some(id), Some(&new_path), 1)?;
+        }
+        MpqCmd::Remove { id, force_modified } => {
+            let removed = engine.remove_mpq_package(id, wow_dir, force_modified)?;
+            report_mpq_change(format, "removed", Some(id), None, removed)?;
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn print_inspection(inspection: &MpqInspection) {
+    println!("{}", inspection.package_name);
+    if let Some(locale) = &inspection.locale.recommended {
+        println!("Detected locale: {locale}");
+    }
+    for candidate in &inspection.candidates {
+        println!(
+            "  {:<40} -> {:<12} {} ({} bytes)",
+            candidate.source_key,
+            candidate.suggested_destination.label(),
+            candidate.suggested_display_name,
+            candidate.size
+        );
+    }
+}
+
+/// Turn the inspected candidates into install selections, honoring `--only`
+/// filters and a destination override.
+fn select_mpq_candidates(
+    inspection: &MpqInspection,
+    only: &[String],
+    destination: Option<&MpqDestination>,
+    version: Option<&str>,
+    replace_unprotected: bool,
+) -> Result<Vec<MpqInstallSelection>> {
+    for wanted in only {
+        let known = inspection.candidates.iter().any(|candidate| {
+            candidate.source_key.eq_ignore_ascii_case(wanted)
+                || candidate.original_file_name.eq_ignore_ascii_case(wanted)
+        });
+        if !known {
+            anyhow::bail!(MpqError::InvalidSelection(format!(
+                "{wanted} is not an MPQ in the selected source"
+            )));
+        }
+    }
+    Ok(inspection
+        .candidates
+        .iter()
+        .filter(|candidate| {
+            only.is_empty()
+                || only.iter().any(|wanted| {
+                    candidate.source_key.eq_ignore_ascii_case(wanted)
+                        || candidate.original_file_name.eq_ignore_ascii_case(wanted)
+                })
+        })
+        .map(|candidate| MpqInstallSelection {
+            source_key: candidate.source_key.clone(),
+            display_name: candidate.suggested_display_name.clone(),
+            file_name: candidate.original_file_name.clone(),
+            destination: destination
+                .cloned()
+                .unwrap_or_else(|| candidate.suggested_destination.clone()),
+            replace_unprotected,
+            version: version.map(str::to_string),
+        })
+        .collect())
+}
+
+/// The engine error an install into this target would fail with, if any.
+fn target_block_reason(preview: &MpqTargetPreview, replace_unprotected: bool) -> Option<MpqError> {
+    let file_name = Path::new(&preview.manifest_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(&preview.manifest_path)
+        .to_string();
+    match preview.status {
+        MpqTargetStatus::Available | MpqTargetStatus::SamePackage => None,
+        MpqTargetStatus::UnprotectedReplacement if replace_unprotected => None,
+        MpqTargetStatus::UnprotectedReplacement => {
+            Some(MpqError::ReplacementNotApproved(file_name))
+        }
+        MpqTargetStatus::ManagedByAnotherPackage => Some(MpqError::ManagedTarget(file_name)),
+        MpqTargetStatus::ProtectedCore | MpqTargetStatus::ProtectedUntracked => {
+            Some(MpqError::ProtectedTarget(file_name))
+        }
+    }
+}
+
+fn report_mpq_change(
+    format: OutputFormat,
+    action: &str,
+    repo_id: Option<i64>,
+    path: Option<&str>,
+    changed: usize,
+) -> Result<()> {
+    if format == OutputFormat::Text {
+        let subject = match (repo_id, path) {
+            (_, Some(path)) => path.to_string(),
+            (Some(id), None) => format!("MPQ package id={id}"),
+            (None, None) => "MPQ".to_string(),
+        };
+        println!("{subject}: {action} ({changed} file(s))");
+    } else {
+        let output = MpqChangeOutput {
+            action,
+            repo_id,
+            path,
+            changed,
+        };
+        emit_json(format, &output, std::slice::from_ref(&output))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(error: Option<&str>, asset_url: &str) -> UpdatePlan {
+        UpdatePlan {
+            repo_id: 7,
+            forge: "github".to_string(),
+            host: "github.com".to_string(),
+            owner: "owner".to_string(),
+            name: "repo".to_string(),
+            url: "https://github.com/owner/repo".to_string(),
+            mode: InstallMode::AddonGit,
+            current: Some("v1".to_string()),
+            install_version: "v2".to_string(),
+            latest: "v2".to_string(),
+            asset_id: "1".to_string(),
+            asset_name: "repo.zip".to_string(),
+            asset_url: asset_url.to_string(),
+            asset_size: None,
+            asset_sha256: None,
+            repair_needed: false,
+            externally_modified: false,
+            not_modified: false,
+            applied: false,
+            error: error.map(str::to_string),
+            release_cache_age_secs: None,
+            extra_assets: Vec::new(),
+            previous_dll_count: 0,
+            new_dll_count: 0,
+            is_manual: false,
+        }
+    }
+
+    fn keys(value: &serde_json::Value) -> Vec<&str> {
+        let mut keys = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect::<Vec<_>>();
+        keys.sort_unstable();
+        keys
+    }
+
+    #[test]
+    fn json_output_keeps_its_field_names() {
+        let plans = [
+            plan(None, "https://example.invalid/repo.zip"),
+            plan(Some("boom"), ""),
+            UpdatePlan {
+                applied: true,
+                ..plan(None, "https://example.invalid/repo.zip")
+            },
+        ];
+        let output = UpdateOutput {
+            plans: plans
+                .iter()
+                .map(|plan| PlanOutput {
+                    status: PlanStatus::of(plan),
+                    plan,
+                })
+                .collect(),
+            updated: 1,
+            failed: 1,
+            client_mismatches: &[],
+            missing_dependencies: &[],
+        };
+        let value = serde_json::to_value(&output).unwrap();
+        assert_eq!(
+            keys(&value),
+            vec![
+                "client_mismatches",
+                "failed",
+                "missing_dependencies",
+                "plans",
+                "updated"
+            ]
+        );
+        let first = &value["plans"][0];
+        assert_eq!(first["status"], "update_available");
+        assert_eq!(first["mode"], "addon_git");
+        for key in [
+            "repo_id",
+            "owner",
+            "name",
+            "current",
+            "latest",
+            "asset_name",
+            "error",
+        ] {
+            assert!(first.get(key).is_some(), "missing plan field {key}");
+        }
+        assert_eq!(value["plans"][1]["status"], "error");
+        assert_eq!(value["plans"][2]["status"], "updated");
+
+        let repos = [Repo {
+            id: 3,
+            url: "https://github.com/owner/repo".to_string(),
+            forge: "github".to_string(),
+            host: "github.com".to_string(),
+            owner: "owner".to_string(),
+            name: "repo".to_string(),
+            mode: InstallMode::Dll,
+            enabled: true,
+            git_branch: None,
+            asset_regex: None,
+            last_version: Some("v1".to_string()),
+            etag: None,
+            installed_asset_id: None,
+            installed_asset_name: None,
+            installed_asset_size: None,
+            installed_asset_url: None,
+            installed_at_unix: None,
+            published_at_unix: None,
+            merge_installs: false,
+            pinned_version: None,
+            selected_addons_json: None,
+            git_tag_policy: None,
+        }];
+        let value = serde_json::to_value(ListOutput { repos: &repos }).unwrap();
+        assert_eq!(keys(&value), vec!["repos"]);
+        let repo = &value["repos"][0];
+        assert_eq!(repo["mode"], "dll");
+        for key in [
+            "id",
+            "url",
+            "forge",
+            "owner",
+            "name",
+            "enabled",
+            "last_version",
+        ] {
+            assert!(repo.get(key).is_some(), "missing repo field {key}");
+        }
+    }
+
+    #[test]
+    fn check_exit_status_puts_errors_before_updates() {
+        assert_eq!(check_exit_status(0, 0), 0);
+        assert_eq!(check_exit_status(0, 2), 100);
+        assert_eq!(check_exit_status(1, 0), 1);
+        assert_eq!(check_exit_status(1, 2), 1);
+    }
+}
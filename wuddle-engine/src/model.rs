@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
 pub enum InstallMode {
     /// Automatically infer what to install from the downloaded asset:
     /// - If it's a .dll => copy into WoW root
@@ -52,7 +53,57 @@ impl InstallMode {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Which tags an addon_git repo follows instead of its branch head.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GitTagPolicy {
+    /// `1.2`, `1.2.3` or `v1.2.3`; pre-release and other tags are ignored.
+    Semver,
+    /// Tags matching this regular expression.
+    Pattern(String),
+}
+
+impl GitTagPolicy {
+    /// The value stored in the `git_tag_policy` column.
+    pub fn to_storage(&self) -> String {
+        match self {
+            GitTagPolicy::Semver => "semver".to_string(),
+            GitTagPolicy::Pattern(pattern) => format!("pattern:{pattern}"),
+        }
+    }
+
+    pub fn from_storage(s: &str) -> Option<Self> {
+        match s.trim() {
+            "" => None,
+            "semver" => Some(GitTagPolicy::Semver),
+            other => other
+                .strip_prefix("pattern:")
+                .map(|pattern| GitTagPolicy::Pattern(pattern.to_string())),
+        }
+    }
+
+    /// Whether `tag` is eligible under this policy. An invalid pattern admits
+    /// nothing; patterns are validated when the policy is set.
+    pub fn admits(&self, tag: &str) -> bool {
+        match self {
+            GitTagPolicy::Semver => is_semver_tag(tag),
+            GitTagPolicy::Pattern(pattern) => regex::Regex::new(pattern)
+                .map(|re| re.is_match(tag))
+                .unwrap_or(false),
+        }
+    }
+}
+
+fn is_semver_tag(tag: &str) -> bool {
+    let version = tag.strip_prefix(['v', 'V']).unwrap_or(tag);
+    let parts = version.split('.').collect::<Vec<_>>();
+    (2..=3).contains(&parts.len())
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct Repo {
     pub id: i64,
     pub url: String,   // canonical repo URL (no /releases)
@@ -80,10 +131,71 @@ pub struct Repo {
     pub merge_installs: bool, // keep existing files when updating (don't delete unmatched)
     pub pinned_version: Option<String>, // lock to a specific release tag (None = latest)
     pub selected_addons_json: Option<String>, // collection selection for addon_git repos
+    pub git_tag_policy: Option<GitTagPolicy>, // addon_git only (None = follow the branch head)
+}
+
+/// What an [`InstallHistoryEntry`] records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryAction {
+    Install,
+    Update,
+    Reinstall,
+    Remove,
+    Rollback,
+}
+
+impl HistoryAction {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Install => "install",
+            Self::Update => "update",
+            Self::Reinstall => "reinstall",
+            Self::Remove => "remove",
+            Self::Rollback => "rollback",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "install" => Some(Self::Install),
+            "update" => Some(Self::Update),
+            "reinstall" => Some(Self::Reinstall),
+            "remove" => Some(Self::Remove),
+            "rollback" => Some(Self::Rollback),
+            _ => None,
+        }
+    }
+}
+
+/// One install, update, reinstall, rollback or removal of a repo. Entries
+/// outlive the repo, so its removal stays listed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InstallHistoryEntry {
+    pub id: i64,
+    pub repo_id: i64,
+    pub action: HistoryAction,
+    pub created_unix: i64,
+    pub from_version: Option<String>,
+    /// Version installed by this entry; `None` for a removal.
+    pub to_version: Option<String>,
+    /// Release asset id, or the commit of a Git addon.
+    pub asset_id: Option<String>,
+    pub asset_name: Option<String>,
+    pub asset_url: Option<String>,
+    /// SHA-256 of the downloaded asset, so a rollback can trust the cached
+    /// copy without the network.
+    pub asset_sha256: Option<String>,
+    /// Additional DLL assets installed alongside the primary one.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extra_assets: Vec<ReleaseAsset>,
+    /// SavedVariables snapshot of the repo's addons taken before the files
+    /// were replaced. `None` when none of its addons declare saved settings.
+    pub settings_snapshot: Option<String>,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LatestRelease {
     pub tag: String,
     pub name: Option<String>,
@@ -92,10 +204,13 @@ pub struct LatestRelease {
     pub prerelease: bool,
     pub assets: Vec<ReleaseAsset>,
     pub published_at: Option<i64>, // unix epoch seconds from forge API
+    /// Release notes as the forge's markdown.
+    #[serde(default)]
+    pub body: Option<String>,
 }
 
 #[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ReleaseAsset {
     pub id: Option<String>,
     pub name: String,
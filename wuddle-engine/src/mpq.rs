@@ -12,9 +12,10 @@ use std::path::{Component, Path, PathBuf};
 
 use anyhow::Result;
 use reqwest::Url;
+use serde::Serialize;
 use tempfile::{Builder, TempDir};
 
-use crate::{db, diagnostics, install, util, InstallMode, Repo};
+use crate::{db, diagnostics, install, util, ChangeSet, InstallMode, Repo};
 
 pub const KNOWN_LOCALES: &[&str] = &[
     "enGB", "enUS", "deDE", "esES", "frFR", "koKR", "zhCN", "zhTW", "enCN", "enTW", "esMX", "ruRU",
@@ -88,13 +89,13 @@ impl std::error::Error for MpqError {}
 
 pub type MpqResult<T> = Result<T, MpqError>;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct LocaleEvidence {
     pub locale: String,
     pub source: String,
 }
 
-#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
 pub struct LocaleDetection {
     pub recommended: Option<String>,
     pub candidates: Vec<String>,
@@ -102,7 +103,8 @@ pub struct LocaleDetection {
     pub ambiguous: bool,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MpqDestination {
     DataRoot,
     Locale(String),
@@ -122,6 +124,36 @@ impl MpqDestination {
             Self::Locale(locale) => format!("Data/{locale}/{file_name}"),
         }
     }
+
+    /// Parse a user-supplied destination: `data` (or `Data/`) for the data
+    /// root, otherwise a known client locale such as `enUS`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let trimmed = value.trim().trim_end_matches(['/', '\\']);
+        if trimmed.eq_ignore_ascii_case("data") || trimmed.eq_ignore_ascii_case("root") {
+            return Some(Self::DataRoot);
+        }
+        let locale = trimmed
+            .strip_prefix("Data/")
+            .or_else(|| trimmed.strip_prefix("data/"))
+            .unwrap_or(trimmed);
+        normalize_locale(locale).map(Self::Locale)
+    }
+
+    /// Recover the destination of a tracked manifest path such as
+    /// `Data/enUS/patch-enUS-4.MPQ`.
+    pub fn from_manifest_path(path: &str) -> Option<Self> {
+        let normalized = path.replace('\\', "/");
+        let mut parts = normalized.split('/').filter(|part| !part.is_empty());
+        if !parts.next()?.eq_ignore_ascii_case("Data") {
+            return None;
+        }
+        let rest = parts.collect::<Vec<_>>();
+        match rest.as_slice() {
+            [_file] => Some(Self::DataRoot),
+            [locale, _file] => normalize_locale(locale).map(Self::Locale),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for MpqDestination {
@@ -130,7 +162,7 @@ impl fmt::Display for MpqDestination {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MpqCandidate {
     /// Stable slash-separated path inside the selected package.
     pub source_key: String,
@@ -140,7 +172,7 @@ pub struct MpqCandidate {
     pub size: u64,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct MpqInspection {
     pub source_path: PathBuf,
     pub package_name: String,
@@ -159,7 +191,7 @@ pub struct MpqInstallSelection {
     pub version: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MpqInstalledFile {
     pub path: String,
     pub display_name: String,
@@ -180,7 +212,8 @@ pub struct MpqPackageFileEdit {
     pub enabled: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MpqFileStatus {
     Installed,
     Missing,
@@ -221,7 +254,7 @@ pub struct MpqRemoteAsset {
     pub version: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MpqProtectionEntry {
     pub path: String,
     pub file_name: String,
@@ -235,7 +268,8 @@ pub struct MpqProtectionEntry {
     pub enabled: bool,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MpqTargetStatus {
     Available,
     SamePackage,
@@ -265,7 +299,7 @@ impl MpqTargetStatus {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
 pub struct MpqTargetPreview {
     pub source_key: String,
     pub manifest_path: String,
@@ -478,7 +512,8 @@ pub fn available_destinations(wow_dir: &Path, detection: &LocaleDetection) -> Ve
 }
 
 pub fn inspect_local_source(wow_dir: &Path, source: &Path) -> MpqResult<MpqInspection> {
-    let staged = stage_source(wow_dir, source)?;
+    // Inspection only reads the staged files, so it leaves the cache alone.
+    let staged = stage_source(None, source)?;
     let locale = detect_wow_locale(wow_dir);
     let destinations = available_destinations(wow_dir, &locale);
     let mut candidates = Vec::new();
@@ -519,7 +554,9 @@ pub fn inspect_local_source(wow_dir: &Path, source: &Path) -> MpqResult<MpqInspe
     })
 }
 
-pub(crate) fn stage_source(wow_dir: &Path, source: &Path) -> MpqResult<StagedMpqSource> {
+/// Copy or extract `source`'s MPQs into a staging directory beneath
+/// `wow_dir`'s cache, or beneath the system temp directory without one.
+pub(crate) fn stage_source(wow_dir: Option<&Path>, source: &Path) -> MpqResult<StagedMpqSource> {
     if !is_supported_local_source(source) {
         return Err(MpqError::UnsupportedSource);
     }
@@ -528,15 +565,20 @@ pub(crate) fn stage_source(wow_dir: &Path, source: &Path) -> MpqResult<StagedMpq
         return Err(MpqError::UnsupportedSource);
     }
 
-    let staging_parent = util::cache_dir(Some(wow_dir))
-        .map_err(|_| MpqError::Filesystem("creating the MPQ cache"))?
-        .join("mpq-staging");
-    fs::create_dir_all(&staging_parent)
-        .map_err(|_| MpqError::Filesystem("creating the MPQ staging directory"))?;
-    let temp_dir = Builder::new()
-        .prefix("install-")
-        .tempdir_in(&staging_parent)
-        .map_err(|_| MpqError::Filesystem("creating an MPQ staging operation"))?;
+    let temp_dir = match wow_dir {
+        Some(wow_dir) => {
+            let staging_parent = util::cache_dir(Some(wow_dir))
+                .map_err(|_| MpqError::Filesystem("creating the MPQ cache"))?
+                .join("mpq-staging");
+            fs::create_dir_all(&staging_parent)
+                .map_err(|_| MpqError::Filesystem("creating the MPQ staging directory"))?;
+            Builder::new()
+                .prefix("install-")
+                .tempdir_in(&staging_parent)
+        }
+        None => Builder::new().prefix("wuddle-mpq-").tempdir(),
+    }
+    .map_err(|_| MpqError::Filesystem("creating an MPQ staging operation"))?;
     let payload = temp_dir.path().join("payload");
     fs::create_dir_all(&payload)
         .map_err(|_| MpqError::Filesystem("creating the MPQ staging payload"))?;
@@ -2197,7 +2239,7 @@ impl crate::Engine {
         source: &Path,
         selections: &[MpqInstallSelection],
     ) -> Result<Vec<MpqTargetPreview>> {
-        let _ = stage_source(wow_dir, source)?;
+        let _ = stage_source(Some(wow_dir), source)?;
         let repo_name = Self::local_mpq_repo_name(source)?;
         let expected_repo_id = self
             .db()
@@ -2248,28 +2290,89 @@ impl crate::Engine {
         Ok(previews)
     }
 
+    /// What `install_local_mpq_package` would change.
+    pub fn dry_run_local_mpq_package(
+        &self,
+        wow_dir: &Path,
+        source: &Path,
+        selections: &[MpqInstallSelection],
+    ) -> Result<ChangeSet> {
+        let mut changes = ChangeSet::new("install", None, Self::local_mpq_base_name(source));
+        if let Err(error) = self.install_local_mpq_package_internal(
+            wow_dir,
+            source,
+            selections,
+            false,
+            Some(&mut changes),
+        ) {
+            changes.blocked = Some(format!("{error:#}"));
+        }
+        Ok(changes)
+    }
+
     pub fn install_local_mpq_package(
         &self,
         wow_dir: &Path,
         source: &Path,
         selections: &[MpqInstallSelection],
         set_xattr_comment: bool,
+    ) -> Result<i64> {
+        self.install_local_mpq_package_internal(
+            wow_dir,
+            source,
+            selections,
+            set_xattr_comment,
+            None,
+        )
+    }
+
+    /// With `record`, nothing is installed: the files and records the
+    /// install would change are added to the change set instead, and the
+    /// returned id is 0 for a package that is not tracked yet.
+    fn install_local_mpq_package_internal(
+        &self,
+        wow_dir: &Path,
+        source: &Path,
+        selections: &[MpqInstallSelection],
+        set_xattr_comment: bool,
+        mut record: Option<&mut ChangeSet>,
     ) -> Result<i64> {
         let _diagnostic = diagnostics::OperationGuard::new("install_local_mpq_package");
-        let staged = stage_source(wow_dir, source)?;
+        let dry_run = record.is_some();
+        let staged = stage_source((!dry_run).then_some(wow_dir), source)?;
         let source_metadata = fs::symlink_metadata(source)
             .map_err(|_| MpqError::Filesystem("reading MPQ source metadata"))?;
         let source_hash = util::sha256_hex(&metadata_fingerprint(&source_metadata));
         let repo_name = Self::local_mpq_repo_name(source)?;
-        let repo_id = self.ensure_mpq_repo(MpqRemotePackage {
-            url: String::new(),
-            forge: "local".to_string(),
-            host: "local-mpq".to_string(),
-            owner: "local".to_string(),
-            name: repo_name,
-        })?;
-        self.db()
-            .ensure_mpq_package_display_name(repo_id, &Self::local_mpq_base_name(source))?;
+        let repo_id = match record.as_deref_mut() {
+            Some(changes) => {
+                let existing = self
+                    .db()
+                    .find_repo_by_identity("local-mpq", "local", &repo_name)?
+                    .map(|repo| repo.id);
+                changes.repo_id = existing;
+                if existing.is_none() {
+                    changes.database.push(format!(
+                        "add the local MPQ package {}",
+                        Self::local_mpq_base_name(source)
+                    ));
+                }
+                // No repo row has id 0, so a new package owns nothing yet.
+                existing.unwrap_or(0)
+            }
+            None => {
+                let repo_id = self.ensure_mpq_repo(MpqRemotePackage {
+                    url: String::new(),
+                    forge: "local".to_string(),
+                    host: "local-mpq".to_string(),
+                    owner: "local".to_string(),
+                    name: repo_name,
+                })?;
+                self.db()
+                    .ensure_mpq_package_display_name(repo_id, &Self::local_mpq_base_name(source))?;
+                repo_id
+            }
+        };
         let installed_asset = db::InstalledAssetState {
             version: Some("Local".to_string()),
             asset_id: Some(source_hash),
@@ -2285,12 +2388,14 @@ impl crate::Engine {
             selections,
             set_xattr_comment,
             &installed_asset,
+            record,
         ) {
-            if self
-                .db()
-                .list_installs(repo_id)
-                .unwrap_or_default()
-                .is_empty()
+            if !dry_run
+                && self
+                    .db()
+                    .list_installs(repo_id)
+                    .unwrap_or_default()
+                    .is_empty()
             {
                 let _ = self.db().remove_repo(repo_id);
             }
@@ -2401,6 +2506,7 @@ impl crate::Engine {
             &selections,
             set_xattr_comment,
             &installed_asset,
+            None,
         ) {
             if self
                 .db()
@@ -2447,9 +2553,11 @@ impl crate::Engine {
             merge_installs: false,
             pinned_version: None,
             selected_addons_json: None,
+            git_tag_policy: None,
         })
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn commit_staged_mpq_package(
         &self,
         repo_id: i64,
@@ -2458,11 +2566,16 @@ impl crate::Engine {
         selections: &[MpqInstallSelection],
         set_xattr_comment: bool,
         installed_asset: &db::InstalledAssetState,
+        record: Option<&mut ChangeSet>,
     ) -> Result<()> {
         if selections.is_empty() {
             anyhow::bail!(MpqError::NoMpqFiles);
         }
-        let _ = self.list_mpq_protection(wow_dir)?;
+        // Scanning records protection for untracked MPQs; a dry run reads the
+        // rows as the scan would leave them instead.
+        if record.is_none() {
+            let _ = self.list_mpq_protection(wow_dir)?;
+        }
 
         let mut target_keys = HashSet::new();
         let mut display_names = HashSet::new();
@@ -2522,9 +2635,19 @@ impl crate::Engine {
                         }
                     }
                 }
-            } else if existing.is_some() {
-                let protection = self.db().get_mpq_protection(&manifest)?;
-                if protection.as_ref().map(|row| row.protected).unwrap_or(true) {
+            } else if let Some(existing_path) = &existing {
+                // A row for another file at this path no longer applies; the
+                // scan resets it to protected.
+                let current = fs::symlink_metadata(existing_path)
+                    .map(|metadata| metadata_fingerprint(&metadata))
+                    .map_err(|_| MpqError::Filesystem("reading MPQ metadata"))?;
+                let protected = self
+                    .db()
+                    .get_mpq_protection(&manifest)?
+                    .filter(|row| row.fingerprint == current)
+                    .map(|row| row.protected)
+                    .unwrap_or(true);
+                if protected {
                     anyhow::bail!(MpqError::ProtectedTarget(selection.file_name.clone()));
                 }
                 if !selection.replace_unprotected {
@@ -2545,18 +2668,6 @@ impl crate::Engine {
             });
         }
 
-        let rollback_parent = util::cache_dir(Some(wow_dir))?.join("mpq-staging");
-        fs::create_dir_all(&rollback_parent)?;
-        let rollback_dir = tempfile::Builder::new()
-            .prefix("rollback-")
-            .tempdir_in(&rollback_parent)?;
-        let mut rollback = Vec::<(PathBuf, Option<PathBuf>)>::new();
-        struct StaleRollback {
-            target: PathBuf,
-            managed_copy: Option<PathBuf>,
-            backup_path: Option<PathBuf>,
-            restored_backup: bool,
-        }
         let desired_paths = prepared
             .iter()
             .map(|item| item.manifest.to_ascii_lowercase())
@@ -2569,6 +2680,41 @@ impl crate::Engine {
                 entry.kind == "mpq" && !desired_paths.contains(&entry.path.to_ascii_lowercase())
             })
             .collect::<Vec<_>>();
+
+        if let Some(changes) = record {
+            for item in &prepared {
+                match &item.existing {
+                    Some(_) if item.owner == Some(repo_id) => {
+                        changes.replaced.push(item.manifest.clone())
+                    }
+                    Some(_) => {
+                        changes.backed_up.push(item.manifest.clone());
+                        changes.replaced.push(item.manifest.clone());
+                    }
+                    None => changes.created.push(item.manifest.clone()),
+                }
+            }
+            for stale in &stale_entries {
+                self.record_mpq_component_removal(repo_id, wow_dir, stale, changes)?;
+            }
+            changes
+                .database
+                .push(format!("record {} MPQ install row(s)", prepared.len()));
+            return Ok(());
+        }
+
+        let rollback_parent = util::cache_dir(Some(wow_dir))?.join("mpq-staging");
+        fs::create_dir_all(&rollback_parent)?;
+        let rollback_dir = tempfile::Builder::new()
+            .prefix("rollback-")
+            .tempdir_in(&rollback_parent)?;
+        let mut rollback = Vec::<(PathBuf, Option<PathBuf>)>::new();
+        struct StaleRollback {
+            target: PathBuf,
+            managed_copy: Option<PathBuf>,
+            backup_path: Option<PathBuf>,
+            restored_backup: bool,
+        }
         let mut stale_rollback = Vec::<StaleRollback>::new();
         let mut installs = Vec::<db::InstallEntry>::new();
         let mut backups = Vec::<db::MpqBackupRow>::new();
@@ -3308,11 +3454,48 @@ impl crate::Engine {
         Ok(())
     }
 
+    /// Record the removal of one MPQ component and the restoration of the
+    /// file it displaced.
+    fn record_mpq_component_removal(
+        &self,
+        repo_id: i64,
+        wow_dir: &Path,
+        entry: &db::InstallEntry,
+        changes: &mut ChangeSet,
+    ) -> Result<()> {
+        let Some(target) = Self::resolve_install_path(&entry.path, Some(wow_dir)) else {
+            return Ok(());
+        };
+        if Self::find_actual_case(&target).is_some_and(|actual| actual.is_file()) {
+            changes.deleted.push(entry.path.clone());
+        }
+        let backup = self.db().get_mpq_backup(repo_id, &entry.path)?;
+        if backup
+            .and_then(|backup| Self::resolve_install_path(&backup.backup_path, Some(wow_dir)))
+            .is_some_and(|path| path.is_file())
+        {
+            changes.restored.push(entry.path.clone());
+        }
+        Ok(())
+    }
+
     pub fn remove_mpq_package(
         &self,
         repo_id: i64,
         wow_dir: &Path,
         force_modified: bool,
+    ) -> Result<usize> {
+        self.remove_mpq_package_internal(repo_id, wow_dir, force_modified, None)
+    }
+
+    /// With `record`, nothing is removed: the files the removal would delete
+    /// and restore are added to the change set instead.
+    pub(crate) fn remove_mpq_package_internal(
+        &self,
+        repo_id: i64,
+        wow_dir: &Path,
+        force_modified: bool,
+        record: Option<&mut ChangeSet>,
     ) -> Result<usize> {
         let _diagnostic = diagnostics::OperationGuard::new("remove_mpq_package");
         let entries = self
@@ -3353,6 +3536,13 @@ impl crate::Engine {
             }
         }
 
+        if let Some(changes) = record {
+            for entry in &entries {
+                self.record_mpq_component_removal(repo_id, wow_dir, entry, changes)?;
+            }
+            return Ok(changes.deleted.len());
+        }
+
         let rollback_parent = util::cache_dir(Some(wow_dir))?.join("mpq-staging");
         fs::create_dir_all(&rollback_parent)?;
         let rollback_dir = Builder::new()
@@ -3876,6 +4066,58 @@ mod tests {
         }
     }
 
+    #[test]
+    fn dry_run_install_leaves_disk_and_profile_untouched() {
+        let temp = tempfile::tempdir().unwrap();
+        let wow = temp.path().join("wow");
+        fs::create_dir_all(wow.join("Data")).unwrap();
+        write_valid_mpq(&wow.join("Data/patch-Manual.MPQ"));
+        let source = temp.path().join("source.MPQ");
+        write_valid_mpq_variant(&source, 1);
+        let engine = crate::Engine::open(&temp.path().join("profile.sqlite3")).unwrap();
+        let selection = MpqInstallSelection {
+            source_key: "source.MPQ".to_string(),
+            display_name: "Map".to_string(),
+            file_name: "patch-X.MPQ".to_string(),
+            destination: MpqDestination::DataRoot,
+            replace_unprotected: false,
+            version: None,
+        };
+
+        let changes = engine
+            .dry_run_local_mpq_package(&wow, &source, std::slice::from_ref(&selection))
+            .unwrap();
+        assert!(changes.blocked.is_none(), "{:?}", changes.blocked);
+        assert_eq!(changes.created, vec!["Data/patch-X.MPQ"]);
+        assert!(!wow.join("Data/patch-X.MPQ").exists());
+        assert!(!wow.join(".wuddle").exists());
+        assert!(engine.db().list_repos().unwrap().is_empty());
+        assert!(engine
+            .db()
+            .get_mpq_protection("Data/patch-Manual.MPQ")
+            .unwrap()
+            .is_none());
+
+        let repo_id = engine
+            .install_local_mpq_package(&wow, &source, std::slice::from_ref(&selection), false)
+            .unwrap();
+        let installed = fs::read(wow.join("Data/patch-X.MPQ")).unwrap();
+        let changes = engine
+            .dry_run_local_mpq_package(&wow, &source, &[selection])
+            .unwrap();
+        assert!(changes.blocked.is_none(), "{:?}", changes.blocked);
+        assert_eq!(changes.repo_id, Some(repo_id));
+        assert_eq!(changes.replaced, vec!["Data/patch-X.MPQ"]);
+        assert_eq!(fs::read(wow.join("Data/patch-X.MPQ")).unwrap(), installed);
+        assert_eq!(engine.db().list_repos().unwrap().len(), 1);
+        assert_eq!(engine.db().list_installs(repo_id).unwrap().len(), 1);
+
+        let removal = engine.dry_run_remove(repo_id, Some(&wow), true).unwrap();
+        assert_eq!(removal.deleted, vec!["Data/patch-X.MPQ"]);
+        assert!(wow.join("Data/patch-X.MPQ").is_file());
+        assert_eq!(engine.db().list_installs(repo_id).unwrap().len(), 1);
+    }
+
     #[test]
     fn engine_installs_labels_detects_modification_and_removes() {
         let temp = tempfile::tempdir().unwrap();
@@ -4362,4 +4604,35 @@ mod tests {
         assert!(wow.join("Data/enUS/patch-Dark-1.MPQ.disabled").is_file());
         assert!(wow.join("Data/patch-Dark-2.MPQ").is_file());
     }
+
+    #[test]
+    fn parses_cli_destinations_and_manifest_paths() {
+        assert_eq!(
+            MpqDestination::parse("data"),
+            Some(MpqDestination::DataRoot)
+        );
+        assert_eq!(
+            MpqDestination::parse("Data/"),
+            Some(MpqDestination::DataRoot)
+        );
+        assert_eq!(
+            MpqDestination::parse("enus"),
+            Some(MpqDestination::Locale("enUS".into()))
+        );
+        assert_eq!(
+            MpqDestination::parse("Data/deDE/"),
+            Some(MpqDestination::Locale("deDE".into()))
+        );
+        assert_eq!(MpqDestination::parse("Interface"), None);
+
+        assert_eq!(
+            MpqDestination::from_manifest_path("Data/patch-Z.MPQ.disabled"),
+            Some(MpqDestination::DataRoot)
+        );
+        assert_eq!(
+            MpqDestination::from_manifest_path("Data/enUS/patch-enUS-4.MPQ"),
+            Some(MpqDestination::Locale("enUS".into()))
+        );
+        assert_eq!(MpqDestination::from_manifest_path("Interface/x.MPQ"), None);
+    }
 }
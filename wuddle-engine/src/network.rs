@@ -47,7 +47,8 @@ fn persist_staged_file(staged: tempfile::NamedTempFile, destination: &Path) -> R
 /// Download an HTTPS resource without delegating redirect decisions to
 /// reqwest. Every hop is passed through `validate_url`, the response is
 /// streamed into a same-directory temporary file, and only a fully received
-/// and validated file is persisted.
+/// and validated file is persisted. A saved forge token is attached only to
+/// hops on the host it was saved for.
 pub(crate) async fn download_to_file<U, F>(
     client: &Client,
     initial_url: &str,
@@ -69,7 +70,10 @@ where
     for redirect_count in 0..=MAX_REDIRECTS {
         validate_url(current.as_str())?;
 
-        let mut response = client.get(current.clone()).send().await?;
+        let mut response =
+            crate::forge::authorize_request(client.get(current.clone()), current.as_str())
+                .send()
+                .await?;
         if response.status().is_redirection() {
             if redirect_count == MAX_REDIRECTS {
                 anyhow::bail!("Download exceeded the redirect limit");
@@ -0,0 +1,536 @@
+//! The Quick Add preset catalog.
+//!
+//! Presets are listed in a versioned JSON catalog (`assets/presets.json`)
+//! built into the binary, so adding a mod no longer needs code changes. Two
+//! files in the app directory take precedence over it:
+//!
+//! - `presets.user.json`, written by the user and trusted as-is;
+//! - `presets.remote.json`, the last remote catalog whose detached Ed25519
+//!   signature (`presets.remote.json.sig`) verified against a trusted key.
+//!
+//! Signatures are checked again every time the cached remote catalog is
+//! loaded, and a remote catalog older than the built-in one is ignored so a
+//! stale or replayed file cannot roll presets back. A catalog that does not
+//! verify is never used; the built-in one is used instead.
+//!
+//! The trusted keys are [`TRUSTED_KEYS`] plus those listed, comma-separated,
+//! in `WUDDLE_PRESET_KEYS` when the binary is built or when it runs.
+
+use crate::toc::ClientFamily;
+use anyhow::{Context, Result};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine as _;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// Catalog format this build understands.
+pub const CATALOG_FORMAT: u32 = 1;
+
+/// Where release builds look for newer presets.
+pub const REMOTE_CATALOG_URL: &str =
+    "https://raw.githubusercontent.com/ZythDr/Wuddle/main/wuddle-engine/assets/presets.json";
+
+/// Base64 Ed25519 public keys remote catalogs must be signed with, in
+/// addition to those configured through [`KEYS_VAR`].
+pub const TRUSTED_KEYS: &[&str] = &[];
+
+/// Comma-separated base64 public keys to trust as well, read both when the
+/// binary is built and when it runs.
+pub const KEYS_VAR: &str = "WUDDLE_PRESET_KEYS";
+
+const EMBEDDED_CATALOG: &str = include_str!("../assets/presets.json");
+const USER_FILE: &str = "presets.user.json";
+const REMOTE_FILE: &str = "presets.remote.json";
+const SIGNATURE_SUFFIX: &str = ".sig";
+
+/// Catalog files larger than this are refused.
+const MAX_CATALOG_BYTES: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CompanionLink {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Preset {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub recommended: bool,
+    /// Shown with the preset; presence marks a known antivirus false positive.
+    #[serde(default)]
+    pub warning: Option<String>,
+    /// Addons that pair with the preset, offered as links.
+    #[serde(default)]
+    pub companion_links: Vec<CompanionLink>,
+    #[serde(default)]
+    pub expanded_notes: Vec<String>,
+    /// Installs into `Interface/AddOns` rather than the game folder.
+    #[serde(default)]
+    pub is_addon: bool,
+    #[serde(default)]
+    pub supported_clients: Vec<ClientFamily>,
+}
+
+impl Preset {
+    pub fn is_av_false_positive(&self) -> bool {
+        self.warning.is_some()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PresetCatalog {
+    pub format: u32,
+    /// Increases with every published change; newer revisions win.
+    pub revision: u64,
+    pub presets: Vec<Preset>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CatalogOrigin {
+    Embedded,
+    Remote,
+    User,
+}
+
+impl CatalogOrigin {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Embedded => "built-in",
+            Self::Remote => "signed remote",
+            Self::User => "user",
+        }
+    }
+}
+
+/// The catalog in use and why the files that outrank it were skipped.
+#[derive(Debug, Clone, Serialize)]
+pub struct LoadedCatalog {
+    pub origin: CatalogOrigin,
+    pub catalog: PresetCatalog,
+    pub problems: Vec<String>,
+}
+
+/// Parse and check a catalog file. Presets need a name and a URL without
+/// credentials; a URL listed twice is refused.
+pub fn parse_catalog(raw: &[u8]) -> Result<PresetCatalog> {
+    anyhow::ensure!(
+        raw.len() <= MAX_CATALOG_BYTES,
+        "Preset catalogs are limited to {} MiB.",
+        MAX_CATALOG_BYTES / (1024 * 1024)
+    );
+    let raw = std::str::from_utf8(raw).context("Preset catalog is not UTF-8")?;
+    let catalog: PresetCatalog =
+        serde_json::from_str(raw.trim_start_matches('\u{feff}')).context("parse preset catalog")?;
+    anyhow::ensure!(
+        catalog.format == CATALOG_FORMAT,
+        "Preset catalog format {} is not supported (expected {CATALOG_FORMAT}).",
+        catalog.format
+    );
+    let mut seen = HashSet::new();
+    for preset in &catalog.presets {
+        if preset.name.trim().is_empty() || preset.url.trim().is_empty() {
+            anyhow::bail!("Every preset needs a name and a url.");
+        }
+        crate::url_safety::reject_embedded_credentials(&preset.url)?;
+        for link in &preset.companion_links {
+            crate::url_safety::reject_embedded_credentials(&link.url)?;
+        }
+        anyhow::ensure!(
+            seen.insert(preset.url.trim_end_matches('/').to_ascii_lowercase()),
+            "{} is listed twice.",
+            preset.url
+        );
+    }
+    Ok(catalog)
+}
+
+/// The catalog built into this binary.
+pub fn embedded() -> PresetCatalog {
+    parse_catalog(EMBEDDED_CATALOG.as_bytes()).expect("built-in preset catalog is valid")
+}
+
+/// [`TRUSTED_KEYS`] and the keys configured through [`KEYS_VAR`], without
+/// duplicates. Empty while no key is configured, which refuses every remote
+/// catalog.
+pub fn trusted_keys() -> Vec<String> {
+    let configured = [
+        option_env!("WUDDLE_PRESET_KEYS").map(str::to_string),
+        std::env::var(KEYS_VAR).ok(),
+    ];
+    let mut keys = Vec::new();
+    for key in TRUSTED_KEYS
+        .iter()
+        .map(|key| key.to_string())
+        .chain(configured.iter().flatten().flat_map(|list| key_list(list)))
+    {
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+fn key_list(raw: &str) -> Vec<String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Check a detached base64 Ed25519 `signature` of `raw` against base64
+/// public keys. Any one key verifying is enough.
+pub fn verify_signature(raw: &[u8], signature: &str, trusted_keys: &[&str]) -> Result<()> {
+    anyhow::ensure!(
+        !trusted_keys.is_empty(),
+        "No trusted keys are configured for preset catalogs."
+    );
+    let signature = BASE64
+        .decode(signature.trim())
+        .context("Preset catalog signature is not base64")?;
+    let verified = trusted_keys.iter().any(|key| {
+        BASE64.decode(key.trim()).is_ok_and(|key| {
+            UnparsedPublicKey::new(&ED25519, key)
+                .verify(raw, &signature)
+                .is_ok()
+        })
+    });
+    anyhow::ensure!(
+        verified,
+        "Preset catalog signature does not match a trusted key."
+    );
+    Ok(())
+}
+
+/// A new signing key as PKCS#8 DER, with its base64 public key.
+pub fn generate_signing_key() -> Result<(Vec<u8>, String)> {
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&ring::rand::SystemRandom::new())
+        .map_err(|_| anyhow::anyhow!("Could not generate a signing key."))?;
+    let pair = Ed25519KeyPair::from_pkcs8(pkcs8.as_ref())
+        .map_err(|_| anyhow::anyhow!("Could not read the generated signing key."))?;
+    let public = BASE64.encode(pair.public_key().as_ref());
+    Ok((pkcs8.as_ref().to_vec(), public))
+}
+
+/// Sign `raw` with a PKCS#8 Ed25519 key, returning the base64 signature.
+pub fn sign_catalog(raw: &[u8], pkcs8: &[u8]) -> Result<String> {
+    let pair = Ed25519KeyPair::from_pkcs8(pkcs8)
+        .map_err(|_| anyhow::anyhow!("Signing key is not a PKCS#8 Ed25519 key."))?;
+    Ok(BASE64.encode(pair.sign(raw).as_ref()))
+}
+
+fn read_optional(path: &Path) -> Result<Option<Vec<u8>>> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error).with_context(|| format!("read {}", path.display())),
+    }
+}
+
+fn load_remote(dir: &Path, trusted_keys: &[&str]) -> Result<Option<PresetCatalog>> {
+    let Some(raw) = read_optional(&dir.join(REMOTE_FILE))? else {
+        return Ok(None);
+    };
+    let signature = read_optional(&dir.join(format!("{REMOTE_FILE}{SIGNATURE_SUFFIX}")))?
+        .context("The cached remote catalog has no signature.")?;
+    verify_signature(&raw, &String::from_utf8_lossy(&signature), trusted_keys)?;
+    parse_catalog(&raw).map(Some)
+}
+
+/// Pick the catalog to use from `dir`: the user file, then a verified remote
+/// catalog at least as new as the built-in one, then the built-in catalog.
+/// Unreadable or untrusted files are skipped and reported in `problems`.
+pub fn load_catalog(dir: &Path, trusted_keys: &[&str]) -> LoadedCatalog {
+    let embedded = embedded();
+    let mut problems = Vec::new();
+
+    match read_optional(&dir.join(USER_FILE))
+        .and_then(|raw| raw.map(|raw| parse_catalog(&raw)).transpose())
+    {
+        Ok(Some(catalog)) => {
+            return LoadedCatalog {
+                origin: CatalogOrigin::User,
+                catalog,
+                problems,
+            }
+        }
+        Ok(None) => {}
+        Err(error) => problems.push(format!("{USER_FILE} ignored: {error:#}")),
+    }
+
+    match load_remote(dir, trusted_keys) {
+        Ok(Some(catalog)) if catalog.revision >= embedded.revision => {
+            return LoadedCatalog {
+                origin: CatalogOrigin::Remote,
+                catalog,
+                problems,
+            }
+        }
+        Ok(Some(catalog)) => problems.push(format!(
+            "{REMOTE_FILE} ignored: revision {} is older than the built-in revision {}.",
+            catalog.revision, embedded.revision
+        )),
+        Ok(None) => {}
+        Err(error) => problems.push(format!("{REMOTE_FILE} ignored: {error:#}")),
+    }
+
+    LoadedCatalog {
+        origin: CatalogOrigin::Embedded,
+        catalog: embedded,
+        problems,
+    }
+}
+
+/// Check a fetched catalog and its signature, and cache it in `dir` when it
+/// is newer than both the built-in and the cached remote catalog. Returns the
+/// catalog when it was stored.
+pub fn store_remote_catalog(
+    dir: &Path,
+    raw: &[u8],
+    signature: &str,
+    trusted_keys: &[&str],
+) -> Result<Option<PresetCatalog>> {
+    verify_signature(raw, signature, trusted_keys)?;
+    let catalog = parse_catalog(raw)?;
+    let current = load_remote(dir, trusted_keys)
+        .ok()
+        .flatten()
+        .map_or(0, |cached| cached.revision)
+        .max(embedded().revision);
+    if catalog.revision <= current {
+        return Ok(None);
+    }
+    fs::create_dir_all(dir).with_context(|| format!("create {}", dir.display()))?;
+    for (name, bytes) in [
+        (
+            format!("{REMOTE_FILE}{SIGNATURE_SUFFIX}"),
+            signature.trim().as_bytes(),
+        ),
+        (REMOTE_FILE.to_string(), raw),
+    ] {
+        let mut staged = tempfile::NamedTempFile::new_in(dir)?;
+        std::io::Write::write_all(&mut staged, bytes)?;
+        staged
+            .persist(dir.join(&name))
+            .map_err(|error| error.error)
+            .with_context(|| format!("write {name}"))?;
+    }
+    Ok(Some(catalog))
+}
+
+/// Addon folder name and repository URL for every addon `presets` know
+/// about, including companion addons.
+pub fn addon_sources(presets: &[Preset]) -> Vec<(String, String)> {
+    presets
+        .iter()
+        .flat_map(|preset| {
+            preset
+                .is_addon
+                .then(|| (preset.name.clone(), preset.url.clone()))
+                .into_iter()
+                .chain(
+                    preset
+                        .companion_links
+                        .iter()
+                        .map(|link| (link.name.clone(), link.url.clone())),
+                )
+        })
+        .collect()
+}
+
+/// [`load_catalog`] from the engine's app directory with [`trusted_keys`].
+pub fn load_default() -> LoadedCatalog {
+    let keys = trusted_keys();
+    let keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+    match crate::util::app_dir() {
+        Ok(dir) => load_catalog(&dir, &keys),
+        Err(error) => LoadedCatalog {
+            origin: CatalogOrigin::Embedded,
+            catalog: embedded(),
+            problems: vec![format!("Preset files were not read: {error:#}")],
+        },
+    }
+}
+
+async fn fetch_bytes(client: &reqwest::Client, url: url::Url) -> Result<Vec<u8>> {
+    let response = client.get(url).send().await?.error_for_status()?;
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_CATALOG_BYTES as u64)
+    {
+        anyhow::bail!(
+            "Preset catalogs are limited to {} MiB.",
+            MAX_CATALOG_BYTES / (1024 * 1024)
+        );
+    }
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Fetch the catalog at `url` and its signature at `url` + `.sig`, and cache
+/// it in `dir` as [`store_remote_catalog`] does. Only HTTPS URLs are fetched,
+/// and nothing is fetched while no key is trusted.
+pub async fn update_remote_catalog(
+    dir: &Path,
+    url: &str,
+    trusted_keys: &[&str],
+) -> Result<Option<PresetCatalog>> {
+    anyhow::ensure!(
+        !trusted_keys.is_empty(),
+        "No trusted keys are configured for preset catalogs."
+    );
+    let parsed = url::Url::parse(url.trim()).context("Preset catalog URL is invalid")?;
+    anyhow::ensure!(
+        parsed.scheme() == "https",
+        "Preset catalogs are only fetched over HTTPS."
+    );
+    crate::url_safety::reject_embedded_credentials(parsed.as_str())?;
+    let signature_url = url::Url::parse(&format!("{parsed}{SIGNATURE_SUFFIX}"))?;
+    let client = reqwest::Client::builder()
+        .user_agent("wuddle-engine")
+        .timeout(std::time::Duration::from_secs(30))
+        .build()?;
+    let raw = fetch_bytes(&client, parsed).await?;
+    let signature = fetch_bytes(&client, signature_url).await?;
+    store_remote_catalog(
+        dir,
+        &raw,
+        &String::from_utf8_lossy(&signature),
+        trusted_keys,
+    )
+}
+
+/// [`update_remote_catalog`] from [`REMOTE_CATALOG_URL`] into the engine's
+/// app directory with [`trusted_keys`].
+pub async fn update_default() -> Result<Option<PresetCatalog>> {
+    let dir = crate::util::app_dir()?;
+    let keys = trusted_keys();
+    let keys = keys.iter().map(String::as_str).collect::<Vec<_>>();
+    update_remote_catalog(&dir, REMOTE_CATALOG_URL, &keys).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        embedded, generate_signing_key, key_list, load_catalog, parse_catalog, sign_catalog,
+        store_remote_catalog, verify_signature, CatalogOrigin, REMOTE_FILE, USER_FILE,
+    };
+    use crate::toc::ClientFamily;
+    use std::fs;
+
+    fn catalog(revision: u64, name: &str) -> String {
+        format!(
+            r#"{{"format": 1, "revision": {revision}, "presets": [
+                {{"name": "{name}", "url": "https://github.com/a/{name}",
+                  "supported_clients": ["vanilla"]}}
+            ]}}"#
+        )
+    }
+
+    #[test]
+    fn built_in_catalog_lists_the_quick_add_presets() {
+        let catalog = embedded();
+        let names = catalog
+            .presets
+            .iter()
+            .map(|preset| preset.name.as_str())
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"VanillaFixes"));
+        assert!(names.contains(&"SuperWoW"));
+        let vanillafixes = &catalog.presets[0];
+        assert!(vanillafixes.is_av_false_positive());
+        assert_eq!(vanillafixes.supported_clients, vec![ClientFamily::Vanilla]);
+        assert!(parse_catalog(br#"{"format": 2, "revision": 1, "presets": []}"#).is_err());
+    }
+
+    #[test]
+    fn remote_catalogs_need_a_trusted_signature_and_a_newer_revision() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let (pkcs8, public) = generate_signing_key().unwrap();
+        let (other_pkcs8, _) = generate_signing_key().unwrap();
+        let keys = [public.as_str()];
+        let newer = catalog(embedded().revision + 1, "Newer");
+        let signature = sign_catalog(newer.as_bytes(), &pkcs8).unwrap();
+
+        verify_signature(newer.as_bytes(), &signature, &keys).unwrap();
+        let forged = sign_catalog(newer.as_bytes(), &other_pkcs8).unwrap();
+        assert!(store_remote_catalog(dir, newer.as_bytes(), &forged, &keys).is_err());
+        let tampered = newer.replace("Newer", "Evil");
+        assert!(store_remote_catalog(dir, tampered.as_bytes(), &signature, &keys).is_err());
+        assert!(verify_signature(newer.as_bytes(), &signature, &[]).is_err());
+
+        let stored = store_remote_catalog(dir, newer.as_bytes(), &signature, &keys).unwrap();
+        assert_eq!(stored.unwrap().presets[0].name, "Newer");
+        let loaded = load_catalog(dir, &keys);
+        assert_eq!(loaded.origin, CatalogOrigin::Remote);
+        assert!(loaded.problems.is_empty());
+
+        let stale = catalog(embedded().revision, "Stale");
+        let stale_signature = sign_catalog(stale.as_bytes(), &pkcs8).unwrap();
+        assert_eq!(
+            store_remote_catalog(dir, stale.as_bytes(), &stale_signature, &keys).unwrap(),
+            None,
+            "an older revision does not replace the cached catalog"
+        );
+
+        fs::write(dir.join(REMOTE_FILE), tampered).unwrap();
+        let loaded = load_catalog(dir, &keys);
+        assert_eq!(loaded.origin, CatalogOrigin::Embedded);
+        assert_eq!(loaded.problems.len(), 1);
+    }
+
+    #[test]
+    fn a_tampered_cached_catalog_falls_back_to_the_built_in_one() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        let (pkcs8, public) = generate_signing_key().unwrap();
+        let keys = key_list(&format!(" {public} ,, "));
+        assert_eq!(keys, vec![public.clone()]);
+        let keys = [public.as_str()];
+        let signed = catalog(embedded().revision + 1, "Signed");
+        let signature = sign_catalog(signed.as_bytes(), &pkcs8).unwrap();
+        store_remote_catalog(dir, signed.as_bytes(), &signature, &keys)
+            .unwrap()
+            .unwrap();
+        assert_eq!(load_catalog(dir, &keys).catalog.presets[0].name, "Signed");
+
+        // One changed byte in the cached file breaks the signature.
+        let tampered = signed.replace("github.com/a/", "github.com/b/");
+        fs::write(dir.join(REMOTE_FILE), &tampered).unwrap();
+        let loaded = load_catalog(dir, &keys);
+        assert_eq!(loaded.origin, CatalogOrigin::Embedded);
+        assert_eq!(loaded.catalog, embedded());
+        assert!(loaded.problems[0].contains("does not match a trusted key"));
+
+        // Without a trusted key even the untouched catalog is refused.
+        fs::write(dir.join(REMOTE_FILE), &signed).unwrap();
+        assert_eq!(load_catalog(dir, &[]).origin, CatalogOrigin::Embedded);
+    }
+
+    #[test]
+    fn a_user_catalog_overrides_the_others() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        assert_eq!(load_catalog(dir, &[]).origin, CatalogOrigin::Embedded);
+
+        fs::write(dir.join(USER_FILE), catalog(1, "Mine")).unwrap();
+        let loaded = load_catalog(dir, &[]);
+        assert_eq!(loaded.origin, CatalogOrigin::User);
+        assert_eq!(loaded.catalog.presets[0].name, "Mine");
+
+        fs::write(dir.join(USER_FILE), "{").unwrap();
+        let loaded = load_catalog(dir, &[]);
+        assert_eq!(loaded.origin, CatalogOrigin::Embedded);
+        assert_eq!(loaded.problems.len(), 1);
+    }
+}
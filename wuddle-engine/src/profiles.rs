@@ -0,0 +1,311 @@
+//! Read-only view of the desktop app's settings document.
+//!
+//! The GUI keeps one SQLite database per profile inside its data directory.
+//! Headless tools read the same `settings.json` here so they operate on the
+//! same repositories, WoW directory and launch configuration as the app. This
+//! module never writes the document; recovery of damaged settings remains the
+//! GUI's responsibility.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+pub const DEFAULT_PROFILE_ID: &str = "default";
+const SETTINGS_FILE_NAME: &str = "settings.json";
+const SETTINGS_BACKUP_FILE_NAME: &str = "settings.json.bak";
+const PORTABLE_FLAG_FILE_NAME: &str = "wuddle-portable.flag";
+const PORTABLE_DATA_DIR_NAME: &str = "wuddle-data";
+
+/// One profile as stored by the GUI. Only the fields that matter outside the
+/// app are read; unknown fields are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileSettings {
+    pub id: String,
+    pub name: String,
+    pub wow_dir: String,
+    pub auto_launch_exe: Option<String>,
+    pub launch_method: String,
+    pub clear_wdb: bool,
+    pub lutris_target: String,
+    pub wine_command: String,
+    pub wine_args: String,
+    pub custom_command: String,
+    pub custom_args: String,
+    pub working_dir: String,
+    pub env_text: String,
+}
+
+impl Default for ProfileSettings {
+    fn default() -> Self {
+        Self {
+            id: DEFAULT_PROFILE_ID.to_string(),
+            name: "Default".to_string(),
+            wow_dir: String::new(),
+            auto_launch_exe: None,
+            launch_method: "auto".to_string(),
+            clear_wdb: false,
+            lutris_target: String::new(),
+            wine_command: "wine".to_string(),
+            wine_args: String::new(),
+            custom_command: String::new(),
+            custom_args: String::new(),
+            working_dir: String::new(),
+            env_text: String::new(),
+        }
+    }
+}
+
+impl ProfileSettings {
+    /// The profile's WoW directory, if one has been configured.
+    pub fn wow_dir(&self) -> Option<PathBuf> {
+        let trimmed = self.wow_dir.trim();
+        (!trimmed.is_empty()).then(|| PathBuf::from(trimmed))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+struct SettingsDocument {
+    wow_dir: String,
+    active_profile_id: String,
+    opt_symlinks: bool,
+    opt_xattr: bool,
+    ssh_key: String,
+    profiles: Vec<ProfileSettings>,
+}
+
+impl Default for SettingsDocument {
+    fn default() -> Self {
+        Self {
+            wow_dir: String::new(),
+            active_profile_id: DEFAULT_PROFILE_ID.to_string(),
+            opt_symlinks: false,
+            opt_xattr: true,
+            ssh_key: String::new(),
+            profiles: vec![ProfileSettings::default()],
+        }
+    }
+}
+
+/// The GUI's settings as seen from a headless tool.
+#[derive(Debug, Clone)]
+pub struct GuiSettings {
+    pub data_dir: PathBuf,
+    pub active_profile_id: String,
+    pub opt_symlinks: bool,
+    pub opt_xattr: bool,
+    pub ssh_key: String,
+    pub profiles: Vec<ProfileSettings>,
+}
+
+impl GuiSettings {
+    /// Load the settings document from `data_dir`, falling back to the GUI's
+    /// last known-good backup. A data directory without either file yields
+    /// the GUI's defaults: a single, unconfigured `default` profile.
+    pub fn load(data_dir: &Path) -> Result<Self> {
+        let primary = data_dir.join(SETTINGS_FILE_NAME);
+        let backup = data_dir.join(SETTINGS_BACKUP_FILE_NAME);
+        let document = match read_document(&primary) {
+            Ok(document) => document,
+            Err(_) if !primary.exists() && !backup.exists() => SettingsDocument::default(),
+            Err(primary_error) => read_document(&backup).map_err(|backup_error| {
+                anyhow::anyhow!(
+                    "could not read {} ({primary_error:#}) or its backup ({backup_error:#})",
+                    primary.display()
+                )
+            })?,
+        };
+
+        let mut profiles = document.profiles;
+        if profiles.is_empty() {
+            profiles.push(ProfileSettings::default());
+        }
+        // Settings written before profiles existed kept the WoW directory at
+        // the top level; the GUI still treats it as the default profile's.
+        if let Some(default) = profiles
+            .iter_mut()
+            .find(|profile| profile.id == DEFAULT_PROFILE_ID && profile.wow_dir.is_empty())
+        {
+            default.wow_dir = document.wow_dir;
+        }
+
+        Ok(Self {
+            data_dir: data_dir.to_path_buf(),
+            active_profile_id: document.active_profile_id,
+            opt_symlinks: document.opt_symlinks,
+            opt_xattr: document.opt_xattr,
+            ssh_key: document.ssh_key,
+            profiles,
+        })
+    }
+
+    /// Load the settings from the directory the GUI itself would use.
+    pub fn load_default() -> Result<Self> {
+        Self::load(&gui_data_dir()?)
+    }
+
+    /// Find a profile by exact id, then by case-insensitive display name.
+    pub fn find_profile(&self, selector: &str) -> Result<&ProfileSettings> {
+        let selector = selector.trim();
+        if let Some(profile) = self.profiles.iter().find(|profile| profile.id == selector) {
+            return Ok(profile);
+        }
+        let mut by_name = self
+            .profiles
+            .iter()
+            .filter(|profile| profile.name.trim().eq_ignore_ascii_case(selector));
+        match (by_name.next(), by_name.next()) {
+            (Some(profile), None) => Ok(profile),
+            (Some(_), Some(_)) => {
+                anyhow::bail!("several profiles are named {selector:?}; select one by id")
+            }
+            (None, _) => anyhow::bail!("no profile with id or name {selector:?}"),
+        }
+    }
+
+    /// The SSH key file chosen in the app's options, if any.
+    pub fn ssh_key_path(&self) -> Option<PathBuf> {
+        let trimmed = self.ssh_key.trim();
+        (!trimmed.is_empty()).then(|| PathBuf::from(trimmed))
+    }
+
+    /// The profile the GUI opens on launch.
+    pub fn active_profile(&self) -> &ProfileSettings {
+        self.profiles
+            .iter()
+            .find(|profile| profile.id == self.active_profile_id)
+            .unwrap_or(&self.profiles[0])
+    }
+
+    pub fn profile_db_path(&self, profile_id: &str) -> PathBuf {
+        self.data_dir.join(profile_db_file_name(profile_id))
+    }
+}
+
+/// Database file name for a profile. `default` uses `wuddle.sqlite`; other
+/// profiles use `wuddle-{id}.sqlite`.
+pub fn profile_db_file_name(profile_id: &str) -> String {
+    if profile_id == DEFAULT_PROFILE_ID {
+        "wuddle.sqlite".to_string()
+    } else {
+        format!("wuddle-{profile_id}.sqlite")
+    }
+}
+
+fn read_document(path: &Path) -> Result<SettingsDocument> {
+    let data = fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+    serde_json::from_str(&data).with_context(|| format!("parse {}", path.display()))
+}
+
+/// Resolve the GUI's data directory without creating it.
+///
+/// Windows builds keep data in `wuddle-data` beside the stable launcher (or
+/// `WUDDLE_DATA_DIR`). Other platforms use the standard application-data
+/// directory unless portable mode is enabled through `WUDDLE_PORTABLE` or a
+/// `wuddle-portable.flag` file beside the executable.
+///
+/// The launcher and portable locations are found from the *running*
+/// executable, so they only match the GUI's when this binary is installed
+/// beside it. Callers elsewhere need the directory passed in explicitly.
+pub fn gui_data_dir() -> Result<PathBuf> {
+    let executable = std::env::current_exe().context("locate the running executable")?;
+    #[cfg(windows)]
+    {
+        let override_dir = std::env::var_os("WUDDLE_DATA_DIR")
+            .map(PathBuf::from)
+            .filter(|path| !path.as_os_str().is_empty());
+        if let Some(dir) = override_dir {
+            return Ok(std::env::current_dir()?.join(dir));
+        }
+        let exe_dir = executable
+            .parent()
+            .context("the executable has no parent directory")?;
+        let root = exe_dir
+            .ancestors()
+            .find(|dir| dir.join("Wuddle.exe").is_file() && dir.join("versions").is_dir())
+            .unwrap_or(exe_dir);
+        Ok(root.join(PORTABLE_DATA_DIR_NAME))
+    }
+    #[cfg(not(windows))]
+    {
+        #[cfg(target_os = "linux")]
+        let anchor = std::env::var_os("APPIMAGE")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .unwrap_or(executable);
+        #[cfg(not(target_os = "linux"))]
+        let anchor = executable;
+        let portable_root = anchor
+            .parent()
+            .context("the executable has no parent directory")?;
+        if portable_mode_enabled(portable_root) {
+            return Ok(portable_root.join(PORTABLE_DATA_DIR_NAME));
+        }
+        Ok(dirs::data_dir().context("no data_dir")?.join("wuddle"))
+    }
+}
+
+#[cfg(not(windows))]
+fn portable_mode_enabled(portable_root: &Path) -> bool {
+    let env_enabled = std::env::var("WUDDLE_PORTABLE")
+        .map(|value| matches!(value.to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+        .unwrap_or(false);
+    env_enabled || portable_root.join(PORTABLE_FLAG_FILE_NAME).exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_settings_yield_the_default_profile() {
+        let temp = tempfile::tempdir().unwrap();
+        let settings = GuiSettings::load(temp.path()).unwrap();
+
+        assert_eq!(settings.active_profile().id, DEFAULT_PROFILE_ID);
+        assert_eq!(
+            settings.profile_db_path(DEFAULT_PROFILE_ID),
+            temp.path().join("wuddle.sqlite")
+        );
+    }
+
+    #[test]
+    fn profiles_resolve_by_id_or_name_and_fall_back_to_backup() {
+        let temp = tempfile::tempdir().unwrap();
+        fs::write(temp.path().join(SETTINGS_FILE_NAME), "{ not json").unwrap();
+        fs::write(
+            temp.path().join(SETTINGS_BACKUP_FILE_NAME),
+            r#"{
+                "wow_dir": "/games/legacy",
+                "active_profile_id": "turtle-1",
+                "theme": "cata",
+                "profiles": [
+                    {"id": "default", "name": "Default"},
+                    {"id": "turtle-1", "name": "Turtle", "wow_dir": "/games/turtle",
+                     "launch_method": "wine", "show_mods_tab": false}
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let settings = GuiSettings::load(temp.path()).unwrap();
+        let turtle = settings.find_profile("turtle").unwrap();
+        assert_eq!(turtle.id, "turtle-1");
+        assert_eq!(turtle.launch_method, "wine");
+        assert_eq!(turtle.wow_dir(), Some(PathBuf::from("/games/turtle")));
+        assert_eq!(settings.active_profile().id, "turtle-1");
+        assert_eq!(
+            settings.profile_db_path(&turtle.id),
+            temp.path().join("wuddle-turtle-1.sqlite")
+        );
+        assert_eq!(
+            settings.find_profile("default").unwrap().wow_dir(),
+            Some(PathBuf::from("/games/legacy"))
+        );
+        assert!(settings.find_profile("missing").is_err());
+    }
+}
@@ -0,0 +1,309 @@
+//! Release notes between a repo's installed version and the version an
+//! update would install, rendered as markdown for the "What's new" dialog.
+//!
+//! Release repos use the bodies of every forge release in that range;
+//! addon_git repos use the commit log between the local and remote HEAD.
+
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::forge::{self, detect_repo, git_sync};
+use crate::{CheckMode, Engine, InstallMode, LatestRelease, UpdatePlan};
+
+/// At most this many releases or commits are collected for one update.
+const MAX_NOTES_ENTRIES: usize = 50;
+
+/// Where a repo's notes come from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NotesSource {
+    Releases,
+    Commits,
+}
+
+/// One release, or one commit for addon_git repos.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseNoteEntry {
+    /// Release tag, or short commit id.
+    pub version: String,
+    /// Release name, or the commit's summary line.
+    pub title: Option<String>,
+    pub published_at: Option<i64>,
+    /// Markdown release body, or the rest of the commit message.
+    pub body: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReleaseNotes {
+    pub repo_id: i64,
+    pub source: NotesSource,
+    /// Installed version, if any.
+    pub from: Option<String>,
+    /// Version the update would install.
+    pub to: String,
+    /// Newest first, ending just after `from`.
+    pub entries: Vec<ReleaseNoteEntry>,
+    /// False when older entries were left out, either past the entry limit
+    /// or because the installed version could not be found.
+    pub complete: bool,
+}
+
+impl ReleaseNotes {
+    /// The notes as one markdown document.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        if self.entries.is_empty() {
+            out.push_str("Nothing new between the installed and the latest version.\n");
+            return out;
+        }
+        match self.source {
+            NotesSource::Releases => {
+                for entry in &self.entries {
+                    let title = entry
+                        .title
+                        .as_deref()
+                        .map(str::trim)
+                        .filter(|title| !title.is_empty() && *title != entry.version);
+                    match title {
+                        Some(title) => {
+                            out.push_str(&format!("## {} \u{2014} {title}\n\n", entry.version))
+                        }
+                        None => out.push_str(&format!("## {}\n\n", entry.version)),
+                    }
+                    let body = entry.body.trim();
+                    if body.is_empty() {
+                        out.push_str("*No release notes.*\n\n");
+                    } else {
+                        out.push_str(body);
+                        out.push_str("\n\n");
+                    }
+                }
+            }
+            NotesSource::Commits => {
+                for entry in &self.entries {
+                    out.push_str(&format!(
+                        "- `{}` {}\n",
+                        entry.version,
+                        entry.title.as_deref().unwrap_or_default()
+                    ));
+                }
+                out.push('\n');
+            }
+        }
+        if !self.complete {
+            out.push_str("*Older changes are not shown.*\n");
+        }
+        out
+    }
+}
+
+/// The releases after `from` up to and including `to`, newest first, from
+/// a newest-first release list. Also returns whether the range reached
+/// `from`.
+fn releases_between<'a>(
+    releases: &'a [LatestRelease],
+    from: Option<&str>,
+    to: &str,
+    limit: usize,
+) -> Result<(Vec<&'a LatestRelease>, bool)> {
+    let position = |tag: &str| {
+        releases
+            .iter()
+            .position(|release| release.tag == tag)
+            .or_else(|| {
+                releases
+                    .iter()
+                    .position(|release| release.tag.eq_ignore_ascii_case(tag))
+            })
+    };
+    let start = position(to).with_context(|| format!("The forge does not list release {to}"))?;
+    let (end, found) = match from {
+        // A fresh install has nothing to compare against.
+        None => (start + 1, true),
+        Some(from) => match position(from) {
+            Some(end) => (end.max(start), true),
+            None => (releases.len(), false),
+        },
+    };
+    let range = &releases[start..end];
+    let complete = found && range.len() <= limit;
+    Ok((range.iter().take(limit).collect(), complete))
+}
+
+impl Engine {
+    /// What changed between the installed version of `repo_id` and the
+    /// version an update would install.
+    pub async fn release_notes_for_repo(
+        &self,
+        repo_id: i64,
+        wow_dir: Option<&Path>,
+    ) -> Result<ReleaseNotes> {
+        let repo = self.db().get_repo(repo_id)?;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let plan = self
+            .build_update_plan_for_repo(
+                &repo,
+                false,
+                false,
+                wow_dir,
+                CheckMode::Force,
+                None,
+                None,
+                &cancelled,
+            )
+            .await?;
+        if let Some(err) = plan.error.clone() {
+            anyhow::bail!(err);
+        }
+        self.release_notes(&plan, wow_dir).await
+    }
+
+    /// What changed between `plan.current` and the version `plan` installs.
+    pub async fn release_notes(
+        &self,
+        plan: &UpdatePlan,
+        wow_dir: Option<&Path>,
+    ) -> Result<ReleaseNotes> {
+        if plan.forge.eq_ignore_ascii_case("direct") {
+            anyhow::bail!("Direct downloads have no release notes.");
+        }
+        let from = plan
+            .current
+            .clone()
+            .filter(|version| !version.trim().is_empty());
+        if matches!(plan.mode, InstallMode::AddonGit) {
+            return self.git_release_notes(plan, from, wow_dir);
+        }
+
+        let det = detect_repo(&plan.url)?;
+        let releases = forge::list_releases(&self.client, &det).await?;
+        let (range, complete) = releases_between(
+            &releases,
+            from.as_deref(),
+            &plan.install_version,
+            MAX_NOTES_ENTRIES,
+        )?;
+        Ok(ReleaseNotes {
+            repo_id: plan.repo_id,
+            source: NotesSource::Releases,
+            from,
+            to: plan.install_version.clone(),
+            entries: range
+                .into_iter()
+                .map(|release| ReleaseNoteEntry {
+                    version: release.tag.clone(),
+                    title: release.name.clone(),
+                    published_at: release.published_at,
+                    body: release.body.clone().unwrap_or_default(),
+                })
+                .collect(),
+            complete,
+        })
+    }
+
+    fn git_release_notes(
+        &self,
+        plan: &UpdatePlan,
+        from: Option<String>,
+        wow_dir: Option<&Path>,
+    ) -> Result<ReleaseNotes> {
+        let repo = self.db().get_repo(plan.repo_id)?;
+        let local_oid = wow_dir
+            .and_then(|wow_dir| self.addon_git_worktree_dir(repo.id, wow_dir, &repo))
+            .and_then(|path| git_sync::local_head(&path).ok().flatten())
+            .map(|head| head.oid)
+            .or(repo.installed_asset_id.clone())
+            .filter(|oid| git_sync::is_commit_id(oid));
+        let reference = plan.asset_name.strip_prefix("git:").unwrap_or_default();
+        let (commits, complete) = git_sync::commit_log(
+            &plan.url,
+            reference,
+            local_oid.as_deref(),
+            &plan.asset_id,
+            MAX_NOTES_ENTRIES,
+        )
+        .with_context(|| format!("read the commit log of {}/{}", plan.owner, plan.name))?;
+        Ok(ReleaseNotes {
+            repo_id: plan.repo_id,
+            source: NotesSource::Commits,
+            from,
+            to: plan.install_version.clone(),
+            entries: commits
+                .into_iter()
+                .map(|commit| ReleaseNoteEntry {
+                    version: commit.short_oid,
+                    title: Some(commit.summary),
+                    published_at: Some(commit.time_unix),
+                    body: commit.body,
+                })
+                .collect(),
+            complete,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(tag: &str, body: &str) -> LatestRelease {
+        LatestRelease {
+            tag: tag.to_string(),
+            name: Some(format!("Release {tag}")),
+            prerelease: false,
+            assets: Vec::new(),
+            published_at: None,
+            body: Some(body.to_string()),
+        }
+    }
+
+    #[test]
+    fn notes_cover_the_releases_after_the_installed_one() {
+        let releases = ["v4", "v3", "v2", "v1"].map(|tag| release(tag, &format!("{tag} notes")));
+        fn tags(range: Vec<&LatestRelease>) -> Vec<&str> {
+            range
+                .into_iter()
+                .map(|release| release.tag.as_str())
+                .collect()
+        }
+
+        let (range, complete) = releases_between(&releases, Some("v1"), "v3", 50).unwrap();
+        assert_eq!(tags(range), vec!["v3", "v2"]);
+        assert!(complete);
+
+        let (range, complete) = releases_between(&releases, None, "v4", 50).unwrap();
+        assert_eq!(tags(range), vec!["v4"]);
+        assert!(complete);
+
+        let (range, complete) = releases_between(&releases, Some("gone"), "v4", 2).unwrap();
+        assert_eq!(tags(range), vec!["v4", "v3"]);
+        assert!(!complete);
+
+        // Rolling back past the installed version has nothing new to show.
+        let (range, _) = releases_between(&releases, Some("v3"), "v2", 50).unwrap();
+        assert!(range.is_empty());
+        assert!(releases_between(&releases, Some("v1"), "v9", 50).is_err());
+
+        let notes = ReleaseNotes {
+            repo_id: 1,
+            source: NotesSource::Releases,
+            from: Some("v1".to_string()),
+            to: "v2".to_string(),
+            entries: vec![ReleaseNoteEntry {
+                version: "v2".to_string(),
+                title: Some("Release v2".to_string()),
+                published_at: None,
+                body: "- Fixed things".to_string(),
+            }],
+            complete: false,
+        };
+        assert_eq!(
+            notes.to_markdown(),
+            "## v2 \u{2014} Release v2\n\n- Fixed things\n\n*Older changes are not shown.*\n"
+        );
+    }
+}
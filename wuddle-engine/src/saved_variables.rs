@@ -0,0 +1,927 @@
+//! Snapshots of the addon settings the game keeps in
+//! `WTF/Account/<ACCOUNT>/SavedVariables/*.lua` and
+//! `WTF/Account/<ACCOUNT>/<Realm>/<Character>/SavedVariables/*.lua`.
+//!
+//! Snapshots are plain copies under `<wow_dir>/.wuddle/saved-variables/<id>`
+//! with a `manifest.json`, so they can be inspected or copied back by hand.
+//! Each file belongs to the addon named by its file stem. Restoring rewrites
+//! a single addon's files and refuses while the game is running, since the
+//! client writes every SavedVariables file again on logout.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MANIFEST_NAME: &str = "manifest.json";
+const FILES_DIR: &str = "files";
+const SAVED_VARIABLES: &str = "SavedVariables";
+/// Lines of unchanged context kept around each change of a diff.
+const DIFF_CONTEXT: usize = 3;
+/// Beyond this many edits a file is shown as replaced instead of diffed.
+const MAX_EDIT_DISTANCE: usize = 4000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotTrigger {
+    Manual,
+    Scheduled,
+    /// Taken automatically before a restore overwrites current files.
+    BeforeRestore,
+    /// Taken before an install or update replaces an addon; holds only the
+    /// files of that repo's addons.
+    BeforeUpdate,
+}
+
+impl SnapshotTrigger {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Manual => "manual",
+            Self::Scheduled => "scheduled",
+            Self::BeforeRestore => "before restore",
+            Self::BeforeUpdate => "before update",
+        }
+    }
+
+    /// Whether snapshots of this kind hold every addon's files.
+    fn covers_all_addons(self) -> bool {
+        self != Self::BeforeUpdate
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    /// Path below `WTF/Account`, with `/` separators.
+    pub path: String,
+    pub addon: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+impl SnapshotFile {
+    /// `Account-wide (ACCOUNT)` or `Name - Realm (ACCOUNT)`.
+    pub fn scope(&self) -> String {
+        scope_label(&self.path)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedVariablesSnapshot {
+    pub id: String,
+    pub created_unix: i64,
+    pub trigger: SnapshotTrigger,
+    pub files: Vec<SnapshotFile>,
+}
+
+impl SavedVariablesSnapshot {
+    /// Addons with at least one file in the snapshot, sorted case-insensitively.
+    pub fn addons(&self) -> Vec<String> {
+        let mut seen = BTreeSet::new();
+        let mut addons = self
+            .files
+            .iter()
+            .filter(|file| seen.insert(file.addon.to_ascii_lowercase()))
+            .map(|file| file.addon.clone())
+            .collect::<Vec<_>>();
+        addons.sort_by_key(|addon| addon.to_ascii_lowercase());
+        addons
+    }
+
+    fn same_contents(&self, other: &Self) -> bool {
+        self.files.len() == other.files.len()
+            && self
+                .files
+                .iter()
+                .zip(&other.files)
+                .all(|(left, right)| left.path == right.path && left.sha256 == right.sha256)
+    }
+}
+
+/// How many snapshots to keep. The newest snapshot is never pruned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnapshotRetention {
+    pub keep_last: usize,
+    pub max_age_days: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DiffHunk {
+    /// 1-based first line of the hunk in the older file.
+    pub old_start: usize,
+    /// 1-based first line of the hunk in the newer file.
+    pub new_start: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileChange {
+    Added,
+    Removed,
+    Modified,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FileDiff {
+    pub path: String,
+    pub scope: String,
+    pub change: FileChange,
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RestoreReport {
+    pub addon: String,
+    pub snapshot_id: String,
+    pub restored_files: usize,
+    /// Snapshot of the files as they were before the restore, if any existed.
+    pub backup_id: Option<String>,
+}
+
+/// Where the snapshots of `wow_dir` are kept.
+pub fn snapshot_root(wow_dir: &Path) -> PathBuf {
+    wow_dir.join(".wuddle").join("saved-variables")
+}
+
+fn now_unix() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn account_dir(wow_dir: &Path) -> Option<PathBuf> {
+    crate::mpq::find_case_insensitive_child(wow_dir, "WTF")
+        .and_then(|wtf| crate::mpq::find_case_insensitive_child(&wtf, "Account"))
+}
+
+fn scope_label(path: &str) -> String {
+    let parts = path.split('/').collect::<Vec<_>>();
+    match parts.as_slice() {
+        [account, _, _] => format!("Account-wide ({account})"),
+        [account, realm, name, _, _] => format!("{name} - {realm} ({account})"),
+        _ => path.to_string(),
+    }
+}
+
+/// `.lua` files of one `SavedVariables` folder, keyed by their path below
+/// `WTF/Account`. The game's `.lua.bak` copies are skipped.
+fn collect_folder(dir: &Path, prefix: &str, out: &mut Vec<(String, String, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(name) = entry.file_name().to_str().map(str::to_string) else {
+            continue;
+        };
+        let Some(stem) = name
+            .len()
+            .checked_sub(4)
+            .filter(|&cut| name.is_char_boundary(cut) && name[cut..].eq_ignore_ascii_case(".lua"))
+            .map(|cut| name[..cut].to_string())
+        else {
+            continue;
+        };
+        if stem.is_empty() || !path.is_file() {
+            continue;
+        }
+        out.push((format!("{prefix}/{name}"), stem, path));
+    }
+}
+
+/// Every SavedVariables file under `WTF/Account`, as
+/// `(path below WTF/Account, addon, absolute path)`, sorted by path.
+fn current_files(wow_dir: &Path) -> Vec<(String, String, PathBuf)> {
+    let Some(accounts) = account_dir(wow_dir) else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    for (account, account_path) in visible_dirs(&accounts) {
+        for (child, child_path) in visible_dirs(&account_path) {
+            if child.eq_ignore_ascii_case(SAVED_VARIABLES) {
+                collect_folder(&child_path, &format!("{account}/{child}"), &mut files);
+                continue;
+            }
+            for (character, character_path) in visible_dirs(&child_path) {
+                if let Some(saved) =
+                    crate::mpq::find_case_insensitive_child(&character_path, SAVED_VARIABLES)
+                {
+                    let folder = saved
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .unwrap_or(SAVED_VARIABLES)
+                        .to_string();
+                    collect_folder(
+                        &saved,
+                        &format!("{account}/{child}/{character}/{folder}"),
+                        &mut files,
+                    );
+                }
+            }
+        }
+    }
+    files.sort_by(|left, right| left.0.cmp(&right.0));
+    files
+}
+
+fn visible_dirs(dir: &Path) -> Vec<(String, PathBuf)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_str()?.to_string();
+            (!name.starts_with('.')).then(|| (name, entry.path()))
+        })
+        .collect()
+}
+
+/// A manifest path as a relative path, rejecting anything that could leave
+/// the folder it is joined to.
+fn safe_relative(path: &str) -> Result<PathBuf> {
+    let relative = PathBuf::from(path);
+    anyhow::ensure!(
+        !path.is_empty()
+            && relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_))),
+        "snapshot manifest has an unsafe path: {path}"
+    );
+    Ok(relative)
+}
+
+fn read_manifest(dir: &Path) -> Result<SavedVariablesSnapshot> {
+    let path = dir.join(MANIFEST_NAME);
+    let raw = fs::read(&path).with_context(|| format!("read {}", path.display()))?;
+    serde_json::from_slice(&raw).with_context(|| format!("parse {}", path.display()))
+}
+
+/// Snapshots of `wow_dir`, newest first. Unreadable snapshot folders are
+/// skipped.
+pub fn list_snapshots(wow_dir: &Path) -> Result<Vec<SavedVariablesSnapshot>> {
+    let root = snapshot_root(wow_dir);
+    let entries = match fs::read_dir(&root) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error).with_context(|| format!("read {}", root.display())),
+    };
+    let mut snapshots = entries
+        .flatten()
+        .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+        .filter_map(|entry| read_manifest(&entry.path()).ok())
+        .collect::<Vec<_>>();
+    snapshots
+        .sort_by(|left, right| (right.created_unix, &right.id).cmp(&(left.created_unix, &left.id)));
+    Ok(snapshots)
+}
+
+pub fn load_snapshot(wow_dir: &Path, id: &str) -> Result<SavedVariablesSnapshot> {
+    let dir = snapshot_root(wow_dir).join(safe_relative(id)?);
+    anyhow::ensure!(dir.is_dir(), "No SavedVariables snapshot with id {id}");
+    read_manifest(&dir)
+}
+
+/// Copy every SavedVariables file into a new snapshot. A scheduled snapshot
+/// is skipped (`None`) when nothing changed since the newest one; any
+/// snapshot is skipped when the game has no SavedVariables yet.
+pub fn take_snapshot(
+    wow_dir: &Path,
+    trigger: SnapshotTrigger,
+) -> Result<Option<SavedVariablesSnapshot>> {
+    write_snapshot(wow_dir, trigger, current_files(wow_dir))
+}
+
+/// Copy only the SavedVariables files of `addons` (folder names, matched
+/// case-insensitively) into a new snapshot. `None` when none of them has
+/// saved anything yet.
+pub fn take_addon_snapshot(
+    wow_dir: &Path,
+    trigger: SnapshotTrigger,
+    addons: &[String],
+) -> Result<Option<SavedVariablesSnapshot>> {
+    let sources = current_files(wow_dir)
+        .into_iter()
+        .filter(|(_, addon, _)| addons.iter().any(|name| name.eq_ignore_ascii_case(addon)))
+        .collect();
+    write_snapshot(wow_dir, trigger, sources)
+}
+
+fn write_snapshot(
+    wow_dir: &Path,
+    trigger: SnapshotTrigger,
+    sources: Vec<(String, String, PathBuf)>,
+) -> Result<Option<SavedVariablesSnapshot>> {
+    if sources.is_empty() {
+        return Ok(None);
+    }
+    let created_unix = now_unix();
+    let root = snapshot_root(wow_dir);
+    fs::create_dir_all(&root).with_context(|| format!("create {}", root.display()))?;
+    let mut id = created_unix.to_string();
+    let mut suffix = 1;
+    while root.join(&id).exists() {
+        id = format!("{created_unix}-{suffix}");
+        suffix += 1;
+    }
+
+    let staging = root.join(format!(".{id}.partial"));
+    let _ = fs::remove_dir_all(&staging);
+    let outcome = (|| -> Result<SavedVariablesSnapshot> {
+        let mut files = Vec::with_capacity(sources.len());
+        for (relative, addon, source) in &sources {
+            let target = staging.join(FILES_DIR).join(safe_relative(relative)?);
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)
+                    .with_context(|| format!("create {}", parent.display()))?;
+            }
+            let size =
+                fs::copy(source, &target).with_context(|| format!("copy {}", source.display()))?;
+            files.push(SnapshotFile {
+                path: relative.clone(),
+                addon: addon.clone(),
+                size,
+                sha256: crate::util::sha256_file_hex(&target)?,
+            });
+        }
+        Ok(SavedVariablesSnapshot {
+            id: id.clone(),
+            created_unix,
+            trigger,
+            files,
+        })
+    })();
+    let snapshot = match outcome {
+        Ok(snapshot) => snapshot,
+        Err(error) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(error);
+        }
+    };
+
+    if trigger == SnapshotTrigger::Scheduled {
+        let snapshots = list_snapshots(wow_dir)?;
+        let latest = snapshots
+            .iter()
+            .find(|snapshot| snapshot.trigger.covers_all_addons());
+        if let Some(latest) = latest {
+            if latest.same_contents(&snapshot) {
+                let _ = fs::remove_dir_all(&staging);
+                return Ok(None);
+            }
+        }
+    }
+
+    let manifest = serde_json::to_vec_pretty(&snapshot)?;
+    fs::write(staging.join(MANIFEST_NAME), manifest)
+        .with_context(|| format!("write {} manifest", staging.display()))?;
+    fs::rename(&staging, root.join(&id))
+        .with_context(|| format!("store snapshot {id} in {}", root.display()))?;
+    Ok(Some(snapshot))
+}
+
+/// Delete snapshots beyond `retention`. Returns how many were removed.
+pub fn prune_snapshots(wow_dir: &Path, retention: SnapshotRetention) -> Result<usize> {
+    let now = now_unix();
+    let root = snapshot_root(wow_dir);
+    let mut removed = 0;
+    for (index, snapshot) in list_snapshots(wow_dir)?.iter().enumerate() {
+        let too_many = index >= retention.keep_last.max(1);
+        let too_old = retention.max_age_days.is_some_and(|days| {
+            index > 0 && now - snapshot.created_unix > i64::from(days) * 24 * 60 * 60
+        });
+        if too_many || too_old {
+            let dir = root.join(safe_relative(&snapshot.id)?);
+            fs::remove_dir_all(&dir).with_context(|| format!("remove {}", dir.display()))?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+fn read_text(path: &Path) -> Result<String> {
+    let raw = fs::read(path).with_context(|| format!("read {}", path.display()))?;
+    Ok(String::from_utf8_lossy(&raw).into_owned())
+}
+
+/// `addon`'s files in `snapshot` as `(path, absolute path)`.
+fn snapshot_addon_files(
+    wow_dir: &Path,
+    snapshot: &SavedVariablesSnapshot,
+    addon: &str,
+) -> Result<Vec<(String, PathBuf)>> {
+    let files_dir = snapshot_root(wow_dir)
+        .join(safe_relative(&snapshot.id)?)
+        .join(FILES_DIR);
+    snapshot
+        .files
+        .iter()
+        .filter(|file| file.addon.eq_ignore_ascii_case(addon))
+        .map(|file| {
+            Ok((
+                file.path.clone(),
+                files_dir.join(safe_relative(&file.path)?),
+            ))
+        })
+        .collect()
+}
+
+/// Line differences of `addon`'s files between snapshot `from` and either
+/// snapshot `to` or, when `to` is `None`, the files in `WTF` right now.
+pub fn diff_addon(
+    wow_dir: &Path,
+    from: &str,
+    to: Option<&str>,
+    addon: &str,
+) -> Result<Vec<FileDiff>> {
+    let older = snapshot_addon_files(wow_dir, &load_snapshot(wow_dir, from)?, addon)?;
+    let newer = match to {
+        Some(id) => snapshot_addon_files(wow_dir, &load_snapshot(wow_dir, id)?, addon)?,
+        None => current_files(wow_dir)
+            .into_iter()
+            .filter(|(_, name, _)| name.eq_ignore_ascii_case(addon))
+            .map(|(path, _, absolute)| (path, absolute))
+            .collect(),
+    };
+
+    let paths = older
+        .iter()
+        .chain(&newer)
+        .map(|(path, _)| path.clone())
+        .collect::<BTreeSet<_>>();
+    let mut diffs = Vec::new();
+    for path in paths {
+        let find = |files: &[(String, PathBuf)]| {
+            files
+                .iter()
+                .find(|(candidate, _)| candidate == &path)
+                .map(|(_, absolute)| read_text(absolute))
+                .transpose()
+        };
+        let (before, after) = (find(&older)?, find(&newer)?);
+        let change = match (&before, &after) {
+            (None, _) => FileChange::Added,
+            (_, None) => FileChange::Removed,
+            (Some(before), Some(after)) if before == after => FileChange::Unchanged,
+            _ => FileChange::Modified,
+        };
+        let hunks = if change == FileChange::Unchanged {
+            Vec::new()
+        } else {
+            diff_text(
+                before.as_deref().unwrap_or_default(),
+                after.as_deref().unwrap_or_default(),
+            )
+        };
+        diffs.push(FileDiff {
+            scope: scope_label(&path),
+            path,
+            change,
+            hunks,
+        });
+    }
+    Ok(diffs)
+}
+
+/// Put `addon`'s files from snapshot `id` back into `WTF`. The current files
+/// are snapshotted first so the restore itself can be undone. Files the addon
+/// has gained since the snapshot are left alone.
+pub fn restore_addon(wow_dir: &Path, id: &str, addon: &str) -> Result<RestoreReport> {
+    anyhow::ensure!(
+        !game_running(wow_dir),
+        "Close the game before restoring SavedVariables; it rewrites them when you log out."
+    );
+    let snapshot = load_snapshot(wow_dir, id)?;
+    let files = snapshot_addon_files(wow_dir, &snapshot, addon)?;
+    anyhow::ensure!(
+        !files.is_empty(),
+        "Snapshot {id} has no SavedVariables for {addon}"
+    );
+
+    let backup_id = take_snapshot(wow_dir, SnapshotTrigger::BeforeRestore)?.map(|backup| backup.id);
+    let accounts = match account_dir(wow_dir) {
+        Some(dir) => dir,
+        None => wow_dir.join("WTF").join("Account"),
+    };
+    for (path, source) in &files {
+        let target = accounts.join(safe_relative(path)?);
+        let parent = target
+            .parent()
+            .with_context(|| format!("resolve folder of {}", target.display()))?;
+        fs::create_dir_all(parent).with_context(|| format!("create {}", parent.display()))?;
+        let temporary = parent.join(format!(".wuddle-restore-{}", std::process::id()));
+        fs::copy(source, &temporary).with_context(|| format!("copy {}", source.display()))?;
+        if let Err(error) = fs::rename(&temporary, &target) {
+            let _ = fs::remove_file(&temporary);
+            return Err(error).with_context(|| format!("replace {}", target.display()));
+        }
+    }
+    Ok(RestoreReport {
+        addon: snapshot
+            .files
+            .iter()
+            .find(|file| file.addon.eq_ignore_ascii_case(addon))
+            .map_or_else(|| addon.to_string(), |file| file.addon.clone()),
+        snapshot_id: snapshot.id,
+        restored_files: files.len(),
+        backup_id,
+    })
+}
+
+/// Executables in the game folder whose running process means the client
+/// may still write `WTF`. Wuddle's own executables are not counted.
+fn game_executables(wow_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(wow_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_ascii_lowercase))
+        .filter(|name| name.ends_with(".exe") && !name.starts_with("wuddle"))
+        .collect()
+}
+
+fn file_name_of(command: &str) -> String {
+    command
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or(command)
+        .trim()
+        .to_ascii_lowercase()
+}
+
+#[cfg(target_os = "linux")]
+fn running_commands() -> Vec<String> {
+    // Under Wine the game shows up as the Windows path of its executable,
+    // either as the first argument or after the wine loader.
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .bytes()
+                .all(|b| b.is_ascii_digit())
+        })
+        .filter_map(|entry| fs::read(entry.path().join("cmdline")).ok())
+        .flat_map(|cmdline| {
+            cmdline
+                .split(|byte| *byte == 0)
+                .take(2)
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+#[cfg(windows)]
+fn running_commands() -> Vec<String> {
+    use std::os::windows::process::CommandExt;
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let Ok(output) = std::process::Command::new("tasklist")
+        .args(["/FO", "CSV", "/NH"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| line.split(',').next())
+        .map(|name| name.trim_matches('"').to_string())
+        .collect()
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn running_commands() -> Vec<String> {
+    let Ok(output) = std::process::Command::new("ps")
+        .args(["-axo", "comm="])
+        .output()
+    else {
+        return Vec::new();
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether a game executable from `wow_dir` is running. Detection goes by
+/// executable name, so another install of the same client also counts.
+pub fn game_running(wow_dir: &Path) -> bool {
+    let executables = game_executables(wow_dir);
+    !executables.is_empty()
+        && running_commands()
+            .iter()
+            .any(|command| executables.contains(&file_name_of(command)))
+}
+
+/// Diff two texts line by line into hunks with [`DIFF_CONTEXT`] lines of
+/// context.
+pub fn diff_text(before: &str, after: &str) -> Vec<DiffHunk> {
+    let old = before.lines().collect::<Vec<_>>();
+    let new = after.lines().collect::<Vec<_>>();
+    let ops = diff_lines(&old, &new);
+
+    let changes = ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (kind, _))| *kind != DiffLineKind::Context)
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    let mut ranges = Vec::<(usize, usize)>::new();
+    for index in changes {
+        let start = index.saturating_sub(DIFF_CONTEXT);
+        let end = (index + DIFF_CONTEXT + 1).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    // Line numbers reached before each op.
+    let mut positions = Vec::with_capacity(ops.len());
+    let (mut old_line, mut new_line) = (1, 1);
+    for (kind, _) in &ops {
+        positions.push((old_line, new_line));
+        match kind {
+            DiffLineKind::Context => {
+                old_line += 1;
+                new_line += 1;
+            }
+            DiffLineKind::Removed => old_line += 1,
+            DiffLineKind::Added => new_line += 1,
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(start, end)| DiffHunk {
+            old_start: positions[start].0,
+            new_start: positions[start].1,
+            lines: ops[start..end]
+                .iter()
+                .map(|(kind, text)| DiffLine {
+                    kind: *kind,
+                    text: text.to_string(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+/// Shortest edit script between two line lists (Myers), with the common
+/// prefix and suffix trimmed first. Very different inputs fall back to
+/// removing every old line and adding every new one.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(DiffLineKind, &'a str)> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(left, right)| left == right)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(left, right)| left == right)
+        .count();
+    let (a, b) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    let mut ops = old[..prefix]
+        .iter()
+        .map(|line| (DiffLineKind::Context, *line))
+        .collect::<Vec<_>>();
+    match myers(a, b) {
+        Some(middle) => ops.extend(middle),
+        None => {
+            ops.extend(a.iter().map(|line| (DiffLineKind::Removed, *line)));
+            ops.extend(b.iter().map(|line| (DiffLineKind::Added, *line)));
+        }
+    }
+    ops.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|line| (DiffLineKind::Context, *line)),
+    );
+    ops
+}
+
+fn myers<'a>(a: &[&'a str], b: &[&'a str]) -> Option<Vec<(DiffLineKind, &'a str)>> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let max = n + m;
+    let offset = max + 1;
+    let index = |k: isize| (k + offset) as usize;
+    let mut v = vec![0isize; 2 * offset as usize + 1];
+    // trace[d] holds the furthest x of each diagonal -d..=d before round d.
+    let mut trace = Vec::<Vec<isize>>::new();
+    let mut distance = None;
+    'rounds: for d in 0..=max {
+        if d as usize > MAX_EDIT_DISTANCE {
+            return None;
+        }
+        trace.push(v[index(-d)..=index(d)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[index(k - 1)] < v[index(k + 1)]) {
+                v[index(k + 1)]
+            } else {
+                v[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[index(k)] = x;
+            if x >= n && y >= m {
+                distance = Some(d);
+                break 'rounds;
+            }
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (1..=distance?).rev() {
+        let previous = &trace[d as usize];
+        let at = |k: isize| previous[(k + d) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            x -= 1;
+            y -= 1;
+            ops.push((DiffLineKind::Context, a[x as usize]));
+        }
+        if x == prev_x {
+            ops.push((DiffLineKind::Added, b[prev_y as usize]));
+        } else {
+            ops.push((DiffLineKind::Removed, a[prev_x as usize]));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    while x > 0 && y > 0 {
+        x -= 1;
+        y -= 1;
+        ops.push((DiffLineKind::Context, a[x as usize]));
+    }
+    ops.reverse();
+    Some(ops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        diff_addon, diff_text, list_snapshots, prune_snapshots, restore_addon, take_addon_snapshot,
+        take_snapshot, DiffLineKind, FileChange, SnapshotRetention, SnapshotTrigger,
+    };
+    use std::fs;
+
+    #[test]
+    fn diff_keeps_context_around_changed_lines() {
+        let before = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let after = "a\nb\nc\nd\nE\nf\ng\nh\ni\nj\nk\n";
+        let hunks = diff_text(before, after);
+        assert_eq!(hunks.len(), 1);
+        assert_eq!((hunks[0].old_start, hunks[0].new_start), (2, 2));
+        let rendered = hunks[0]
+            .lines
+            .iter()
+            .map(|line| match line.kind {
+                DiffLineKind::Context => format!(" {}", line.text),
+                DiffLineKind::Added => format!("+{}", line.text),
+                DiffLineKind::Removed => format!("-{}", line.text),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rendered,
+            vec![" b", " c", " d", "-e", "+E", " f", " g", " h", " i", " j", "+k"]
+        );
+        assert!(diff_text("same\n", "same\n").is_empty());
+    }
+
+    #[test]
+    fn snapshots_skip_unchanged_schedules_and_restore_one_addon() {
+        let temp = tempfile::tempdir().unwrap();
+        let account = temp.path().join("WTF/Account/ACC");
+        let character = account.join("Realm/Alpha/SavedVariables");
+        fs::create_dir_all(account.join("SavedVariables")).unwrap();
+        fs::create_dir_all(&character).unwrap();
+        fs::write(
+            account.join("SavedVariables/pfQuest.lua"),
+            "pfQuest_config = 1\n",
+        )
+        .unwrap();
+        fs::write(account.join("SavedVariables/pfQuest.lua.bak"), "old\n").unwrap();
+        fs::write(character.join("pfQuest.lua"), "pfQuest_char = 1\n").unwrap();
+        fs::write(character.join("Atlas.lua"), "Atlas = 1\n").unwrap();
+
+        let first = take_snapshot(temp.path(), SnapshotTrigger::Manual)
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.files.len(), 3);
+        assert_eq!(first.addons(), vec!["Atlas", "pfQuest"]);
+        assert!(take_snapshot(temp.path(), SnapshotTrigger::Scheduled)
+            .unwrap()
+            .is_none());
+
+        fs::write(
+            account.join("SavedVariables/pfQuest.lua"),
+            "pfQuest_config = 2\n",
+        )
+        .unwrap();
+        fs::write(character.join("Atlas.lua"), "Atlas = 2\n").unwrap();
+        let diffs = diff_addon(temp.path(), &first.id, None, "PFQUEST").unwrap();
+        let changes = diffs
+            .iter()
+            .map(|diff| (diff.scope.as_str(), diff.change))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                ("Alpha - Realm (ACC)", FileChange::Unchanged),
+                ("Account-wide (ACC)", FileChange::Modified),
+            ]
+        );
+
+        let report = restore_addon(temp.path(), &first.id, "pfQuest").unwrap();
+        assert_eq!(report.restored_files, 2);
+        assert!(report.backup_id.is_some());
+        assert_eq!(
+            fs::read_to_string(account.join("SavedVariables/pfQuest.lua")).unwrap(),
+            "pfQuest_config = 1\n"
+        );
+        assert_eq!(
+            fs::read_to_string(character.join("Atlas.lua")).unwrap(),
+            "Atlas = 2\n"
+        );
+
+        let snapshots = list_snapshots(temp.path()).unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].trigger, SnapshotTrigger::BeforeRestore);
+        let retention = SnapshotRetention {
+            keep_last: 1,
+            max_age_days: None,
+        };
+        assert_eq!(prune_snapshots(temp.path(), retention).unwrap(), 1);
+        assert_eq!(list_snapshots(temp.path()).unwrap()[0].id, snapshots[0].id);
+    }
+
+    #[test]
+    fn addon_snapshots_hold_only_their_addons() {
+        let temp = tempfile::tempdir().unwrap();
+        let saved = temp.path().join("WTF/Account/ACC/SavedVariables");
+        fs::create_dir_all(&saved).unwrap();
+        fs::write(saved.join("pfQuest.lua"), "pfQuest_config = 1\n").unwrap();
+        fs::write(saved.join("Atlas.lua"), "Atlas = 1\n").unwrap();
+        take_snapshot(temp.path(), SnapshotTrigger::Manual)
+            .unwrap()
+            .unwrap();
+
+        let addons = vec!["PFQUEST".to_string()];
+        let partial = take_addon_snapshot(temp.path(), SnapshotTrigger::BeforeUpdate, &addons)
+            .unwrap()
+            .unwrap();
+        assert_eq!(partial.addons(), vec!["pfQuest"]);
+        let missing = vec!["Bagnon".to_string()];
+        assert!(
+            take_addon_snapshot(temp.path(), SnapshotTrigger::BeforeUpdate, &missing)
+                .unwrap()
+                .is_none()
+        );
+        // The partial snapshot is newest, but a schedule still compares
+        // against the last complete one and finds nothing changed.
+        assert!(take_snapshot(temp.path(), SnapshotTrigger::Scheduled)
+            .unwrap()
+            .is_none());
+    }
+}
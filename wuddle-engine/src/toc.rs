@@ -0,0 +1,496 @@
+//! Addon `.toc` metadata.
+//!
+//! A `.toc` file starts with `## Key: Value` lines that the game client reads
+//! before loading an addon. Titles and notes commonly carry UI escape
+//! sequences (`|cAARRGGBB…|r` colors, `|T…|t` textures); those are stripped
+//! so the values can be shown as plain text.
+//!
+//! The `## Interface` number also tells which client an addon was written
+//! for, which lets installs warn about and avoid variants built for another
+//! client.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Metadata read from an addon folder's `.toc` file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddonToc {
+    /// Client interface versions (`## Interface`), e.g. `11200` or `30300`.
+    /// Some addons list several, separated by commas.
+    #[serde(default)]
+    pub interface: Vec<u32>,
+    pub title: Option<String>,
+    pub version: Option<String>,
+    pub author: Option<String>,
+    pub notes: Option<String>,
+    /// Required addons, from `## Dependencies`, `## RequiredDeps` or any
+    /// other `## Dep…` field.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    #[serde(default)]
+    pub optional_deps: Vec<String>,
+    #[serde(default)]
+    pub saved_variables: Vec<String>,
+    #[serde(default)]
+    pub saved_variables_per_character: Vec<String>,
+    /// `X-` fields keyed by their full name, e.g. `X-Website`.
+    #[serde(default)]
+    pub extra: BTreeMap<String, String>,
+}
+
+impl AddonToc {
+    /// A display name: the title when the `.toc` has one.
+    pub fn display_title(&self) -> Option<&str> {
+        self.title.as_deref().filter(|title| !title.is_empty())
+    }
+}
+
+/// Legacy WoW client families an addon or profile can target. Modern WoW
+/// Classic clients deliberately do not match their superficially similar
+/// major version numbers because their engine and API are not compatible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientFamily {
+    Vanilla,
+    Tbc,
+    Wotlk,
+    Unsupported,
+    #[default]
+    Unknown,
+}
+
+impl ClientFamily {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Vanilla => "Vanilla 1.12.1 or earlier",
+            Self::Tbc => "The Burning Crusade 2.0–2.4.3",
+            Self::Wotlk => "Wrath of the Lich King 3.0–3.3.5",
+            Self::Unsupported => "an unsupported or modern WoW client",
+            Self::Unknown => "an unknown WoW client",
+        }
+    }
+
+    /// Whether this is one of the legacy families addons can be matched to.
+    pub fn is_legacy(self) -> bool {
+        matches!(self, Self::Vanilla | Self::Tbc | Self::Wotlk)
+    }
+
+    /// The family an `## Interface` number was written for. Classic
+    /// re-releases (`11300` and up, `20500` and up, `30400` and up) are not
+    /// legacy clients.
+    pub fn from_interface(interface: u32) -> Self {
+        match interface {
+            10000..=11200 => Self::Vanilla,
+            20000..=20400 => Self::Tbc,
+            30000..=30300 => Self::Wotlk,
+            _ => Self::Unsupported,
+        }
+    }
+
+    /// The family named by a client suffix on an addon folder or `.toc`
+    /// stem, e.g. `pfQuest-tbc` or `Atlas_Wrath`, together with the name
+    /// without that suffix.
+    pub fn from_variant_suffix(name: &str) -> Option<(&str, Self)> {
+        const SUFFIXES: &[(&str, ClientFamily)] = &[
+            ("vanilla", ClientFamily::Vanilla),
+            ("classicera", ClientFamily::Vanilla),
+            ("classic", ClientFamily::Vanilla),
+            ("era", ClientFamily::Vanilla),
+            ("112", ClientFamily::Vanilla),
+            ("tbc", ClientFamily::Tbc),
+            ("bcc", ClientFamily::Tbc),
+            ("243", ClientFamily::Tbc),
+            ("wotlkc", ClientFamily::Wotlk),
+            ("wotlk", ClientFamily::Wotlk),
+            ("wrath", ClientFamily::Wotlk),
+            ("335", ClientFamily::Wotlk),
+            ("mainline", ClientFamily::Unsupported),
+        ];
+        let (base, suffix) = name.trim().rsplit_once(['-', '_'])?;
+        let base = base.trim_end_matches(['-', '_']);
+        if base.is_empty() {
+            return None;
+        }
+        SUFFIXES
+            .iter()
+            .find(|(known, _)| suffix.eq_ignore_ascii_case(known))
+            .map(|(_, family)| (base, *family))
+    }
+}
+
+/// An addon whose `## Interface` targets another client than the profile's.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClientMismatch {
+    pub addon_name: String,
+    pub interface: Vec<u32>,
+    /// The family the addon was written for.
+    pub addon_client: ClientFamily,
+    /// The profile's client.
+    pub client: ClientFamily,
+}
+
+impl ClientMismatch {
+    /// Compare an addon's interface versions with `client`. Addons without
+    /// an interface, and clients that are not a known legacy family, never
+    /// mismatch; one matching interface is enough.
+    pub fn check(addon_name: &str, interface: &[u32], client: ClientFamily) -> Option<Self> {
+        if !client.is_legacy() || interface.is_empty() {
+            return None;
+        }
+        if interface
+            .iter()
+            .any(|&version| ClientFamily::from_interface(version) == client)
+        {
+            return None;
+        }
+        Some(Self {
+            addon_name: addon_name.to_string(),
+            interface: interface.to_vec(),
+            addon_client: ClientFamily::from_interface(interface[0]),
+            client,
+        })
+    }
+
+    /// A one-line explanation for logs and warnings.
+    pub fn message(&self) -> String {
+        let interface = self
+            .interface
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "{} is written for {} (Interface {}), but this profile runs {}.",
+            self.addon_name,
+            self.addon_client.label(),
+            interface,
+            self.client.label()
+        )
+    }
+}
+
+/// Drop addon variants built for another client when the same addon ships
+/// one for `client`, e.g. keep `pfQuest-tbc` and drop `pfQuest` and
+/// `pfQuest-wotlk` on a TBC client. When no variant is suffixed for
+/// `client`, the unsuffixed one is kept; groups without either are left
+/// alone, as are single addons and unknown clients.
+pub fn retain_client_variants<T>(
+    items: &mut Vec<T>,
+    client: ClientFamily,
+    name: impl Fn(&T) -> &str,
+) {
+    if !client.is_legacy() {
+        return;
+    }
+    let keys = items
+        .iter()
+        .map(|item| {
+            let name = name(item);
+            match ClientFamily::from_variant_suffix(name) {
+                Some((base, family)) => (base.to_ascii_lowercase(), Some(family)),
+                None => (name.trim().to_ascii_lowercase(), None),
+            }
+        })
+        .collect::<Vec<_>>();
+    // The variant each group keeps: the client's own, else the unsuffixed
+    // one; `None` keeps the whole group.
+    let preferred = |base: &str| {
+        let mut group = keys
+            .iter()
+            .filter(|(other, _)| other == base)
+            .map(|(_, family)| *family);
+        if group.clone().any(|family| family == Some(client)) {
+            Some(Some(client))
+        } else if group.any(|family| family.is_none()) {
+            Some(None)
+        } else {
+            None
+        }
+    };
+    let keep = keys
+        .iter()
+        .map(|(base, family)| preferred(base).is_none_or(|preferred| *family == preferred))
+        .collect::<Vec<_>>();
+    let mut keep = keep.into_iter();
+    items.retain(|_| keep.next().unwrap_or(true));
+}
+
+/// Parse the contents of a `.toc` file. Unknown fields and file lines are
+/// ignored; the first occurrence of a field wins, as in the game client.
+pub fn parse_toc(contents: &[u8]) -> AddonToc {
+    let contents = contents.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(contents);
+    let text = String::from_utf8_lossy(contents);
+    let mut toc = AddonToc::default();
+    let mut seen = Vec::<String>::new();
+
+    for line in text.lines() {
+        let Some(field) = line.trim().strip_prefix("##") else {
+            continue;
+        };
+        let Some((key, value)) = field.split_once(':') else {
+            continue;
+        };
+        let key = key.trim();
+        let value = strip_escape_sequences(value).trim().to_string();
+        if key.is_empty() || value.is_empty() {
+            continue;
+        }
+        let lower = key.to_ascii_lowercase();
+        if seen.contains(&lower) {
+            continue;
+        }
+        seen.push(lower.clone());
+
+        match lower.as_str() {
+            "interface" => {
+                toc.interface = value
+                    .split(',')
+                    .filter_map(|part| part.trim().parse().ok())
+                    .collect();
+            }
+            "title" => toc.title = Some(value),
+            "version" => toc.version = Some(value),
+            "author" => toc.author = Some(value),
+            "notes" => toc.notes = Some(value),
+            "optionaldeps" => toc.optional_deps.extend(split_list(&value)),
+            "savedvariables" => toc.saved_variables = split_list(&value),
+            "savedvariablespercharacter" => {
+                toc.saved_variables_per_character = split_list(&value);
+            }
+            _ if lower.starts_with("dep") || lower == "requireddeps" => {
+                for name in split_list(&value) {
+                    if !toc.dependencies.contains(&name) {
+                        toc.dependencies.push(name);
+                    }
+                }
+            }
+            _ if lower.starts_with("x-") => {
+                toc.extra.insert(key.to_string(), value);
+            }
+            _ => {}
+        }
+    }
+    toc
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Remove `|c`/`|r` color codes and `|T…|t` / `|A…|a` inline textures. A
+/// doubled `||` is a literal pipe.
+pub fn strip_escape_sequences(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '|' {
+            out.push(c);
+            continue;
+        }
+        match chars.peek().copied() {
+            Some('|') => {
+                chars.next();
+                out.push('|');
+            }
+            Some('c' | 'C') => {
+                chars.next();
+                for _ in 0..8 {
+                    if chars.next_if(char::is_ascii_hexdigit).is_none() {
+                        break;
+                    }
+                }
+            }
+            Some('r' | 'R') => {
+                chars.next();
+            }
+            Some(open @ ('T' | 'A')) => {
+                chars.next();
+                let close = open.to_ascii_lowercase();
+                while let Some(next) = chars.next() {
+                    if next == '|' && chars.next_if_eq(&close).is_some() {
+                        break;
+                    }
+                }
+            }
+            _ => out.push('|'),
+        }
+    }
+    out
+}
+
+/// The `.toc` file that describes the addon folder `dir`: the one named
+/// after the folder, then one whose name differs only by a client suffix
+/// such as `_Vanilla`, then the first in name order.
+pub fn find_addon_toc(dir: &Path) -> Option<PathBuf> {
+    let folder = dir.file_name()?.to_str()?.to_ascii_lowercase();
+    let mut tocs = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .extension()
+                    .and_then(|ext| ext.to_str())
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("toc"))
+        })
+        .collect::<Vec<_>>();
+    tocs.sort();
+    let stem = |path: &PathBuf| {
+        path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or_default()
+            .to_string()
+    };
+    tocs.iter()
+        .find(|path| stem(path).eq_ignore_ascii_case(&folder))
+        .or_else(|| {
+            tocs.iter().find(|path| {
+                crate::install::normalize_toc_stem(&stem(path)).eq_ignore_ascii_case(&folder)
+            })
+        })
+        .or_else(|| tocs.first())
+        .cloned()
+}
+
+/// Read and parse the `.toc` describing the addon folder `dir`.
+pub fn read_addon_toc(dir: &Path) -> Option<AddonToc> {
+    let path = find_addon_toc(dir)?;
+    fs::read(path).ok().map(|contents| parse_toc(&contents))
+}
+
+/// Read `{dir}/{addon_name}.toc` from the HEAD tree of the Git repository at
+/// `root`, for worktrees whose files are not checked out.
+pub(crate) fn read_git_addon_toc(root: &Path, dir: &str, addon_name: &str) -> Option<AddonToc> {
+    let repo = git2::Repository::open(root).ok()?;
+    let tree = repo.head().ok()?.peel_to_tree().ok()?;
+    let dir_tree = if dir.is_empty() {
+        tree
+    } else {
+        tree.get_path(Path::new(dir))
+            .ok()?
+            .to_object(&repo)
+            .ok()?
+            .peel_to_tree()
+            .ok()?
+    };
+    let file_name = format!("{addon_name}.toc");
+    let entry = dir_tree.iter().find(|entry| {
+        entry
+            .name()
+            .is_ok_and(|name| name.eq_ignore_ascii_case(&file_name))
+    })?;
+    let blob = entry.to_object(&repo).ok()?.peel_to_blob().ok()?;
+    Some(parse_toc(blob.content()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        find_addon_toc, parse_toc, retain_client_variants, strip_escape_sequences, ClientFamily,
+        ClientMismatch,
+    };
+    use std::fs;
+
+    #[test]
+    fn parses_fields_through_bom_crlf_and_color_codes() {
+        let toc = parse_toc(
+            b"\xEF\xBB\xBF## Interface: 11200, 30300\r\n\
+              ## Title: |cff33ff99Ace|r Config |TInterface\\\\Icon:16|t\r\n\
+              ## Version: 2.1\r\n\
+              ## Author: Someone\r\n\
+              ## Notes: Settings ||  more\r\n\
+              ## Dependencies: Core, Lib\r\n\
+              ## RequiredDeps: Lib, Extra\r\n\
+              ## OptionalDeps: Ace2\r\n\
+              ## SavedVariables: AceDB, AceDB2\r\n\
+              ## X-Website: https://example.org\r\n\
+              ## Title: ignored duplicate\r\n\
+              Core.lua\r\n",
+        );
+        assert_eq!(toc.interface, vec![11200, 30300]);
+        assert_eq!(toc.title.as_deref(), Some("Ace Config"));
+        assert_eq!(toc.version.as_deref(), Some("2.1"));
+        assert_eq!(toc.author.as_deref(), Some("Someone"));
+        assert_eq!(toc.notes.as_deref(), Some("Settings |  more"));
+        assert_eq!(toc.dependencies, vec!["Core", "Lib", "Extra"]);
+        assert_eq!(toc.optional_deps, vec!["Ace2"]);
+        assert_eq!(toc.saved_variables, vec!["AceDB", "AceDB2"]);
+        assert_eq!(
+            toc.extra.get("X-Website").map(String::as_str),
+            Some("https://example.org")
+        );
+        assert_eq!(strip_escape_sequences("|cFF00ff00Green|r"), "Green");
+    }
+
+    #[test]
+    fn prefers_the_toc_named_after_its_folder() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path().join("pfQuest");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("pfQuest-tbc.toc"), "## Title: TBC").unwrap();
+        fs::write(dir.join("Another.toc"), "## Title: Other").unwrap();
+        assert!(find_addon_toc(&dir).unwrap().ends_with("pfQuest-tbc.toc"));
+        fs::write(dir.join("PFQUEST.toc"), "## Title: Vanilla").unwrap();
+        assert!(find_addon_toc(&dir).unwrap().ends_with("PFQUEST.toc"));
+    }
+
+    #[test]
+    fn interface_versions_are_matched_to_legacy_clients() {
+        assert_eq!(ClientFamily::from_interface(11200), ClientFamily::Vanilla);
+        assert_eq!(ClientFamily::from_interface(20400), ClientFamily::Tbc);
+        assert_eq!(ClientFamily::from_interface(30300), ClientFamily::Wotlk);
+        assert_eq!(
+            ClientFamily::from_interface(11503),
+            ClientFamily::Unsupported
+        );
+
+        let mismatch = ClientMismatch::check("Questie", &[30300], ClientFamily::Vanilla).unwrap();
+        assert_eq!(mismatch.addon_client, ClientFamily::Wotlk);
+        assert!(mismatch.message().contains("Interface 30300"));
+        assert!(ClientMismatch::check("Multi", &[11200, 30300], ClientFamily::Wotlk).is_none());
+        assert!(ClientMismatch::check("NoToc", &[], ClientFamily::Vanilla).is_none());
+        assert!(ClientMismatch::check("Any", &[30300], ClientFamily::Unknown).is_none());
+    }
+
+    #[test]
+    fn client_variants_prefer_the_clients_suffix_then_the_plain_name() {
+        let names = |client| {
+            let mut names = vec![
+                "pfQuest",
+                "pfQuest-tbc",
+                "pfQuest-wotlk",
+                "Atlas_Vanilla",
+                "Atlas_Wrath",
+                "Standalone-classic",
+            ];
+            retain_client_variants(&mut names, client, |name| name);
+            names
+        };
+        assert_eq!(
+            names(ClientFamily::Tbc),
+            vec![
+                "pfQuest-tbc",
+                "Atlas_Vanilla",
+                "Atlas_Wrath",
+                "Standalone-classic"
+            ]
+        );
+        assert_eq!(
+            names(ClientFamily::Wotlk),
+            vec!["pfQuest-wotlk", "Atlas_Wrath", "Standalone-classic"]
+        );
+        assert_eq!(
+            names(ClientFamily::Vanilla),
+            vec!["pfQuest", "Atlas_Vanilla", "Standalone-classic"]
+        );
+        assert_eq!(names(ClientFamily::Unknown).len(), 6);
+    }
+}
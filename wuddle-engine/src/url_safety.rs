@@ -1,4 +1,4 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use url::Url;
 
 /// Reject credentials supplied as part of a user-entered repository URL.
@@ -87,9 +87,110 @@ pub(crate) fn safe_remote_label(raw: &str) -> String {
     "Git repository".to_string()
 }
 
+/// Whether `raw` is reached over SSH: an `ssh://` URL or an SCP-style
+/// `user@host:path` remote. Local paths, including Windows drive paths, are
+/// never SSH remotes.
+pub(crate) fn is_ssh_remote(raw: &str) -> bool {
+    let trimmed = raw.trim();
+    if let Some((scheme, _)) = trimmed.split_once("://") {
+        return matches!(
+            scheme.to_ascii_lowercase().as_str(),
+            "ssh" | "git+ssh" | "ssh+git"
+        );
+    }
+    // As in git itself, a colon only separates a host when no path
+    // separator precedes it.
+    trimmed
+        .split_once(':')
+        .filter(|(user_host, _)| !user_host.contains(['/', '\\']))
+        .and_then(|(user_host, _)| user_host.rsplit_once('@'))
+        .is_some_and(|(user, host)| !user.is_empty() && !host.is_empty())
+}
+
+/// Reduce a user-entered host or URL to a lower-case `host[:port]` key.
+pub(crate) fn normalize_host_authority(input: &str) -> Result<String> {
+    let input = input.trim();
+    let with_scheme = if input.contains("://") {
+        input.to_string()
+    } else {
+        format!("https://{input}")
+    };
+    reject_embedded_credentials(&with_scheme)?;
+    let url = Url::parse(&with_scheme).context("invalid host")?;
+    let host = url
+        .host_str()
+        .filter(|host| !host.is_empty())
+        .context("host is empty")?
+        .to_ascii_lowercase();
+    Ok(match url.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host,
+    })
+}
+
+/// The `host[:port]` a stored credential must be registered under to be sent
+/// with a request to `raw`.
+///
+/// Only HTTPS URLs without userinfo qualify, and the match is exact: a token
+/// saved for `git.example.org` is never sent to a subdomain, another port, or
+/// a plaintext mirror of the same host.
+pub(crate) fn credential_authority(raw: &str) -> Option<String> {
+    let parsed = Url::parse(raw.trim()).ok()?;
+    if parsed.scheme() != "https" || !parsed.username().is_empty() || parsed.password().is_some() {
+        return None;
+    }
+    let host = parsed.host_str()?.to_ascii_lowercase();
+    Some(match parsed.port() {
+        Some(port) => format!("{host}:{port}"),
+        None => host,
+    })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{reject_embedded_credentials, safe_remote_label, sanitize_remote_for_storage};
+    use super::{
+        credential_authority, is_ssh_remote, normalize_host_authority, reject_embedded_credentials,
+        safe_remote_label, sanitize_remote_for_storage,
+    };
+
+    #[test]
+    fn recognizes_ssh_remotes_but_not_local_or_web_paths() {
+        assert!(is_ssh_remote("git@example.org:team/project.git"));
+        assert!(is_ssh_remote("ssh://git@example.org:2222/team/project.git"));
+        assert!(is_ssh_remote("git+ssh://example.org/team/project.git"));
+        assert!(!is_ssh_remote("https://example.org/team/project.git"));
+        assert!(!is_ssh_remote(r"C:\Games\addon.git"));
+        assert!(!is_ssh_remote("/srv/git/user@host:addon.git"));
+        assert!(!is_ssh_remote("example.org:team/project.git"));
+    }
+
+    #[test]
+    fn credentials_are_scoped_to_their_exact_https_host() {
+        assert_eq!(
+            credential_authority("https://Git.Example.org/api/v1/user").as_deref(),
+            Some("git.example.org")
+        );
+        assert_eq!(
+            credential_authority("https://git.example.org:443/x").as_deref(),
+            Some("git.example.org")
+        );
+        assert_eq!(
+            credential_authority("https://git.example.org:8443/x").as_deref(),
+            Some("git.example.org:8443")
+        );
+        assert_eq!(credential_authority("http://git.example.org/x"), None);
+        assert_eq!(credential_authority("https://user@git.example.org/x"), None);
+        assert_eq!(
+            credential_authority("https://git.example.org.evil.test/x").as_deref(),
+            Some("git.example.org.evil.test")
+        );
+
+        assert_eq!(
+            normalize_host_authority(" https://Code.Example.org:3000/group ").unwrap(),
+            "code.example.org:3000"
+        );
+        assert!(normalize_host_authority("user:pw@code.example.org").is_err());
+    }
 
     #[test]
     fn rejects_web_credentials_but_allows_ssh_usernames() {
@@ -47,13 +47,18 @@ pub fn db_path() -> Result<PathBuf> {
 /// cache when no WoW directory is known.
 pub fn cache_dir(wow_dir: Option<&Path>) -> Result<PathBuf> {
     let d = match wow_dir {
-        Some(w) => w.join(".wuddle").join("cache"),
+        Some(w) => wow_cache_path(w),
         None => app_dir()?.join("cache"),
     };
     fs::create_dir_all(&d)?;
     Ok(d)
 }
 
+/// The cache directory inside `wow_dir`, without creating it.
+pub fn wow_cache_path(wow_dir: &Path) -> PathBuf {
+    wow_dir.join(".wuddle").join("cache")
+}
+
 pub fn sha256_hex(s: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(s.as_bytes());
@@ -3,6 +3,7 @@ use crate::{Dialog, Message};
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(super) enum MessageRoute {
     BackupRestore,
+    SavedVariables,
     Mpq,
     #[cfg(feature = "auto-login")]
     AutoLogin,
@@ -43,6 +44,21 @@ pub(super) fn classify(message: &Message, dialog: &Option<Dialog>) -> MessageRou
         | Message::WuddleResetPrepared(..)
         | Message::WuddleResetRestarted(..) => MessageRoute::BackupRestore,
 
+        Message::ToggleSavedVariablesSnapshots(..)
+        | Message::SetSavedVariablesSnapshotHours(..)
+        | Message::SetSavedVariablesKeep(..)
+        | Message::SavedVariablesSnapshotTick
+        | Message::OpenSavedVariables
+        | Message::SavedVariablesListed(..)
+        | Message::TakeSavedVariablesSnapshot
+        | Message::SavedVariablesSnapshotTaken { .. }
+        | Message::SetSavedVariablesFrom(..)
+        | Message::SetSavedVariablesTo(..)
+        | Message::SetSavedVariablesAddon(..)
+        | Message::SavedVariablesDiffLoaded { .. }
+        | Message::RestoreSavedVariables
+        | Message::SavedVariablesRestored(..) => MessageRoute::SavedVariables,
+
         Message::LocalArchiveHovered(_) | Message::LocalArchiveDropped(_)
             if matches!(dialog, Some(Dialog::MpqInstall)) =>
         {
@@ -194,16 +210,25 @@ pub(super) fn classify(message: &Message, dialog: &Option<Dialog>) -> MessageRou
         | Message::ToggleDesktopNotify(..)
         | Message::ToggleSymlinks(..)
         | Message::ToggleXattr(..)
+        | Message::ToggleAtomicUpdates(..)
         | Message::ToggleClock12(..)
         | Message::ToggleFrizFont(..)
         | Message::ToggleRememberWindowGeometry(..)
         | Message::SetUiScaleMode(..)
+        | Message::SetSshKey(..)
         | Message::SetGithubTokenInput(..)
         | Message::SaveGithubToken
         | Message::SaveGithubTokenResult(..)
         | Message::ValidateGithubTokenResult { .. }
         | Message::ForgetGithubToken
         | Message::ForgetGithubTokenResult(..)
+        | Message::SetForgeTokenHostInput(..)
+        | Message::SetForgeTokenInput(..)
+        | Message::SaveForgeToken
+        | Message::SaveForgeTokenResult(..)
+        | Message::ValidateForgeTokenResult { .. }
+        | Message::ForgetForgeToken(..)
+        | Message::ForgetForgeTokenResult(..)
         | Message::UpdateInstanceField(..)
         | Message::SaveInstanceSettings
         | Message::SwitchProfile(..)
@@ -211,6 +236,7 @@ pub(super) fn classify(message: &Message, dialog: &Option<Dialog>) -> MessageRou
         | Message::RemoveProfileResult(..)
         | Message::InitializeProfileDbResult(..)
         | Message::SettingsLoaded(..)
+        | Message::PresetCatalogLoaded(..)
         | Message::SaveSettings
         | Message::PickWowDirectory
         | Message::PickWowExecutable
@@ -278,6 +304,8 @@ pub(super) fn classify(message: &Message, dialog: &Option<Dialog>) -> MessageRou
         | Message::ReinstallRepo(..)
         | Message::ReinstallRepoProbeResult { .. }
         | Message::ReinstallRepoResult { .. }
+        | Message::RollbackInstall { .. }
+        | Message::RollbackInstallResult { .. }
         | Message::FetchBranches(..)
         | Message::FetchBranchesResult(..)
         | Message::SetRepoBranch(..)
@@ -289,6 +317,8 @@ pub(super) fn classify(message: &Message, dialog: &Option<Dialog>) -> MessageRou
         | Message::FetchRepoPreview(..)
         | Message::OpenRepoReadmePreview(..)
         | Message::RepoReadmePreviewLoaded(..)
+        | Message::OpenWhatsNew(..)
+        | Message::WhatsNewLoaded(..)
         | Message::FetchRepoPreviewResult(..)
         | Message::FetchReleaseAssetOptions(..)
         | Message::FetchReleaseAssetOptionsResult(..)
@@ -307,7 +337,41 @@ pub(super) fn classify(message: &Message, dialog: &Option<Dialog>) -> MessageRou
         | Message::RefocusAddRepoUrl
         | Message::ResolveAddRepoUrl
         | Message::OpenModFileInfo(..)
-        | Message::FetchDllDescriptionResult(..) => MessageRoute::Repos,
+        | Message::FetchDllDescriptionResult(..)
+        | Message::OpenAddonSets
+        | Message::AddonSetsLoaded(..)
+        | Message::SetAddonSetCharacter(..)
+        | Message::SetAddonSetName(..)
+        | Message::CaptureAddonSet
+        | Message::ApplyAddonSet(..)
+        | Message::ApplyAddonSetResult { .. }
+        | Message::DeleteAddonSet(..)
+        | Message::ExportAddonSet(..)
+        | Message::AddonSetExported(..)
+        | Message::ImportAddonSet
+        | Message::AddonSetClipboardRead(..)
+        | Message::AddonSetsChanged(..)
+        | Message::OpenAddonIndex(..)
+        | Message::AddonIndexLoaded(..)
+        | Message::SetAddonIndexQuery(..)
+        | Message::SetAddonIndexClient(..)
+        | Message::SetAddonIndexKind(..)
+        | Message::SetAddonIndexUrl(..)
+        | Message::AddAddonIndexUrl
+        | Message::ImportAddonIndex
+        | Message::AddonIndexClipboardRead(..)
+        | Message::RefreshAddonIndexes
+        | Message::RemoveAddonIndex(..)
+        | Message::AddonIndexChanged(..)
+        | Message::OpenIdentifyAddons
+        | Message::IdentifyAddonsLoaded(..)
+        | Message::LinkAddonFolder { .. }
+        | Message::LinkAddonFolderResult { .. }
+        | Message::ExportAddonCatalog
+        | Message::AddonCatalogExported(..)
+        | Message::ImportAddonCatalog
+        | Message::AddonCatalogClipboardRead(..)
+        | Message::AddonCatalogImported(..) => MessageRoute::Repos,
 
         Message::SetTab(..)
         | Message::SetFilter(..)
@@ -347,6 +411,7 @@ pub(super) fn classify(message: &Message, dialog: &Option<Dialog>) -> MessageRou
         | Message::RepoDetailsLoaded(..)
         | Message::ToggleRepoDetailsPath(..)
         | Message::RepoDetailsChildrenLoaded(..)
+        | Message::InstallHistoryLoaded(..)
         | Message::SetCollectionMarqueeHover(..)
         | Message::LogEditorAction(..)
         | Message::ToggleReadmeSourceView
@@ -376,6 +441,13 @@ mod tests {
             ),
             MessageRoute::Settings
         );
+        assert_eq!(
+            classify(
+                &Message::SetForgeTokenInput("sensitive test value".to_string()),
+                &None
+            ),
+            MessageRoute::Settings
+        );
     }
 
     #[test]
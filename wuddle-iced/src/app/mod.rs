@@ -12,7 +12,10 @@ use crate::components::helpers::*;
 use crate::components::markdown::ImageViewer;
 use crate::components::presets::build_quick_add_presets;
 use crate::components::text_input_context::context_text_input;
+use crate::dialogs::addon_index;
 use crate::dialogs::addon_local_changes;
+use crate::dialogs::addon_sets;
+use crate::dialogs::identify_addons;
 use crate::dialogs::mods_warning;
 use crate::dialogs::patches_warning;
 use crate::dialogs::simple_warnings::{
@@ -25,6 +28,7 @@ use crate::settings::{self, UpdateChannel};
 use crate::theme::{self, ThemeColors, WuddleTheme, FRIZ, LIFECRAFT, NOTO};
 use crate::types::*;
 use crate::{chrono_now, chrono_now_fmt, monitor};
+use wuddle_engine::HistoryAction;
 
 mod message_route;
 
@@ -46,6 +50,15 @@ mod shutdown_tests {
     }
 }
 
+/// "Title 1.2 by Author" for an addon folder in the details dialog.
+fn toc_summary(toc: &wuddle_engine::toc::AddonToc) -> String {
+    let mut parts = Vec::new();
+    parts.extend(toc.display_title().map(str::to_string));
+    parts.extend(toc.version.clone());
+    parts.extend(toc.author.as_ref().map(|author| format!("by {author}")));
+    parts.join(" ")
+}
+
 fn move_dialog_focus(reverse: bool) -> Task<Message> {
     let scope_id = iced::widget::Id::new(DIALOG_FOCUS_SCOPE_ID);
     if reverse {
@@ -77,6 +90,14 @@ impl GitHubTokenStatus {
     }
 }
 
+/// A GitLab or Gitea/Forgejo host with a saved token. `validation` is `None`
+/// until the token has been checked in this session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgeTokenEntry {
+    pub host: String,
+    pub validation: Option<wuddle_engine::ForgeTokenValidation>,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UpdateCheckTrigger {
     Manual,
@@ -100,9 +121,11 @@ pub struct App {
     pub opt_desktop_notify: bool,
     pub opt_symlinks: bool,
     pub opt_xattr: bool,
+    pub opt_atomic_updates: bool,
     pub opt_clock12: bool,
     pub opt_friz_font: bool,
     pub remember_window_geometry: bool,
+    pub ssh_key: String,
 
     // GitHub auth
     pub github_token_input: String,
@@ -114,6 +137,11 @@ pub struct App {
     /// Tokens themselves are never kept here or shown in the UI.
     pub github_token_storage_error: Option<String>,
 
+    // GitLab / Gitea tokens
+    pub forge_token_host_input: String,
+    pub forge_token_input: String,
+    pub forge_tokens: Vec<ForgeTokenEntry>,
+
     // Tweaks
     pub tweaks: TweakState,
 
@@ -205,6 +233,9 @@ pub struct App {
     /// Profiles that have received their once-per-session launch check.
     pub autocheck_done_profile_ids: HashSet<String>,
     pub auto_check_minutes: u32,
+    pub opt_saved_variables_snapshots: bool,
+    pub saved_variables_snapshot_hours: u32,
+    pub saved_variables_keep: u32,
     /// Tracks when infrequent repos were last checked (wall-clock unix seconds).
     pub last_infrequent_check_unix: i64,
     /// Identifies whether the visible check was manual or automatic.
@@ -218,6 +249,7 @@ pub struct App {
     pub auto_login_ui: crate::auto_login::UiState,
     pub auto_login_warning_acknowledged: bool,
     pub mpq_ui: crate::mpq::UiState,
+    pub saved_variables_ui: crate::saved_variables::UiState,
 
     // Spinner animation tick (0..36, one full rotation = 36 ticks @ 80ms each)
     pub spinner_tick: usize,
@@ -327,6 +359,7 @@ impl App {
             Some(service::GitHubTokenSource::None) | None => GitHubTokenStatus::None,
         };
         let github_token_storage_error = token_sync.err();
+        let forge_token_sync = service::sync_forge_tokens();
         let mut app = Self {
             active_tab: Tab::default(),
             theme_colors,
@@ -340,13 +373,29 @@ impl App {
             opt_desktop_notify: false,
             opt_symlinks: false,
             opt_xattr: true,
+            opt_atomic_updates: false,
             opt_clock12: false,
             opt_friz_font: false,
             remember_window_geometry: true,
+            ssh_key: String::new(),
             github_token_input: String::new(),
             github_token_status,
             github_token_validation_generation: 0,
             github_token_storage_error,
+            forge_token_host_input: String::new(),
+            forge_token_input: String::new(),
+            forge_tokens: forge_token_sync
+                .as_ref()
+                .map(|hosts| {
+                    hosts
+                        .iter()
+                        .map(|host| ForgeTokenEntry {
+                            host: host.clone(),
+                            validation: None,
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
             tweaks: TweakState::default(),
             log_lines: {
                 let mut lines = vec![LogLine {
@@ -431,6 +480,9 @@ impl App {
             self_update_launch_check_started: false,
             autocheck_done_profile_ids: HashSet::new(),
             auto_check_minutes: 60,
+            opt_saved_variables_snapshots: false,
+            saved_variables_snapshot_hours: 24,
+            saved_variables_keep: 20,
             last_infrequent_check_unix: 0,
             update_check_trigger: None,
             infrequent_repo_ids: std::collections::HashSet::new(),
@@ -439,6 +491,7 @@ impl App {
             auto_login_ui: crate::auto_login::UiState::default(),
             auto_login_warning_acknowledged: false,
             mpq_ui: crate::mpq::UiState::default(),
+            saved_variables_ui: crate::saved_variables::UiState::default(),
             spinner_tick: 0,
             collection_marquee_hovered: false,
             collection_marquee_tick: 0,
@@ -494,6 +547,9 @@ impl App {
         if let Some(error) = app.github_token_storage_error.clone() {
             app.log(LogLevel::Error, &error);
         }
+        if let Err(error) = forge_token_sync {
+            app.log(LogLevel::Error, &error);
+        }
         if let Some(error) = crate::diagnostics::init_error() {
             app.log(
                 LogLevel::Error,
@@ -731,13 +787,18 @@ impl App {
             opt_desktop_notify: self.opt_desktop_notify,
             opt_symlinks: self.opt_symlinks,
             opt_xattr: self.opt_xattr,
+            opt_atomic_updates: self.opt_atomic_updates,
             opt_clock12: self.opt_clock12,
             opt_friz_font: self.opt_friz_font,
             remember_window_geometry: self.remember_window_geometry,
+            ssh_key: self.ssh_key.clone(),
             log_wrap: self.log_wrap,
             log_autoscroll: self.log_autoscroll,
             verbose_diagnostics: self.verbose_diagnostics,
             auto_check_minutes: self.auto_check_minutes,
+            opt_saved_variables_snapshots: self.opt_saved_variables_snapshots,
+            saved_variables_snapshot_hours: self.saved_variables_snapshot_hours,
+            saved_variables_keep: self.saved_variables_keep,
             profiles: self.profiles.clone(),
             ignored_update_ids: current_ignored,
             ignored_update_ids_by_profile,
@@ -1010,6 +1071,14 @@ impl App {
             );
         }
 
+        if self.opt_saved_variables_snapshots && !self.wow_dir.is_empty() {
+            let hours = self.saved_variables_snapshot_hours.max(1) as u64;
+            subs.push(
+                iced::time::every(std::time::Duration::from_secs(hours * 60 * 60))
+                    .map(|_| Message::SavedVariablesSnapshotTick),
+            );
+        }
+
         // Hourly self-update check for unauthenticated users; authenticated users get
         // checked on launch and on every About-tab navigation.
         if wuddle_engine::github_token().is_none() {
@@ -1247,6 +1316,10 @@ impl App {
                 let task = crate::mpq::update(self, message);
                 return self.finish_routed_update(task, "MPQ");
             }
+            MessageRoute::SavedVariables => {
+                let task = crate::saved_variables::update(self, message);
+                return self.finish_routed_update(task, "SavedVariables");
+            }
             MessageRoute::BackupRestore => {
                 let task = crate::backup_restore::update(self, message);
                 return self.finish_routed_update(Some(task), "Backup and Restore");
@@ -1358,10 +1431,14 @@ impl App {
             | Message::ToggleDesktopNotify(_)
             | Message::ToggleSymlinks(_)
             | Message::ToggleXattr(_)
+            | Message::ToggleAtomicUpdates(_)
             | Message::ToggleClock12(_)
             | Message::ToggleFrizFont(_)
             | Message::SetUiScaleMode(_)
-            | Message::SetGithubTokenInput(_) => {}
+            | Message::SetSshKey(_)
+            | Message::SetGithubTokenInput(_)
+            | Message::SetForgeTokenHostInput(_)
+            | Message::SetForgeTokenInput(_) => {}
 
             // Tweaks
             Message::ToggleTweak(id, val) => self.tweaks.set(id, val),
@@ -1460,21 +1537,19 @@ impl App {
                     );
                 }
             },
-            Message::LogEditorAction(action) => {
-                if !action.is_edit() {
-                    self.log_editor_content.perform(action);
-                }
+            Message::LogEditorAction(action) if !action.is_edit() => {
+                self.log_editor_content.perform(action);
             }
+            Message::LogEditorAction(_) => {}
 
             // README source toggle
             Message::ToggleReadmeSourceView => {
                 self.readme_source_view = !self.readme_source_view;
             }
-            Message::ReadmeEditorAction(action) => {
-                if !action.is_edit() {
-                    self.readme_editor_content.perform(action);
-                }
+            Message::ReadmeEditorAction(action) if !action.is_edit() => {
+                self.readme_editor_content.perform(action);
             }
+            Message::ReadmeEditorAction(_) => {}
             Message::SetCollectionMarqueeHover(hovered) => {
                 self.collection_marquee_hovered = hovered;
                 if hovered {
@@ -1528,12 +1603,28 @@ impl App {
                     let db = self.db_path.clone();
                     let repo_id = *id;
                     let scope = self.profile_operation_scope();
-                    Task::perform(
-                        service::load_repo_details(db, repo_id, PathBuf::from(&self.wow_dir)),
-                        move |result| {
-                            Message::RepoDetailsLoaded(ProfileScoped::new(scope.clone(), result))
-                        },
-                    )
+                    let history_scope = scope.clone();
+                    Task::batch([
+                        Task::perform(
+                            service::load_repo_details(
+                                db.clone(),
+                                repo_id,
+                                PathBuf::from(&self.wow_dir),
+                            ),
+                            move |result| {
+                                Message::RepoDetailsLoaded(ProfileScoped::new(
+                                    scope.clone(),
+                                    result,
+                                ))
+                            },
+                        ),
+                        Task::perform(service::load_install_history(db, repo_id), move |result| {
+                            Message::InstallHistoryLoaded(ProfileScoped::new(
+                                history_scope.clone(),
+                                result,
+                            ))
+                        }),
+                    ])
                 } else if matches!(
                     d,
                     Dialog::EditUntrackedMpq { .. }
@@ -1587,6 +1678,15 @@ impl App {
                     *files = result.unwrap_or_default();
                 }
             }
+            Message::InstallHistoryLoaded(scoped) => {
+                let Some(result) = self.accept_profile_result(scoped, "install history load")
+                else {
+                    return self.finish_update(Task::none());
+                };
+                if let Some(Dialog::RepoDetails { history, .. }) = &mut self.dialog {
+                    *history = result.unwrap_or_default();
+                }
+            }
             Message::ToggleRepoDetailsPath(path) => {
                 let scope = self.profile_operation_scope();
                 if let Some(Dialog::RepoDetails {
@@ -1894,7 +1994,12 @@ impl App {
             | Message::SaveGithubTokenResult(_)
             | Message::ValidateGithubTokenResult { .. }
             | Message::ForgetGithubToken
-            | Message::ForgetGithubTokenResult(_) => {}
+            | Message::ForgetGithubTokenResult(_)
+            | Message::SaveForgeToken
+            | Message::SaveForgeTokenResult(_)
+            | Message::ValidateForgeTokenResult { .. }
+            | Message::ForgetForgeToken(_)
+            | Message::ForgetForgeTokenResult(_) => {}
 
             // --- Shared actions ---
             Message::CopyToClipboard(text_val) => match copy_to_clipboard(&text_val) {
@@ -2038,6 +2143,7 @@ impl App {
             replace_file_conflicts: false,
             replace_local_changes: false,
             cache_keep_versions: 2,
+            client_family: self.quick_add_client_family(),
         }
     }
 
@@ -2108,6 +2214,7 @@ impl App {
                     Dialog::BackupRestore => (940u32, 0),
                     Dialog::AddRepo { .. } => (1400u32, 16),
                     Dialog::MpqAdd => (1000u32, 16),
+                    Dialog::SavedVariables => (960u32, 24),
                     Dialog::MpqInstall
                     | Dialog::ProtectedMpqs
                     | Dialog::WdmInstall
@@ -2130,7 +2237,11 @@ impl App {
                     | Dialog::RemoveCollectionAddon { .. } => (650u32, 24),
                     Dialog::AddonConflict { .. } => (920u32, 24),
                     Dialog::CollectionAddonConflict { .. } => (920u32, 24),
-                    Dialog::FileConflict { .. } | Dialog::AddonLocalChanges { .. } => (650u32, 24),
+                    Dialog::FileConflict { .. }
+                    | Dialog::AddonLocalChanges { .. }
+                    | Dialog::AddonSets { .. }
+                    | Dialog::IdentifyAddons { .. } => (650u32, 24),
+                    Dialog::AddonIndex { .. } => (760u32, 24),
                     _ => (480u32, 24),
                 };
                 let c_dlg = c;
@@ -2193,6 +2304,7 @@ impl App {
                 dialog,
                 Dialog::AddRepo { .. }
                     | Dialog::MpqAdd
+                    | Dialog::SavedVariables
                     | Dialog::MpqInstall
                     | Dialog::ProtectedMpqs
                     | Dialog::WdmInstall
@@ -2488,6 +2600,7 @@ impl App {
         let c = colors;
         match dialog {
             Dialog::BackupRestore => crate::backup_restore::view_dialog(self, colors),
+            Dialog::SavedVariables => crate::saved_variables::view_dialog(self, colors),
             Dialog::MpqAdd
             | Dialog::MpqInstall
             | Dialog::ProtectedMpqs
@@ -4458,13 +4571,14 @@ impl App {
                 .into()
             }
             Dialog::RepoDetails {
+                id,
                 name,
                 files,
                 loading,
                 expanded_paths,
                 loading_paths,
                 children,
-                ..
+                history,
             } => {
                 let file_rows = files.iter().map(|entry| {
                     let (icon, label): (Element<Message>, &str) = match entry.kind.as_str() {
@@ -4490,6 +4604,9 @@ impl App {
                         .color(colors.muted),
                         icon,
                         text(&entry.path).size(12).color(colors.text),
+                        text(entry.toc.as_ref().map(toc_summary).unwrap_or_default())
+                            .size(11)
+                            .color(colors.muted),
                         Space::new().width(Length::Fill),
                         text(if is_loading { "Loading…" } else { label })
                             .size(11)
@@ -4600,6 +4717,78 @@ impl App {
                         .style(move |theme, status| theme::scrollable_style(c)(theme, status))
                         .into()
                 };
+                let history_rows = history.iter().enumerate().take(5).map(|(index, entry)| {
+                    let to = entry.to_version.as_deref().unwrap_or("?");
+                    let change = match (entry.action, entry.from_version.as_deref()) {
+                        (HistoryAction::Remove, from) => {
+                            format!("Removed {}", from.unwrap_or("?"))
+                        }
+                        (HistoryAction::Rollback, _) => format!("Rolled back to {to}"),
+                        (_, Some(from)) if from != to => format!("{from} \u{2192} {to}"),
+                        (_, Some(_)) => format!("Reinstalled {to}"),
+                        (_, None) => format!("Installed {to}"),
+                    };
+                    let settings = if entry.settings_snapshot.is_some() {
+                        "settings saved before"
+                    } else {
+                        "no saved settings"
+                    };
+                    let mut line = row![
+                        column![
+                            text(change).size(12).color(colors.text),
+                            text(format!(
+                                "{} \u{2022} {settings}",
+                                crate::saved_variables::format_timestamp(entry.created_unix)
+                            ))
+                            .size(11)
+                            .color(colors.muted),
+                        ]
+                        .spacing(2),
+                        Space::new().width(Length::Fill),
+                    ]
+                    .spacing(8)
+                    .align_y(iced::Alignment::Center);
+                    // Undoing an entry goes back to what the entry before it
+                    // installed.
+                    let target = history
+                        .get(index + 1)
+                        .filter(|previous| previous.action != HistoryAction::Remove)
+                        .filter(|previous| {
+                            entry.action != HistoryAction::Remove
+                                && previous.to_version.as_ref().is_some_and(|version| {
+                                    *version != to || entry.settings_snapshot.is_some()
+                                })
+                        });
+                    if let (Some(repo_id), Some(target)) = (*id, target) {
+                        line = line.push(
+                            button(text("Roll back addon and settings").size(11))
+                                .on_press(Message::RollbackInstall {
+                                    repo_id,
+                                    entry_id: target.id,
+                                })
+                                .padding([5, 9])
+                                .style(move |_theme, status| match status {
+                                    button::Status::Hovered => theme::tab_button_hovered_style(c),
+                                    _ => theme::tab_button_style(c),
+                                }),
+                        );
+                    }
+                    container(line)
+                        .padding([5, 8])
+                        .width(Length::Fill)
+                        .style(move |_theme| theme::card_style(c))
+                        .into()
+                });
+                let history_section: Element<Message> = if history.is_empty() {
+                    Space::new().width(0).into()
+                } else {
+                    column![
+                        text("Recent updates").size(13).color(colors.title),
+                        column(history_rows).spacing(4),
+                    ]
+                    .spacing(6)
+                    .into()
+                };
                 column![
                     row![
                         text(format!("{} — Details", name))
@@ -4613,6 +4802,7 @@ impl App {
                         .size(12)
                         .color(colors.muted),
                     body,
+                    history_section,
                     row![
                         Space::new().width(Length::Fill),
                         button(text("Close").size(13))
@@ -4704,15 +4894,42 @@ impl App {
                         .into()
                 };
 
+                let dependent_names = self
+                    .repos
+                    .iter()
+                    .find(|repo| repo.id == rid)
+                    .map(|repo| {
+                        repo.dependents
+                            .iter()
+                            .filter_map(|dependent| {
+                                self.repos.iter().find(|other| other.id == *dependent)
+                            })
+                            .map(|other| other.name.as_str())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let mut prompt = column![text(format!("Remove \"{}\" from Wuddle?", name))
+                    .size(13)
+                    .color(colors.text)]
+                .spacing(4);
+                if !dependent_names.is_empty() {
+                    prompt = prompt.push(
+                        text(format!(
+                            "\u{26a0}\u{fe0f} Addons from {} depend on this repository and will stop loading without it.",
+                            dependent_names.join(", ")
+                        ))
+                        .size(12)
+                        .color(colors.warn),
+                    );
+                }
+
                 column![
                     row![
                         text(if is_mpq { "Remove MPQ Package" } else { "Remove Repository" }).size(18).color(colors.title),
                         Space::new().width(Length::Fill),
                         close_button(c),
                     ].align_y(iced::Alignment::Center),
-                    text(format!("Remove \"{}\" from Wuddle?", name))
-                        .size(13)
-                        .color(colors.text),
+                    prompt,
                     file_section,
                     remove_files_control,
                     text(if rf {
@@ -5508,6 +5725,70 @@ impl App {
                 patches_warning::view(*do_not_show_again, colors)
             }
             Dialog::AddonLocalChanges { repos } => addon_local_changes::view(repos, colors),
+            Dialog::AddonSets {
+                sets,
+                characters,
+                character,
+                new_name,
+                loading,
+            } => {
+                let name_input: Element<Message> =
+                    context_text_input(self, colors, "addon-set-name", "New set name", new_name)
+                        .on_input(Message::SetAddonSetName)
+                        .on_submit(Message::CaptureAddonSet)
+                        .padding([6, 10])
+                        .into();
+                addon_sets::view(
+                    sets, characters, *character, new_name, *loading, name_input, colors,
+                )
+            }
+            Dialog::IdentifyAddons {
+                addons,
+                loading,
+                linking,
+            } => identify_addons::view(addons, *loading, linking.as_deref(), colors),
+            Dialog::AddonIndex {
+                entries,
+                sources,
+                query,
+                client,
+                kind,
+                url,
+                loading,
+            } => {
+                let query_input: Element<Message> = context_text_input(
+                    self,
+                    colors,
+                    "addon-index-query",
+                    "Search the catalog",
+                    query,
+                )
+                .on_input(Message::SetAddonIndexQuery)
+                .padding([6, 10])
+                .into();
+                let url_input: Element<Message> = context_text_input(
+                    self,
+                    colors,
+                    "addon-index-url",
+                    "https://… catalog URL",
+                    url,
+                )
+                .on_input(Message::SetAddonIndexUrl)
+                .on_submit(Message::AddAddonIndexUrl)
+                .padding([6, 10])
+                .into();
+                addon_index::view(
+                    entries,
+                    sources,
+                    query,
+                    *client,
+                    *kind,
+                    *loading,
+                    query_input,
+                    url_input,
+                    colors,
+                )
+            }
             Dialog::FileConflict {
                 repo_id,
                 repo_name,
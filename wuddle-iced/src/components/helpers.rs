@@ -96,11 +96,11 @@ pub fn dialog_description(
 // ---------------------------------------------------------------------------
 
 pub fn badge_tag<'a>(
-    label: &'static str,
+    label: &str,
     text_color: iced::Color,
     base_color: iced::Color,
 ) -> Element<'a, Message> {
-    container(text(label).size(14).color(text_color))
+    container(text(label.to_string()).size(14).color(text_color))
         .padding([2, 6])
         .style(move |_t| container::Style {
             background: Some(iced::Background::Color(iced::Color::from_rgba(
@@ -310,6 +310,7 @@ pub fn inline_context_menu<'a>(
                 expanded_paths: Default::default(),
                 loading_paths: Default::default(),
                 children: Default::default(),
+                history: Vec::new(),
             }),
             c,
         ));
@@ -406,6 +407,13 @@ pub fn inline_context_menu<'a>(
             c,
         ));
     }
+    if has_update {
+        items.push(ctx_menu_item(
+            "What's new\u{2026}",
+            Message::OpenWhatsNew(rid),
+            c,
+        ));
+    }
     items.push(ctx_menu_item(
         "Details\u{2026}",
         Message::OpenDialog(Dialog::RepoDetails {
@@ -416,6 +424,7 @@ pub fn inline_context_menu<'a>(
             expanded_paths: Default::default(),
             loading_paths: Default::default(),
             children: Default::default(),
+            history: Vec::new(),
         }),
         c,
     ));
@@ -424,6 +433,11 @@ pub fn inline_context_menu<'a>(
         Message::ReinstallRepo(rid),
         c,
     ));
+    for unmet in repo.missing_dependencies.iter().filter(|unmet| {
+        collection_addon.is_none_or(|addon| unmet.addon.eq_ignore_ascii_case(addon))
+    }) {
+        items.push(dependency_menu_item(app, unmet, c));
+    }
     if let Some(addon_name) = collection_addon {
         items.push(ctx_menu_item(
             "Manage Collection\u{2026}",
@@ -544,6 +558,54 @@ pub fn inline_context_menu<'a>(
         .into()
 }
 
+/// Offer the fix for one unmet addon dependency: enable or repair the
+/// tracked repo that provides it, or add the repository known to provide it.
+fn dependency_menu_item<'a>(
+    app: &crate::App,
+    unmet: &wuddle_engine::UnmetDependency,
+    c: ThemeColors,
+) -> Element<'a, Message> {
+    use wuddle_engine::DependencySource;
+
+    match &unmet.source {
+        Some(DependencySource::TrackedRepo { repo_id }) => {
+            let provider_enabled = app
+                .repos
+                .iter()
+                .find(|repo| repo.id == *repo_id)
+                .is_none_or(|repo| repo.enabled);
+            if provider_enabled {
+                ctx_menu_item(
+                    &format!("Repair {}", unmet.dependency),
+                    Message::ReinstallRepo(*repo_id),
+                    c,
+                )
+            } else {
+                ctx_menu_item(
+                    &format!("Enable {}", unmet.dependency),
+                    Message::ToggleRepoEnabled(*repo_id, true),
+                    c,
+                )
+            }
+        }
+        Some(DependencySource::Known { url }) => ctx_menu_item(
+            &format!("Install {}\u{2026}", unmet.dependency),
+            Message::OpenDialog(Dialog::AddRepo {
+                url: url.clone(),
+                mode: String::from("addon_git"),
+                is_addons: true,
+                advanced: false,
+            }),
+            c,
+        ),
+        None => ctx_menu_item_disabled_with_tooltip(
+            &format!("Missing {}", unmet.dependency),
+            &format!("{} Add a repository that provides it.", unmet.message()),
+            c,
+        ),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Markdown / Code block helpers
 // ---------------------------------------------------------------------------
@@ -1,11 +1,13 @@
 //! Quick Add preset data and card rendering.
 //!
-//! Mirrors the preset list from `wuddle-gui/src/presets.js`.
+//! The presets come from the engine's preset catalog (built-in, a user file
+//! or a signed remote catalog); the WeirdUtils module list stays here.
 //! `build_quick_add_presets` renders the preset grid shown inside the
 //! AddRepo dialog when the URL field is empty.
 
 use iced::widget::{button, column, container, row, text, Space};
 use iced::{Element, Length};
+use std::sync::RwLock;
 
 use crate::components::helpers::{badge_tag, tip};
 use crate::service::{ClientFamily, RepoRow};
@@ -16,26 +18,11 @@ use crate::Message;
 // Data types
 // ---------------------------------------------------------------------------
 
-pub struct Preset {
-    pub name: &'static str,
-    pub url: &'static str,
-    pub description: &'static str,
-    pub categories: &'static [&'static str],
-    pub recommended: bool,
-    pub warning: Option<&'static str>,
-    pub companion_links: &'static [(&'static str, &'static str)],
-    pub expanded_notes: &'static [&'static str],
-    pub is_addon: bool,
-    pub supported_clients: &'static [ClientFamily],
-}
+pub use wuddle_engine::presets::Preset;
 
-const VANILLA: &[ClientFamily] = &[ClientFamily::Vanilla];
-const LEGACY_DXVK: &[ClientFamily] = &[
-    ClientFamily::Vanilla,
-    ClientFamily::Tbc,
-    ClientFamily::Wotlk,
-];
-const WOTLK: &[ClientFamily] = &[ClientFamily::Wotlk];
+/// Presets from the catalog chosen at startup. Until it is read, the catalog
+/// built into the engine is used.
+static ACTIVE_PRESETS: RwLock<Option<Vec<Preset>>> = RwLock::new(None);
 
 pub const WEIRD_UTILS_DLLS: [&str; 11] = [
     "weirdutils.dll",
@@ -70,147 +57,18 @@ pub static WEIRD_UTILS_DESCRIPTIONS: [(&str, &str); 11] = [
 // ---------------------------------------------------------------------------
 
 pub fn create_quick_add_presets() -> Vec<Preset> {
-    vec![
-        Preset {
-            name: "VanillaFixes",
-            url: "https://github.com/hannesmann/vanillafixes",
-            description: "A client modification for World of Warcraft 1.6.1-1.12.1 to eliminate stutter and animation lag. VanillaFixes also acts as a launcher (start game via VanillaFixes.exe instead of Wow.exe) and DLL mod loader which loads DLL files listed in dlls.txt found in the WoW install directory.",
-            categories: &["Performance"],
-            recommended: true,
-            warning: Some("VanillaFixes may trigger antivirus false-positive alerts on Windows."),
-            companion_links: &[],
-            expanded_notes: &[],
-            is_addon: false,
-            supported_clients: VANILLA,
-        },
-        Preset {
-            name: "Interact",
-            url: "https://github.com/lookino/Interact",
-            description: "Legacy WoW client mod for 1.12 that brings Dragonflight-style interact key support to Vanilla, reducing click friction and improving moment-to-moment gameplay.",
-            categories: &["QoL"],
-            recommended: false,
-            warning: None,
-            companion_links: &[],
-            expanded_notes: &[],
-            is_addon: false,
-            supported_clients: VANILLA,
-        },
-        Preset {
-            name: "UnitXP_SP3",
-            url: "https://codeberg.org/konaka/UnitXP_SP3",
-            description: "Adds optional camera offset, proper nameplates (showing only with LoS), improved tab-targeting keybind behavior, LoS and distance checks in Lua, screenshot format options, network tweaks, background notifications, and additional QoL features.",
-            categories: &["QoL", "API"],
-            recommended: true,
-            warning: Some("UnitXP_SP3 may trigger antivirus false-positive alerts on Windows."),
-            companion_links: &[],
-            expanded_notes: &[],
-            is_addon: true,
-            supported_clients: VANILLA,
-        },
-        Preset {
-            name: "nampower",
-            url: "https://gitea.com/jilinge2/nampower",
-            description: "Addresses a 1.12 client casting limitation where follow-up casts wait on round-trip completion feedback. The result is reduced cast downtime and better effective DPS, especially on higher-latency connections.",
-            categories: &["API"],
-            recommended: true,
-            warning: None,
-            companion_links: &[("NampowerSettings", "https://gitea.com/jilinge2/NampowerSettings")],
-            expanded_notes: &[],
-            is_addon: false,
-            supported_clients: VANILLA,
-        },
-        Preset {
-            name: "SuperWoW",
-            url: "https://github.com/balakethelock/SuperWoW",
-            description: "Client mod for WoW 1.12.1 that fixes engine/client bugs and expands the Lua API used by addons. Some addons require SuperWoW directly, and many others gain improved functionality when it is present.",
-            categories: &["QoL", "API"],
-            recommended: true,
-            warning: Some("SuperWoW may trigger antivirus false-positive alerts on Windows."),
-            companion_links: &[
-                ("SuperAPI", "https://github.com/balakethelock/SuperAPI"),
-                ("SuperAPI_Castlib", "https://github.com/balakethelock/SuperAPI_Castlib"),
-            ],
-            expanded_notes: &[
-                "SuperAPI improves compatibility with the default interface and adds a minimap icon for persistent mod settings.",
-                "It exposes settings like autoloot, clickthrough corpses, GUID in combat log/events, adjustable FoV, enable background sound, uncapped sound channels, and targeting circle style.",
-                "SuperAPI_Castlib adds default-style nameplate castbars. If you're using pfUI/shaguplates, you do not need this module.",
-            ],
-            is_addon: false,
-            supported_clients: VANILLA,
-        },
-        Preset {
-            name: "DXVK (GPLAsync fork)",
-            url: "https://gitlab.com/Ph42oN/dxvk-gplasync",
-            description: "DXVK can massively improve performance in old Direct3D titles (including WoW) by using Vulkan. This fork includes Async + GPL options aimed at further reducing stutters. Async/GPL behavior is controlled through dxvk.conf, so users can keep default behavior if they prefer.",
-            categories: &["Performance"],
-            recommended: true,
-            warning: None,
-            companion_links: &[],
-            expanded_notes: &[],
-            is_addon: false,
-            supported_clients: LEGACY_DXVK,
-        },
-        Preset {
-            name: "wow-optimize",
-            url: "https://github.com/suprepupre/wow-optimize",
-            description: "Engine-level performance optimization for standard WoW 3.3.5a clients. Wuddle installs the proxy DLLs and the bundled launcher; use the launcher badge in the Mods list to configure features, profiles, and launch WoW.",
-            categories: &["Performance"],
-            recommended: false,
-            warning: Some("wow-optimize uses DLL injection and may trigger antivirus alerts on Windows."),
-            companion_links: &[("LuaBoost", "https://github.com/suprepupre/LuaBoost")],
-            expanded_notes: &[],
-            is_addon: false,
-            supported_clients: WOTLK,
-        },
-        Preset {
-            name: "Awesome WotLK",
-            url: "https://github.com/noname08662/awesome_wotlk",
-            description: "A WoW 3.3.5a improvements library with rendering, API, and quality-of-life enhancements. After installation, run its patch tool once from Wuddle to patch the selected WoW.exe.",
-            categories: &["QoL", "API"],
-            recommended: false,
-            warning: Some("Awesome WotLK permanently patches WoW.exe. Wuddle will always ask for confirmation before launching its patch tool."),
-            companion_links: &[],
-            expanded_notes: &[],
-            is_addon: false,
-            supported_clients: WOTLK,
-        },
-        Preset {
-            name: "perf_boost",
-            url: "https://gitea.com/jilinge2/perf_boost",
-            description: "Performance-focused DLL for WoW 1.12.1 intended to improve FPS in crowded areas and raids. Uses advanced render-distance controls.",
-            categories: &["Performance"],
-            recommended: false,
-            warning: None,
-            companion_links: &[("PerfBoostSettings", "https://gitea.com/jilinge2/PerfBoostSettings")],
-            expanded_notes: &[],
-            is_addon: false,
-            supported_clients: VANILLA,
-        },
-        Preset {
-            name: "VanillaHelpers",
-            url: "https://github.com/isfir/VanillaHelpers",
-            description: "Utility library for WoW 1.12 adding file read/write helpers, minimap blip customization, larger allocator capacity, higher-resolution texture/skin support, and character morph-related functionality.",
-            categories: &["API", "Performance"],
-            recommended: true,
-            warning: None,
-            companion_links: &[],
-            expanded_notes: &[],
-            is_addon: false,
-            supported_clients: VANILLA,
-        },
-        Preset {
-            name: "WeirdUtils",
-            url: "https://codeberg.org/MarcelineVQ/WeirdUtils",
-            description: "WeirdUtils is a DLL mods package which provides many pre-built DLLs for enhancing the vanilla 1.12 client WoW gameplay experience, aimed in particular at ease of use and accessibility but also bug fixes.\n\nYou may get all features by installing weirdutils.dll, or choose any selection of features via individual DLLs.",
-            categories: &["QoL", "Performance"],
-            recommended: false,
-            warning: None,
-            companion_links: &[],
-            expanded_notes: &[],
-            is_addon: false,
-            supported_clients: VANILLA,
-        },
-    ]
+    ACTIVE_PRESETS
+        .read()
+        .ok()
+        .and_then(|presets| presets.clone())
+        .unwrap_or_else(|| wuddle_engine::presets::embedded().presets)
+}
+
+/// Replace the presets shown by Quick Add.
+pub fn set_active_presets(presets: Vec<Preset>) {
+    *ACTIVE_PRESETS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(presets);
 }
 
 /// Returns true if the given URL corresponds to a preset with an "AV false-positive" warning.
@@ -218,7 +76,13 @@ pub fn is_av_false_positive(url: &str) -> bool {
     let url = url.trim_end_matches('/');
     create_quick_add_presets()
         .iter()
-        .any(|p| p.url.trim_end_matches('/').eq_ignore_ascii_case(url) && p.warning.is_some())
+        .any(|p| p.url.trim_end_matches('/').eq_ignore_ascii_case(url) && p.is_av_false_positive())
+}
+
+/// Addon folder name -> repository URL for every addon the presets know
+/// about, used to resolve missing addon dependencies.
+pub fn known_addon_sources() -> Vec<(String, String)> {
+    wuddle_engine::presets::addon_sources(&create_quick_add_presets())
 }
 
 // ---------------------------------------------------------------------------
@@ -286,7 +150,7 @@ pub fn build_quick_add_presets<'a>(
             let preset_url = preset.url.to_string();
             let title_action = Message::SetAddRepoUrl(preset_url.clone());
             let title_btn = button(iced::widget::rich_text::<(), _, _, _>([
-                iced::widget::span(preset.name)
+                iced::widget::span(preset.name.clone())
                     .underline(true)
                     .font(iced::Font {
                         weight: iced::font::Weight::Bold,
@@ -314,7 +178,7 @@ pub fn build_quick_add_presets<'a>(
                     iced::Color::from_rgb8(0x10, 0xb9, 0x81),
                 ));
             }
-            if preset.warning.is_some() {
+            if preset.is_av_false_positive() {
                 tags.push(tip(
                     badge_tag(
                         "AV false-positive",
@@ -326,8 +190,8 @@ pub fn build_quick_add_presets<'a>(
                     colors,
                 ));
             }
-            for cat in preset.categories {
-                let (text_col, base_col, tooltip_text) = match *cat {
+            for cat in &preset.categories {
+                let (text_col, base_col, tooltip_text) = match cat.as_str() {
                 "Performance" => (
                     iced::Color::from_rgb8(0xc4, 0xb5, 0xfd),
                     iced::Color::from_rgb8(0xa8, 0x55, 0xf7),
@@ -359,16 +223,18 @@ pub fn build_quick_add_presets<'a>(
             }
 
             let tags_row = row(tags).spacing(4).align_y(iced::Alignment::Center);
-            let readme_button = quick_add_readme_button(preset.name, preset.url, colors);
+            let readme_button = quick_add_readme_button(&preset.name, &preset.url, colors);
 
             // Description + notes + optional warning
-            let mut desc_col: Vec<Element<Message>> =
-                vec![text(preset.description).size(16).color(colors.title).into()];
-            for note in preset.expanded_notes {
+            let mut desc_col: Vec<Element<Message>> = vec![text(preset.description.clone())
+                .size(16)
+                .color(colors.title)
+                .into()];
+            for note in &preset.expanded_notes {
                 desc_col.push(
                     row![
                         text("\u{2022}").size(15).color(c.text),
-                        text(*note).size(15).color(c.text),
+                        text(note.clone()).size(15).color(c.text),
                     ]
                     .spacing(4)
                     .into(),
@@ -378,10 +244,10 @@ pub fn build_quick_add_presets<'a>(
                 let companions: Vec<Element<Message>> = preset
                     .companion_links
                     .iter()
-                    .map(|(label, lurl)| {
-                        let l = lurl.to_string();
+                    .map(|link| {
+                        let l = link.url.clone();
                         button(iced::widget::rich_text::<(), _, _, _>([
-                            iced::widget::span(*label)
+                            iced::widget::span(link.name.clone())
                                 .underline(true)
                                 .color(c.link)
                                 .size(16.0_f32),
@@ -0,0 +1,228 @@
+//! The local addon catalog: search imported catalogs offline and open the
+//! Add dialog for an entry.
+
+use crate::components::helpers::{badge_tag, close_button};
+use crate::theme::{self, ThemeColors};
+use crate::{Dialog, Message};
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, Space};
+use iced::{Element, Length};
+use wuddle_engine::addon_index;
+use wuddle_engine::{ClientFamily, IndexEntry, IndexKind, IndexQuery, IndexSource};
+
+/// Results rendered at once; narrowing the search shows the rest.
+const RESULTS_SHOWN: usize = 100;
+
+pub const ALL_CLIENTS: &str = "All clients";
+pub const ALL_KINDS: &str = "Addons and mods";
+
+pub fn client_choices() -> Vec<String> {
+    let mut choices = vec![ALL_CLIENTS.to_string()];
+    choices.extend(
+        [
+            ClientFamily::Vanilla,
+            ClientFamily::Tbc,
+            ClientFamily::Wotlk,
+        ]
+        .into_iter()
+        .map(|family| family.label().to_string()),
+    );
+    choices
+}
+
+pub fn kind_label(kind: Option<IndexKind>) -> &'static str {
+    match kind {
+        None => ALL_KINDS,
+        Some(IndexKind::Addon) => "Addons",
+        Some(IndexKind::Mod) => "Mods",
+    }
+}
+
+fn secondary_button(label: &str, c: ThemeColors) -> button::Button<'_, Message> {
+    button(text(label).size(13))
+        .padding([5, 12])
+        .style(move |_theme, status| match status {
+            button::Status::Hovered => theme::tab_button_hovered_style(c),
+            _ => theme::tab_button_style(c),
+        })
+}
+
+fn entry_card<'a>(entry: IndexEntry, c: ThemeColors) -> Element<'a, Message> {
+    let is_addons = entry.kind == IndexKind::Addon;
+    let add = Message::OpenDialog(Dialog::AddRepo {
+        url: entry.url.clone(),
+        mode: if is_addons { "addon_git" } else { "auto" }.to_string(),
+        is_addons,
+        advanced: false,
+    });
+    let mut title = row![text(entry.name).size(15).color(c.title)]
+        .spacing(6)
+        .align_y(iced::Alignment::Center);
+    if !is_addons {
+        title = title.push(badge_tag("Mod", c.muted, c.muted));
+    }
+    if !entry.categories.is_empty() {
+        title = title.push(text(entry.categories.join(" · ")).size(12).color(c.muted));
+    }
+    let mut details = column![title].spacing(2);
+    if !entry.description.is_empty() {
+        details = details.push(text(entry.description).size(13).color(c.text));
+    }
+    details = details.push(text(entry.url).size(12).color(c.muted));
+    container(
+        row![
+            details.width(Length::Fill),
+            button(text("Add").size(13))
+                .on_press(add)
+                .padding([5, 12])
+                .style(move |_theme, _status| theme::btn_primary_style(c)),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+    )
+    .padding([8, 10])
+    .width(Length::Fill)
+    .style(move |_theme| theme::card_style(c))
+    .into()
+}
+
+/// `query_input` and `url_input` are built by the caller because text inputs
+/// need the app's context-menu state.
+#[allow(clippy::too_many_arguments)]
+pub fn view<'a>(
+    entries: &'a [IndexEntry],
+    sources: &'a [IndexSource],
+    query: &'a str,
+    client: Option<ClientFamily>,
+    kind: Option<IndexKind>,
+    loading: bool,
+    query_input: Element<'a, Message>,
+    url_input: Element<'a, Message>,
+    colors: ThemeColors,
+) -> Element<'a, Message> {
+    let c = colors;
+    let results = addon_index::search(
+        entries,
+        &IndexQuery {
+            text: query.to_string(),
+            client,
+            category: None,
+            kind,
+        },
+    );
+
+    let list: Element<'a, Message> = if loading {
+        text("Loading addon catalogs…")
+            .size(13)
+            .color(c.muted)
+            .into()
+    } else if sources.is_empty() {
+        text("No catalogs imported yet. Add a catalog URL or copy a catalog file and import it from the clipboard.")
+            .size(13)
+            .color(c.muted)
+            .into()
+    } else if results.is_empty() {
+        text("Nothing in the catalogs matches.")
+            .size(13)
+            .color(c.muted)
+            .into()
+    } else {
+        let total = results.len();
+        let mut cards = column(
+            results
+                .into_iter()
+                .take(RESULTS_SHOWN)
+                .map(|entry| entry_card(entry, c)),
+        )
+        .spacing(6);
+        if total > RESULTS_SHOWN {
+            cards = cards.push(
+                text(format!(
+                    "{} more; refine the search to see them.",
+                    total - RESULTS_SHOWN
+                ))
+                .size(12)
+                .color(c.muted),
+            );
+        }
+        scrollable(cards)
+            .height(Length::Fixed(340.0))
+            .direction(theme::vscroll_overlay())
+            .style(move |theme, status| theme::scrollable_style(c)(theme, status))
+            .into()
+    };
+
+    let current_client = Some(
+        client
+            .map(|family| family.label().to_string())
+            .unwrap_or_else(|| ALL_CLIENTS.to_string()),
+    );
+    let kinds = [None, Some(IndexKind::Addon), Some(IndexKind::Mod)]
+        .into_iter()
+        .map(|kind| kind_label(kind).to_string())
+        .collect::<Vec<_>>();
+
+    let catalogs = sources.iter().map(|source| {
+        let origin = source
+            .url
+            .as_deref()
+            .unwrap_or("imported from the clipboard");
+        row![
+            text(format!("{} · {} entries", source.name, source.entries))
+                .size(13)
+                .color(c.text),
+            text(origin).size(12).color(c.muted),
+            Space::new().width(Length::Fill),
+            button(text("Remove").size(12))
+                .on_press(Message::RemoveAddonIndex(source.id))
+                .padding([3, 10])
+                .style(move |_theme, status| theme::btn_danger_style(c, status)),
+        ]
+        .spacing(8)
+        .align_y(iced::Alignment::Center)
+        .into()
+    });
+    let mut refresh = secondary_button("Refresh", c);
+    if sources.iter().any(|source| source.url.is_some()) {
+        refresh = refresh.on_press(Message::RefreshAddonIndexes);
+    }
+
+    column![
+        row![
+            text("Addon Catalog").size(18).color(c.title),
+            Space::new().width(Length::Fill),
+            close_button(c),
+        ]
+        .align_y(iced::Alignment::Center),
+        row![
+            query_input,
+            pick_list(
+                client_choices(),
+                current_client,
+                Message::SetAddonIndexClient
+            )
+            .text_size(13)
+            .padding([4, 8]),
+            pick_list(
+                kinds,
+                Some(kind_label(kind).to_string()),
+                Message::SetAddonIndexKind
+            )
+            .text_size(13)
+            .padding([4, 8]),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+        list,
+        column(catalogs).spacing(4),
+        row![
+            url_input,
+            secondary_button("Add URL", c).on_press(Message::AddAddonIndexUrl),
+            secondary_button("Import from clipboard", c).on_press(Message::ImportAddonIndex),
+            refresh,
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+    ]
+    .spacing(12)
+    .into()
+}
@@ -0,0 +1,126 @@
+//! Named addon sets: switch several addons on or off per character at once.
+
+use crate::components::helpers::close_button;
+use crate::theme::{self, ThemeColors};
+use crate::Message;
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, Space};
+use iced::{Element, Length};
+use wuddle_engine::{AddonSet, WtfCharacter};
+
+const ALL_CHARACTERS: &str = "All characters";
+
+fn secondary_button(label: &str, c: ThemeColors) -> button::Button<'_, Message> {
+    button(text(label).size(13))
+        .padding([5, 12])
+        .style(move |_theme, status| match status {
+            button::Status::Hovered => theme::tab_button_hovered_style(c),
+            _ => theme::tab_button_style(c),
+        })
+}
+
+/// `name_input` is built by the caller because text inputs need the app's
+/// context-menu state.
+pub fn view<'a>(
+    sets: &'a [AddonSet],
+    characters: &'a [WtfCharacter],
+    character: Option<usize>,
+    new_name: &'a str,
+    loading: bool,
+    name_input: Element<'a, Message>,
+    colors: ThemeColors,
+) -> Element<'a, Message> {
+    let c = colors;
+    let selected = character.and_then(|index| characters.get(index));
+    let mut choices = vec![ALL_CHARACTERS.to_string()];
+    choices.extend(characters.iter().map(WtfCharacter::label));
+    let current = Some(
+        selected
+            .map(WtfCharacter::label)
+            .unwrap_or_else(|| ALL_CHARACTERS.to_string()),
+    );
+
+    let list: Element<'a, Message> = if loading {
+        text("Loading addon sets…").size(13).color(c.muted).into()
+    } else if sets.is_empty() {
+        text("No addon sets yet. Pick a character, name the set and save it from that character's current addon list.")
+            .size(13)
+            .color(c.muted)
+            .into()
+    } else {
+        let cards = sets.iter().map(|set| {
+            let summary = format!(
+                "{} repo(s), {} folder(s); {} enabled",
+                set.repos.len(),
+                set.folders.len(),
+                set.repos.iter().filter(|repo| repo.enabled).count()
+                    + set.folders.iter().filter(|folder| folder.enabled).count()
+            );
+            container(
+                row![
+                    column![
+                        text(&set.name).size(15).color(c.title),
+                        text(summary).size(12).color(c.muted),
+                    ]
+                    .spacing(2),
+                    Space::new().width(Length::Fill),
+                    button(text("Apply").size(13))
+                        .on_press(Message::ApplyAddonSet(set.name.clone()))
+                        .padding([5, 12])
+                        .style(move |_theme, _status| theme::btn_primary_style(c)),
+                    secondary_button("Export", c)
+                        .on_press(Message::ExportAddonSet(set.name.clone())),
+                    button(text("Delete").size(13))
+                        .on_press(Message::DeleteAddonSet(set.name.clone()))
+                        .padding([5, 12])
+                        .style(move |_theme, status| theme::btn_danger_style(c, status)),
+                ]
+                .spacing(6)
+                .align_y(iced::Alignment::Center),
+            )
+            .padding([8, 10])
+            .width(Length::Fill)
+            .style(move |_theme| theme::card_style(c))
+            .into()
+        });
+        let list_height = (sets.len() as f32 * 56.0).min(280.0);
+        scrollable(column(cards).spacing(6))
+            .height(Length::Fixed(list_height))
+            .direction(theme::vscroll_overlay())
+            .style(move |theme, status| theme::scrollable_style(c)(theme, status))
+            .into()
+    };
+
+    let can_capture = selected.is_some() && !new_name.trim().is_empty();
+    let mut save = secondary_button("Save from character", c);
+    if can_capture {
+        save = save.on_press(Message::CaptureAddonSet);
+    }
+
+    column![
+        row![
+            text("Addon Sets").size(18).color(c.title),
+            Space::new().width(Length::Fill),
+            close_button(c),
+        ]
+        .align_y(iced::Alignment::Center),
+        text(
+            "A set switches addons on or off by rewriting each character's AddOns.txt; no addon folders are moved. Apply sets while the game is closed, since the game rewrites AddOns.txt when you log out.",
+        )
+        .size(13)
+        .color(c.muted),
+        row![
+            text("Character").size(13).color(c.text),
+            pick_list(choices, current, Message::SetAddonSetCharacter)
+                .text_size(13)
+                .padding([4, 8]),
+        ]
+        .spacing(8)
+        .align_y(iced::Alignment::Center),
+        list,
+        row![name_input, save, secondary_button("Import from clipboard", c).on_press(Message::ImportAddonSet)]
+            .spacing(6)
+            .align_y(iced::Alignment::Center),
+    ]
+    .spacing(12)
+    .into()
+}
@@ -0,0 +1,127 @@
+//! Untracked addon folders matched against known sources, with a button to
+//! link each to the repo it came from.
+
+use crate::components::helpers::close_button;
+use crate::theme::{self, ThemeColors};
+use crate::Message;
+use iced::widget::{button, column, container, row, scrollable, text, Space};
+use iced::{Element, Length};
+use wuddle_engine::AddonIdentification;
+
+/// Matches shown per folder; weaker ones are rarely the right source.
+const MATCHES_SHOWN: usize = 3;
+
+fn secondary_button(label: &str, c: ThemeColors) -> button::Button<'_, Message> {
+    button(text(label).size(13))
+        .padding([5, 12])
+        .style(move |_theme, status| match status {
+            button::Status::Hovered => theme::tab_button_hovered_style(c),
+            _ => theme::tab_button_style(c),
+        })
+}
+
+pub fn view<'a>(
+    addons: &'a [AddonIdentification],
+    loading: bool,
+    linking: Option<&'a str>,
+    colors: ThemeColors,
+) -> Element<'a, Message> {
+    let c = colors;
+
+    let list: Element<'a, Message> = if loading {
+        text("Fingerprinting addon folders…")
+            .size(13)
+            .color(c.muted)
+            .into()
+    } else if addons.is_empty() {
+        text("No untracked addon matches a known source. Importing a catalog from another user may identify more.")
+            .size(13)
+            .color(c.muted)
+            .into()
+    } else {
+        let cards = addons.iter().map(|addon| {
+            let busy = linking.is_some();
+            let mut title = row![text(&addon.folder).size(15).color(c.title)]
+                .spacing(8)
+                .align_y(iced::Alignment::End);
+            if addon.manual_repo_id.is_some() {
+                title = title.push(text("imported manually").size(12).color(c.muted));
+            }
+            if linking == Some(addon.folder.as_str()) {
+                title = title.push(text("Linking…").size(12).color(c.muted));
+            }
+            let mut card = column![title].spacing(4);
+            for (index, candidate) in addon.matches.iter().take(MATCHES_SHOWN).enumerate() {
+                let mut detail = format!(
+                    "{} from {}",
+                    candidate.kind.label(),
+                    candidate.source.as_str()
+                );
+                if let Some(version) = &candidate.version {
+                    detail.push_str(&format!(", version {version}"));
+                }
+                let mut link = if index == 0 {
+                    button(text("Link").size(13))
+                        .padding([5, 12])
+                        .style(move |_theme, _status| theme::btn_primary_style(c))
+                } else {
+                    secondary_button("Link", c)
+                };
+                if !busy {
+                    link = link.on_press(Message::LinkAddonFolder {
+                        folder: addon.folder.clone(),
+                        source: candidate.clone(),
+                    });
+                }
+                card = card.push(
+                    row![
+                        column![
+                            text(&candidate.url).size(13).color(c.text),
+                            text(detail).size(12).color(c.muted),
+                        ]
+                        .spacing(2),
+                        Space::new().width(Length::Fill),
+                        link,
+                    ]
+                    .spacing(6)
+                    .align_y(iced::Alignment::Center),
+                );
+            }
+            container(card)
+                .padding([8, 10])
+                .width(Length::Fill)
+                .style(move |_theme| theme::card_style(c))
+                .into()
+        });
+        scrollable(column(cards).spacing(6))
+            .height(Length::Fixed(320.0))
+            .direction(theme::vscroll_overlay())
+            .style(move |theme, status| theme::scrollable_style(c)(theme, status))
+            .into()
+    };
+
+    column![
+        row![
+            text("Identify Addons").size(18).color(c.title),
+            Space::new().width(Length::Fill),
+            close_button(c),
+        ]
+        .align_y(iced::Alignment::Center),
+        text(
+            "Addon folders Wuddle does not update are compared with the files of tracked addons, the Quick Add presets and imported catalogs. Linking a folder tracks its repo; the next update replaces the folder with the repo's files.",
+        )
+        .size(13)
+        .color(c.muted),
+        list,
+        row![
+            Space::new().width(Length::Fill),
+            secondary_button("Export catalog", c).on_press(Message::ExportAddonCatalog),
+            secondary_button("Import catalog from clipboard", c)
+                .on_press(Message::ImportAddonCatalog),
+        ]
+        .spacing(6)
+        .align_y(iced::Alignment::Center),
+    ]
+    .spacing(12)
+    .into()
+}
@@ -1,9 +1,12 @@
 /// Dialog rendering modules.
 /// Each file renders one or more Dialog variants as a free function that
 /// receives the destructured dialog fields + ThemeColors — no &App required.
+pub mod addon_index;
 pub mod addon_local_changes;
+pub mod addon_sets;
 pub mod changelog;
 pub mod dll_warning;
+pub mod identify_addons;
 pub mod mod_file_info;
 pub mod mods_warning;
 pub mod patches_warning;
@@ -15,6 +15,7 @@ mod mpq;
 mod network;
 pub mod panels;
 mod platform_identity;
+mod saved_variables;
 #[cfg_attr(test, allow(dead_code))]
 mod self_update;
 pub mod service;
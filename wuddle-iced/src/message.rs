@@ -75,11 +75,15 @@ pub enum Message {
     ToggleDesktopNotify(bool),
     ToggleSymlinks(bool),
     ToggleXattr(bool),
+    ToggleAtomicUpdates(bool),
     ToggleClock12(bool),
     ToggleFrizFont(bool),
     ToggleRememberWindowGeometry(bool),
     SetUiScaleMode(settings::UiScaleMode),
+    SetSshKey(String),
     SetGithubTokenInput(String),
+    SetForgeTokenHostInput(String),
+    SetForgeTokenInput(String),
 
     // Tweaks
     ToggleTweak(TweakId, bool),
@@ -125,6 +129,42 @@ pub enum Message {
     WuddleResetPrepared(Result<(), String>),
     WuddleResetRestarted(Result<(), String>),
 
+    // SavedVariables snapshots
+    ToggleSavedVariablesSnapshots(bool),
+    SetSavedVariablesSnapshotHours(String),
+    SetSavedVariablesKeep(String),
+    SavedVariablesSnapshotTick,
+    OpenSavedVariables,
+    SavedVariablesListed(
+        ProfileScoped<Result<Vec<wuddle_engine::saved_variables::SavedVariablesSnapshot>, String>>,
+    ),
+    TakeSavedVariablesSnapshot,
+    SavedVariablesSnapshotTaken {
+        scheduled: bool,
+        result: ProfileScoped<Result<service::SnapshotTaken, String>>,
+    },
+    /// Older snapshot of the comparison, by label.
+    SetSavedVariablesFrom(String),
+    /// Newer side of the comparison, by label; includes the current files.
+    SetSavedVariablesTo(String),
+    SetSavedVariablesAddon(String),
+    SavedVariablesDiffLoaded {
+        request_id: u64,
+        result: ProfileScoped<Result<Vec<wuddle_engine::saved_variables::FileDiff>, String>>,
+    },
+    RestoreSavedVariables,
+    SavedVariablesRestored(
+        ProfileScoped<
+            Result<
+                (
+                    wuddle_engine::saved_variables::RestoreReport,
+                    Vec<wuddle_engine::saved_variables::SavedVariablesSnapshot>,
+                ),
+                String,
+            >,
+        >,
+    ),
+
     // Toast notifications
     DismissToast(usize),
     ToastHovered(usize, bool),
@@ -273,6 +313,7 @@ pub enum Message {
     ReposLoaded(ProfileScoped<Result<RepoLoadResult, String>>),
     PlansLoaded(ProfileScoped<Result<Vec<PlanRow>, String>>),
     SettingsLoaded(settings::LoadedSettings),
+    PresetCatalogLoaded(Result<wuddle_engine::presets::LoadedCatalog, String>),
 
     // Operations (Phase 3)
     CheckUpdates,
@@ -303,7 +344,7 @@ pub enum Message {
     /// Carries `repo_id` so the conflict handler can force-reinstall the right repo.
     InstallAfterAddResult {
         repo_id: i64,
-        result: ProfileScoped<Result<String, String>>,
+        result: ProfileScoped<Result<service::InstalledRepo, String>>,
     },
     /// Fires when the user confirms overwriting file conflicts for a repo that is
     /// already in the DB (the initial install attempt raised ADDON_CONFLICT).
@@ -331,6 +372,7 @@ pub enum Message {
     RepoDetailsChildrenLoaded(
         ProfileScoped<(String, Result<Vec<service::RepoDetailChild>, String>)>,
     ),
+    InstallHistoryLoaded(ProfileScoped<Result<Vec<wuddle_engine::InstallHistoryEntry>, String>>),
     RemoveRepoResult {
         repo_id: i64,
         repo_name: String,
@@ -373,6 +415,16 @@ pub enum Message {
         repo_id: i64,
         result: ProfileScoped<Result<PlanRow, String>>,
     },
+    /// Redeploy the version a history entry recorded and restore the addon
+    /// settings snapshotted when it was replaced.
+    RollbackInstall {
+        repo_id: i64,
+        entry_id: i64,
+    },
+    RollbackInstallResult {
+        repo_id: i64,
+        result: ProfileScoped<Result<wuddle_engine::RollbackReport, String>>,
+    },
     FetchBranches(i64),
     GithubRateTick,
     FetchBranchesResult(ProfileScoped<(i64, Result<Vec<String>, String>)>),
@@ -482,6 +534,88 @@ pub enum Message {
         addon_name: String,
     },
 
+    // Addon sets
+    OpenAddonSets,
+    AddonSetsLoaded(
+        ProfileScoped<
+            Result<
+                (
+                    Vec<wuddle_engine::AddonSet>,
+                    Vec<wuddle_engine::WtfCharacter>,
+                ),
+                String,
+            >,
+        >,
+    ),
+    SetAddonSetCharacter(String),
+    SetAddonSetName(String),
+    CaptureAddonSet,
+    ApplyAddonSet(String),
+    ApplyAddonSetResult {
+        name: String,
+        result: ProfileScoped<Result<wuddle_engine::AddonSetReport, String>>,
+    },
+    DeleteAddonSet(String),
+    ExportAddonSet(String),
+    AddonSetExported(ProfileScoped<Result<String, String>>),
+    ImportAddonSet,
+    AddonSetClipboardRead(Option<String>),
+    AddonSetsChanged(ProfileScoped<Result<(Vec<wuddle_engine::AddonSet>, String), String>>),
+
+    // Local addon catalog
+    OpenAddonIndex(wuddle_engine::IndexKind),
+    AddonIndexLoaded(
+        ProfileScoped<
+            Result<
+                (
+                    Vec<wuddle_engine::IndexSource>,
+                    Vec<wuddle_engine::IndexEntry>,
+                ),
+                String,
+            >,
+        >,
+    ),
+    SetAddonIndexQuery(String),
+    SetAddonIndexClient(String),
+    SetAddonIndexKind(String),
+    SetAddonIndexUrl(String),
+    AddAddonIndexUrl,
+    ImportAddonIndex,
+    AddonIndexClipboardRead(Option<String>),
+    RefreshAddonIndexes,
+    RemoveAddonIndex(i64),
+    AddonIndexChanged(
+        ProfileScoped<
+            Result<
+                (
+                    Vec<wuddle_engine::IndexSource>,
+                    Vec<wuddle_engine::IndexEntry>,
+                    String,
+                ),
+                String,
+            >,
+        >,
+    ),
+
+    // Untracked addon identification
+    OpenIdentifyAddons,
+    IdentifyAddonsLoaded(ProfileScoped<Result<Vec<wuddle_engine::AddonIdentification>, String>>),
+    LinkAddonFolder {
+        folder: String,
+        source: wuddle_engine::SourceMatch,
+    },
+    LinkAddonFolderResult {
+        folder: String,
+        result: ProfileScoped<Result<Vec<wuddle_engine::AddonIdentification>, String>>,
+    },
+    ExportAddonCatalog,
+    AddonCatalogExported(ProfileScoped<Result<String, String>>),
+    ImportAddonCatalog,
+    AddonCatalogClipboardRead(Option<String>),
+    AddonCatalogImported(
+        ProfileScoped<Result<(Vec<wuddle_engine::AddonIdentification>, String), String>>,
+    ),
+
     // GitHub token
     SaveGithubToken,
     SaveGithubTokenResult(Result<(), String>),
@@ -492,6 +626,16 @@ pub enum Message {
     ForgetGithubToken,
     ForgetGithubTokenResult(Result<service::GitHubTokenSource, String>),
 
+    // GitLab / Gitea tokens
+    SaveForgeToken,
+    SaveForgeTokenResult(Result<String, String>),
+    ValidateForgeTokenResult {
+        host: String,
+        result: wuddle_engine::ForgeTokenValidation,
+    },
+    ForgetForgeToken(String),
+    ForgetForgeTokenResult(Result<String, String>),
+
     // Instance settings
     SaveInstanceSettings,
     UpdateInstanceField(InstanceField),
@@ -570,6 +714,9 @@ pub enum Message {
     FetchRepoPreviewResult(u64, String, Result<service::RepoPreviewInfo, String>),
     OpenRepoReadmePreview(String, String),
     RepoReadmePreviewLoaded(u64, Result<service::RepoPreviewInfo, String>),
+    /// Show the release notes between a repo's installed and latest version.
+    OpenWhatsNew(i64),
+    WhatsNewLoaded(u64, Result<String, String>),
     ToggleAddRepoDir(String),
     PreviewRepoFile(String),
     PreviewRepoFileResult(u64, Result<(String, String), String>),
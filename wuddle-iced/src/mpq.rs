@@ -1705,6 +1705,7 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                 replace_file_conflicts: false,
                 replace_local_changes: false,
                 cache_keep_versions: 0,
+                client_family: app.quick_add_client_family(),
             };
             let (operation_id, scope) = begin_operation(app, true);
             Some(Task::perform(
@@ -228,6 +228,14 @@ pub fn view<'a>(app: &'a App, colors: ThemeColors) -> Element<'a, Message> {
             checkbox(app.opt_xattr)
                 .label("Set xattr file comments")
                 .on_toggle(Message::ToggleXattr),
+            tip(
+                checkbox(app.opt_atomic_updates)
+                    .label("Update All installs release updates all-or-nothing")
+                    .on_toggle(Message::ToggleAtomicUpdates),
+                "Every release update is downloaded and prepared before any file changes, then all of them are installed together. If one fails, the others are rolled back so addons never run against mismatched library versions.\n\nGit addons still update one by one.",
+                tooltip::Position::Top,
+                colors,
+            ),
         ]
         .spacing(8),
         c,
@@ -497,6 +505,198 @@ pub fn view<'a>(app: &'a App, colors: ThemeColors) -> Element<'a, Message> {
         c,
     );
 
+    // --- GitLab / Gitea Authentication section ---
+    let saved_forge_tokens: Vec<Element<Message>> = app
+        .forge_tokens
+        .iter()
+        .map(|entry| {
+            let (status, status_color) = match &entry.validation {
+                None => ("verification pending".to_string(), colors.warn),
+                Some(wuddle_engine::ForgeTokenValidation::Valid) => {
+                    ("active (validated)".to_string(), colors.good)
+                }
+                Some(wuddle_engine::ForgeTokenValidation::Invalid) => {
+                    ("rejected by host".to_string(), colors.bad)
+                }
+                Some(wuddle_engine::ForgeTokenValidation::Unverified(_)) => {
+                    ("stored — verification unavailable".to_string(), colors.warn)
+                }
+            };
+            row![
+                text(entry.host.clone()).size(13).color(colors.text),
+                text(status).size(12).color(status_color),
+                Space::new().width(Length::Fill),
+                tip(
+                    {
+                        let c2 = c;
+                        button(text("Forget").size(13))
+                            .on_press(Message::ForgetForgeToken(entry.host.clone()))
+                            .padding([4, 10])
+                            .style(move |_theme, status| theme::btn_danger_style(c2, status))
+                    },
+                    "Remove the saved token for this host",
+                    tooltip::Position::Top,
+                    colors,
+                ),
+            ]
+            .spacing(8)
+            .align_y(iced::Alignment::Center)
+            .into()
+        })
+        .collect();
+
+    let forge_section = settings_card(
+        column![
+            text("GitLab and Gitea Authentication").size(18).color(colors.title),
+            text("Optional: add a read-only access token to track private projects on GitLab, Codeberg, or a self-hosted Gitea/Forgejo server.")
+                .size(12)
+                .color(colors.muted),
+            text("Each token is only ever sent to the host it was saved for.")
+                .size(12)
+                .color(colors.muted),
+            row![
+                context_text_input(
+                    app,
+                    colors,
+                    "forge-token-host",
+                    "gitlab.com",
+                    &app.forge_token_host_input,
+                )
+                .on_input(Message::SetForgeTokenHostInput)
+                .width(Length::FillPortion(2))
+                .padding([8, 12]),
+                context_text_input(
+                    app,
+                    colors,
+                    "forge-token",
+                    "Access token",
+                    &app.forge_token_input,
+                )
+                .on_input(Message::SetForgeTokenInput)
+                .width(Length::FillPortion(3))
+                .padding([8, 12]),
+                tip(
+                    {
+                        let c2 = c;
+                        button(text("Save token").size(13))
+                            .on_press(Message::SaveForgeToken)
+                            .padding([6, 12])
+                            .style(move |_theme, _status| theme::tab_button_active_style(c2))
+                    },
+                    "Store this token for the host on the left",
+                    tooltip::Position::Top,
+                    colors,
+                ),
+            ]
+            .spacing(8)
+            .align_y(iced::Alignment::Center),
+            column(saved_forge_tokens).spacing(4),
+        ]
+        .spacing(8),
+        c,
+    );
+
+    // --- Git over SSH section ---
+    let ssh_section = settings_card(
+        column![
+            text("Git over SSH").size(18).color(colors.title),
+            text("Addon repositories added as git@host:owner/repo or ssh:// use ssh-agent first, then this key file, then the default keys in ~/.ssh.")
+                .size(12)
+                .color(colors.muted),
+            text("Passphrase-protected keys must be loaded into ssh-agent. The key path is stored in settings; the key itself is never copied.")
+                .size(12)
+                .color(colors.muted),
+            context_text_input(
+                app,
+                colors,
+                "ssh-key",
+                "Path to a private key (optional)",
+                &app.ssh_key,
+            )
+            .on_input(Message::SetSshKey)
+            .width(Length::Fill)
+            .padding([8, 12]),
+        ]
+        .spacing(8),
+        c,
+    );
+
+    // --- SavedVariables snapshots section ---
+    let snapshot_hours_value = app.saved_variables_snapshot_hours.to_string();
+    let snapshot_hours_input = context_text_input(
+        app,
+        colors,
+        "saved-variables-hours",
+        "24",
+        &snapshot_hours_value,
+    )
+    .width(60)
+    .padding([4, 8]);
+    let snapshot_hours_input = if app.opt_saved_variables_snapshots {
+        snapshot_hours_input.on_input(Message::SetSavedVariablesSnapshotHours)
+    } else {
+        snapshot_hours_input
+    };
+    let snapshot_keep_value = app.saved_variables_keep.to_string();
+    let saved_variables_section = settings_card(
+        column![
+            row![
+                column![
+                    text("SavedVariables Snapshots").size(18).color(colors.title),
+                    text("Keep copies of your addon settings from WTF, compare them per addon, and restore one addon's settings after a bad update.")
+                        .size(12)
+                        .color(colors.muted),
+                ]
+                .spacing(4),
+                Space::new().width(Length::Fill),
+                tip(
+                    {
+                        let c2 = c;
+                        button(text("Snapshots...").size(13))
+                            .on_press(Message::OpenSavedVariables)
+                            .padding([6, 12])
+                            .style(move |_theme, status| match status {
+                                button::Status::Hovered => theme::tab_button_hovered_style(c2),
+                                _ => theme::tab_button_style(c2),
+                            })
+                    },
+                    "Take a snapshot now, compare snapshots, or restore an addon's settings.",
+                    tooltip::Position::Top,
+                    colors,
+                ),
+            ]
+            .spacing(12)
+            .align_y(iced::Alignment::Center),
+            checkbox(app.opt_saved_variables_snapshots)
+                .label("Snapshot SavedVariables automatically")
+                .on_toggle(Message::ToggleSavedVariablesSnapshots),
+            container(
+                row![
+                    text("Interval (hours):").size(12).color(
+                        if app.opt_saved_variables_snapshots { colors.text } else { colors.muted }
+                    ),
+                    snapshot_hours_input,
+                    text("Snapshots to keep:").size(12).color(colors.text),
+                    context_text_input(
+                        app,
+                        colors,
+                        "saved-variables-keep",
+                        "20",
+                        &snapshot_keep_value,
+                    )
+                    .on_input(Message::SetSavedVariablesKeep)
+                    .width(60)
+                    .padding([4, 8]),
+                ]
+                .spacing(8)
+                .align_y(iced::Alignment::Center)
+            )
+            .padding(child_padding),
+        ]
+        .spacing(8),
+        c,
+    );
+
     // --- Backup and Restore section ---
     let backup_section = settings_card(
         row![
@@ -539,6 +739,9 @@ pub fn view<'a>(app: &'a App, colors: ThemeColors) -> Element<'a, Message> {
                 .spacing(8)
                 .height(280),
             github_section,
+            forge_section,
+            ssh_section,
+            saved_variables_section,
             backup_section,
         ]
         .spacing(8)
@@ -333,9 +333,16 @@ pub fn view<'a>(app: &'a App, colors: ThemeColors, label: &str) -> Element<'a, M
                     return true;
                 }
                 let q = app.project_search.to_lowercase();
+                let toc_title_matches = row.repo.addon_tocs.iter().any(|entry| {
+                    entry
+                        .toc
+                        .display_title()
+                        .is_some_and(|title| title.to_lowercase().contains(&q))
+                });
                 if row.addon_name.to_lowercase().contains(&q)
                     || row.repo.name.to_lowercase().contains(&q)
                     || row.repo.owner.to_lowercase().contains(&q)
+                    || toc_title_matches
                 {
                     return true;
                 }
@@ -641,6 +648,32 @@ pub fn view<'a>(app: &'a App, colors: ThemeColors, label: &str) -> Element<'a, M
             tooltip::Position::Bottom,
             colors,
         ));
+        let c3 = c;
+        action_items.push(tip(
+            button(text("Sets").size(13))
+                .on_press(Message::OpenAddonSets)
+                .padding([4, 10])
+                .style(move |_theme, status| match status {
+                    button::Status::Hovered => theme::tab_button_hovered_style(c3),
+                    _ => theme::tab_button_style(c3),
+                }),
+            "Switch between named sets of enabled addons",
+            tooltip::Position::Bottom,
+            colors,
+        ));
+        let c4 = c;
+        action_items.push(tip(
+            button(text("Identify").size(13))
+                .on_press(Message::OpenIdentifyAddons)
+                .padding([4, 10])
+                .style(move |_theme, status| match status {
+                    button::Status::Hovered => theme::tab_button_hovered_style(c4),
+                    _ => theme::tab_button_style(c4),
+                }),
+            "Find untracked addons that match a known source and link them",
+            tooltip::Position::Bottom,
+            colors,
+        ));
     }
     if is_patches_tab {
         let c2 = c;
@@ -670,6 +703,26 @@ pub fn view<'a>(app: &'a App, colors: ThemeColors, label: &str) -> Element<'a, M
             colors,
         ));
     }
+    if !is_patches_tab {
+        let c2 = c;
+        let kind = if is_mods_tab {
+            wuddle_engine::IndexKind::Mod
+        } else {
+            wuddle_engine::IndexKind::Addon
+        };
+        action_items.push(tip(
+            button(text("Browse").size(13))
+                .on_press(Message::OpenAddonIndex(kind))
+                .padding([4, 10])
+                .style(move |_theme, status| match status {
+                    button::Status::Hovered => theme::tab_button_hovered_style(c2),
+                    _ => theme::tab_button_style(c2),
+                }),
+            "Search the imported addon catalogs",
+            tooltip::Position::Bottom,
+            colors,
+        ));
+    }
     {
         let c2 = c;
         let add_tip = if is_patches_tab {
@@ -1305,11 +1358,13 @@ fn manual_mpq_row<'a>(
                 path: entry.path.clone(),
                 kind: "mpq".to_string(),
                 is_directory: false,
+                toc: None,
             }],
             loading: false,
             expanded_paths: Default::default(),
             loading_paths: Default::default(),
             children: Default::default(),
+            history: Vec::new(),
         }),
         c,
     ));
@@ -2049,14 +2104,31 @@ fn addon_name_cell<'a>(
 ) -> Element<'a, Message> {
     let c = colors;
     let url = repo.url.clone();
-    let subtitle = if repo.enabled {
-        format!("{} • {}", repo.owner, repo.forge)
+    let toc = if is_collection_member {
+        repo.addon_toc(&addon_name)
     } else {
-        format!("{} • {} • disabled", repo.owner, repo.forge)
+        repo.primary_addon_toc()
     };
+    let title = toc
+        .and_then(|toc| toc.display_title())
+        .map(str::to_string)
+        .unwrap_or_else(|| addon_name.clone());
+    let mut subtitle_parts = Vec::new();
+    if let Some(version) = toc.and_then(|toc| toc.version.as_deref()) {
+        subtitle_parts.push(version.to_string());
+    }
+    if let Some(author) = toc.and_then(|toc| toc.author.as_deref()) {
+        subtitle_parts.push(format!("by {author}"));
+    }
+    subtitle_parts.push(repo.owner.clone());
+    subtitle_parts.push(repo.forge.clone());
+    if !repo.enabled {
+        subtitle_parts.push("disabled".to_string());
+    }
+    let subtitle = subtitle_parts.join(" • ");
 
     let title_btn = button(iced::widget::rich_text::<(), _, _, _>([
-        iced::widget::span(addon_name.clone())
+        iced::widget::span(title)
             .underline(true)
             .color(c.link)
             .font(name_font(colors))
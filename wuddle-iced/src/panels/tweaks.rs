@@ -325,6 +325,7 @@ fn tweak_row_check<'a>(
 }
 
 /// Tweak row with checkbox + text input for numeric value
+#[allow(clippy::too_many_arguments)]
 fn tweak_row_input<'a, F>(
     app: &'a App,
     name: &str,
@@ -0,0 +1,617 @@
+//! SavedVariables snapshots: scheduled and on-demand copies of the addon
+//! settings in `WTF`, a per-addon diff between snapshots, and restoring one
+//! addon's files. The snapshot store itself lives in the engine.
+
+use crate::app::App;
+use crate::components::helpers::close_button;
+use crate::message::Message;
+use crate::service;
+use crate::theme::{self, ThemeColors};
+use crate::types::{Dialog, LogLevel, ToastKind};
+use iced::widget::{button, column, container, pick_list, row, scrollable, text, Space};
+use iced::{Element, Font, Length, Task};
+use wuddle_engine::saved_variables::{
+    DiffLineKind, FileChange, FileDiff, SavedVariablesSnapshot, SnapshotTrigger,
+};
+
+const CURRENT_FILES: &str = "Current files";
+/// Diff lines rendered in the dialog; the CLI shows complete diffs.
+const MAX_RENDERED_LINES: usize = 1500;
+
+#[derive(Debug, Clone, Default)]
+pub struct UiState {
+    pub snapshots: Vec<SavedVariablesSnapshot>,
+    /// Older snapshot of the comparison and the one restores come from.
+    pub from: Option<String>,
+    /// Newer snapshot of the comparison; `None` compares with the current files.
+    pub to: Option<String>,
+    pub addon: Option<String>,
+    pub diff: Option<Vec<FileDiff>>,
+    pub diff_request: Option<u64>,
+    pub busy: bool,
+    pub error: Option<String>,
+}
+
+impl UiState {
+    fn snapshot(&self, id: &str) -> Option<&SavedVariablesSnapshot> {
+        self.snapshots.iter().find(|snapshot| snapshot.id == id)
+    }
+
+    fn addons(&self) -> Vec<String> {
+        self.from
+            .as_deref()
+            .and_then(|id| self.snapshot(id))
+            .map(SavedVariablesSnapshot::addons)
+            .unwrap_or_default()
+    }
+
+    /// Keep the selection valid after the snapshot list changed, defaulting
+    /// to the newest snapshot compared with the current files.
+    fn reconcile_selection(&mut self) {
+        if self
+            .from
+            .as_deref()
+            .and_then(|id| self.snapshot(id))
+            .is_none()
+        {
+            self.from = self.snapshots.first().map(|snapshot| snapshot.id.clone());
+            self.diff = None;
+        }
+        if self
+            .to
+            .as_deref()
+            .is_some_and(|id| self.snapshot(id).is_none())
+        {
+            self.to = None;
+        }
+        let addons = self.addons();
+        let selected_exists = self.addon.as_deref().is_some_and(|addon| {
+            addons
+                .iter()
+                .any(|candidate| candidate.eq_ignore_ascii_case(addon))
+        });
+        if !selected_exists {
+            self.addon = addons.into_iter().next();
+            self.diff = None;
+        }
+    }
+}
+
+pub(crate) fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| "an unknown time".to_string())
+}
+
+fn snapshot_label(snapshot: &SavedVariablesSnapshot) -> String {
+    format!(
+        "{} ({})",
+        format_timestamp(snapshot.created_unix),
+        snapshot.trigger.label()
+    )
+}
+
+fn snapshot_id_for_label(app: &App, label: &str) -> Option<String> {
+    app.saved_variables_ui
+        .snapshots
+        .iter()
+        .find(|snapshot| snapshot_label(snapshot) == label)
+        .map(|snapshot| snapshot.id.clone())
+}
+
+fn snapshot_task(app: &mut App, trigger: SnapshotTrigger) -> Task<Message> {
+    let scope = app.profile_operation_scope();
+    let scheduled = trigger == SnapshotTrigger::Scheduled;
+    Task::perform(
+        service::take_saved_variables_snapshot(
+            app.wow_dir.clone(),
+            trigger,
+            app.saved_variables_keep,
+        ),
+        move |result| Message::SavedVariablesSnapshotTaken {
+            scheduled,
+            result: crate::ProfileScoped::new(scope.clone(), result),
+        },
+    )
+}
+
+/// Load the diff for the current selection, superseding any older request.
+fn diff_task(app: &mut App) -> Task<Message> {
+    let ui = &app.saved_variables_ui;
+    let (Some(from), Some(addon)) = (ui.from.clone(), ui.addon.clone()) else {
+        app.saved_variables_ui.diff_request = None;
+        return Task::none();
+    };
+    let to = ui.to.clone();
+    let request_id = app.next_async_request_id();
+    app.saved_variables_ui.diff_request = Some(request_id);
+    let scope = app.profile_operation_scope();
+    Task::perform(
+        service::diff_saved_variables(app.wow_dir.clone(), from, to, addon),
+        move |result| Message::SavedVariablesDiffLoaded {
+            request_id,
+            result: crate::ProfileScoped::new(scope.clone(), result),
+        },
+    )
+}
+
+fn parse_setting(value: &str, current: u32) -> u32 {
+    if value.is_empty() {
+        1
+    } else {
+        value.parse::<u32>().map(|n| n.max(1)).unwrap_or(current)
+    }
+}
+
+pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
+    match message {
+        Message::ToggleSavedVariablesSnapshots(enabled) => {
+            app.opt_saved_variables_snapshots = enabled;
+            app.save_settings();
+            app.log(
+                LogLevel::Info,
+                &format!(
+                    "Scheduled SavedVariables snapshots: {}.",
+                    if enabled { "enabled" } else { "disabled" }
+                ),
+            );
+            Some(Task::none())
+        }
+        Message::SetSavedVariablesSnapshotHours(value) => {
+            app.saved_variables_snapshot_hours =
+                parse_setting(&value, app.saved_variables_snapshot_hours);
+            app.save_settings();
+            app.log(
+                LogLevel::Info,
+                &format!(
+                    "SavedVariables snapshot interval set to {} h.",
+                    app.saved_variables_snapshot_hours
+                ),
+            );
+            Some(Task::none())
+        }
+        Message::SetSavedVariablesKeep(value) => {
+            app.saved_variables_keep = parse_setting(&value, app.saved_variables_keep);
+            app.save_settings();
+            app.log(
+                LogLevel::Info,
+                &format!(
+                    "Keeping the newest {} SavedVariables snapshot(s).",
+                    app.saved_variables_keep
+                ),
+            );
+            Some(Task::none())
+        }
+        Message::SavedVariablesSnapshotTick => {
+            if !app.opt_saved_variables_snapshots
+                || app.wow_dir.is_empty()
+                || app.saved_variables_ui.busy
+            {
+                return Some(Task::none());
+            }
+            Some(snapshot_task(app, SnapshotTrigger::Scheduled))
+        }
+        Message::OpenSavedVariables => {
+            if app.wow_dir.is_empty() {
+                app.show_toast("Set a WoW directory in Options first.", ToastKind::Warn);
+                return Some(Task::none());
+            }
+            app.saved_variables_ui = UiState {
+                busy: true,
+                ..UiState::default()
+            };
+            app.dialog = Some(Dialog::SavedVariables);
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::list_saved_variables_snapshots(app.wow_dir.clone()),
+                move |result| {
+                    Message::SavedVariablesListed(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::SavedVariablesListed(result) => {
+            let Some(result) = app.accept_profile_result(result, "SavedVariables snapshot list")
+            else {
+                return Some(Task::none());
+            };
+            app.saved_variables_ui.busy = false;
+            match result {
+                Ok(snapshots) => {
+                    app.saved_variables_ui.snapshots = snapshots;
+                    app.saved_variables_ui.reconcile_selection();
+                    Some(diff_task(app))
+                }
+                Err(error) => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("Could not list SavedVariables snapshots: {error}"),
+                    );
+                    app.saved_variables_ui.error = Some(error);
+                    Some(Task::none())
+                }
+            }
+        }
+        Message::TakeSavedVariablesSnapshot => {
+            if app.saved_variables_ui.busy {
+                return Some(Task::none());
+            }
+            app.saved_variables_ui.busy = true;
+            app.saved_variables_ui.error = None;
+            Some(snapshot_task(app, SnapshotTrigger::Manual))
+        }
+        Message::SavedVariablesSnapshotTaken { scheduled, result } => {
+            let Some(result) = app.accept_profile_result(result, "SavedVariables snapshot") else {
+                return Some(Task::none());
+            };
+            if !scheduled {
+                app.saved_variables_ui.busy = false;
+            }
+            match result {
+                Ok(taken) => {
+                    match (&taken.snapshot, scheduled) {
+                        (Some(snapshot), _) => {
+                            let message = format!(
+                                "Saved a {} SavedVariables snapshot with {} file(s).",
+                                snapshot.trigger.label(),
+                                snapshot.files.len()
+                            );
+                            app.log(LogLevel::Info, &message);
+                            if !scheduled {
+                                app.show_toast(message, ToastKind::Success);
+                            }
+                        }
+                        (None, false) => app.show_toast(
+                            "The game has not written any SavedVariables yet.",
+                            ToastKind::Info,
+                        ),
+                        (None, true) => {}
+                    }
+                    if taken.pruned > 0 {
+                        app.log(
+                            LogLevel::Info,
+                            &format!("Removed {} old SavedVariables snapshot(s).", taken.pruned),
+                        );
+                    }
+                    app.saved_variables_ui.snapshots = taken.snapshots;
+                    if matches!(app.dialog, Some(Dialog::SavedVariables)) {
+                        app.saved_variables_ui.reconcile_selection();
+                        if app.saved_variables_ui.diff.is_none() {
+                            return Some(diff_task(app));
+                        }
+                    }
+                    Some(Task::none())
+                }
+                Err(error) => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("SavedVariables snapshot failed: {error}"),
+                    );
+                    if !scheduled {
+                        app.show_toast("Could not snapshot SavedVariables.", ToastKind::Error);
+                        app.saved_variables_ui.error = Some(error);
+                    }
+                    Some(Task::none())
+                }
+            }
+        }
+        Message::SetSavedVariablesFrom(label) => {
+            app.saved_variables_ui.from = snapshot_id_for_label(app, &label);
+            app.saved_variables_ui.diff = None;
+            app.saved_variables_ui.reconcile_selection();
+            Some(diff_task(app))
+        }
+        Message::SetSavedVariablesTo(label) => {
+            app.saved_variables_ui.to = if label == CURRENT_FILES {
+                None
+            } else {
+                snapshot_id_for_label(app, &label)
+            };
+            app.saved_variables_ui.diff = None;
+            Some(diff_task(app))
+        }
+        Message::SetSavedVariablesAddon(addon) => {
+            app.saved_variables_ui.addon = Some(addon);
+            app.saved_variables_ui.diff = None;
+            Some(diff_task(app))
+        }
+        Message::SavedVariablesDiffLoaded { request_id, result } => {
+            if app.saved_variables_ui.diff_request != Some(request_id) {
+                return Some(Task::none());
+            }
+            app.saved_variables_ui.diff_request = None;
+            let Some(result) = app.accept_profile_result(result, "SavedVariables diff") else {
+                return Some(Task::none());
+            };
+            match result {
+                Ok(diff) => {
+                    app.saved_variables_ui.diff = Some(diff);
+                    app.saved_variables_ui.error = None;
+                }
+                Err(error) => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("SavedVariables diff failed: {error}"),
+                    );
+                    app.saved_variables_ui.error = Some(error);
+                }
+            }
+            Some(Task::none())
+        }
+        Message::RestoreSavedVariables => {
+            let ui = &app.saved_variables_ui;
+            let (Some(snapshot), Some(addon)) = (ui.from.clone(), ui.addon.clone()) else {
+                return Some(Task::none());
+            };
+            if ui.busy {
+                return Some(Task::none());
+            }
+            app.saved_variables_ui.busy = true;
+            app.saved_variables_ui.error = None;
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::restore_saved_variables(app.wow_dir.clone(), snapshot, addon),
+                move |result| {
+                    Message::SavedVariablesRestored(crate::ProfileScoped::new(
+                        scope.clone(),
+                        result,
+                    ))
+                },
+            ))
+        }
+        Message::SavedVariablesRestored(result) => {
+            let Some(result) = app.accept_profile_result(result, "SavedVariables restore") else {
+                return Some(Task::none());
+            };
+            app.saved_variables_ui.busy = false;
+            match result {
+                Ok((report, snapshots)) => {
+                    let mut message = format!(
+                        "Restored {} SavedVariables file(s) of {}.",
+                        report.restored_files, report.addon
+                    );
+                    if let Some(backup) = &report.backup_id {
+                        message.push_str(&format!(
+                            " The replaced files were kept as snapshot {backup}."
+                        ));
+                    }
+                    app.log(LogLevel::Info, &message);
+                    app.show_toast(
+                        format!("Restored {}'s settings.", report.addon),
+                        ToastKind::Success,
+                    );
+                    app.saved_variables_ui.snapshots = snapshots;
+                    app.saved_variables_ui.diff = None;
+                    app.saved_variables_ui.reconcile_selection();
+                    Some(diff_task(app))
+                }
+                Err(error) => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("SavedVariables restore failed: {error}"),
+                    );
+                    app.show_toast(error.clone(), ToastKind::Error);
+                    app.saved_variables_ui.error = Some(error);
+                    Some(Task::none())
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+fn secondary_button_style(colors: ThemeColors, status: button::Status) -> button::Style {
+    match status {
+        button::Status::Hovered => theme::tab_button_hovered_style(colors),
+        _ => theme::tab_button_style(colors),
+    }
+}
+
+fn change_label(change: FileChange) -> &'static str {
+    match change {
+        FileChange::Added => "added",
+        FileChange::Removed => "removed",
+        FileChange::Modified => "changed",
+        FileChange::Unchanged => "unchanged",
+    }
+}
+
+fn view_diff<'a>(diff: &'a [FileDiff], colors: ThemeColors) -> Element<'a, Message> {
+    let c = colors;
+    if diff.is_empty() {
+        return text("No SavedVariables for this addon in either version.")
+            .size(13)
+            .color(c.muted)
+            .into();
+    }
+    let mut budget = MAX_RENDERED_LINES;
+    let mut hidden = 0usize;
+    let mut sections = column![].spacing(10);
+    for file in diff {
+        let mut lines = column![row![
+            text(&file.scope).size(14).color(c.title),
+            text(format!("{} — {}", file.path, change_label(file.change)))
+                .size(12)
+                .color(c.muted),
+        ]
+        .spacing(8)
+        .align_y(iced::Alignment::Center)]
+        .spacing(1);
+        for hunk in &file.hunks {
+            if budget == 0 {
+                hidden += hunk.lines.len();
+                continue;
+            }
+            lines = lines.push(
+                text(format!("@@ -{} +{} @@", hunk.old_start, hunk.new_start))
+                    .size(12)
+                    .font(Font::MONOSPACE)
+                    .color(c.link),
+            );
+            for line in &hunk.lines {
+                if budget == 0 {
+                    hidden += 1;
+                    continue;
+                }
+                budget -= 1;
+                let (marker, color) = match line.kind {
+                    DiffLineKind::Context => (' ', c.muted),
+                    DiffLineKind::Added => ('+', c.good),
+                    DiffLineKind::Removed => ('-', c.bad),
+                };
+                lines = lines.push(
+                    text(format!("{marker} {}", line.text))
+                        .size(12)
+                        .font(Font::MONOSPACE)
+                        .color(color),
+                );
+            }
+        }
+        sections = sections.push(
+            container(lines)
+                .padding([8, 10])
+                .width(Length::Fill)
+                .style(move |_theme| theme::card_style(c)),
+        );
+    }
+    if hidden > 0 {
+        sections = sections.push(
+            text(format!(
+                "{hidden} more line(s) not shown. Run `wuddle saved-vars diff` for the full diff."
+            ))
+            .size(12)
+            .color(c.muted),
+        );
+    }
+    sections.into()
+}
+
+pub fn view_dialog(app: &App, colors: ThemeColors) -> Element<'_, Message> {
+    let c = colors;
+    let ui = &app.saved_variables_ui;
+
+    let snapshot_button = {
+        let button = button(text("Snapshot now").size(13))
+            .padding([6, 12])
+            .style(move |_theme, status| secondary_button_style(c, status));
+        if ui.busy {
+            button
+        } else {
+            button.on_press(Message::TakeSavedVariablesSnapshot)
+        }
+    };
+    let summary = if ui.busy && ui.snapshots.is_empty() {
+        "Loading snapshots…".to_string()
+    } else {
+        format!(
+            "{} snapshot(s); the newest {} are kept.",
+            ui.snapshots.len(),
+            app.saved_variables_keep
+        )
+    };
+
+    let mut body = column![
+        row![
+            text("SavedVariables Snapshots").size(18).color(c.title),
+            Space::new().width(Length::Fill),
+            close_button(c),
+        ]
+        .align_y(iced::Alignment::Center),
+        text(
+            "Copies of every addon's account and character settings from WTF, kept in .wuddle/saved-variables inside the WoW folder. Restoring puts back one addon's files; the files it replaces are snapshotted first. Close the game before restoring, since it rewrites SavedVariables when you log out.",
+        )
+        .size(13)
+        .color(c.muted),
+        row![snapshot_button, text(summary).size(13).color(c.text)]
+            .spacing(10)
+            .align_y(iced::Alignment::Center),
+    ]
+    .spacing(12);
+
+    if let Some(error) = &ui.error {
+        body = body.push(
+            container(text(error).size(13).color(c.bad))
+                .padding(8)
+                .width(Length::Fill)
+                .style(move |_theme| theme::card_style(c)),
+        );
+    }
+
+    if ui.snapshots.is_empty() {
+        if !ui.busy {
+            body = body.push(
+                text("No snapshots yet. Take one now or enable scheduled snapshots in Options.")
+                    .size(13)
+                    .color(c.muted),
+            );
+        }
+        return body.into();
+    }
+
+    let labels = ui.snapshots.iter().map(snapshot_label).collect::<Vec<_>>();
+    let from_label = ui
+        .from
+        .as_deref()
+        .and_then(|id| ui.snapshot(id))
+        .map(snapshot_label);
+    let mut to_options = vec![CURRENT_FILES.to_string()];
+    to_options.extend(labels.iter().cloned());
+    let to_label = Some(
+        ui.to
+            .as_deref()
+            .and_then(|id| ui.snapshot(id))
+            .map(snapshot_label)
+            .unwrap_or_else(|| CURRENT_FILES.to_string()),
+    );
+    let addons = ui.addons();
+
+    body = body.push(
+        row![
+            text("Compare").size(13).color(c.text),
+            pick_list(labels, from_label, Message::SetSavedVariablesFrom)
+                .text_size(13)
+                .padding([4, 8]),
+            text("with").size(13).color(c.text),
+            pick_list(to_options, to_label, Message::SetSavedVariablesTo)
+                .text_size(13)
+                .padding([4, 8]),
+            text("Addon").size(13).color(c.text),
+            pick_list(addons, ui.addon.clone(), Message::SetSavedVariablesAddon)
+                .text_size(13)
+                .padding([4, 8]),
+        ]
+        .spacing(8)
+        .align_y(iced::Alignment::Center),
+    );
+
+    let diff: Element<'_, Message> = match &ui.diff {
+        Some(diff) => view_diff(diff, c),
+        None if ui.diff_request.is_some() => text("Comparing…").size(13).color(c.muted).into(),
+        None => Space::new().height(0).into(),
+    };
+    body = body.push(
+        scrollable(diff)
+            .height(Length::Fixed(360.0))
+            .direction(theme::vscroll_overlay())
+            .style(move |theme, status| theme::scrollable_style(c)(theme, status)),
+    );
+
+    let restore_label = match &ui.addon {
+        Some(addon) => format!("Restore {addon} from the older snapshot"),
+        None => "Restore".to_string(),
+    };
+    let restore = button(text(restore_label).size(13))
+        .padding([6, 12])
+        .style(move |_theme, status| theme::btn_danger_style(c, status));
+    let restore = if ui.busy || ui.addon.is_none() {
+        restore
+    } else {
+        restore.on_press(Message::RestoreSavedVariables)
+    };
+    body =
+        body.push(row![Space::new().width(Length::Fill), restore].align_y(iced::Alignment::Center));
+    body.into()
+}
@@ -15,7 +15,22 @@ use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc;
 use std::sync::{Arc, Mutex, OnceLock, Weak};
 use std::time::{Duration, Instant};
-use wuddle_engine::{CheckMode, Engine, InstallMode, InstallOptions, Repo, UpdatePlan};
+use wuddle_engine::saved_variables::{
+    self, FileDiff, RestoreReport, SavedVariablesSnapshot, SnapshotRetention, SnapshotTrigger,
+};
+use wuddle_engine::{
+    AddonIdentification, AddonSet, AddonSetReport, CheckMode, Engine, IndexEntry, IndexQuery,
+    IndexSource, InstallMode, InstallOptions, Repo, SourceMatch, UpdatePlan, WtfCharacter,
+};
+
+/// Outcome of a SavedVariables snapshot followed by pruning.
+#[derive(Debug, Clone)]
+pub struct SnapshotTaken {
+    /// `None` when a scheduled snapshot found nothing changed.
+    pub snapshot: Option<SavedVariablesSnapshot>,
+    pub pruned: usize,
+    pub snapshots: Vec<SavedVariablesSnapshot>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RescanProgress {
@@ -351,10 +366,12 @@ mod primary_toc_tests {
                 AddonProbeEntry {
                     addon_name: "Questie".to_string(),
                     source_path: String::new(),
+                    interface: Vec::new(),
                 },
                 AddonProbeEntry {
                     addon_name: "Questie-335".to_string(),
                     source_path: String::new(),
+                    interface: Vec::new(),
                 },
             ],
             conflicts: Vec::new(),
@@ -448,11 +465,17 @@ pub struct RepoRow {
     /// Empty for non-DLL repos. More than one entry means this is a multi-DLL mod.
     pub installed_dlls: Vec<(String, bool, Option<String>)>,
     pub installed_addons: Vec<String>,
+    /// Parsed `.toc` metadata of the installed addon folders.
+    pub addon_tocs: Vec<wuddle_engine::InstalledAddonToc>,
     pub installed_mpqs: Vec<wuddle_engine::mpq::MpqInstalledFile>,
     /// User-facing MPQ package label. The repository name remains the stable
     /// collision-safe identity used to recognize local archive reinstalls.
     pub mpq_package_name: Option<String>,
     pub dependencies: Vec<(i64, String)>,
+    /// Hard dependencies of this repo's addons that are missing or disabled.
+    pub missing_dependencies: Vec<wuddle_engine::UnmetDependency>,
+    /// Repos whose addons require a folder this repo installs.
+    pub dependents: Vec<i64>,
     pub selected_addons: Vec<String>,
     pub is_collection: bool,
     pub merge_installs: bool,
@@ -466,6 +489,8 @@ pub struct RepoDetailEntry {
     pub path: String,
     pub kind: String,
     pub is_directory: bool,
+    /// `.toc` metadata when the entry is an addon folder.
+    pub toc: Option<wuddle_engine::toc::AddonToc>,
 }
 
 #[derive(Debug, Clone)]
@@ -474,6 +499,25 @@ pub struct RepoDetailChild {
     pub is_directory: bool,
 }
 
+impl RepoRow {
+    /// `.toc` metadata of one installed addon folder.
+    pub fn addon_toc(&self, folder: &str) -> Option<&wuddle_engine::toc::AddonToc> {
+        self.addon_tocs
+            .iter()
+            .find(|entry| entry.folder.eq_ignore_ascii_case(folder))
+            .map(|entry| &entry.toc)
+    }
+
+    /// The `.toc` describing the repo as a whole: its only addon folder, or
+    /// the folder named after the repo.
+    pub fn primary_addon_toc(&self) -> Option<&wuddle_engine::toc::AddonToc> {
+        match self.addon_tocs.as_slice() {
+            [only] => Some(&only.toc),
+            _ => self.addon_toc(&self.name),
+        }
+    }
+}
+
 fn parse_selected_addons(raw: Option<&str>) -> Vec<String> {
     let Some(raw) = raw.map(str::trim).filter(|raw| !raw.is_empty()) else {
         return Vec::new();
@@ -511,9 +555,12 @@ impl From<Repo> for RepoRow {
                 .map(|branch| branch.to_string()),
             installed_dlls: Vec::new(),
             installed_addons: Vec::new(),
+            addon_tocs: Vec::new(),
             installed_mpqs: Vec::new(),
             mpq_package_name: None,
             dependencies: Vec::new(),
+            missing_dependencies: Vec::new(),
+            dependents: Vec::new(),
             selected_addons: parse_selected_addons(r.selected_addons_json.as_deref()),
             is_collection: r
                 .selected_addons_json
@@ -545,6 +592,9 @@ pub struct PlanRow {
     pub error: Option<String>,
     pub previous_dll_count: usize,
     pub new_dll_count: usize,
+    /// Installed addons written for another client than the profile's, found
+    /// after the plan was applied.
+    pub client_mismatches: Vec<wuddle_engine::ClientMismatch>,
 }
 
 #[derive(Debug, Clone)]
@@ -575,30 +625,7 @@ pub struct ClientVersionInfo {
     pub quick_add_family: ClientFamily,
 }
 
-/// Legacy WoW client families Wuddle can safely target with curated Quick Add mods.
-/// Modern WoW Classic clients deliberately do not match their superficially similar
-/// major version numbers because their engine and API are not compatible.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
-pub enum ClientFamily {
-    Vanilla,
-    Tbc,
-    Wotlk,
-    Unsupported,
-    #[default]
-    Unknown,
-}
-
-impl ClientFamily {
-    pub fn label(self) -> &'static str {
-        match self {
-            Self::Vanilla => "Vanilla 1.12.1 or earlier",
-            Self::Tbc => "The Burning Crusade 2.0–2.4.3",
-            Self::Wotlk => "Wrath of the Lich King 3.0–3.3.5",
-            Self::Unsupported => "an unsupported or modern WoW client",
-            Self::Unknown => "an unknown WoW client",
-        }
-    }
-}
+pub use wuddle_engine::ClientFamily;
 
 fn classify_legacy_client(version: Option<(u16, u16, u16, u16)>) -> ClientFamily {
     let Some((major, minor, patch, _build)) = version else {
@@ -1005,6 +1032,7 @@ impl From<UpdatePlan> for PlanRow {
             error: p.error,
             previous_dll_count: p.previous_dll_count,
             new_dll_count: p.new_dll_count,
+            client_mismatches: Vec::new(),
         }
     }
 }
@@ -1632,6 +1660,7 @@ mod wdm_recipe_tests {
                 asset("notes.zip"),
             ],
             published_at: None,
+            body: None,
         };
         let main = wdm_release_set(&release, 'M').unwrap();
         assert_eq!(main.assets.len(), 2);
@@ -2318,7 +2347,17 @@ async fn list_repos_inner(
         "Opening profile database...",
     )?;
     let eng = open_engine(db_path.as_deref())?;
-    let mut logs = Vec::new();
+    let mut logs = wuddle_engine::take_recovered_transactions()
+        .into_iter()
+        .map(|recovered| RepoLoadLog {
+            level: if recovered.action == wuddle_engine::RecoveryAction::Failed {
+                LogLevel::Error
+            } else {
+                LogLevel::Info
+            },
+            text: recovered.describe(),
+        })
+        .collect::<Vec<_>>();
 
     rescan_checkpoint(
         operation_id,
@@ -2639,6 +2678,12 @@ async fn list_repos_inner(
             "Repository load",
             "Building repository rows...",
         )?;
+        let unmet_dependencies = eng
+            .addon_dependency_report(
+                wow_path,
+                &crate::components::presets::known_addon_sources(),
+            )
+            .unwrap_or_default();
         let mut rows: Vec<RepoRow> = Vec::with_capacity(repos.len());
         for repo in repos {
             if background_cancelled.load(Ordering::Acquire) {
@@ -2678,6 +2723,15 @@ async fn list_repos_inner(
                 .sort_by_key(|name| name.to_ascii_lowercase());
             row.installed_addons
                 .dedup_by(|left, right| left.eq_ignore_ascii_case(right));
+            if !row.installed_addons.is_empty() {
+                row.addon_tocs = eng.addon_metadata(row.id, wow_path).unwrap_or_default();
+                row.missing_dependencies = unmet_dependencies
+                    .iter()
+                    .filter(|unmet| unmet.repo_id == Some(row.id))
+                    .cloned()
+                    .collect();
+                row.dependents = eng.repo_dependents(row.id).unwrap_or_default();
+            }
             if row.mode == "mpq" {
                 row.installed_mpqs = eng
                     .list_installed_mpqs(row.id, wow_path)
@@ -2767,6 +2821,7 @@ async fn build_wdm_update_plan(eng: &Engine, repo: Repo) -> PlanRow {
                 error: None,
                 previous_dll_count: 0,
                 new_dll_count: 0,
+                client_mismatches: Vec::new(),
             }
         }
         Err(error) => PlanRow {
@@ -2785,6 +2840,7 @@ async fn build_wdm_update_plan(eng: &Engine, repo: Repo) -> PlanRow {
             error: Some(error),
             previous_dll_count: 0,
             new_dll_count: 0,
+            client_mismatches: Vec::new(),
         },
     }
 }
@@ -2810,6 +2866,7 @@ async fn build_epoch_water_update_plan(repo: Repo) -> PlanRow {
                 error: None,
                 previous_dll_count: 0,
                 new_dll_count: 0,
+                client_mismatches: Vec::new(),
             }
         }
         Err(error) => PlanRow {
@@ -2828,6 +2885,7 @@ async fn build_epoch_water_update_plan(repo: Repo) -> PlanRow {
             error: Some(error),
             previous_dll_count: 0,
             new_dll_count: 0,
+            client_mismatches: Vec::new(),
         },
     }
 }
@@ -3264,17 +3322,23 @@ pub struct PreInstallConflictInfo {
     pub existing_repos: Vec<CollectionConflictOwnerGroup>,
     pub new_repo_label: String,
     pub addon_names: Vec<String>,
+    /// Addons whose `## Interface` targets another client than the profile's.
+    /// These are warnings; they never block the install.
+    pub client_mismatches: Vec<wuddle_engine::ClientMismatch>,
 }
 
 /// Lightweight pre-install conflict check that runs after `add_repo` but before
 /// `install_new_repo`. Uses the engine's DB + filesystem queries (no network call)
 /// to detect whether the repo's target files already exist or are tracked by
-/// another repository.
+/// another repository. Interface versions read by the add dialog's probe are
+/// compared with the profile's client.
 pub async fn check_pre_install_conflicts(
     db_path: Option<PathBuf>,
     repo_id: i64,
     wow_dir: String,
     addon_names: Vec<String>,
+    client: ClientFamily,
+    probe_entries: Vec<wuddle_engine::AddonProbeEntry>,
 ) -> Result<PreInstallConflictInfo, String> {
     let _diagnostic = crate::diagnostics::OperationGuard::new("check_pre_install_conflicts");
     crate::diagnostics::trace(
@@ -3304,11 +3368,24 @@ pub async fn check_pre_install_conflicts(
             build_collection_conflict_owner_groups(&conflicts)
         };
 
+        let client_mismatches = probe_entries
+            .iter()
+            .filter(|entry| {
+                names_to_check
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(&entry.addon_name))
+            })
+            .filter_map(|entry| {
+                wuddle_engine::ClientMismatch::check(&entry.addon_name, &entry.interface, client)
+            })
+            .collect();
+
         Ok(PreInstallConflictInfo {
             conflicts,
             existing_repos,
             new_repo_label: format!("{}/{}", repo.owner, repo.name),
             addon_names: names_to_check,
+            client_mismatches,
         })
     })
     .await
@@ -3366,6 +3443,401 @@ pub async fn set_repo_enabled(
     .map_err(|e| e.to_string())?
 }
 
+/// Saved addon sets and the characters found under `WTF`.
+pub async fn load_addon_sets(
+    db_path: Option<PathBuf>,
+    wow_dir: String,
+) -> Result<(Vec<AddonSet>, Vec<WtfCharacter>), String> {
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        let sets = eng.list_addon_sets().map_err(|e| e.to_string())?;
+        let characters = if wow_dir.trim().is_empty() {
+            Vec::new()
+        } else {
+            wuddle_engine::character_addons::discover_characters(Path::new(&wow_dir))
+        };
+        Ok((sets, characters))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Save one character's addon states as the set `name`. Returns the updated
+/// set list and a confirmation.
+pub async fn capture_addon_set(
+    db_path: Option<PathBuf>,
+    wow_dir: String,
+    name: String,
+    character: WtfCharacter,
+) -> Result<(Vec<AddonSet>, String), String> {
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        let set = eng
+            .capture_addon_set(&name, Path::new(&wow_dir), &character)
+            .map_err(|e| e.to_string())?;
+        let message = format!(
+            "Saved addon set \"{}\" from {}: {} repo(s), {} other folder(s).",
+            set.name,
+            character.label(),
+            set.repos.len(),
+            set.folders.len()
+        );
+        Ok((eng.list_addon_sets().map_err(|e| e.to_string())?, message))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub async fn apply_addon_set(
+    db_path: Option<PathBuf>,
+    wow_dir: String,
+    name: String,
+    character: Option<WtfCharacter>,
+    use_dlls_txt: bool,
+) -> Result<AddonSetReport, String> {
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        eng.apply_addon_set(&name, Path::new(&wow_dir), character.as_ref(), use_dlls_txt)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub async fn delete_addon_set(
+    db_path: Option<PathBuf>,
+    name: String,
+) -> Result<(Vec<AddonSet>, String), String> {
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        eng.delete_addon_set(&name).map_err(|e| e.to_string())?;
+        let message = format!("Deleted addon set \"{name}\".");
+        Ok((eng.list_addon_sets().map_err(|e| e.to_string())?, message))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The set `name` as portable JSON.
+pub async fn export_addon_set(db_path: Option<PathBuf>, name: String) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        let export = eng.export_addon_set(&name).map_err(|e| e.to_string())?;
+        serde_json::to_string_pretty(&export).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Save a set exported as JSON. Repos that are not tracked in this profile
+/// are left out and named in the returned message.
+pub async fn import_addon_set(
+    db_path: Option<PathBuf>,
+    json: String,
+) -> Result<(Vec<AddonSet>, String), String> {
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    tokio::task::spawn_blocking(move || {
+        let export: wuddle_engine::AddonSetExport = serde_json::from_str(json.trim())
+            .map_err(|_| "The clipboard does not contain an exported addon set.".to_string())?;
+        let eng = open_engine(db_path.as_deref())?;
+        let unmatched = eng.import_addon_set(&export).map_err(|e| e.to_string())?;
+        let mut message = format!("Imported addon set \"{}\".", export.name.trim());
+        if !unmatched.is_empty() {
+            message.push_str(&format!(
+                " Left out {} repo(s) not tracked in this profile: {}",
+                unmatched.len(),
+                unmatched.join(", ")
+            ));
+        }
+        Ok((eng.list_addon_sets().map_err(|e| e.to_string())?, message))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn addon_index_state(eng: &Engine) -> Result<(Vec<IndexSource>, Vec<IndexEntry>), String> {
+    let sources = eng.addon_index_sources().map_err(|e| e.to_string())?;
+    let entries = eng
+        .search_addon_index(&IndexQuery::default())
+        .map_err(|e| e.to_string())?;
+    Ok((sources, entries))
+}
+
+/// The imported addon catalogs and every entry in them.
+pub async fn load_addon_index(
+    db_path: Option<PathBuf>,
+) -> Result<(Vec<IndexSource>, Vec<IndexEntry>), String> {
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        addon_index_state(&eng)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Import a catalog file pasted from the clipboard.
+pub async fn import_addon_index(
+    db_path: Option<PathBuf>,
+    json: String,
+) -> Result<(Vec<IndexSource>, Vec<IndexEntry>, String), String> {
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        let source = eng
+            .import_addon_index(&json, "Imported catalog")
+            .map_err(|e| format!("{e:#}"))?;
+        let (sources, entries) = addon_index_state(&eng)?;
+        let message = format!(
+            "Imported catalog \"{}\" with {} entries.",
+            source.name, source.entries
+        );
+        Ok((sources, entries, message))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Fetch a catalog from `url`, or refresh every catalog added by URL when
+/// `url` is `None`.
+pub async fn fetch_addon_index(
+    db_path: Option<PathBuf>,
+    url: Option<String>,
+) -> Result<(Vec<IndexSource>, Vec<IndexEntry>, String), String> {
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?;
+        let message = match url {
+            Some(url) => {
+                let source = runtime
+                    .block_on(eng.add_addon_index_url(&url))
+                    .map_err(|e| format!("{e:#}"))?;
+                format!(
+                    "Added catalog \"{}\" with {} entries.",
+                    source.name, source.entries
+                )
+            }
+            None => {
+                let remote = eng
+                    .addon_index_sources()
+                    .map_err(|e| e.to_string())?
+                    .into_iter()
+                    .filter(|source| source.url.is_some())
+                    .collect::<Vec<_>>();
+                for source in &remote {
+                    runtime
+                        .block_on(eng.refresh_addon_index(source.id))
+                        .map_err(|e| format!("{}: {e:#}", source.name))?;
+                }
+                format!("Refreshed {} catalog(s).", remote.len())
+            }
+        };
+        let (sources, entries) = addon_index_state(&eng)?;
+        Ok((sources, entries, message))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub async fn remove_addon_index(
+    db_path: Option<PathBuf>,
+    id: i64,
+) -> Result<(Vec<IndexSource>, Vec<IndexEntry>, String), String> {
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        eng.remove_addon_index(id).map_err(|e| e.to_string())?;
+        let (sources, entries) = addon_index_state(&eng)?;
+        Ok((sources, entries, "Removed the catalog.".to_string()))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Untracked addon folders that match a tracked repo, a Quick Add preset or
+/// an imported catalog entry.
+pub async fn identify_untracked_addons(
+    db_path: Option<PathBuf>,
+    wow_dir: String,
+) -> Result<Vec<AddonIdentification>, String> {
+    tokio::task::spawn_blocking(move || {
+        let wow_path = require_wow_dir(&wow_dir)?;
+        let eng = open_engine(db_path.as_deref())?;
+        eng.identify_untracked_addons(wow_path, &crate::components::presets::known_addon_sources())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Track `folder` as installed from `source`, then identify the remaining
+/// folders again.
+pub async fn link_addon_folder(
+    db_path: Option<PathBuf>,
+    wow_dir: String,
+    folder: String,
+    source: SourceMatch,
+) -> Result<Vec<AddonIdentification>, String> {
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    tokio::task::spawn_blocking(move || {
+        let wow_path = require_wow_dir(&wow_dir)?;
+        let eng = open_engine(db_path.as_deref())?;
+        eng.link_addon_folder(wow_path, &folder, &source)
+            .map_err(|e| e.to_string())?;
+        eng.identify_untracked_addons(wow_path, &crate::components::presets::known_addon_sources())
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// The fingerprints of the tracked addons as a catalog file others can import.
+pub async fn export_addon_catalog(
+    db_path: Option<PathBuf>,
+    wow_dir: String,
+) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || {
+        let wow_path = require_wow_dir(&wow_dir)?;
+        let eng = open_engine(db_path.as_deref())?;
+        eng.refresh_addon_catalog(wow_path)
+            .map_err(|e| e.to_string())?;
+        let catalog = eng.export_addon_catalog().map_err(|e| e.to_string())?;
+        serde_json::to_string_pretty(&catalog).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Read the Quick Add preset catalog. When a release key is trusted, a newer
+/// signed catalog is fetched first; a failed fetch keeps the one on disk.
+pub async fn load_preset_catalog() -> Result<wuddle_engine::presets::LoadedCatalog, String> {
+    tokio::task::spawn_blocking(|| {
+        let mut problems = Vec::new();
+        if !wuddle_engine::presets::trusted_keys().is_empty() {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| e.to_string())?;
+            if let Err(e) = runtime.block_on(wuddle_engine::presets::update_default()) {
+                problems.push(format!("Could not update the Quick Add presets: {e:#}"));
+            }
+        }
+        let mut loaded = wuddle_engine::presets::load_default();
+        loaded.problems.extend(problems);
+        Ok(loaded)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Add a catalog file's entries, then identify the untracked folders again.
+pub async fn import_addon_catalog(
+    db_path: Option<PathBuf>,
+    wow_dir: String,
+    json: String,
+) -> Result<(Vec<AddonIdentification>, String), String> {
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    tokio::task::spawn_blocking(move || {
+        let catalog: wuddle_engine::CatalogFile = serde_json::from_str(json.trim())
+            .map_err(|_| "The clipboard does not contain an addon catalog.".to_string())?;
+        let wow_path = require_wow_dir(&wow_dir)?;
+        let eng = open_engine(db_path.as_deref())?;
+        let imported = eng
+            .import_addon_catalog(&catalog)
+            .map_err(|e| e.to_string())?;
+        let addons = eng
+            .identify_untracked_addons(wow_path, &crate::components::presets::known_addon_sources())
+            .map_err(|e| e.to_string())?;
+        Ok((addons, format!("Imported {imported} catalog entries.")))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+fn require_wow_dir(wow_dir: &str) -> Result<&Path, String> {
+    let trimmed = wow_dir.trim();
+    if trimmed.is_empty() {
+        return Err("Set a WoW directory in Options first.".to_string());
+    }
+    Ok(Path::new(trimmed))
+}
+
+/// SavedVariables snapshots of `wow_dir`, newest first.
+pub async fn list_saved_variables_snapshots(
+    wow_dir: String,
+) -> Result<Vec<SavedVariablesSnapshot>, String> {
+    tokio::task::spawn_blocking(move || {
+        saved_variables::list_snapshots(require_wow_dir(&wow_dir)?).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Take a snapshot, then prune down to the newest `keep` snapshots.
+pub async fn take_saved_variables_snapshot(
+    wow_dir: String,
+    trigger: SnapshotTrigger,
+    keep: u32,
+) -> Result<SnapshotTaken, String> {
+    tokio::task::spawn_blocking(move || {
+        let wow_dir = require_wow_dir(&wow_dir)?;
+        let snapshot =
+            saved_variables::take_snapshot(wow_dir, trigger).map_err(|e| e.to_string())?;
+        let retention = SnapshotRetention {
+            keep_last: keep.max(1) as usize,
+            max_age_days: None,
+        };
+        let pruned =
+            saved_variables::prune_snapshots(wow_dir, retention).map_err(|e| e.to_string())?;
+        let snapshots = saved_variables::list_snapshots(wow_dir).map_err(|e| e.to_string())?;
+        Ok(SnapshotTaken {
+            snapshot,
+            pruned,
+            snapshots,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// `addon`'s changes from snapshot `from` to snapshot `to`, or to the
+/// current files when `to` is `None`.
+pub async fn diff_saved_variables(
+    wow_dir: String,
+    from: String,
+    to: Option<String>,
+    addon: String,
+) -> Result<Vec<FileDiff>, String> {
+    tokio::task::spawn_blocking(move || {
+        saved_variables::diff_addon(require_wow_dir(&wow_dir)?, &from, to.as_deref(), &addon)
+            .map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub async fn restore_saved_variables(
+    wow_dir: String,
+    snapshot: String,
+    addon: String,
+) -> Result<(RestoreReport, Vec<SavedVariablesSnapshot>), String> {
+    tokio::task::spawn_blocking(move || {
+        let wow_dir = require_wow_dir(&wow_dir)?;
+        let report = saved_variables::restore_addon(wow_dir, &snapshot, &addon)
+            .map_err(|e| e.to_string())?;
+        let snapshots = saved_variables::list_snapshots(wow_dir).map_err(|e| e.to_string())?;
+        Ok((report, snapshots))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 pub async fn is_awesome_wotlk_repo(db_path: Option<PathBuf>, repo_id: i64) -> bool {
     tokio::task::spawn_blocking(move || {
         let Ok(engine) = open_engine(db_path.as_deref()) else {
@@ -3405,6 +3877,7 @@ pub async fn load_repo_details(
     let _diagnostic = crate::diagnostics::OperationGuard::new("load_repo_details");
     tokio::task::spawn_blocking(move || {
         let eng = open_engine(db_path.as_deref())?;
+        let mut tocs = eng.addon_metadata(repo_id, &wow_dir).unwrap_or_default();
         let mut entries = eng
             .db()
             .list_installs(repo_id)
@@ -3412,10 +3885,15 @@ pub async fn load_repo_details(
             .into_iter()
             .map(|entry| {
                 let is_directory = wow_dir.join(&entry.path).is_dir();
+                let toc = tocs
+                    .iter()
+                    .position(|toc| toc.path == entry.path)
+                    .map(|index| tocs.swap_remove(index).toc);
                 RepoDetailEntry {
                     path: entry.path,
                     kind: entry.kind,
                     is_directory,
+                    toc,
                 }
             })
             .collect::<Vec<_>>();
@@ -3532,6 +4010,17 @@ pub async fn update_all(
         "service",
         format!("update_all: requested_count={}", ids_to_update.len()),
     );
+    update_each(db_path, wow_dir, ids_to_update, opts).await
+}
+
+/// Update every repo in `ids_to_update` independently; the caller holds the
+/// repository mutation lock.
+async fn update_each(
+    db_path: Option<PathBuf>,
+    wow_dir: String,
+    ids_to_update: Vec<i64>,
+    opts: InstallOptions,
+) -> Result<Vec<UpdateOneResult>, String> {
     if ids_to_update.is_empty() {
         return Ok(Vec::new());
     }
@@ -3610,6 +4099,9 @@ pub async fn update_all(
                     })
                 }
                 Ok(Some(plan)) => {
+                    let client_mismatches = eng
+                        .addon_client_mismatches(plan.repo_id, Path::new(&wow), opts.client_family)
+                        .unwrap_or_default();
                     if plan.mode.as_str() == "addon_git" {
                         log.push(format!("{}/{}: repository synced.", plan.owner, plan.name));
                     } else if !plan.asset_name.is_empty() {
@@ -3619,12 +4111,14 @@ pub async fn update_all(
                         ));
                     }
                     log.push(format!("{}/{}: update complete.", plan.owner, plan.name));
+                    let mut row = PlanRow::from(plan);
+                    row.client_mismatches = client_mismatches;
                     Ok(UpdateOneResult {
-                        repo_id: plan.repo_id,
-                        owner: plan.owner.clone(),
-                        name: plan.name.clone(),
+                        repo_id: row.repo_id,
+                        owner: row.owner.clone(),
+                        name: row.name.clone(),
                         skipped: false,
-                        plan: Some(PlanRow::from(plan)),
+                        plan: Some(row),
                         log_lines: log,
                         error: None,
                     })
@@ -3638,6 +4132,144 @@ pub async fn update_all(
     Ok(results)
 }
 
+/// Like [`update_all`], but the release updates are installed as one unit:
+/// if any of them fails, all of them are rolled back. Git addons cannot join
+/// the transaction and update one by one afterwards.
+pub async fn update_all_atomic(
+    db_path: Option<PathBuf>,
+    wow_dir: String,
+    ids_to_update: Vec<i64>,
+    opts: InstallOptions,
+) -> Result<Vec<UpdateOneResult>, String> {
+    let _diagnostic = crate::diagnostics::OperationGuard::new("update_all_atomic");
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    crate::diagnostics::trace(
+        "service",
+        format!("update_all_atomic: requested_count={}", ids_to_update.len()),
+    );
+    if ids_to_update.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let db = db_path.clone();
+    let wow = wow_dir.clone();
+    let (mut results, git_ids) =
+        tokio::task::spawn_blocking(move || -> Result<(Vec<UpdateOneResult>, Vec<i64>), String> {
+            let eng = open_engine(db.as_deref())?;
+            let mut results = Vec::new();
+            let mut release_repos = Vec::new();
+            let mut git_ids = Vec::new();
+            for id in ids_to_update {
+                match eng.db().get_repo_optional(id).map_err(|e| e.to_string())? {
+                    None => results.push(UpdateOneResult {
+                        repo_id: id,
+                        owner: String::new(),
+                        name: format!("repository #{id}"),
+                        skipped: true,
+                        plan: None,
+                        log_lines: vec![
+                            "The repository is no longer tracked by this profile; the stale update entry was skipped."
+                                .to_string(),
+                        ],
+                        error: None,
+                    }),
+                    Some(repo) if repo.mode.as_str() == "addon_git" => git_ids.push(id),
+                    Some(repo) => release_repos.push(repo),
+                }
+            }
+            if release_repos.is_empty() {
+                return Ok((results, git_ids));
+            }
+
+            let release_ids = release_repos.iter().map(|repo| repo.id).collect::<Vec<_>>();
+            let outcome = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| e.to_string())?
+                .block_on(async {
+                    eng.update_repos_atomically(&release_ids, Path::new(&wow), None, opts)
+                        .await
+                });
+            match outcome {
+                Err(e) => {
+                    let err = e.to_string();
+                    for repo in release_repos {
+                        results.push(UpdateOneResult {
+                            repo_id: repo.id,
+                            log_lines: vec![format!(
+                                "{}/{}: rolled back with the other release updates — {}",
+                                repo.owner, repo.name, err
+                            )],
+                            owner: repo.owner,
+                            name: repo.name,
+                            skipped: false,
+                            plan: None,
+                            error: Some(err.clone()),
+                        });
+                    }
+                }
+                Ok(plans) => {
+                    for plan in plans {
+                        if !plan.applied {
+                            results.push(UpdateOneResult {
+                                repo_id: plan.repo_id,
+                                log_lines: vec![format!(
+                                    "{}/{}: already up to date.",
+                                    plan.owner, plan.name
+                                )],
+                                owner: plan.owner,
+                                name: plan.name,
+                                skipped: false,
+                                plan: None,
+                                error: None,
+                            });
+                            continue;
+                        }
+                        let client_mismatches = eng
+                            .addon_client_mismatches(
+                                plan.repo_id,
+                                Path::new(&wow),
+                                opts.client_family,
+                            )
+                            .unwrap_or_default();
+                        let log_lines = vec![
+                            format!(
+                                "{}/{}: installed '{}'.",
+                                plan.owner, plan.name, plan.asset_name
+                            ),
+                            format!("{}/{}: update complete.", plan.owner, plan.name),
+                        ];
+                        let mut row = PlanRow::from(plan);
+                        row.client_mismatches = client_mismatches;
+                        results.push(UpdateOneResult {
+                            repo_id: row.repo_id,
+                            owner: row.owner.clone(),
+                            name: row.name.clone(),
+                            skipped: false,
+                            plan: Some(row),
+                            log_lines,
+                            error: None,
+                        });
+                    }
+                }
+            }
+            Ok((results, git_ids))
+        })
+        .await
+        .map_err(|error| format!("Update task failed: {error}"))??;
+
+    results.extend(update_each(db_path, wow_dir, git_ids, opts).await?);
+    Ok(results)
+}
+
+/// Outcome of [`install_new_repo`].
+#[derive(Debug, Clone)]
+pub struct InstalledRepo {
+    pub message: String,
+    /// Installed addons written for another client than the profile's.
+    pub client_mismatches: Vec<wuddle_engine::ClientMismatch>,
+}
+
 /// Install a freshly-added repo, mirroring Tauri's add flow:
 /// try `update_repo` first; if it returns None (engine says nothing to do),
 /// fall back to `reinstall_repo` to force a fresh clone/download.
@@ -3646,7 +4278,7 @@ pub async fn install_new_repo(
     id: i64,
     wow_dir: String,
     opts: InstallOptions,
-) -> Result<String, String> {
+) -> Result<InstalledRepo, String> {
     let _diagnostic = crate::diagnostics::OperationGuard::new("install_new_repo");
     let _mutation = serialize_repository_mutation(&db_path).await;
     crate::diagnostics::trace("service", format!("install_new_repo: repo_id={id}"));
@@ -3662,14 +4294,18 @@ pub async fn install_new_repo(
             .block_on(async { eng.update_repo(id, wow_path, None, opts).await })
             .map_err(|e| e.to_string())?;
 
-        if let Some(plan) = update_result {
-            Ok(format!("Installed {}/{}.", plan.owner, plan.name))
-        } else {
-            let plan = runtime
+        let plan = match update_result {
+            Some(plan) => plan,
+            None => runtime
                 .block_on(async { eng.reinstall_repo(id, wow_path, None, opts).await })
-                .map_err(|e| e.to_string())?;
-            Ok(format!("Installed {}/{}.", plan.owner, plan.name))
-        }
+                .map_err(|e| e.to_string())?,
+        };
+        Ok(InstalledRepo {
+            message: format!("Installed {}/{}.", plan.owner, plan.name),
+            client_mismatches: eng
+                .addon_client_mismatches(id, wow_path, opts.client_family)
+                .unwrap_or_default(),
+        })
     })
     .await
     .map_err(|e| e.to_string())?
@@ -3692,7 +4328,13 @@ pub async fn update_repo(
             .map_err(|e| e.to_string())?
             .block_on(async { eng.update_repo(id, Path::new(&wow_dir), None, opts).await })
             .map_err(|e| e.to_string())?;
-        Ok(plan.map(PlanRow::from))
+        Ok(plan.map(|plan| {
+            let mut row = PlanRow::from(plan);
+            row.client_mismatches = eng
+                .addon_client_mismatches(id, Path::new(&wow_dir), opts.client_family)
+                .unwrap_or_default();
+            row
+        }))
     })
     .await
     .map_err(|e| e.to_string())?
@@ -3724,6 +4366,73 @@ pub async fn reinstall_repo(
     .map_err(|e| e.to_string())?
 }
 
+pub async fn load_install_history(
+    db_path: Option<PathBuf>,
+    repo_id: i64,
+) -> Result<Vec<wuddle_engine::InstallHistoryEntry>, String> {
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        eng.install_history(repo_id).map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Release notes, or the commit log of a Git addon, between a repo's
+/// installed version and the one an update would install, as markdown.
+pub async fn load_release_notes(
+    db_path: Option<PathBuf>,
+    repo_id: i64,
+    wow_dir: Option<String>,
+) -> Result<String, String> {
+    let _diagnostic = crate::diagnostics::OperationGuard::new("load_release_notes");
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?
+            .block_on(async {
+                eng.release_notes_for_repo(repo_id, wow_dir.as_deref().map(Path::new))
+                    .await
+            })
+            .map(|notes| notes.to_markdown())
+            .map_err(|e| format!("{e:#}"))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Redeploy the version an install history entry recorded and put back the
+/// addon settings snapshotted when it was replaced, as one operation.
+pub async fn rollback_to_history(
+    db_path: Option<PathBuf>,
+    entry_id: i64,
+    wow_dir: String,
+    opts: InstallOptions,
+) -> Result<wuddle_engine::RollbackReport, String> {
+    let _diagnostic = crate::diagnostics::OperationGuard::new("rollback_to_history");
+    let _mutation = serialize_repository_mutation(&db_path).await;
+    crate::diagnostics::trace(
+        "service",
+        format!("rollback_to_history: entry_id={entry_id}"),
+    );
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?
+            .block_on(async {
+                eng.rollback_to_history(entry_id, Path::new(&wow_dir), None, opts)
+                    .await
+            })
+            .map_err(|e| format!("{e:#}"))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
 pub async fn reinstall_repo_with_selection(
     db_path: Option<PathBuf>,
     id: i64,
@@ -4622,9 +5331,12 @@ where
 }
 
 fn read_keychain_token() -> Result<Option<String>, String> {
-    keychain_call_with_timeout("reading token", || {
-        let entry =
-            keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).map_err(|e| e.to_string())?;
+    read_keychain_secret(KEYCHAIN_ACCOUNT.to_string())
+}
+
+fn read_keychain_secret(account: String) -> Result<Option<String>, String> {
+    keychain_call_with_timeout("reading token", move || {
+        let entry = keyring::Entry::new(KEYCHAIN_SERVICE, &account).map_err(|e| e.to_string())?;
         match entry.get_password() {
             Ok(token) => {
                 let token = token.trim().to_string();
@@ -4637,14 +5349,20 @@ fn read_keychain_token() -> Result<Option<String>, String> {
 }
 
 fn write_keychain_token(token: &str) -> Result<(), String> {
-    let entry =
-        keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).map_err(|e| e.to_string())?;
-    entry.set_password(token).map_err(|e| e.to_string())
+    write_keychain_secret(KEYCHAIN_ACCOUNT, token)
+}
+
+fn write_keychain_secret(account: &str, secret: &str) -> Result<(), String> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, account).map_err(|e| e.to_string())?;
+    entry.set_password(secret).map_err(|e| e.to_string())
 }
 
 fn delete_keychain_token() -> Result<(), String> {
-    let entry =
-        keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).map_err(|e| e.to_string())?;
+    delete_keychain_secret(KEYCHAIN_ACCOUNT)
+}
+
+fn delete_keychain_secret(account: &str) -> Result<(), String> {
+    let entry = keyring::Entry::new(KEYCHAIN_SERVICE, account).map_err(|e| e.to_string())?;
     if let Err(e) = entry.delete_credential() {
         if !matches!(e, keyring::Error::NoEntry) {
             return Err(e.to_string());
@@ -5023,6 +5741,111 @@ pub async fn clear_github_token() -> Result<GitHubTokenSource, String> {
     .map_err(|e| e.to_string())?
 }
 
+// ---------------------------------------------------------------------------
+// GitLab / Gitea access tokens
+// ---------------------------------------------------------------------------
+
+/// The credential vault cannot enumerate entries, so the hosts that have a
+/// token are listed in one more, non-secret vault entry.
+const FORGE_TOKEN_INDEX_ACCOUNT: &str = "forge_token_hosts";
+
+fn forge_token_account(host: &str) -> String {
+    format!("forge_token:{host}")
+}
+
+fn read_forge_token_index() -> Result<Vec<String>, String> {
+    let Some(raw) = read_keychain_secret(FORGE_TOKEN_INDEX_ACCOUNT.to_string())? else {
+        return Ok(Vec::new());
+    };
+    serde_json::from_str(&raw).map_err(|e| format!("The saved forge host list is damaged: {e}"))
+}
+
+fn write_forge_token_index(hosts: &[String]) -> Result<(), String> {
+    if hosts.is_empty() {
+        return delete_keychain_secret(FORGE_TOKEN_INDEX_ACCOUNT);
+    }
+    let raw = serde_json::to_string(hosts).map_err(|e| e.to_string())?;
+    write_keychain_secret(FORGE_TOKEN_INDEX_ACCOUNT, &raw)
+}
+
+/// Activate every saved GitLab/Gitea token and return the hosts that have
+/// one. A host whose token cannot be read is skipped and reported.
+pub fn sync_forge_tokens() -> Result<Vec<String>, String> {
+    let _mutation = github_token_mutation_guard();
+    let hosts = read_forge_token_index()?;
+    let mut active = Vec::new();
+    let mut failures = Vec::new();
+    for host in hosts {
+        match read_keychain_secret(forge_token_account(&host)) {
+            Ok(Some(token)) => {
+                if wuddle_engine::set_forge_token(&host, Some(token)).is_ok() {
+                    active.push(host);
+                }
+            }
+            Ok(None) => {}
+            Err(error) => failures.push(format!("{host}: {error}")),
+        }
+    }
+    if failures.is_empty() {
+        Ok(active)
+    } else {
+        Err(format!(
+            "Could not read saved forge tokens ({})",
+            failures.join("; ")
+        ))
+    }
+}
+
+/// Store a token for a GitLab or Gitea/Forgejo host and return the
+/// normalized host it was saved under.
+pub async fn save_forge_token(host: String, token: String) -> Result<String, String> {
+    let _diagnostic = crate::diagnostics::OperationGuard::new("save_forge_token");
+    tokio::task::spawn_blocking(move || {
+        let _mutation = github_token_mutation_guard();
+        let host = wuddle_engine::normalize_forge_token_host(&host).map_err(|e| e.to_string())?;
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            return Err("Token is empty.".to_string());
+        }
+        let account = forge_token_account(&host);
+        write_keychain_secret(&account, &token)?;
+        verify_stored_token(&token, read_keychain_secret(account)?)?;
+
+        let mut hosts = read_forge_token_index()?;
+        if !hosts.contains(&host) {
+            hosts.push(host.clone());
+            hosts.sort();
+            write_forge_token_index(&hosts)?;
+        }
+        wuddle_engine::set_forge_token(&host, Some(token)).map_err(|e| e.to_string())?;
+        Ok(host)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub async fn clear_forge_token(host: String) -> Result<String, String> {
+    let _diagnostic = crate::diagnostics::OperationGuard::new("clear_forge_token");
+    tokio::task::spawn_blocking(move || {
+        let _mutation = github_token_mutation_guard();
+        delete_keychain_secret(&forge_token_account(&host))?;
+        let mut hosts = read_forge_token_index()?;
+        hosts.retain(|known| known != &host);
+        write_forge_token_index(&hosts)?;
+        wuddle_engine::set_forge_token(&host, None).map_err(|e| e.to_string())?;
+        Ok(host)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+pub async fn validate_forge_token(host: String) -> wuddle_engine::ForgeTokenValidation {
+    match wuddle_engine::forge_token(&host) {
+        Some(token) => wuddle_engine::validate_forge_token(&host, &token).await,
+        None => wuddle_engine::ForgeTokenValidation::Invalid,
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Repo preview (for Add dialog)
 // ---------------------------------------------------------------------------
@@ -6154,140 +6977,51 @@ pub struct ReleaseItem {
     pub prerelease: bool,
 }
 
-pub async fn fetch_releases(forge_url: String) -> Result<Vec<ReleaseItem>, String> {
+/// Releases shown in the release notes panel, newest first.
+const RELEASE_NOTES_LIMIT: usize = 20;
+
+pub async fn fetch_releases(
+    db_path: Option<PathBuf>,
+    forge_url: String,
+) -> Result<Vec<ReleaseItem>, String> {
     let _diagnostic = crate::diagnostics::OperationGuard::new("fetch_releases");
     let fi = parse_forge_url(&forge_url).ok_or_else(|| "Could not parse forge URL".to_string())?;
-
-    let client = Client::builder()
-        .user_agent("wuddle-iced")
-        .timeout(std::time::Duration::from_secs(15))
-        .build()
-        .map_err(|e| e.to_string())?;
-
-    match fi.forge {
-        "github" => {
-            #[derive(Deserialize)]
-            struct GhRelease {
-                tag_name: String,
-                name: Option<String>,
-                published_at: Option<String>,
-                body: Option<String>,
-                prerelease: bool,
-            }
-            let url = format!(
-                "https://api.github.com/repos/{}/{}/releases?per_page=20",
-                fi.owner, fi.repo
-            );
-            let mut req = client
-                .get(&url)
-                .header("Accept", "application/vnd.github+json");
-            if let Some(token) = wuddle_engine::github_token() {
-                req = req.bearer_auth(token);
-            }
-            let response = tokio::time::timeout(std::time::Duration::from_secs(15), req.send())
-                .await
-                .map_err(|_| "Timed out fetching releases".to_string())?
-                .map_err(|e| e.to_string())?;
-            let releases: Vec<GhRelease> = crate::github_api::checked_response(response)
-                .await?
-                .json()
-                .await
-                .map_err(|e| e.to_string())?;
-            Ok(releases
-                .into_iter()
-                .map(|r| {
-                    let body = r.body.unwrap_or_default();
-                    let items = iced::widget::markdown::Content::parse(&body)
-                        .items()
-                        .to_vec();
-                    ReleaseItem {
-                        tag_name: r.tag_name.clone(),
-                        name: r.name.filter(|s| !s.is_empty()).unwrap_or(r.tag_name),
-                        published_at: r.published_at.unwrap_or_default(),
-                        body,
-                        items,
-                        prerelease: r.prerelease,
-                    }
-                })
-                .collect())
-        }
-        "gitlab" => {
-            #[derive(Deserialize)]
-            struct GlRelease {
-                tag_name: String,
-                name: Option<String>,
-                released_at: Option<String>,
-                description: Option<String>,
-            }
-            let encoded = format!("{}/{}", fi.owner, fi.repo).replace('/', "%2F");
-            let url = format!("https://gitlab.com/api/v4/projects/{}/releases", encoded);
-            let releases: Vec<GlRelease> =
-                tokio::time::timeout(std::time::Duration::from_secs(15), client.get(&url).send())
-                    .await
-                    .map_err(|_| "Timed out fetching releases".to_string())?
-                    .map_err(|e| e.to_string())?
-                    .json()
-                    .await
-                    .map_err(|e| e.to_string())?;
-            Ok(releases
-                .into_iter()
-                .map(|r| {
-                    let body = r.description.unwrap_or_default();
-                    let items = iced::widget::markdown::Content::parse(&body)
-                        .items()
-                        .to_vec();
-                    ReleaseItem {
-                        tag_name: r.tag_name.clone(),
-                        name: r.name.filter(|s| !s.is_empty()).unwrap_or(r.tag_name),
-                        published_at: r.released_at.unwrap_or_default(),
-                        body,
-                        items,
-                        prerelease: false,
-                    }
-                })
-                .collect())
-        }
-        _ => {
-            // Gitea / Forgejo / Codeberg
-            #[derive(Deserialize)]
-            struct GiteaRelease {
-                tag_name: String,
-                name: Option<String>,
-                published_at: Option<String>,
-                body: Option<String>,
-                prerelease: bool,
-            }
-            let url = format!(
-                "{}://{}/api/v1/repos/{}/{}/releases?limit=20",
-                fi.scheme, fi.host, fi.owner, fi.repo
-            );
-            let releases: Vec<GiteaRelease> =
-                tokio::time::timeout(std::time::Duration::from_secs(15), client.get(&url).send())
-                    .await
-                    .map_err(|_| "Timed out fetching releases".to_string())?
-                    .map_err(|e| e.to_string())?
-                    .json()
-                    .await
-                    .map_err(|e| e.to_string())?;
-            Ok(releases
-                .into_iter()
-                .map(|r| {
-                    let body = r.body.unwrap_or_default();
-                    let items = iced::widget::markdown::Content::parse(&body)
-                        .items()
-                        .to_vec();
-                    ReleaseItem {
-                        tag_name: r.tag_name.clone(),
-                        name: r.name.filter(|s| !s.is_empty()).unwrap_or(r.tag_name),
-                        published_at: r.published_at.unwrap_or_default(),
-                        body,
-                        items,
-                        prerelease: r.prerelease,
-                    }
-                })
-                .collect())
-        }
-    }
+    // The engine resolves the repository from its root URL, not from a
+    // tree or release page inside it.
+    let repo_url = format!("{}://{}/{}/{}", fi.scheme, fi.host, fi.owner, fi.repo);
+    tokio::task::spawn_blocking(move || {
+        let eng = open_engine(db_path.as_deref())?;
+        let releases = tokio::runtime::Handle::current()
+            .block_on(eng.list_releases(&repo_url))
+            .map_err(|e| e.to_string())?;
+        Ok(releases
+            .into_iter()
+            .take(RELEASE_NOTES_LIMIT)
+            .map(|release| {
+                let body = release.body.unwrap_or_default();
+                let items = iced::widget::markdown::Content::parse(&body)
+                    .items()
+                    .to_vec();
+                ReleaseItem {
+                    name: release
+                        .name
+                        .filter(|name| !name.is_empty())
+                        .unwrap_or_else(|| release.tag.clone()),
+                    tag_name: release.tag,
+                    published_at: release
+                        .published_at
+                        .and_then(|timestamp| chrono::DateTime::from_timestamp(timestamp, 0))
+                        .map(|time| time.to_rfc3339())
+                        .unwrap_or_default(),
+                    body,
+                    items,
+                    prerelease: release.prerelease,
+                }
+            })
+            .collect())
+    })
+    .await
+    .map_err(|e| e.to_string())?
 }
 
 // ---------------------------------------------------------------------------
@@ -280,13 +280,24 @@ pub struct AppSettings {
     pub opt_desktop_notify: bool,
     pub opt_symlinks: bool,
     pub opt_xattr: bool,
+    /// Update All installs release updates as one unit that commits or
+    /// rolls back together.
+    pub opt_atomic_updates: bool,
     pub opt_clock12: bool,
     pub opt_friz_font: bool,
     pub remember_window_geometry: bool,
+    /// Private key for SSH Git remotes; empty relies on ssh-agent and the
+    /// default `~/.ssh` keys.
+    pub ssh_key: String,
     pub log_wrap: bool,
     pub log_autoscroll: bool,
     pub verbose_diagnostics: bool,
     pub auto_check_minutes: u32,
+    /// Snapshot `WTF` SavedVariables every `saved_variables_snapshot_hours`.
+    pub opt_saved_variables_snapshots: bool,
+    pub saved_variables_snapshot_hours: u32,
+    /// Snapshots kept per WoW directory; older ones are pruned.
+    pub saved_variables_keep: u32,
     pub profiles: Vec<ProfileConfig>,
     pub ignored_update_ids: Vec<i64>,
     pub ignored_update_ids_by_profile: HashMap<String, Vec<i64>>,
@@ -311,13 +322,18 @@ impl Default for AppSettings {
             opt_desktop_notify: false,
             opt_symlinks: false,
             opt_xattr: true,
+            opt_atomic_updates: false,
             opt_clock12: false,
             opt_friz_font: false,
             remember_window_geometry: true,
+            ssh_key: String::new(),
             log_wrap: false,
             log_autoscroll: true,
             verbose_diagnostics: false,
             auto_check_minutes: 15,
+            opt_saved_variables_snapshots: false,
+            saved_variables_snapshot_hours: 24,
+            saved_variables_keep: 20,
             profiles: vec![ProfileConfig::default()],
             ignored_update_ids: Vec::new(),
             ignored_update_ids_by_profile: HashMap::new(),
@@ -472,13 +488,9 @@ fn portable_root_from_paths(executable: &Path, appimage: Option<&Path>) -> Resul
 }
 
 /// DB path for a profile. "default" uses `wuddle.sqlite`, others use `wuddle-{id}.sqlite`.
+/// The naming lives in the engine so the CLI's `--profile` resolves the same file.
 pub fn profile_db_path(profile_id: &str) -> Result<PathBuf, String> {
-    let dir = app_dir()?;
-    if profile_id == "default" {
-        Ok(dir.join("wuddle.sqlite"))
-    } else {
-        Ok(dir.join(format!("wuddle-{}.sqlite", profile_id)))
-    }
+    Ok(app_dir()?.join(wuddle_engine::profiles::profile_db_file_name(profile_id)))
 }
 
 /// Resolve the database path for exactly one profile.
@@ -127,6 +127,8 @@ pub struct MpqPackageFileDraft {
 #[derive(Debug, Clone)]
 pub enum Dialog {
     BackupRestore,
+    /// SavedVariables snapshots; state lives in `App::saved_variables_ui`.
+    SavedVariables,
     MpqAdd,
     MpqInstall,
     ProtectedMpqs,
@@ -201,6 +203,8 @@ pub enum Dialog {
         expanded_paths: std::collections::HashSet<String>,
         loading_paths: std::collections::HashSet<String>,
         children: std::collections::HashMap<String, Vec<crate::service::RepoDetailChild>>,
+        /// Install history, newest first.
+        history: Vec<wuddle_engine::InstallHistoryEntry>,
     },
     RemoveCollectionAddon {
         repo_id: i64,
@@ -227,6 +231,33 @@ pub enum Dialog {
     AddonLocalChanges {
         repos: Vec<AddonLocalChangesEntry>,
     },
+    /// Named addon sets and the characters they can be applied to.
+    AddonSets {
+        sets: Vec<wuddle_engine::AddonSet>,
+        characters: Vec<wuddle_engine::WtfCharacter>,
+        /// Index into `characters`; `None` targets every character.
+        character: Option<usize>,
+        new_name: String,
+        loading: bool,
+    },
+    /// The local addon catalog; results are searched as the query changes.
+    AddonIndex {
+        entries: Vec<wuddle_engine::IndexEntry>,
+        sources: Vec<wuddle_engine::IndexSource>,
+        query: String,
+        client: Option<wuddle_engine::ClientFamily>,
+        kind: Option<wuddle_engine::IndexKind>,
+        /// Catalog URL being typed.
+        url: String,
+        loading: bool,
+    },
+    /// Untracked addon folders that match a known source.
+    IdentifyAddons {
+        addons: Vec<wuddle_engine::AddonIdentification>,
+        loading: bool,
+        /// Folder being linked; other links wait until it finishes.
+        linking: Option<String>,
+    },
     InstanceSettings {
         is_new: bool,
         profile_id: String,
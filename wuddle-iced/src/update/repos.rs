@@ -107,6 +107,7 @@ fn apply_addon_git_rescan_state(
                 error: None,
                 previous_dll_count: 0,
                 new_dll_count: 0,
+                client_mismatches: Vec::new(),
             });
         }
         detected.push((
@@ -296,6 +297,25 @@ fn show_addon_local_changes_dialog(app: &mut App, repos: Vec<AddonLocalChangesEn
     app.dialog = Some(Dialog::AddonLocalChanges { repos });
 }
 
+/// Log addons written for another client than the profile's and show one
+/// warning toast for them.
+fn warn_client_mismatches(app: &mut App, mismatches: &[wuddle_engine::ClientMismatch]) {
+    for mismatch in mismatches {
+        app.log(LogLevel::Info, &format!("Warning: {}", mismatch.message()));
+    }
+    match mismatches {
+        [] => {}
+        [only] => app.show_toast(only.message(), ToastKind::Warn),
+        _ => app.show_toast(
+            format!(
+                "{} addons are written for another client than this profile's. See the log for details.",
+                mismatches.len()
+            ),
+            ToastKind::Warn,
+        ),
+    }
+}
+
 fn install_local_archive(app: &mut App, path: std::path::PathBuf) -> Option<Task<Message>> {
     crate::diagnostics::register_private_path(&path, "<LOCAL_ARCHIVE>");
     if !service::is_local_archive_path(&path) {
@@ -662,6 +682,15 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                     }
 
                     if is_explicit_check {
+                        let missing_dependencies = app
+                            .repos
+                            .iter()
+                            .flat_map(|repo| &repo.missing_dependencies)
+                            .map(|unmet| format!("Warning: {}", unmet.message()))
+                            .collect::<Vec<_>>();
+                        for message in &missing_dependencies {
+                            app.log(LogLevel::Info, message);
+                        }
                         if let Some(error) = rate_limit_error.as_deref() {
                             app.show_github_rate_limit(
                                 "Some GitHub update checks could not finish.",
@@ -1046,8 +1075,21 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                         };
 
                         app.log(LogLevel::Info, "Checking for conflicts\u{2026}");
+                        let client = app.quick_add_client_family();
+                        let probe_entries = app
+                            .add_repo_probe
+                            .as_ref()
+                            .map(|p| p.addon_entries.clone())
+                            .unwrap_or_default();
                         return Some(Task::perform(
-                            service::check_pre_install_conflicts(db, id, wow, addon_names),
+                            service::check_pre_install_conflicts(
+                                db,
+                                id,
+                                wow,
+                                addon_names,
+                                client,
+                                probe_entries,
+                            ),
                             move |result| Message::PreInstallConflictResult {
                                 repo_id: id,
                                 result: crate::ProfileScoped::new(scope.clone(), result),
@@ -1096,9 +1138,14 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                         existing_repos: Vec::new(),
                         new_repo_label: String::new(),
                         addon_names: Vec::new(),
+                        client_mismatches: Vec::new(),
                     }
                 }
             };
+            // The install result shows the toast once the files are in place.
+            for mismatch in &info.client_mismatches {
+                app.log(LogLevel::Info, &format!("Warning: {}", mismatch.message()));
+            }
 
             if info.conflicts.is_empty() {
                 // No conflicts — proceed to install.
@@ -1161,9 +1208,13 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             };
             app.updating_repo_ids.remove(&repo_id);
             match result {
-                Ok(msg) => {
-                    app.log(LogLevel::Info, &msg);
-                    app.show_toast(msg, ToastKind::Info);
+                Ok(installed) => {
+                    app.log(LogLevel::Info, &installed.message);
+                    if installed.client_mismatches.is_empty() {
+                        app.show_toast(installed.message, ToastKind::Info);
+                    } else {
+                        warn_client_mismatches(app, &installed.client_mismatches);
+                    }
                     let db = app.db_path.clone();
                     let scope = app.profile_operation_scope();
                     let prompt_task =
@@ -1546,7 +1597,14 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             if is_collection {
                 if let Some(probe) = app.add_repo_probe.as_ref() {
                     if app.add_repo_selected_addons.is_empty() || from_primary_toc_choice {
-                        app.add_repo_selected_addons = probe.addon_names.iter().cloned().collect();
+                        // Preselect only the variants built for this profile's client.
+                        let mut names = probe.addon_names.clone();
+                        wuddle_engine::toc::retain_client_variants(
+                            &mut names,
+                            app.quick_add_client_family(),
+                            |name| name,
+                        );
+                        app.add_repo_selected_addons = names.into_iter().collect();
                     }
                 }
             } else if app.add_repo_selected_addons.len() != 1 {
@@ -2468,7 +2526,11 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                 Ok(Some(plan)) => {
                     let name = format!("{}/{}", plan.owner, plan.name);
                     app.log(LogLevel::Info, &format!("Updated {}.", name));
-                    app.show_toast(format!("Updated {}.", name), ToastKind::Info);
+                    if plan.client_mismatches.is_empty() {
+                        app.show_toast(format!("Updated {}.", name), ToastKind::Info);
+                    } else {
+                        warn_client_mismatches(app, &plan.client_mismatches);
+                    }
                     // Remove from plans so it disappears from 'Updates' list in UI immediately
                     app.plans.retain(|p| p.repo_id != plan.repo_id);
                     sync_active_plan_cache(app);
@@ -2620,6 +2682,579 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             }
             Some(Task::none())
         }
+        Message::OpenAddonSets => {
+            app.dialog = Some(Dialog::AddonSets {
+                sets: Vec::new(),
+                characters: Vec::new(),
+                character: None,
+                new_name: String::new(),
+                loading: true,
+            });
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::load_addon_sets(app.db_path.clone(), app.wow_dir.clone()),
+                move |result| {
+                    Message::AddonSetsLoaded(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::AddonSetsLoaded(result) => {
+            let Some(result) = app.accept_profile_result(result, "addon set load") else {
+                return Some(Task::none());
+            };
+            match result {
+                Ok((loaded_sets, loaded_characters)) => {
+                    if let Some(Dialog::AddonSets {
+                        sets,
+                        characters,
+                        loading,
+                        ..
+                    }) = app.dialog.as_mut()
+                    {
+                        *sets = loaded_sets;
+                        *characters = loaded_characters;
+                        *loading = false;
+                    }
+                }
+                Err(e) => {
+                    app.log(LogLevel::Error, &format!("Could not load addon sets: {e}"));
+                    if matches!(app.dialog, Some(Dialog::AddonSets { .. })) {
+                        app.dialog = None;
+                    }
+                }
+            }
+            Some(Task::none())
+        }
+        Message::SetAddonSetCharacter(label) => {
+            if let Some(Dialog::AddonSets {
+                characters,
+                character,
+                ..
+            }) = app.dialog.as_mut()
+            {
+                *character = characters
+                    .iter()
+                    .position(|candidate| candidate.label() == label);
+            }
+            Some(Task::none())
+        }
+        Message::SetAddonSetName(value) => {
+            if let Some(Dialog::AddonSets { new_name, .. }) = app.dialog.as_mut() {
+                *new_name = value;
+            }
+            Some(Task::none())
+        }
+        Message::CaptureAddonSet => {
+            let Some(Dialog::AddonSets {
+                characters,
+                character: Some(index),
+                new_name,
+                ..
+            }) = app.dialog.as_ref()
+            else {
+                return Some(Task::none());
+            };
+            let (Some(character), name) = (characters.get(*index).cloned(), new_name.trim()) else {
+                return Some(Task::none());
+            };
+            if name.is_empty() {
+                return Some(Task::none());
+            }
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::capture_addon_set(
+                    app.db_path.clone(),
+                    app.wow_dir.clone(),
+                    name.to_string(),
+                    character,
+                ),
+                move |result| {
+                    Message::AddonSetsChanged(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::ApplyAddonSet(name) => {
+            let character = match app.dialog.as_ref() {
+                Some(Dialog::AddonSets {
+                    characters,
+                    character,
+                    ..
+                }) => character.and_then(|index| characters.get(index).cloned()),
+                _ => None,
+            };
+            app.log(
+                LogLevel::Info,
+                &format!(
+                    "Applying addon set \"{name}\" to {}.",
+                    character
+                        .as_ref()
+                        .map(|character| character.label())
+                        .unwrap_or_else(|| "every character".to_string())
+                ),
+            );
+            let use_dlls_txt = app.quick_add_client_family() == service::ClientFamily::Vanilla;
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::apply_addon_set(
+                    app.db_path.clone(),
+                    app.wow_dir.clone(),
+                    name.clone(),
+                    character,
+                    use_dlls_txt,
+                ),
+                move |result| Message::ApplyAddonSetResult {
+                    name: name.clone(),
+                    result: crate::ProfileScoped::new(scope.clone(), result),
+                },
+            ))
+        }
+        Message::ApplyAddonSetResult { name, result } => {
+            let Some(result) = app.accept_profile_result(result, "addon set apply") else {
+                return Some(Task::none());
+            };
+            match result {
+                Ok(report) => {
+                    for skipped in &report.skipped {
+                        app.log(
+                            LogLevel::Info,
+                            &format!("Warning: {} {}.", skipped.entry, skipped.reason),
+                        );
+                    }
+                    app.log(
+                        LogLevel::Info,
+                        &format!(
+                            "Applied addon set \"{name}\": {} addon folder(s); switched {} repo(s); updated {} AddOns.txt file(s).",
+                            report.folders, report.repos_changed, report.characters_updated
+                        ),
+                    );
+                    if report.skipped.is_empty() {
+                        app.show_toast(format!("Applied \"{name}\"."), ToastKind::Success);
+                    } else {
+                        app.show_toast(
+                            format!(
+                                "Applied \"{name}\"; {} entr{} could not be toggled. See the log for details.",
+                                report.skipped.len(),
+                                if report.skipped.len() == 1 { "y" } else { "ies" }
+                            ),
+                            ToastKind::Warn,
+                        );
+                    }
+                    if report.repos_changed > 0 {
+                        return Some(refresh_repos_task(app));
+                    }
+                }
+                Err(e) => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("Applying addon set \"{name}\" failed: {e}"),
+                    );
+                    app.show_toast(format!("Could not apply \"{name}\"."), ToastKind::Error);
+                }
+            }
+            Some(Task::none())
+        }
+        Message::DeleteAddonSet(name) => {
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::delete_addon_set(app.db_path.clone(), name),
+                move |result| {
+                    Message::AddonSetsChanged(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::ExportAddonSet(name) => {
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::export_addon_set(app.db_path.clone(), name),
+                move |result| {
+                    Message::AddonSetExported(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::AddonSetExported(result) => {
+            let Some(result) = app.accept_profile_result(result, "addon set export") else {
+                return Some(Task::none());
+            };
+            match result {
+                Ok(json) => Some(Task::done(Message::CopyToClipboard(json))),
+                Err(e) => {
+                    app.log(LogLevel::Error, &format!("Addon set export failed: {e}"));
+                    Some(Task::none())
+                }
+            }
+        }
+        Message::ImportAddonSet => {
+            Some(iced::clipboard::read().map(Message::AddonSetClipboardRead))
+        }
+        Message::AddonSetClipboardRead(contents) => {
+            let Some(json) = contents.filter(|json| !json.trim().is_empty()) else {
+                app.show_toast("The clipboard is empty.", ToastKind::Warn);
+                return Some(Task::none());
+            };
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::import_addon_set(app.db_path.clone(), json),
+                move |result| {
+                    Message::AddonSetsChanged(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::AddonSetsChanged(result) => {
+            let Some(result) = app.accept_profile_result(result, "addon set change") else {
+                return Some(Task::none());
+            };
+            match result {
+                Ok((updated, message)) => {
+                    app.log(LogLevel::Info, &message);
+                    app.show_toast(message, ToastKind::Success);
+                    if let Some(Dialog::AddonSets { sets, new_name, .. }) = app.dialog.as_mut() {
+                        *sets = updated;
+                        new_name.clear();
+                    }
+                }
+                Err(e) => {
+                    app.log(LogLevel::Error, &format!("Addon set change failed: {e}"));
+                    app.show_toast(e, ToastKind::Error);
+                }
+            }
+            Some(Task::none())
+        }
+        Message::OpenAddonIndex(kind) => {
+            let family = app.quick_add_client_family();
+            app.dialog = Some(Dialog::AddonIndex {
+                entries: Vec::new(),
+                sources: Vec::new(),
+                query: String::new(),
+                client: family.is_legacy().then_some(family),
+                kind: Some(kind),
+                url: String::new(),
+                loading: true,
+            });
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::load_addon_index(app.db_path.clone()),
+                move |result| {
+                    Message::AddonIndexLoaded(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::AddonIndexLoaded(result) => {
+            let Some(result) = app.accept_profile_result(result, "addon catalog load") else {
+                return Some(Task::none());
+            };
+            match result {
+                Ok((loaded_sources, loaded_entries)) => {
+                    if let Some(Dialog::AddonIndex {
+                        entries,
+                        sources,
+                        loading,
+                        ..
+                    }) = app.dialog.as_mut()
+                    {
+                        *sources = loaded_sources;
+                        *entries = loaded_entries;
+                        *loading = false;
+                    }
+                }
+                Err(e) => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("Could not load addon catalogs: {e}"),
+                    );
+                    if matches!(app.dialog, Some(Dialog::AddonIndex { .. })) {
+                        app.dialog = None;
+                    }
+                }
+            }
+            Some(Task::none())
+        }
+        Message::SetAddonIndexQuery(value) => {
+            if let Some(Dialog::AddonIndex { query, .. }) = app.dialog.as_mut() {
+                *query = value;
+            }
+            Some(Task::none())
+        }
+        Message::SetAddonIndexClient(label) => {
+            if let Some(Dialog::AddonIndex { client, .. }) = app.dialog.as_mut() {
+                *client = [
+                    wuddle_engine::ClientFamily::Vanilla,
+                    wuddle_engine::ClientFamily::Tbc,
+                    wuddle_engine::ClientFamily::Wotlk,
+                ]
+                .into_iter()
+                .find(|family| family.label() == label);
+            }
+            Some(Task::none())
+        }
+        Message::SetAddonIndexKind(label) => {
+            if let Some(Dialog::AddonIndex { kind, .. }) = app.dialog.as_mut() {
+                *kind = [
+                    wuddle_engine::IndexKind::Addon,
+                    wuddle_engine::IndexKind::Mod,
+                ]
+                .into_iter()
+                .find(|candidate| {
+                    crate::dialogs::addon_index::kind_label(Some(*candidate)) == label
+                });
+            }
+            Some(Task::none())
+        }
+        Message::SetAddonIndexUrl(value) => {
+            if let Some(Dialog::AddonIndex { url, .. }) = app.dialog.as_mut() {
+                *url = value;
+            }
+            Some(Task::none())
+        }
+        Message::AddAddonIndexUrl => {
+            let Some(Dialog::AddonIndex { url, loading, .. }) = app.dialog.as_mut() else {
+                return Some(Task::none());
+            };
+            let url = url.trim().to_string();
+            if url.is_empty() {
+                return Some(Task::none());
+            }
+            *loading = true;
+            app.log(LogLevel::Info, "Fetching addon catalog...");
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::fetch_addon_index(app.db_path.clone(), Some(url)),
+                move |result| {
+                    Message::AddonIndexChanged(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::RefreshAddonIndexes => {
+            if let Some(Dialog::AddonIndex { loading, .. }) = app.dialog.as_mut() {
+                *loading = true;
+            }
+            app.log(LogLevel::Info, "Refreshing addon catalogs...");
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::fetch_addon_index(app.db_path.clone(), None),
+                move |result| {
+                    Message::AddonIndexChanged(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::ImportAddonIndex => {
+            Some(iced::clipboard::read().map(Message::AddonIndexClipboardRead))
+        }
+        Message::AddonIndexClipboardRead(contents) => {
+            let Some(json) = contents.filter(|json| !json.trim().is_empty()) else {
+                app.show_toast("The clipboard is empty.", ToastKind::Warn);
+                return Some(Task::none());
+            };
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::import_addon_index(app.db_path.clone(), json),
+                move |result| {
+                    Message::AddonIndexChanged(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::RemoveAddonIndex(id) => {
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::remove_addon_index(app.db_path.clone(), id),
+                move |result| {
+                    Message::AddonIndexChanged(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::AddonIndexChanged(result) => {
+            let Some(result) = app.accept_profile_result(result, "addon catalog change") else {
+                return Some(Task::none());
+            };
+            if let Some(Dialog::AddonIndex { loading, .. }) = app.dialog.as_mut() {
+                *loading = false;
+            }
+            match result {
+                Ok((updated_sources, updated_entries, message)) => {
+                    app.log(LogLevel::Info, &message);
+                    app.show_toast(message, ToastKind::Success);
+                    if let Some(Dialog::AddonIndex {
+                        entries,
+                        sources,
+                        url,
+                        ..
+                    }) = app.dialog.as_mut()
+                    {
+                        *sources = updated_sources;
+                        *entries = updated_entries;
+                        url.clear();
+                    }
+                }
+                Err(e) => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("Addon catalog change failed: {e}"),
+                    );
+                    app.show_toast(e, ToastKind::Error);
+                }
+            }
+            Some(Task::none())
+        }
+        Message::OpenIdentifyAddons => {
+            app.dialog = Some(Dialog::IdentifyAddons {
+                addons: Vec::new(),
+                loading: true,
+                linking: None,
+            });
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::identify_untracked_addons(app.db_path.clone(), app.wow_dir.clone()),
+                move |result| {
+                    Message::IdentifyAddonsLoaded(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::IdentifyAddonsLoaded(result) => {
+            let Some(result) = app.accept_profile_result(result, "addon identification") else {
+                return Some(Task::none());
+            };
+            match result {
+                Ok(found) => {
+                    if let Some(Dialog::IdentifyAddons {
+                        addons, loading, ..
+                    }) = app.dialog.as_mut()
+                    {
+                        *addons = found;
+                        *loading = false;
+                    }
+                }
+                Err(e) => {
+                    app.log(LogLevel::Error, &format!("Could not identify addons: {e}"));
+                    app.show_toast(e, ToastKind::Error);
+                    if matches!(app.dialog, Some(Dialog::IdentifyAddons { .. })) {
+                        app.dialog = None;
+                    }
+                }
+            }
+            Some(Task::none())
+        }
+        Message::LinkAddonFolder { folder, source } => {
+            if let Some(Dialog::IdentifyAddons { linking, .. }) = app.dialog.as_mut() {
+                *linking = Some(folder.clone());
+            }
+            app.log(
+                LogLevel::Info,
+                &format!(
+                    "Linking addon folder \"{folder}\" to {} ({}).",
+                    source.url,
+                    source.kind.label()
+                ),
+            );
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::link_addon_folder(
+                    app.db_path.clone(),
+                    app.wow_dir.clone(),
+                    folder.clone(),
+                    source,
+                ),
+                move |result| Message::LinkAddonFolderResult {
+                    folder: folder.clone(),
+                    result: crate::ProfileScoped::new(scope.clone(), result),
+                },
+            ))
+        }
+        Message::LinkAddonFolderResult { folder, result } => {
+            let Some(result) = app.accept_profile_result(result, "addon folder link") else {
+                return Some(Task::none());
+            };
+            if let Some(Dialog::IdentifyAddons { linking, .. }) = app.dialog.as_mut() {
+                *linking = None;
+            }
+            match result {
+                Ok(found) => {
+                    app.log(
+                        LogLevel::Info,
+                        &format!("Linked addon folder \"{folder}\"; it updates from its repo now."),
+                    );
+                    app.show_toast(format!("Linked \"{folder}\"."), ToastKind::Success);
+                    if let Some(Dialog::IdentifyAddons { addons, .. }) = app.dialog.as_mut() {
+                        *addons = found;
+                    }
+                    Some(refresh_repos_task(app))
+                }
+                Err(e) => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("Linking addon folder \"{folder}\" failed: {e}"),
+                    );
+                    app.show_toast(format!("Could not link \"{folder}\"."), ToastKind::Error);
+                    Some(Task::none())
+                }
+            }
+        }
+        Message::ExportAddonCatalog => {
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::export_addon_catalog(app.db_path.clone(), app.wow_dir.clone()),
+                move |result| {
+                    Message::AddonCatalogExported(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::AddonCatalogExported(result) => {
+            let Some(result) = app.accept_profile_result(result, "addon catalog export") else {
+                return Some(Task::none());
+            };
+            match result {
+                Ok(json) => Some(Task::done(Message::CopyToClipboard(json))),
+                Err(e) => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("Addon catalog export failed: {e}"),
+                    );
+                    Some(Task::none())
+                }
+            }
+        }
+        Message::ImportAddonCatalog => {
+            Some(iced::clipboard::read().map(Message::AddonCatalogClipboardRead))
+        }
+        Message::AddonCatalogClipboardRead(contents) => {
+            let Some(json) = contents.filter(|json| !json.trim().is_empty()) else {
+                app.show_toast("The clipboard is empty.", ToastKind::Warn);
+                return Some(Task::none());
+            };
+            if let Some(Dialog::IdentifyAddons { loading, .. }) = app.dialog.as_mut() {
+                *loading = true;
+            }
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::import_addon_catalog(app.db_path.clone(), app.wow_dir.clone(), json),
+                move |result| {
+                    Message::AddonCatalogImported(crate::ProfileScoped::new(scope.clone(), result))
+                },
+            ))
+        }
+        Message::AddonCatalogImported(result) => {
+            let Some(result) = app.accept_profile_result(result, "addon catalog import") else {
+                return Some(Task::none());
+            };
+            if let Some(Dialog::IdentifyAddons { loading, .. }) = app.dialog.as_mut() {
+                *loading = false;
+            }
+            match result {
+                Ok((found, message)) => {
+                    app.log(LogLevel::Info, &message);
+                    app.show_toast(message, ToastKind::Success);
+                    if let Some(Dialog::IdentifyAddons { addons, .. }) = app.dialog.as_mut() {
+                        *addons = found;
+                    }
+                }
+                Err(e) => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("Addon catalog import failed: {e}"),
+                    );
+                    app.show_toast(e, ToastKind::Error);
+                }
+            }
+            Some(Task::none())
+        }
         Message::ToggleRepoExpanded(id) => {
             if app.expanded_repo_ids.contains(&id) {
                 app.expanded_repo_ids.remove(&id);
@@ -2741,12 +3376,21 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                     );
                     let scope = app.profile_operation_scope();
                     let completed_ids = targets.clone();
-                    return Some(Task::perform(
-                        service::update_all(db, wow, targets, opts),
-                        move |result| Message::UpdateAllResult {
+                    let on_result = move |result: Result<Vec<service::UpdateOneResult>, String>| {
+                        Message::UpdateAllResult {
                             repo_ids: completed_ids.clone(),
                             result: crate::ProfileScoped::new(scope.clone(), result),
-                        },
+                        }
+                    };
+                    if app.opt_atomic_updates {
+                        return Some(Task::perform(
+                            service::update_all_atomic(db, wow, targets, opts),
+                            on_result,
+                        ));
+                    }
+                    return Some(Task::perform(
+                        service::update_all(db, wow, targets, opts),
+                        on_result,
                     ));
                 }
             }
@@ -2769,6 +3413,7 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                     let mut skipped = 0;
                     let mut rate_limit_error = None;
                     let mut local_changes = Vec::new();
+                    let mut client_mismatches = Vec::new();
                     for r in results {
                         let name = if r.owner.is_empty() {
                             r.name.clone()
@@ -2810,6 +3455,9 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                         } else {
                             applied += 1;
                             app.log(LogLevel::Info, &format!("Updated {}.", name));
+                            if let Some(plan) = r.plan {
+                                client_mismatches.extend(plan.client_mismatches);
+                            }
                             // Remove from plans so it disappears from UI immediately
                             app.plans.retain(|p| p.repo_id != r.repo_id);
                         }
@@ -2827,13 +3475,19 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                             LogLevel::Info,
                             &format!("Done. Updated {} repo(s).", applied),
                         );
-                        app.show_toast(format!("Updated {} repo(s).", applied), ToastKind::Info);
+                        if client_mismatches.is_empty() {
+                            app.show_toast(
+                                format!("Updated {} repo(s).", applied),
+                                ToastKind::Info,
+                            );
+                        }
                     } else if skipped > 0 {
                         app.show_toast(
                             "Removed stale update entries. Check again for current updates.",
                             ToastKind::Info,
                         );
                     }
+                    warn_client_mismatches(app, &client_mismatches);
                     show_addon_local_changes_dialog(app, local_changes);
                     return Some(refresh_repos_task(app));
                 }
@@ -2989,6 +3643,80 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             }
             Some(Task::none())
         }
+        Message::RollbackInstall { repo_id, entry_id } => {
+            if app.updating_repo_ids.contains(&repo_id) {
+                app.show_toast(
+                    "That repository already has an operation in progress.",
+                    ToastKind::Warn,
+                );
+                return Some(Task::none());
+            }
+            if app.wow_dir.is_empty() {
+                app.log(LogLevel::Error, "Set a WoW directory in Options first.");
+                return Some(Task::none());
+            }
+            app.dialog = None;
+            app.updating_repo_ids.insert(repo_id);
+            app.log(
+                LogLevel::Info,
+                &format!("Rolling back repo id={repo_id} and its addon settings..."),
+            );
+            let db = app.db_path.clone();
+            let wow = app.wow_dir.clone();
+            let opts = app.install_options();
+            let scope = app.profile_operation_scope();
+            Some(Task::perform(
+                service::rollback_to_history(db, entry_id, wow, opts),
+                move |result| Message::RollbackInstallResult {
+                    repo_id,
+                    result: crate::ProfileScoped::new(scope.clone(), result),
+                },
+            ))
+        }
+        Message::RollbackInstallResult {
+            repo_id,
+            result: scoped,
+        } => {
+            let Some(result) = app.accept_profile_result(scoped, "install rollback") else {
+                return Some(Task::none());
+            };
+            app.updating_repo_ids.remove(&repo_id);
+            match result {
+                Ok(report) => {
+                    forget_repo_update_plan(app, repo_id);
+                    let name = app
+                        .repos
+                        .iter()
+                        .find(|repo| repo.id == repo_id)
+                        .map(|repo| format!("{}/{}", repo.owner, repo.name))
+                        .unwrap_or_else(|| format!("repo#{repo_id}"));
+                    let files = report
+                        .settings
+                        .iter()
+                        .map(|restored| restored.restored_files)
+                        .sum::<usize>();
+                    let message = if report.settings.is_empty() {
+                        format!(
+                            "Rolled back {name} to {}; it stays pinned to that version.",
+                            report.version
+                        )
+                    } else {
+                        format!(
+                            "Rolled back {name} to {} and restored {files} SavedVariables file(s); it stays pinned to that version.",
+                            report.version
+                        )
+                    };
+                    app.log(LogLevel::Info, &message);
+                    app.show_toast(message, ToastKind::Success);
+                    return Some(refresh_repos_task(app));
+                }
+                Err(e) => {
+                    app.log(LogLevel::Error, &format!("Rollback failed: {e}"));
+                    app.show_toast(format!("Rollback failed: {e}"), ToastKind::Error);
+                }
+            }
+            Some(Task::none())
+        }
         Message::FetchBranches(repo_id) => {
             let db = app.db_path.clone();
             let scope = app.profile_operation_scope();
@@ -3202,6 +3930,49 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             }
             Some(Task::none())
         }
+        Message::OpenWhatsNew(repo_id) => {
+            let generation = app.begin_preview_request();
+            let name = app
+                .repos
+                .iter()
+                .find(|repo| repo.id == repo_id)
+                .map(|repo| repo.name.clone())
+                .unwrap_or_else(|| format!("repo id={repo_id}"));
+            app.markdown_image_cache.clear();
+            app.markdown_gif_cache.clear();
+            app.dialog = Some(Dialog::Changelog {
+                title: format!("{name} \u{2014} What's new"),
+                items: Vec::new(),
+                loading: true,
+            });
+            let wow = (!app.wow_dir.is_empty()).then(|| app.wow_dir.clone());
+            Some(Task::perform(
+                service::load_release_notes(app.db_path.clone(), repo_id, wow),
+                move |result| Message::WhatsNewLoaded(generation, result),
+            ))
+        }
+        Message::WhatsNewLoaded(generation, result) => {
+            if !app.preview_request_is_current(generation, "release notes") {
+                return Some(Task::none());
+            }
+            let text = match result {
+                Ok(text) => text,
+                Err(error) => {
+                    app.show_github_rate_limit("The release notes could not be loaded.", &error);
+                    format!(
+                        "Could not load the release notes.\n\n{}",
+                        crate::github_api::user_facing_error(&error)
+                    )
+                }
+            };
+            if let Some(Dialog::Changelog { items, loading, .. }) = app.dialog.as_mut() {
+                *items = iced::widget::markdown::Content::parse(&text)
+                    .items()
+                    .to_vec();
+                *loading = false;
+            }
+            Some(Task::none())
+        }
         Message::FetchRepoPreviewResult(generation, url, result) => {
             if !app.preview_request_is_current(generation, "repository preview") {
                 return Some(Task::none());
@@ -3402,9 +4173,10 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                 let url = preview.forge_url.clone();
                 app.add_repo_show_releases = true;
                 let generation = app.preview_request_generation;
-                return Some(Task::perform(service::fetch_releases(url), move |result| {
-                    Message::FetchReleaseNotesResult(generation, result)
-                }));
+                return Some(Task::perform(
+                    service::fetch_releases(app.db_path.clone(), url),
+                    move |result| Message::FetchReleaseNotesResult(generation, result),
+                ));
             }
             Some(Task::none())
         }
@@ -3678,9 +4450,10 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
                 .map(|r| r.url.clone());
 
             if let Some(url) = url {
-                Some(Task::perform(service::fetch_releases(url), move |result| {
-                    Message::FetchReleaseNotesResult(generation, result)
-                }))
+                Some(Task::perform(
+                    service::fetch_releases(app.db_path.clone(), url),
+                    move |result| Message::FetchReleaseNotesResult(generation, result),
+                ))
             } else {
                 // If no repo found, just show "No info available"
                 if let Some(Dialog::Changelog {
@@ -4034,9 +4807,12 @@ mod update_plan_tests {
             installed_branch: None,
             installed_dlls: Vec::new(),
             installed_addons: Vec::new(),
+            addon_tocs: Vec::new(),
             installed_mpqs: Vec::new(),
             mpq_package_name: None,
             dependencies: Vec::new(),
+            missing_dependencies: Vec::new(),
+            dependents: Vec::new(),
             selected_addons: Vec::new(),
             is_collection: false,
             merge_installs: false,
@@ -4063,6 +4839,7 @@ mod update_plan_tests {
             error: None,
             previous_dll_count: 0,
             new_dll_count: 0,
+            client_mismatches: Vec::new(),
         }
     }
 
@@ -1,4 +1,4 @@
-use crate::app::GitHubTokenStatus;
+use crate::app::{ForgeTokenEntry, GitHubTokenStatus};
 use crate::service;
 use crate::settings::{self, resolve_ui_scale, ProfileConfig};
 use crate::theme::WuddleTheme;
@@ -105,6 +105,13 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             );
             Some(Task::none())
         }
+        Message::SetSshKey(s) => {
+            app.ssh_key = s;
+            let path = app.ssh_key.trim();
+            wuddle_engine::set_ssh_key_path((!path.is_empty()).then(|| path.into()));
+            app.save_settings();
+            Some(Task::none())
+        }
         Message::ToggleDesktopNotify(b) => {
             app.opt_desktop_notify = b;
             app.save_settings();
@@ -138,6 +145,18 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             );
             Some(Task::none())
         }
+        Message::ToggleAtomicUpdates(b) => {
+            app.opt_atomic_updates = b;
+            app.save_settings();
+            app.log(
+                LogLevel::Info,
+                &format!(
+                    "All-or-nothing updates: {}.",
+                    if b { "enabled" } else { "disabled" }
+                ),
+            );
+            Some(Task::none())
+        }
         Message::ToggleClock12(b) => {
             app.opt_clock12 = b;
             app.save_settings();
@@ -279,6 +298,104 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             Some(Task::none())
         }
 
+        Message::SetForgeTokenHostInput(s) => {
+            app.forge_token_host_input = s;
+            Some(Task::none())
+        }
+        Message::SetForgeTokenInput(s) => {
+            app.forge_token_input = s;
+            Some(Task::none())
+        }
+        Message::SaveForgeToken => {
+            let host = app.forge_token_host_input.trim().to_string();
+            let token = app.forge_token_input.trim().to_string();
+            Some(Task::perform(
+                service::save_forge_token(host, token),
+                Message::SaveForgeTokenResult,
+            ))
+        }
+        Message::SaveForgeTokenResult(result) => {
+            match result {
+                Ok(host) => {
+                    app.forge_token_host_input.clear();
+                    app.forge_token_input.clear();
+                    match app.forge_tokens.iter_mut().find(|entry| entry.host == host) {
+                        Some(entry) => entry.validation = None,
+                        None => {
+                            app.forge_tokens.push(ForgeTokenEntry {
+                                host: host.clone(),
+                                validation: None,
+                            });
+                            app.forge_tokens.sort_by(|a, b| a.host.cmp(&b.host));
+                        }
+                    }
+                    app.log(LogLevel::Info, &format!("Access token for {host} saved."));
+                    return Some(Task::perform(
+                        service::validate_forge_token(host.clone()),
+                        move |result| Message::ValidateForgeTokenResult {
+                            host: host.clone(),
+                            result,
+                        },
+                    ));
+                }
+                Err(e) => {
+                    app.log(LogLevel::Error, &format!("Token save error: {}", e));
+                    app.show_toast(format!("Failed to save token: {}", e), ToastKind::Error);
+                }
+            }
+            Some(Task::none())
+        }
+        Message::ValidateForgeTokenResult { host, result } => {
+            let Some(entry) = app.forge_tokens.iter_mut().find(|entry| entry.host == host) else {
+                return Some(Task::none());
+            };
+            match &result {
+                wuddle_engine::ForgeTokenValidation::Valid => {
+                    app.log(
+                        LogLevel::Info,
+                        &format!("Access token for {host} validated."),
+                    );
+                }
+                wuddle_engine::ForgeTokenValidation::Invalid => {
+                    app.log(
+                        LogLevel::Error,
+                        &format!("{host} rejected the saved access token."),
+                    );
+                    app.show_toast(
+                        format!(
+                            "{host} rejected the saved token. Replace or remove it in Options."
+                        ),
+                        ToastKind::Error,
+                    );
+                }
+                wuddle_engine::ForgeTokenValidation::Unverified(reason) => {
+                    app.log(LogLevel::Info, reason);
+                }
+            }
+            entry.validation = Some(result);
+            Some(Task::none())
+        }
+        Message::ForgetForgeToken(host) => Some(Task::perform(
+            service::clear_forge_token(host),
+            Message::ForgetForgeTokenResult,
+        )),
+        Message::ForgetForgeTokenResult(result) => {
+            match result {
+                Ok(host) => {
+                    app.forge_tokens.retain(|entry| entry.host != host);
+                    app.log(
+                        LogLevel::Info,
+                        &format!("Access token for {host} removed from secure storage."),
+                    );
+                }
+                Err(e) => {
+                    app.log(LogLevel::Error, &format!("Clear token failed: {}", e));
+                    app.show_toast(format!("Clear token failed: {}", e), ToastKind::Error);
+                }
+            }
+            Some(Task::none())
+        }
+
         // --- Instance settings ---
         Message::UpdateInstanceField(field) => {
             if let Some(Dialog::InstanceSettings {
@@ -721,7 +838,11 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             app.opt_desktop_notify = s.opt_desktop_notify;
             app.opt_symlinks = s.opt_symlinks;
             app.opt_xattr = s.opt_xattr;
+            app.opt_atomic_updates = s.opt_atomic_updates;
             app.opt_clock12 = s.opt_clock12;
+            app.ssh_key = s.ssh_key.clone();
+            let ssh_key = app.ssh_key.trim();
+            wuddle_engine::set_ssh_key_path((!ssh_key.is_empty()).then(|| ssh_key.into()));
             app.migrated_from_tauri = s.migrated_from_tauri;
             app.auto_login_warning_acknowledged = s.auto_login_warning_acknowledged;
             app.window_geometry = s.window_geometry;
@@ -731,6 +852,9 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             app.verbose_diagnostics = s.verbose_diagnostics;
             crate::diagnostics::set_verbose(s.verbose_diagnostics);
             app.auto_check_minutes = s.auto_check_minutes.max(1);
+            app.opt_saved_variables_snapshots = s.opt_saved_variables_snapshots;
+            app.saved_variables_snapshot_hours = s.saved_variables_snapshot_hours.max(1);
+            app.saved_variables_keep = s.saved_variables_keep.max(1);
             app.ignored_update_ids_by_profile = s
                 .ignored_update_ids_by_profile
                 .into_iter()
@@ -830,8 +954,37 @@ pub fn update(app: &mut App, message: Message) -> Option<Task<Message>> {
             if let Some(task) = schedule_tweak_client_detection(app) {
                 tasks.push(task);
             }
+            tasks.push(Task::perform(
+                service::load_preset_catalog(),
+                Message::PresetCatalogLoaded,
+            ));
             Some(Task::batch(tasks))
         }
+        Message::PresetCatalogLoaded(result) => {
+            match result {
+                Ok(loaded) => {
+                    for problem in &loaded.problems {
+                        app.log(LogLevel::Error, problem);
+                    }
+                    if loaded.origin != wuddle_engine::presets::CatalogOrigin::Embedded {
+                        app.log(
+                            LogLevel::Info,
+                            &format!(
+                                "Using the {} Quick Add preset catalog (revision {}).",
+                                loaded.origin.label(),
+                                loaded.catalog.revision
+                            ),
+                        );
+                    }
+                    crate::components::presets::set_active_presets(loaded.catalog.presets);
+                }
+                Err(e) => app.log(
+                    LogLevel::Error,
+                    &format!("Could not load the Quick Add presets: {e}"),
+                ),
+            }
+            Some(Task::none())
+        }
         Message::SaveSettings => {
             app.save_settings();
             Some(Task::none())